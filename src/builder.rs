@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::models::{
+    GraphGLBody,
+    HttpAuth,
+    HttpBasicAuth,
+    HttpBearerToken,
+    HttpBody,
+    HttpFormBody,
+    HttpGraphQLBody,
+    HttpJsonBody,
+    HttpMethod,
+    HttpTextBody,
+    KeyValueList,
+    KeyValuePair,
+};
+use crate::RequestModel;
+
+/// Build a request in code instead of loading it from a request file.
+///
+/// Values can contain templates, e.g. `{{token}}`, which are rendered when the request is
+/// executed, like the ones of request files.
+#[derive(Debug, Default)]
+pub struct RequestBuilder {
+    request: RequestModel,
+}
+
+impl RequestBuilder {
+    pub fn new(method: HttpMethod, url: impl Into<String>) -> Self {
+        let mut request = RequestModel::default();
+        request.http.method = method;
+        request.http.url = url.into();
+
+        Self { request }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.request.meta.name = Some(name.into());
+        self
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        push(&mut self.request.http.headers, name, value);
+        self
+    }
+
+    pub fn with_query_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        push(&mut self.request.http.params.query, name, value);
+        self
+    }
+
+    /// Value of a `:name` or `{name}` placeholder of the url path.
+    pub fn with_path_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        push(&mut self.request.http.params.path, name, value);
+        self
+    }
+
+    /// Variable available to the templates of the request.
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        push(&mut self.request.vars.pre_request, name, value);
+        self
+    }
+
+    pub fn with_json_body(mut self, json: Value) -> Self {
+        self.request.http.body = Some(HttpBody::Json(HttpJsonBody {
+            json,
+            content_type: None,
+        }));
+        self
+    }
+
+    /// Text body, sent as `text/plain` unless a content type is given.
+    pub fn with_text_body(mut self, text: impl Into<String>, content_type: Option<&str>) -> Self {
+        self.request.http.body = Some(HttpBody::Text(HttpTextBody {
+            text: text.into(),
+            content_type: content_type.map(str::to_string),
+        }));
+        self
+    }
+
+    /// Add a field to the url-encoded form sent as body, replacing any other kind of body.
+    pub fn with_form_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        if !matches!(self.request.http.body, Some(HttpBody::Form(_))) {
+            self.request.http.body = Some(HttpBody::Form(HttpFormBody {
+                form: KeyValueList::default(),
+            }));
+        }
+        if let Some(HttpBody::Form(f)) = &mut self.request.http.body {
+            push(&mut f.form, name, value);
+        }
+        self
+    }
+
+    pub fn with_graphql_body(
+        mut self,
+        query: impl Into<String>,
+        variables: HashMap<String, Value>,
+    ) -> Self {
+        self.request.http.body = Some(HttpBody::GraphQL(HttpGraphQLBody {
+            graphql: GraphGLBody {
+                query: query.into(),
+                variables,
+            },
+        }));
+        self
+    }
+
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.request.http.auth = Some(HttpAuth::Basic(HttpBasicAuth {
+            username: username.into(),
+            password: password.into(),
+        }));
+        self
+    }
+
+    pub fn with_bearer_auth(mut self, token: impl Into<String>) -> Self {
+        self.request.http.auth = Some(HttpAuth::Bearer(HttpBearerToken {
+            token: token.into(),
+            refresh: None,
+        }));
+        self
+    }
+
+    /// Send no credentials, even if the collection defines some.
+    pub fn without_auth(mut self) -> Self {
+        self.request.http.auth = Some(HttpAuth::None);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.request.http.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> RequestModel {
+        self.request
+    }
+}
+
+impl From<RequestBuilder> for RequestModel {
+    fn from(builder: RequestBuilder) -> Self {
+        builder.build()
+    }
+}
+
+fn push(list: &mut KeyValueList, key: impl Into<String>, value: impl Into<String>) {
+    list.push(KeyValuePair::new(key, value));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+    use super::RequestBuilder;
+    use crate::models::HttpMethod;
+    use crate::{ApiClientRequest, CollectionModel};
+
+    #[tokio::test]
+    async fn test_builder_builds_executable_request() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/users/42"))
+            .and(matchers::query_param("notify", "true"))
+            .and(matchers::header("X-Source", "builder"))
+            .and(matchers::header("Authorization", "Bearer some-token"))
+            .and(matchers::body_json(json!({"name": "Alice"})))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = RequestBuilder::new(HttpMethod::Post, format!("{}/users/:id", server.uri()))
+            .with_path_param("id", "42")
+            .with_query_param("notify", "true")
+            .with_header("X-Source", "builder")
+            .with_bearer_auth("{{token}}")
+            .with_variable("token", "some-token")
+            .with_json_body(json!({"name": "Alice"}))
+            .build();
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute()
+            .await
+            .expect("request failed");
+
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_builder_accumulates_form_fields() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::body_string("a=1&b=2"))
+            .and(matchers::header(
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let request = RequestBuilder::new(HttpMethod::Put, server.uri())
+            .with_graphql_body("{ a }", HashMap::new())
+            .with_form_field("a", "1")
+            .with_form_field("b", "2");
+
+        ApiClientRequest::new(CollectionModel::default(), request.into())
+            .execute()
+            .await
+            .expect("request failed");
+    }
+
+    #[test]
+    fn test_builder_sets_request_fields() {
+        let request = RequestBuilder::new(HttpMethod::Get, "http://localhost/search")
+            .with_name("Search")
+            .with_header("Accept", "application/json")
+            .with_query_param("q", "api")
+            .build();
+
+        assert_eq!(request.meta().name(), Some("Search"));
+        assert_eq!(request.method(), "GET");
+        assert_eq!(request.url(), "http://localhost/search");
+        assert_eq!(request.headers(), vec![("Accept", "application/json")]);
+        assert_eq!(request.query_params(), vec![("q", "api")]);
+    }
+}
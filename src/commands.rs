@@ -1,22 +1,64 @@
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 use std::{env, io};
 
 use api_cli::error::Result;
-use clap::{Args, CommandFactory, Parser, Subcommand};
-use clap_complete::{generate, Shell};
+use api_cli::report::ReportFormat;
+use api_cli::{HttpMethod, JqFilter, StatusMatcher, TabularFormat, TagExpression, TraceParent};
+use clap::{ArgAction, ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::env::Shells;
+use clap_complete::{ArgValueCompleter, Shell};
+pub use clipboard::hold_clipboard_content;
 pub use collection::run_collection_command;
+use config::config;
+pub use config::{load_config, run_config_command};
+pub use cookies::run_cookies_command;
 pub use environment::run_environment_command;
+pub use grep::run_grep;
+use jsonpath_rust::JsonPathInst;
+pub use lint::run_lint;
 use log::debug;
+pub use logging::init_logger;
+pub use mock::run_mock;
 use once_cell::sync::Lazy;
+pub use record::run_record;
 pub use request::run_request_command;
+use reqwest::Url;
 pub use run::execute_request;
+pub use secret::run_secret_command;
+use serde::{Deserialize, Serialize};
+pub use tui::run_tui;
 use utils::get_collections_directory;
+pub use variables::run_var_command;
+pub use watch::run_watch;
 
+mod cache;
+mod clipboard;
 mod collection;
+mod completion;
+mod conditional;
+mod config;
+mod cookies;
 mod environment;
+mod grep;
+mod history;
+mod lint;
+mod logging;
+mod mock;
+mod pagination;
+mod picker;
+mod record;
+mod refresh;
 mod request;
 mod run;
+mod runner;
+mod secret;
+mod tui;
 mod utils;
+mod variables;
+mod watch;
 
 static APP_NAME: &str = "api-cli";
 
@@ -24,6 +66,7 @@ static API_CLI_BASE_DIRECTORY: Lazy<PathBuf> = Lazy::new(|| {
     env::var("API_CLI_BASE_DIRECTORY")
         .ok()
         .map(PathBuf::from)
+        .or_else(|| config().base_directory.clone())
         .unwrap_or_else(|| {
             let mut d = dirs::data_dir().unwrap_or(PathBuf::from("."));
             d.push(APP_NAME);
@@ -43,7 +86,7 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Command {
     /// Execute a request
-    Run(RunArgs),
+    Run(Box<RunArgs>),
 
     /// Generate shell completion
     Completion(CompletionArgs),
@@ -60,26 +103,616 @@ pub enum Command {
     #[command(subcommand)]
     Request(RequestCmd),
 
+    /// Manage the cookies saved for a collection
+    #[command(subcommand)]
+    Cookies(CookiesCmd),
+
+    /// Manage secrets stored in the OS keyring
+    #[command(subcommand)]
+    Secret(SecretCmd),
+
+    /// Manage the runtime variables of a collection, used by all its requests
+    #[command(subcommand)]
+    Var(VarCmd),
+
+    /// Check the collections for errors
+    Lint(LintArgs),
+
+    /// Search the requests, environments and configuration of the collections
+    Grep(GrepArgs),
+
+    /// Serve the examples of a collection's requests from a local server
+    Mock(MockArgs),
+
+    /// Forward requests to a server from a local proxy, saving each one in a collection
+    Record(RecordArgs),
+
+    /// Execute a request again every time its files change
+    Watch(WatchArgs),
+
+    /// Manage the configuration
+    #[command(subcommand)]
+    Config(ConfigCmd),
+
     /// Launch a shell in the collections directory
     Cd,
+
+    /// Browse and execute requests in an interactive interface
+    Tui,
 }
 
-#[derive(Args)]
+#[derive(Args, Default)]
+#[command(group(ArgGroup::new("collection_run").args(["all", "tag"]).multiple(true)))]
+#[command(group(ArgGroup::new("batch").args(["all", "tag", "repeat", "data"]).multiple(true)))]
+#[command(group(ArgGroup::new("reported").args(["all", "tag", "data"]).multiple(true)))]
 pub struct RunArgs {
-    collection: String,
-    request: String,
+    /// Collection of the request, picked interactively if omitted
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: Option<String>,
+
+    /// Request to execute, picked interactively if omitted
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    request: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "request", "stream", "repeat", "output_file", "dry_run", "status_only", "json_path",
+            "jq"
+        ],
+        help = "Execute every request of the collection and report on their results"
+    )]
+    all: bool,
 
-    #[arg(short, long, help = "Select an environment for the request")]
+    #[arg(
+        long,
+        value_name = "EXPR",
+        conflicts_with_all = [
+            "request", "stream", "repeat", "output_file", "dry_run", "status_only", "json_path",
+            "jq"
+        ],
+        help = "Execute the requests of the collection with matching tags, e.g. `smoke && !slow`"
+    )]
+    tag: Option<TagExpression>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "output_file", "dry_run", "status_only",
+            "json_path", "jq", "save_example", "diff", "captures", "all_pages", "conditional",
+            "cache", "offline"
+        ],
+        help = "Execute the request once per row of a csv file or json array, with the values of \
+                the row as variables, and report on each iteration"
+    )]
+    data: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FORMAT:PATH",
+        value_parser = parse_report,
+        requires = "reported",
+        help = "Write a report of the run, as junit or json (can be repeated)"
+    )]
+    report: Vec<(ReportFormat, PathBuf)>,
+
+    #[arg(
+        short,
+        long,
+        help = "Select an environment for the request",
+        add = ArgValueCompleter::new(completion::complete_environments)
+    )]
     environment: Option<String>,
 
-    #[arg(short, long, help = "Apply a json-path filter to the response")]
+    #[arg(
+        long,
+        help = "Load global variables from a dotenv file, on top of the collection's .env"
+    )]
+    env_file: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        value_parser = parse_json_path,
+        help = "Apply a json-path filter to the response"
+    )]
     json_path: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "FILTER",
+        conflicts_with = "json_path",
+        help = "Apply a jq filter to the response, e.g. `.items | map(select(.active) | .id)`"
+    )]
+    jq: Option<JqFilter>,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "no_body", "headers_only", "status_only", "hex", "stream", "output_file"
+        ],
+        help = "Display a json array body in columns, one row per item, as a `table` or `csv`"
+    )]
+    format: Option<TabularFormat>,
+
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        requires = "format",
+        help = "Fields displayed as columns, e.g. `id,owner.name`, every field if omitted"
+    )]
+    fields: Vec<String>,
+
     #[arg(long, help = "Disable display of the headers")]
     no_headers: bool,
 
-    #[arg(long, help = "Display only the headers of the response")]
+    #[arg(
+        long,
+        conflicts_with_all = ["no_headers", "no_body"],
+        help = "Display only the headers of the response"
+    )]
     headers_only: bool,
+
+    #[arg(long, help = "Disable display of the body")]
+    no_body: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "no_headers", "headers_only", "raw_headers", "no_body", "json_path", "jq", "stream",
+            "repeat"
+        ],
+        help = "Display only the status code of the response"
+    )]
+    status_only: bool,
+
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = [
+            "status_only", "raw_headers", "stream", "repeat", "dry_run", "export", "all", "tag"
+        ],
+        help = "Display nothing and fail unless the response has a 2xx or expected status, or \
+                the gRPC call succeeded"
+    )]
+    quiet: bool,
+
+    #[arg(
+        long,
+        value_name = "STATUS",
+        conflicts_with_all = ["stream", "repeat", "dry_run", "all", "tag"],
+        help = "Fail unless the response has this status, e.g. 201 or 2xx, replacing the one of \
+                the request (can be repeated)"
+    )]
+    expect_status: Vec<StatusMatcher>,
+
+    #[arg(
+        long,
+        conflicts_with = "no_headers",
+        help = "Display the status line and every header value, with the values of a header grouped together"
+    )]
+    raw_headers: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "json_path", "jq", "no_body", "status_only", "stream", "output_file"
+        ],
+        help = "Display the body as a hexdump"
+    )]
+    hex: bool,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = config().output.unwrap_or_default(),
+        help = "Select how the response is displayed"
+    )]
+    output: OutputMode,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the response body to a file instead of displaying it"
+    )]
+    output_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "data", "output_file", "dry_run", "diff",
+            "all_pages"
+        ],
+        help = "Copy the response body, or its json-path or jq matches, to the clipboard (served by \
+                a background process on Linux until it is replaced)"
+    )]
+    copy: bool,
+
+    #[arg(
+        short,
+        long,
+        value_parser = humantime::parse_duration,
+        help = "Override the request timeout (e.g. 500ms, 30s)"
+    )]
+    timeout: Option<Duration>,
+
+    #[arg(
+        long,
+        help = "Don't follow redirects, regardless of the request configuration"
+    )]
+    no_follow: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["stream", "output_file"],
+        help = "Keep compressed response bodies as received"
+    )]
+    no_decompress: bool,
+
+    #[arg(long, help = "Number of times to retry a failed request")]
+    retries: Option<u32>,
+
+    #[arg(
+        long,
+        help = "Send the request through a proxy (http, https or socks5)"
+    )]
+    proxy: Option<String>,
+
+    #[arg(
+        short = '4',
+        long,
+        conflicts_with = "ipv6",
+        help = "Only connect to IPv4 addresses"
+    )]
+    ipv4: bool,
+
+    #[arg(short = '6', long, help = "Only connect to IPv6 addresses")]
+    ipv6: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Send the ID of the run in this header, instead of the one of the collection"
+    )]
+    request_id_header: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "TRACEPARENT",
+        num_args = 0..=1,
+        help = "Propagate a trace in the traceparent header, a new one if no value is given"
+    )]
+    traceparent: Option<Option<TraceParent>>,
+
+    #[arg(
+        long,
+        requires = "traceparent",
+        help = "Vendor specific trace data sent in the tracestate header"
+    )]
+    tracestate: Option<String>,
+
+    #[arg(
+        long = "var",
+        value_name = "NAME=VALUE",
+        value_parser = parse_key_value,
+        help = "Set a variable, overriding any other definition (can be repeated)"
+    )]
+    vars: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        help = "Print server-sent events as they arrive instead of waiting for the whole body"
+    )]
+    stream: bool,
+
+    #[arg(long, help = "Stop streaming after receiving this many events")]
+    max_events: Option<usize>,
+
+    #[arg(
+        long,
+        value_parser = humantime::parse_duration,
+        help = "Stop streaming after this duration (e.g. 30s, 5m)"
+    )]
+    max_duration: Option<Duration>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["stream", "output_file"],
+        help = "Execute the request N times and report on the latencies and errors"
+    )]
+    repeat: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "M",
+        default_value_t = 1,
+        requires = "repeat",
+        help = "Number of requests in flight when using --repeat"
+    )]
+    concurrency: usize,
+
+    #[arg(
+        long,
+        value_name = "N/s",
+        value_parser = parse_rate,
+        requires = "batch",
+        help = "Maximum number of requests per second of a collection run or --repeat, e.g. 5 or \
+                30/min"
+    )]
+    rate: Option<f64>,
+
+    #[arg(
+        long,
+        value_parser = humantime::parse_duration,
+        requires = "batch",
+        help = "Time to wait between the requests of a collection run or --repeat (e.g. 500ms, 2s)"
+    )]
+    delay: Option<Duration>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["collection_run", "stream", "repeat", "output_file", "dry_run"],
+        help = "Save the response as an example of the request"
+    )]
+    save_example: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "output_file", "dry_run", "save_example"
+        ],
+        help = "Compare the response with an example of the request"
+    )]
+    diff: Option<String>,
+
+    #[arg(
+        long = "capture",
+        value_name = "NAME=JSON_PATH",
+        value_parser = parse_capture,
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "output_file", "dry_run", "diff"
+        ],
+        help = "Save a value of the response as a runtime variable of the collection (can be repeated)"
+    )]
+    captures: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        requires = "captures",
+        help = "Print the captured values as shell exports instead of saving them"
+    )]
+    export: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "output_file", "dry_run", "save_example", "diff",
+            "captures", "status_only", "raw_headers", "headers_only", "hex"
+        ],
+        help = "Request all the pages of a paginated list and print their items as a single json \
+                array"
+    )]
+    all_pages: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["collection_run", "stream", "repeat", "output_file", "all_pages"],
+        help = "Send the ETag and Last-Modified of the last response in If-None-Match and \
+                If-Modified-Since, to only get the response if it changed"
+    )]
+    conditional: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["collection_run", "stream", "repeat", "output_file", "dry_run"],
+        help = "Keep the response in the cache of the collection, to be replayed with --offline"
+    )]
+    cache: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "collection_run", "stream", "repeat", "output_file", "dry_run", "all_pages",
+            "conditional", "cache"
+        ],
+        help = "Replay the cached response of the request instead of sending it"
+    )]
+    offline: bool,
+
+    #[arg(
+        short,
+        long,
+        action = ArgAction::Count,
+        help = "Print the request as it is sent, -vv also prints the connection and response headers"
+    )]
+    verbose: u8,
+
+    #[arg(long, help = "Print the rendered request instead of sending it")]
+    dry_run: bool,
+
+    #[arg(
+        long,
+        requires = "dry_run",
+        help = "Hide the value of sensitive headers in the dry run output"
+    )]
+    mask_secrets: bool,
+}
+
+fn parse_key_value(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => Err(format!("invalid NAME=VALUE: `{}`", s)),
+    }
+}
+
+fn parse_header(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_string(), v.trim().to_string())),
+        _ => Err(format!("invalid NAME:VALUE: `{}`", s)),
+    }
+}
+
+fn parse_capture(s: &str) -> std::result::Result<(String, String), String> {
+    let (name, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid NAME=JSON_PATH: `{}`", s))?;
+
+    let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Err(format!("invalid variable name: `{}`", name));
+    }
+
+    Ok((name.to_string(), parse_json_path(path)?))
+}
+
+fn parse_json_path(s: &str) -> std::result::Result<String, String> {
+    JsonPathInst::from_str(s)
+        .map(|_| s.to_string())
+        .map_err(|e| format!("invalid json-path: {}", e))
+}
+
+fn parse_json(s: &str) -> std::result::Result<serde_json::Value, String> {
+    serde_json::from_str(s).map_err(|e| format!("invalid json: {}", e))
+}
+
+/// Parse a number of requests per second, or per minute or hour, e.g. `5`, `5/s` or `30/min`.
+fn parse_rate(s: &str) -> std::result::Result<f64, String> {
+    let invalid = || format!("invalid rate: `{}`, expected a number like 5 or 30/min", s);
+
+    let (n, unit) = s.split_once('/').unwrap_or((s, "s"));
+    let seconds = match unit {
+        "s" | "sec" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(invalid()),
+    };
+    let n: f64 = n.trim().parse().map_err(|_| invalid())?;
+    if !n.is_finite() || n <= 0.0 {
+        return Err(invalid());
+    }
+
+    Ok(n / seconds)
+}
+
+fn parse_report(s: &str) -> std::result::Result<(ReportFormat, PathBuf), String> {
+    match s.split_once(':') {
+        Some((f, p)) if !p.is_empty() => Ok((f.parse()?, PathBuf::from(p))),
+        _ => Err(format!("invalid FORMAT:PATH: `{}`", s)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Formatted table with the status, latency, headers and body
+    #[default]
+    Table,
+
+    /// Raw response body only
+    Body,
+
+    /// Json document with the status, latency, headers and body
+    Json,
+}
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Collection to check, all the collections are checked if omitted
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: Option<String>,
+}
+
+#[derive(Args)]
+pub struct GrepArgs {
+    /// Text to search for
+    pattern: String,
+
+    /// Collection to search, all the collections are searched if omitted
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: Option<String>,
+
+    #[arg(
+        short = 'E',
+        long,
+        help = "Interpret the pattern as a regular expression"
+    )]
+    regex: bool,
+
+    #[arg(short, long, help = "Ignore case when matching")]
+    ignore_case: bool,
+}
+
+#[derive(Args)]
+pub struct MockArgs {
+    /// Collection to serve
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: String,
+
+    #[arg(long, default_value = "127.0.0.1", help = "Address to listen on")]
+    host: IpAddr,
+
+    #[arg(short, long, default_value_t = 8080, help = "Port to listen on")]
+    port: u16,
+}
+
+#[derive(Args)]
+pub struct RecordArgs {
+    /// Collection to save the requests in
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: String,
+
+    #[arg(
+        long,
+        help = "Base url of the server the requests are forwarded to, e.g. https://api.example.com"
+    )]
+    target: Url,
+
+    #[arg(long, default_value = "127.0.0.1", help = "Address to listen on")]
+    host: IpAddr,
+
+    #[arg(short, long, default_value_t = 8080, help = "Port to listen on")]
+    port: u16,
+}
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Collection of the request
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    collection: String,
+
+    /// Request to execute
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    request: String,
+
+    #[arg(
+        short,
+        long,
+        help = "Select an environment for the request",
+        add = ArgValueCompleter::new(completion::complete_environments)
+    )]
+    environment: Option<String>,
+
+    #[arg(
+        long,
+        help = "Load global variables from a dotenv file, on top of the collection's .env"
+    )]
+    env_file: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = config().output.unwrap_or_default(),
+        help = "Select how the response is displayed"
+    )]
+    output: OutputMode,
 }
 
 #[derive(Args)]
@@ -97,6 +730,15 @@ pub enum CollectionCmd {
 
     /// List available collections
     List,
+
+    /// Delete a collection, with all its requests and environments
+    Delete(CollectionDeleteArgs),
+
+    /// Rename a collection
+    Rename(CollectionRenameArgs),
+
+    /// Create a collection from an Insomnia export or a Bruno collection
+    Import(CollectionImportArgs),
 }
 
 #[derive(Args)]
@@ -112,7 +754,51 @@ pub struct CollectionCreateArgs {
 #[derive(Args)]
 pub struct CollectionEditArgs {
     /// Name of the collection to edit
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    name: String,
+}
+
+#[derive(Args)]
+pub struct CollectionDeleteArgs {
+    /// Name of the collection to delete
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    name: String,
+
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct CollectionRenameArgs {
+    /// Name of the collection to rename
+    #[arg(add = ArgValueCompleter::new(completion::complete_collections))]
+    name: String,
+
+    /// New name of the collection
+    new_name: String,
+}
+
+#[derive(Args)]
+pub struct CollectionImportArgs {
+    /// Name of the collection to create
     name: String,
+
+    /// Insomnia export file, Bruno collection directory or Bruno request file
+    path: PathBuf,
+
+    /// Format of the import, guessed from the path if omitted
+    #[arg(short, long, value_enum)]
+    format: Option<ImportFormat>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// Insomnia export, in the v4 json format
+    Insomnia,
+
+    /// Bruno collection directory or `.bru` file
+    Bruno,
 }
 
 #[derive(Subcommand)]
@@ -125,12 +811,21 @@ pub enum EnvironmentCmd {
 
     /// List available environment
     List(EnvironmentListArgs),
+
+    /// Delete an environment
+    Delete(EnvironmentDeleteArgs),
+
+    /// Rename an environment
+    Rename(EnvironmentRenameArgs),
+
+    /// Copy an environment under a new name
+    Copy(EnvironmentCopyArgs),
 }
 
 #[derive(Args)]
 pub struct EnvironmentCreateArgs {
     /// Name of the collection in which to create the environment
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
 
     /// Name of the environment to create
@@ -144,19 +839,67 @@ pub struct EnvironmentCreateArgs {
 #[derive(Args)]
 pub struct EnvironmentEditArgs {
     /// Name of the collection
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
 
     /// Name of the environment to create
+    #[arg(add = ArgValueCompleter::new(completion::complete_environments))]
     name: String,
 }
 
 #[derive(Args)]
 pub struct EnvironmentListArgs {
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
 }
 
+#[derive(Args)]
+pub struct EnvironmentDeleteArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the environment to delete
+    #[arg(add = ArgValueCompleter::new(completion::complete_environments))]
+    name: String,
+
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct EnvironmentRenameArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the environment to rename
+    #[arg(add = ArgValueCompleter::new(completion::complete_environments))]
+    name: String,
+
+    /// New name of the environment
+    new_name: String,
+}
+
+#[derive(Args)]
+pub struct EnvironmentCopyArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the environment to copy
+    #[arg(add = ArgValueCompleter::new(completion::complete_environments))]
+    name: String,
+
+    /// Name of the new environment
+    new_name: String,
+
+    /// Edit after copying
+    #[arg(short, long)]
+    edit: bool,
+}
+
 #[derive(Subcommand)]
 pub enum RequestCmd {
     /// Create a new request
@@ -167,12 +910,25 @@ pub enum RequestCmd {
 
     /// List available request
     List(RequestListArgs),
+
+    /// Print the definition of a request
+    Show(RequestShowArgs),
+
+    /// Print a request as an equivalent curl command
+    ExportCurl(RequestExportCurlArgs),
+
+    /// Delete a request
+    Delete(RequestDeleteArgs),
+
+    /// Rename a request, or move it to another folder using `:` as separator
+    #[command(visible_alias = "move")]
+    Rename(RequestRenameArgs),
 }
 
 #[derive(Args)]
 pub struct RequestCreateArgs {
     /// Name of the collection in which to create the request
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
 
     /// Name of the request to create
@@ -181,28 +937,285 @@ pub struct RequestCreateArgs {
     /// Edit after creating
     #[arg(short, long)]
     edit: bool,
+
+    /// Start from a built-in template
+    #[arg(short, long, value_enum)]
+    template: Option<RequestTemplate>,
+
+    /// Start from a copy of another request of the collection
+    #[arg(
+        long,
+        value_name = "REQUEST",
+        conflicts_with = "template",
+        add = ArgValueCompleter::new(completion::complete_requests)
+    )]
+    from: Option<String>,
+
+    /// Method of the request, e.g. POST
+    #[arg(short, long)]
+    method: Option<HttpMethod>,
+
+    /// Url of the request
+    #[arg(short, long)]
+    url: Option<String>,
+
+    /// Add a header to the request (can be repeated)
+    #[arg(
+        short = 'H',
+        long = "header",
+        value_name = "NAME:VALUE",
+        value_parser = parse_header
+    )]
+    headers: Vec<(String, String)>,
+
+    /// Json body of the request
+    #[arg(long, value_name = "JSON", value_parser = parse_json)]
+    body_json: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RequestTemplate {
+    /// GET request accepting json
+    Get,
+
+    /// POST request with a json body
+    JsonPost,
+
+    /// GraphQL query with variables
+    Graphql,
+
+    /// POST request with an url-encoded form
+    Form,
+
+    /// PUT request uploading a file
+    Upload,
 }
 
 #[derive(Args)]
 pub struct RequestEditArgs {
     /// Name of the collection
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
 
     /// Name of the request to create
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
     name: String,
 }
 
 #[derive(Args)]
 pub struct RequestListArgs {
-    #[arg(value_name = "COLLECTION")]
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    #[arg(
+        short,
+        long,
+        value_name = "TEXT",
+        help = "Only list the requests whose name or url contains TEXT"
+    )]
+    filter: Option<String>,
+
+    #[arg(long, help = "Print the requests as a json document")]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct RequestShowArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the request to show
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    name: String,
+
+    /// Select an environment for the request
+    #[arg(
+        short,
+        long,
+        requires = "resolved",
+        add = ArgValueCompleter::new(completion::complete_environments)
+    )]
+    environment: Option<String>,
+
+    /// Print the request as it would be sent, with its variables resolved
+    #[arg(long)]
+    resolved: bool,
+
+    /// Hide the value of sensitive headers
+    #[arg(long, requires = "resolved")]
+    mask_secrets: bool,
+}
+
+#[derive(Args)]
+pub struct RequestExportCurlArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the request to export
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    name: String,
+
+    /// Select an environment for the request
+    #[arg(short, long, add = ArgValueCompleter::new(completion::complete_environments))]
+    environment: Option<String>,
+}
+
+#[derive(Args)]
+pub struct RequestDeleteArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
     collection_name: String,
+
+    /// Name of the request to delete
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    name: String,
+
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct RequestRenameArgs {
+    /// Name of the collection
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the request to rename
+    #[arg(add = ArgValueCompleter::new(completion::complete_requests))]
+    name: String,
+
+    /// New name of the request
+    new_name: String,
+}
+
+#[derive(Subcommand)]
+pub enum CookiesCmd {
+    /// List the cookies saved for a collection
+    List(CookiesListArgs),
+
+    /// Delete the cookies saved for a collection
+    Clear(CookiesClearArgs),
+}
+
+#[derive(Args)]
+pub struct CookiesListArgs {
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+}
+
+#[derive(Args)]
+pub struct CookiesClearArgs {
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+}
+
+#[derive(Subcommand)]
+pub enum SecretCmd {
+    /// Store a secret, reading the value from stdin if it is not specified
+    Set(SecretSetArgs),
+
+    /// Print the value of a secret
+    Get(SecretNameArgs),
+
+    /// Delete a secret
+    Delete(SecretNameArgs),
+}
+
+#[derive(Args)]
+pub struct SecretSetArgs {
+    /// Name of the secret
+    name: String,
+
+    /// Value of the secret
+    value: Option<String>,
 }
 
+#[derive(Args)]
+pub struct SecretNameArgs {
+    /// Name of the secret
+    name: String,
+}
+
+#[derive(Subcommand)]
+pub enum VarCmd {
+    /// Set a runtime variable
+    Set(VarSetArgs),
+
+    /// Print the value of a runtime variable
+    Get(VarNameArgs),
+
+    /// Delete a runtime variable
+    Unset(VarNameArgs),
+}
+
+#[derive(Args)]
+pub struct VarSetArgs {
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the variable
+    key: String,
+
+    /// Value of the variable
+    value: String,
+}
+
+#[derive(Args)]
+pub struct VarNameArgs {
+    #[arg(value_name = "COLLECTION", add = ArgValueCompleter::new(completion::complete_collections))]
+    collection_name: String,
+
+    /// Name of the variable
+    key: String,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCmd {
+    /// Print the value of a configuration key
+    Get(ConfigKeyArgs),
+
+    /// Set the value of a configuration key
+    Set(ConfigSetArgs),
+
+    /// Remove a configuration key, restoring its default
+    Unset(ConfigKeyArgs),
+
+    /// List the configured keys with their values
+    List,
+
+    /// Print the path of the configuration file
+    Path,
+}
+
+#[derive(Args)]
+pub struct ConfigKeyArgs {
+    /// Configuration key, e.g. `editor` or `default_collection`
+    key: String,
+}
+
+#[derive(Args)]
+pub struct ConfigSetArgs {
+    /// Configuration key, e.g. `editor` or `default_collection`
+    key: String,
+
+    /// Value of the key
+    value: String,
+}
+
+/// Print the script registering the dynamic completions for `shell`. The script calls back into
+/// the binary with the `COMPLETE` environment variable set to get the candidates.
 pub fn generate_shell_completion(shell: Shell) -> Result<()> {
-    let mut cmd = Cli::command();
-    let name = cmd.get_name().to_string();
-    generate(shell, &mut cmd, name, &mut io::stdout());
+    let cmd = Cli::command();
+    let name = cmd.get_name();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .expect("all the shells supported by clap_complete have a dynamic completer");
+
+    completer.write_registration("COMPLETE", name, name, name, &mut io::stdout())?;
 
     Ok(())
 }
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::{ApiResponse, CachedResponse};
+use serde::{Deserialize, Serialize};
+
+use super::utils::get_responses_file_path;
+
+/// Last response of a request in an environment.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<String>,
+    #[serde(flatten)]
+    response: CachedResponse,
+}
+
+fn load_cache(collection_name: &str) -> Result<BTreeMap<String, Vec<CacheEntry>>> {
+    let path = get_responses_file_path(collection_name);
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data =
+        fs::read_to_string(&path).map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+    serde_json::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_json_error_with_path(e, &path))
+}
+
+/// Load the cached response of a request in an environment, failing if there is none.
+pub(super) fn load_cached_response(
+    collection_name: &str,
+    request: &str,
+    environment: Option<&str>,
+) -> Result<ApiResponse> {
+    let mut cache = load_cache(collection_name)?;
+
+    cache
+        .remove(request)
+        .and_then(|entries| {
+            entries
+                .into_iter()
+                .find(|e| e.environment.as_deref() == environment)
+        })
+        .ok_or_else(|| ApiClientError::new_response_not_cached(request.to_string()))?
+        .response
+        .replay()
+}
+
+/// Keep the response of a request in an environment, replacing the previous one.
+pub(super) fn save_cached_response(
+    collection_name: &str,
+    request: &str,
+    environment: Option<&str>,
+    res: &ApiResponse,
+) -> Result<()> {
+    let mut cache = load_cache(collection_name)?;
+    let entries = cache.entry(request.to_string()).or_default();
+    entries.retain(|e| e.environment.as_deref() != environment);
+    entries.push(CacheEntry {
+        environment: environment.map(str::to_string),
+        response: CachedResponse::from_response(res),
+    });
+
+    let path = get_responses_file_path(collection_name);
+    fs::write(&path, serde_json::to_string_pretty(&cache)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))
+}
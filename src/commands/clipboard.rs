@@ -0,0 +1,75 @@
+use std::env;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+use api_cli::error::{ApiClientError, Result};
+use arboard::Clipboard;
+
+/// Set on the background process serving the content of the clipboard.
+const HOLDER_ENV_VAR: &str = "API_CLI_CLIPBOARD_HOLDER";
+
+/// Replace the content of the system clipboard with some text.
+///
+/// On Linux, the content of the clipboard is served by the process that set it, and is lost when
+/// it exits. It's handed off to a copy of this process running in the background instead, which
+/// serves it until another application replaces the content of the clipboard.
+pub(super) fn copy_to_clipboard(text: &str) -> Result<()> {
+    // Fail here rather than in the background when there is no clipboard.
+    let mut clipboard = Clipboard::new().map_err(clipboard_error)?;
+
+    if cfg!(target_os = "linux") {
+        drop(clipboard);
+        return spawn_holder(text);
+    }
+
+    clipboard.set_text(text).map_err(clipboard_error)
+}
+
+/// Serve the text read on stdin as the content of the clipboard if this process was spawned to
+/// do so, until it is replaced. Returns whether it was.
+pub fn hold_clipboard_content() -> bool {
+    if env::var_os(HOLDER_ENV_VAR).is_none() {
+        return false;
+    }
+
+    let mut text = String::new();
+    if io::stdin().read_to_string(&mut text).is_ok() {
+        if let Ok(mut clipboard) = Clipboard::new() {
+            let _ = serve(&mut clipboard, text);
+        }
+    }
+
+    true
+}
+
+#[cfg(target_os = "linux")]
+fn serve(clipboard: &mut Clipboard, text: String) -> std::result::Result<(), arboard::Error> {
+    use arboard::SetExtLinux;
+
+    clipboard.set().wait().text(text)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn serve(clipboard: &mut Clipboard, text: String) -> std::result::Result<(), arboard::Error> {
+    clipboard.set_text(text)
+}
+
+fn spawn_holder(text: &str) -> Result<()> {
+    let mut holder = Command::new(env::current_exe()?)
+        .env(HOLDER_ENV_VAR, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| ApiClientError::new_clipboard_error(e.to_string()))?;
+
+    // The holder takes over the clipboard once its stdin is closed, when it is dropped.
+    let mut stdin = holder.stdin.take().expect("stdin of the holder is piped");
+    stdin
+        .write_all(text.as_bytes())
+        .map_err(|e| ApiClientError::new_clipboard_error(e.to_string()))
+}
+
+fn clipboard_error(e: arboard::Error) -> ApiClientError {
+    ApiClientError::new_clipboard_error(e.to_string())
+}
@@ -1,22 +1,39 @@
 use std::fs;
 use std::fs::File;
+use std::path::Path;
 
 use api_cli::error::{ApiClientError, Result};
-use api_cli::CollectionModel;
+use api_cli::import::{import_bruno, import_insomnia, ImportedCollection};
+use api_cli::{CollectionModel, FOLDER_FILE_NAME};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stderr;
 
 use super::utils::{
+    confirm,
     ensure_collection_directory,
     get_collection_file_path,
     get_collections_directory,
+    get_environment_file_path,
     open_file_in_editor,
 };
-use super::{CollectionCmd, CollectionCreateArgs, CollectionEditArgs};
+use super::{
+    CollectionCmd,
+    CollectionCreateArgs,
+    CollectionDeleteArgs,
+    CollectionEditArgs,
+    CollectionImportArgs,
+    CollectionRenameArgs,
+    ImportFormat,
+};
 
 pub fn run_collection_command(cmd: CollectionCmd) -> Result<()> {
     match cmd {
         CollectionCmd::Create(args) => create_collection(args),
         CollectionCmd::Edit(args) => edit_collection(args),
         CollectionCmd::List => list_collections(),
+        CollectionCmd::Delete(args) => delete_collection(args),
+        CollectionCmd::Rename(args) => rename_collection(args),
+        CollectionCmd::Import(args) => import_collection(args),
     }
 }
 
@@ -63,7 +80,118 @@ fn list_collections() -> Result<()> {
     Ok(())
 }
 
-fn find_collections() -> Result<Vec<String>> {
+fn delete_collection(args: CollectionDeleteArgs) -> Result<()> {
+    let collection_dir_path = ensure_collection_directory(&args.name)?;
+
+    let prompt = format!(
+        "Delete collection {} with all its requests and environments?",
+        args.name
+    );
+    if !args.force && !confirm(&prompt)? {
+        return Ok(());
+    }
+
+    fs::remove_dir_all(&collection_dir_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &collection_dir_path))?;
+
+    Ok(())
+}
+
+fn rename_collection(args: CollectionRenameArgs) -> Result<()> {
+    let collection_dir_path = ensure_collection_directory(&args.name)?;
+
+    let new_collection_dir_path = get_collection_file_path(&args.new_name)
+        .parent()
+        .unwrap()
+        .to_owned();
+    if new_collection_dir_path.exists() {
+        return Err(ApiClientError::new_collection_already_exists(args.new_name));
+    }
+
+    fs::rename(&collection_dir_path, &new_collection_dir_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &collection_dir_path))?;
+
+    Ok(())
+}
+
+fn import_collection(args: CollectionImportArgs) -> Result<()> {
+    let collection_file_path = get_collection_file_path(&args.name);
+    if collection_file_path.exists() {
+        return Err(ApiClientError::new_collection_already_exists(args.name));
+    }
+
+    let format = args.format.unwrap_or_else(|| {
+        if args.path.is_dir() || args.path.extension().is_some_and(|e| e == "bru") {
+            ImportFormat::Bruno
+        } else {
+            ImportFormat::Insomnia
+        }
+    });
+    let imported = match format {
+        ImportFormat::Insomnia => {
+            let data = fs::read_to_string(&args.path)
+                .map_err(|e| ApiClientError::from_io_error_with_path(e, &args.path))?;
+            import_insomnia(&data)?
+        }
+        ImportFormat::Bruno => import_bruno(&args.path)?,
+    };
+
+    write_imported_collection(&args.name, &imported)?;
+
+    for w in &imported.warnings {
+        eprintln!(
+            "{}",
+            format!("warning: {}", w).if_supports_color(Stderr, |w| w.yellow())
+        );
+    }
+    eprintln!(
+        "Imported {} request(s) and {} environment(s) into {}",
+        imported.requests.len(),
+        imported.environments.len(),
+        args.name
+    );
+
+    Ok(())
+}
+
+fn write_imported_collection(name: &str, imported: &ImportedCollection) -> Result<()> {
+    let collection_file_path = get_collection_file_path(name);
+    let collection_dir_path = collection_file_path.parent().unwrap();
+
+    write_yaml(&collection_file_path, &imported.collection)?;
+
+    for (folder, model) in &imported.folders {
+        write_yaml(
+            &collection_dir_path.join(folder).join(FOLDER_FILE_NAME),
+            model,
+        )?;
+    }
+
+    for (request_name, request) in &imported.requests {
+        write_yaml(&collection_dir_path.join(request_name.to_path()), request)?;
+    }
+
+    for (environment_name, environment) in &imported.environments {
+        write_yaml(
+            &get_environment_file_path(name, environment_name),
+            environment,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_yaml<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    let writer =
+        File::create(path).map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+    serde_yaml::to_writer(writer, value)?;
+
+    Ok(())
+}
+
+pub(super) fn find_collections() -> Result<Vec<String>> {
     let collections_directory = get_collections_directory();
     if !collections_directory.exists() {
         return Ok(vec![]);
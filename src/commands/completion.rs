@@ -0,0 +1,50 @@
+use std::env;
+use std::ffi::OsStr;
+
+use clap_complete::CompletionCandidate;
+
+use super::collection::find_collections;
+use super::environment::find_environments;
+use super::request::find_requests;
+
+pub fn complete_collections(current: &OsStr) -> Vec<CompletionCandidate> {
+    candidates(find_collections().unwrap_or_default(), current)
+}
+
+pub fn complete_requests(current: &OsStr) -> Vec<CompletionCandidate> {
+    let names = match find_collection_in_args() {
+        Some(c) => find_requests(c).unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    candidates(names, current)
+}
+
+pub fn complete_environments(current: &OsStr) -> Vec<CompletionCandidate> {
+    let names = match find_collection_in_args() {
+        Some(c) => find_environments(c).unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    candidates(names, current)
+}
+
+/// The completers only receive the value being completed, the collection is the first word of the
+/// command line being completed that is the name of an existing collection.
+fn find_collection_in_args() -> Option<String> {
+    let collections = find_collections().ok()?;
+
+    env::args()
+        .skip(1)
+        .find(|a| collections.iter().any(|c| c == a))
+}
+
+fn candidates(names: Vec<String>, current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+
+    names
+        .into_iter()
+        .filter(|n| n.starts_with(current.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect()
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::{ApiResponse, Validators};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use super::utils::get_validators_file_path;
+
+/// Validators of the last response of a request in an environment.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedValidators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<String>,
+    #[serde(flatten)]
+    validators: Validators,
+}
+
+fn load_cache(collection_name: &str) -> Result<BTreeMap<String, Vec<CachedValidators>>> {
+    let path = get_validators_file_path(collection_name);
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data =
+        fs::read_to_string(&path).map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+    serde_json::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_json_error_with_path(e, &path))
+}
+
+/// Load the validators of the last response of a request in an environment, if it had any.
+pub(super) fn load_validators(
+    collection_name: &str,
+    request: &str,
+    environment: Option<&str>,
+) -> Result<Option<Validators>> {
+    let mut cache = load_cache(collection_name)?;
+
+    Ok(cache.remove(request).and_then(|entries| {
+        entries
+            .into_iter()
+            .find(|e| e.environment.as_deref() == environment)
+            .map(|e| e.validators)
+    }))
+}
+
+/// Keep the validators of a response for the next conditional request.
+///
+/// A successful response replaces the previous validators, or forgets them if it has none. A `304
+/// Not Modified` only replaces them when it comes with new ones, other responses are ignored.
+pub(super) fn save_validators(
+    collection_name: &str,
+    request: &str,
+    environment: Option<&str>,
+    res: &ApiResponse,
+) -> Result<()> {
+    let validators = Validators::from_headers(res.headers());
+    let validators = match res.status() {
+        StatusCode::NOT_MODIFIED if validators.is_none() => return Ok(()),
+        StatusCode::NOT_MODIFIED => validators,
+        s if s.is_success() => validators,
+        _ => return Ok(()),
+    };
+
+    let mut cache = load_cache(collection_name)?;
+    let entries = cache.entry(request.to_string()).or_default();
+    entries.retain(|e| e.environment.as_deref() != environment);
+    if let Some(validators) = validators {
+        entries.push(CachedValidators {
+            environment: environment.map(str::to_string),
+            validators,
+        });
+    }
+    if entries.is_empty() {
+        cache.remove(request);
+    }
+
+    let path = get_validators_file_path(collection_name);
+    fs::write(&path, serde_json::to_string_pretty(&cache)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))
+}
@@ -0,0 +1,251 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs};
+
+use api_cli::error::{ApiClientError, Result};
+use colored_json::{ColorMode, Output};
+use log::debug;
+use once_cell::sync::OnceCell;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stderr;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use super::utils::read_file;
+use super::{ConfigCmd, ConfigKeyArgs, ConfigSetArgs, OutputMode, APP_NAME};
+
+static CONFIG_FILE_NAME: &str = "config.yaml";
+
+static CONFIG_KEYS: [&str; 8] = [
+    "editor",
+    "output",
+    "color",
+    "timeout",
+    "base_directory",
+    "default_collection",
+    "trace_url",
+    "history",
+];
+
+static CONFIG: OnceCell<ConfigModel> = OnceCell::new();
+
+/// User preferences, read from `config.yaml` in the configuration directory of the user.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigModel {
+    /// Editor used to edit the files, `$EDITOR` or `vi` if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+
+    /// How responses are displayed when `--output` is not specified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputMode>,
+
+    /// Whether the output is colored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorChoice>,
+
+    /// Timeout of the requests that don't define one, nor their collection
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Directory of the collections, overridden by `$API_CLI_BASE_DIRECTORY`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_directory: Option<PathBuf>,
+
+    /// Collection used by `run` when none is specified, instead of picking one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_collection: Option<String>,
+
+    /// Link to a trace in a tracing UI, where `{{traceId}}` is replaced by the ID of the trace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_url: Option<String>,
+
+    /// Whether the executions of the requests are recorded in the history of their collection,
+    /// `true` if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color the output when it is a terminal
+    #[default]
+    Auto,
+
+    /// Always color the output
+    Always,
+
+    /// Never color the output
+    Never,
+}
+
+/// How json documents printed on stdout are colored, according to the color preference.
+pub(super) fn json_color_mode() -> ColorMode {
+    match config().color.unwrap_or_default() {
+        ColorChoice::Auto => ColorMode::Auto(Output::StdOut),
+        ColorChoice::Always => ColorMode::On,
+        ColorChoice::Never => ColorMode::Off,
+    }
+}
+
+/// Load the configuration file and apply the color preference. Must be called once, before the
+/// configuration is used, including by the defaults of the arguments. An invalid configuration is
+/// reported and replaced by the defaults, so that it can still be fixed with the `config`
+/// commands.
+pub fn load_config() {
+    let path = get_config_file_path();
+    let config = match path.exists() {
+        true => match read_file::<Option<ConfigModel>>(&path) {
+            Ok(c) => c.unwrap_or_default(),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format!("warning: ignoring the configuration: {}", e)
+                        .if_supports_color(Stderr, |s| s.yellow())
+                );
+                ConfigModel::default()
+            }
+        },
+        false => ConfigModel::default(),
+    };
+    debug!("Config: {:#?}", config);
+
+    match config.color {
+        Some(ColorChoice::Always) => owo_colors::set_override(true),
+        Some(ColorChoice::Never) => owo_colors::set_override(false),
+        _ => {}
+    }
+
+    let loaded = CONFIG.set(config).is_ok();
+    debug_assert!(loaded, "the configuration was used before being loaded");
+}
+
+/// The configuration of the user, or the defaults if it has not been loaded.
+pub(super) fn config() -> &'static ConfigModel {
+    CONFIG.get_or_init(ConfigModel::default)
+}
+
+pub(super) fn get_config_file_path() -> PathBuf {
+    if let Ok(p) = env::var("API_CLI_CONFIG") {
+        return PathBuf::from(p);
+    }
+
+    let mut p = dirs::config_dir().unwrap_or(PathBuf::from("."));
+    p.push(APP_NAME);
+    p.push(CONFIG_FILE_NAME);
+
+    p
+}
+
+pub fn run_config_command(cmd: ConfigCmd) -> Result<()> {
+    match cmd {
+        ConfigCmd::Get(args) => get_config_value(args),
+        ConfigCmd::Set(args) => set_config_value(args),
+        ConfigCmd::Unset(args) => unset_config_value(args),
+        ConfigCmd::List => list_config(),
+        ConfigCmd::Path => {
+            println!("{}", get_config_file_path().display());
+            Ok(())
+        }
+    }
+}
+
+fn get_config_value(args: ConfigKeyArgs) -> Result<()> {
+    let key = ensure_config_key(&args.key)?;
+    if let Some(v) = read_config_mapping(&get_config_file_path())?.get(key) {
+        println!("{}", format_value(v)?);
+    }
+
+    Ok(())
+}
+
+fn set_config_value(args: ConfigSetArgs) -> Result<()> {
+    let key = ensure_config_key(&args.key)?;
+    let path = get_config_file_path();
+
+    let value = match args.value.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::from(args.value),
+    };
+
+    let mut mapping = read_config_mapping(&path)?;
+    mapping.insert(Value::from(key), value);
+
+    write_config_mapping(&path, mapping)
+}
+
+/// Unknown keys can be unset, and the rest of the configuration is not validated, to be able to
+/// fix an invalid configuration.
+fn unset_config_value(args: ConfigKeyArgs) -> Result<()> {
+    let path = get_config_file_path();
+
+    let mut mapping = read_config_mapping(&path)?;
+    if !mapping.contains_key(args.key.as_str()) {
+        ensure_config_key(&args.key)?;
+        return Ok(());
+    }
+
+    mapping.remove(args.key.as_str());
+    write_config_file(&path, &Value::Mapping(mapping))
+}
+
+fn list_config() -> Result<()> {
+    for (k, v) in read_config_mapping(&get_config_file_path())? {
+        println!("{}: {}", format_value(&k)?, format_value(&v)?);
+    }
+
+    Ok(())
+}
+
+fn ensure_config_key(key: &str) -> Result<&str> {
+    if !CONFIG_KEYS.contains(&key) {
+        return Err(ApiClientError::new_invalid_config_key(format!(
+            "{} (expected one of {})",
+            key,
+            CONFIG_KEYS.join(", ")
+        )));
+    }
+
+    Ok(key)
+}
+
+fn read_config_mapping(path: &Path) -> Result<Mapping> {
+    if !path.exists() {
+        return Ok(Mapping::new());
+    }
+
+    let mapping: Option<Mapping> = read_file(path)?;
+
+    Ok(mapping.unwrap_or_default())
+}
+
+/// Write the configuration, after making sure it is still valid.
+fn write_config_mapping(path: &Path, mapping: Mapping) -> Result<()> {
+    let value = Value::Mapping(mapping);
+    serde_yaml::from_value::<ConfigModel>(value.clone())
+        .map_err(|e| ApiClientError::from_serde_yaml_error_with_path(e, path))?;
+
+    write_config_file(path, &value)
+}
+
+fn write_config_file(path: &Path, value: &Value) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_yaml::to_string(value)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, path))
+}
+
+fn format_value(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        v => Ok(serde_yaml::to_string(v)?.trim_end().to_string()),
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use api_cli::error::{ApiClientError, Result};
+use cookie_store::{CookieExpiration, CookieStore};
+use tabled::settings::Style;
+use tabled::{Table, Tabled};
+
+use super::utils::{ensure_collection_directory, get_cookie_file_path};
+use super::{CookiesClearArgs, CookiesCmd, CookiesListArgs};
+
+#[derive(Tabled)]
+struct CookieRow {
+    domain: String,
+    path: String,
+    name: String,
+    value: String,
+    expires: String,
+}
+
+pub fn run_cookies_command(cmd: CookiesCmd) -> Result<()> {
+    match cmd {
+        CookiesCmd::List(args) => list_cookies(args),
+        CookiesCmd::Clear(args) => clear_cookies(args),
+    }
+}
+
+/// Load the cookies persisted for a collection, or an empty store if there are none.
+pub fn load_cookie_store(collection_name: &str) -> Result<CookieStore> {
+    let cookie_file_path = get_cookie_file_path(collection_name);
+
+    if !cookie_file_path.exists() {
+        return Ok(CookieStore::default());
+    }
+
+    let reader = File::open(&cookie_file_path)
+        .map(BufReader::new)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &cookie_file_path))?;
+
+    cookie_store::serde::json::load(reader)
+        .map_err(|e| ApiClientError::from_cookie_store_error_with_path(e, &cookie_file_path))
+}
+
+/// Persist the cookies of a collection, including session cookies, so they can be reused by the
+/// next run.
+pub fn save_cookie_store(collection_name: &str, store: &CookieStore) -> Result<()> {
+    let cookie_file_path = get_cookie_file_path(collection_name);
+
+    let mut writer = File::create(&cookie_file_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &cookie_file_path))?;
+
+    cookie_store::serde::json::save_incl_expired_and_nonpersistent(store, &mut writer)
+        .map_err(|e| ApiClientError::from_cookie_store_error_with_path(e, &cookie_file_path))
+}
+
+fn list_cookies(args: CookiesListArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let store = load_cookie_store(&args.collection_name)?;
+
+    let rows: Vec<CookieRow> = store
+        .iter_unexpired()
+        .map(|c| CookieRow {
+            domain: c.domain.as_cow().unwrap_or_default().to_string(),
+            path: c.path.to_string(),
+            name: c.name().to_string(),
+            value: c.value().to_string(),
+            expires: match c.expires {
+                CookieExpiration::AtUtc(t) => t.to_string(),
+                CookieExpiration::SessionEnd => "Session".to_string(),
+            },
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut table = Table::new(rows);
+    table.with(Style::modern());
+    println!("{}", table);
+
+    Ok(())
+}
+
+fn clear_cookies(args: CookiesClearArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let cookie_file_path = get_cookie_file_path(&args.collection_name);
+
+    if cookie_file_path.exists() {
+        fs::remove_file(&cookie_file_path)
+            .map_err(|e| ApiClientError::from_io_error_with_path(e, &cookie_file_path))?;
+    }
+
+    Ok(())
+}
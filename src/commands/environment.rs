@@ -5,14 +5,30 @@ use std::path::PathBuf;
 use api_cli::error::{ApiClientError, Result};
 use api_cli::EnvironmentModel;
 
-use super::utils::{ensure_collection_directory, get_environment_file_path, open_file_in_editor};
-use super::{EnvironmentCmd, EnvironmentCreateArgs, EnvironmentEditArgs, EnvironmentListArgs};
+use super::utils::{
+    confirm,
+    ensure_collection_directory,
+    get_environment_file_path,
+    open_file_in_editor,
+};
+use super::{
+    EnvironmentCmd,
+    EnvironmentCopyArgs,
+    EnvironmentCreateArgs,
+    EnvironmentDeleteArgs,
+    EnvironmentEditArgs,
+    EnvironmentListArgs,
+    EnvironmentRenameArgs,
+};
 
 pub fn run_environment_command(cmd: EnvironmentCmd) -> Result<()> {
     match cmd {
         EnvironmentCmd::Create(args) => create_environment(args),
         EnvironmentCmd::Edit(args) => edit_environment(args),
         EnvironmentCmd::List(args) => list_environments(args),
+        EnvironmentCmd::Delete(args) => delete_environment(args),
+        EnvironmentCmd::Rename(args) => rename_environment(args),
+        EnvironmentCmd::Copy(args) => copy_environment(args),
     }
 }
 
@@ -61,7 +77,72 @@ fn list_environments(args: EnvironmentListArgs) -> Result<()> {
     Ok(())
 }
 
-fn find_environments(collection_name: String) -> Result<Vec<String>> {
+fn delete_environment(args: EnvironmentDeleteArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let environment_path = get_environment_file_path(&args.collection_name, &args.name);
+    if !environment_path.exists() {
+        return Err(ApiClientError::new_environment_not_found(args.name));
+    }
+
+    if !args.force && !confirm(&format!("Delete environment {}?", args.name))? {
+        return Ok(());
+    }
+
+    fs::remove_file(&environment_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &environment_path))?;
+
+    Ok(())
+}
+
+fn rename_environment(args: EnvironmentRenameArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let (environment_path, new_environment_path) =
+        get_source_and_target_paths(&args.collection_name, args.name, args.new_name)?;
+
+    fs::rename(&environment_path, &new_environment_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &environment_path))?;
+
+    Ok(())
+}
+
+fn copy_environment(args: EnvironmentCopyArgs) -> Result<()> {
+    let collection_dir = ensure_collection_directory(&args.collection_name)?;
+
+    let (environment_path, new_environment_path) =
+        get_source_and_target_paths(&args.collection_name, args.name, args.new_name)?;
+
+    fs::copy(&environment_path, &new_environment_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &environment_path))?;
+
+    if args.edit {
+        open_file_in_editor(&collection_dir, &new_environment_path)?;
+    }
+
+    Ok(())
+}
+
+/// Get the paths of an existing environment and of a new one that doesn't exist yet.
+fn get_source_and_target_paths(
+    collection_name: &str,
+    name: String,
+    new_name: String,
+) -> Result<(PathBuf, PathBuf)> {
+    let environment_path = get_environment_file_path(collection_name, &name);
+    if !environment_path.exists() {
+        return Err(ApiClientError::new_environment_not_found(name));
+    }
+
+    let new_environment_path = get_environment_file_path(collection_name, &new_name);
+    if new_environment_path.exists() {
+        return Err(ApiClientError::new_environment_already_exists(new_name));
+    }
+
+    Ok((environment_path, new_environment_path))
+}
+
+pub(super) fn find_environments(collection_name: String) -> Result<Vec<String>> {
     let collection_directory = ensure_collection_directory(&collection_name)?;
 
     let mut environment_names = find_environments_in_directory(collection_directory)?;
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use api_cli::error::{ApiClientError, Result};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use regex::{Regex, RegexBuilder};
+
+use super::collection::find_collections;
+use super::environment::find_environments;
+use super::request::find_requests;
+use super::utils::{
+    ensure_collection_directory,
+    get_collection_file_path,
+    get_collections_directory,
+    get_environment_file_path,
+    get_folder_file_paths,
+    get_request_file_path,
+};
+use super::GrepArgs;
+
+pub fn run_grep(args: GrepArgs) -> Result<()> {
+    let pattern = if args.regex {
+        args.pattern.clone()
+    } else {
+        regex::escape(&args.pattern)
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(args.ignore_case)
+        .build()
+        .map_err(|e| ApiClientError::new_invalid_pattern(args.pattern.clone(), e))?;
+
+    let collections = match args.collection {
+        Some(c) => {
+            ensure_collection_directory(&c)?;
+            vec![c]
+        }
+        None => find_collections()?,
+    };
+
+    for collection in collections {
+        for path in collection_files(&collection)? {
+            if path.exists() {
+                search_file(&path, &regex)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The files defining a collection: the collection itself, its folders, requests and environments.
+fn collection_files(collection: &str) -> Result<Vec<PathBuf>> {
+    let mut files = vec![get_collection_file_path(collection)];
+
+    let requests = find_requests(collection.to_string())?;
+    let folders: BTreeSet<PathBuf> = requests
+        .iter()
+        .map(|r| get_folder_file_paths(collection, r))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    files.extend(folders);
+    files.extend(
+        requests
+            .iter()
+            .map(|r| get_request_file_path(collection, r))
+            .collect::<Result<Vec<_>>>()?,
+    );
+
+    files.extend(
+        find_environments(collection.to_string())?
+            .iter()
+            .map(|e| get_environment_file_path(collection, e)),
+    );
+
+    Ok(files)
+}
+
+fn search_file(path: &PathBuf, regex: &Regex) -> Result<()> {
+    let content =
+        fs::read_to_string(path).map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+
+    let base_dir = get_collections_directory();
+    let display_path = path.strip_prefix(&base_dir).unwrap_or(path).display();
+
+    for (idx, line) in content.lines().enumerate() {
+        if !regex.is_match(line) {
+            continue;
+        }
+
+        let mut highlighted = String::with_capacity(line.len());
+        let mut last = 0;
+        for m in regex.find_iter(line) {
+            highlighted.push_str(&line[last..m.start()]);
+            highlighted.push_str(
+                &m.as_str()
+                    .if_supports_color(Stdout, |s| s.red().bold().to_string())
+                    .to_string(),
+            );
+            last = m.end();
+        }
+        highlighted.push_str(&line[last..]);
+
+        println!(
+            "{}: {}",
+            format!("{}:{}", display_path, idx + 1).if_supports_color(Stdout, |l| l.bold()),
+            highlighted.trim()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use api_cli::error::{ApiClientError, Result};
+use log::debug;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stderr;
+use serde::{Deserialize, Serialize};
+
+use super::config::config;
+use super::utils::get_history_file_path;
+
+/// Outcome of the last execution of a request.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct HistoryEntry {
+    pub(super) status: String,
+    /// RFC 3339 timestamp of the execution
+    pub(super) timestamp: String,
+    pub(super) duration_ms: u64,
+    /// Latest executions, oldest first, to show how the latency evolves
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) executions: Vec<PastExecution>,
+}
+
+/// Latency of a past execution of a request, in the environment it was executed in.
+#[derive(Debug, Serialize, Deserialize)]
+pub(super) struct PastExecution {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) environment: Option<String>,
+    pub(super) duration_ms: u64,
+}
+
+/// Number of past executions kept for each request and environment.
+const MAX_EXECUTIONS: usize = 20;
+
+/// Load the last execution of each request of a collection, or nothing if none was recorded.
+pub(super) fn load_history(collection_name: &str) -> Result<BTreeMap<String, HistoryEntry>> {
+    let path = get_history_file_path(collection_name);
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data =
+        fs::read_to_string(&path).map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+    serde_json::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_json_error_with_path(e, &path))
+}
+
+/// Load the latencies of the past executions of a request in an environment, oldest first.
+///
+/// There are none if the history is disabled or can't be read, which only hides the trend of the
+/// latency.
+pub(super) fn load_latencies(
+    collection_name: &str,
+    request: &str,
+    environment: Option<&str>,
+) -> Vec<Duration> {
+    if !history_enabled() {
+        return Vec::new();
+    }
+
+    let history = match load_history(collection_name) {
+        Ok(h) => h,
+        Err(e) => {
+            debug!("Unable to load the latencies of {}: {}", request, e);
+            return Vec::new();
+        }
+    };
+
+    history
+        .get(request)
+        .map(|e| {
+            e.executions
+                .iter()
+                .filter(|x| x.environment.as_deref() == environment)
+                .map(|x| Duration::from_millis(x.duration_ms))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the executions are recorded, as set in the configuration.
+pub(super) fn history_enabled() -> bool {
+    config().history.unwrap_or(true)
+}
+
+/// Record the status of executions of requests of a collection, replacing the previous ones.
+///
+/// The latencies are also appended to the past executions of the requests in the environment.
+/// Nothing is recorded if the history is disabled, and failing to record it is only reported, as
+/// the requests were executed anyway.
+pub(super) fn record_history<I>(collection_name: &str, environment: Option<&str>, executions: I)
+where
+    I: IntoIterator<Item = (String, String, Duration)>,
+{
+    if !history_enabled() {
+        return;
+    }
+
+    if let Err(e) = write_history(collection_name, environment, executions) {
+        eprintln!(
+            "{}",
+            format!("warning: unable to record the history: {}", e)
+                .if_supports_color(Stderr, |s| s.yellow())
+        );
+    }
+}
+
+fn write_history<I>(collection_name: &str, environment: Option<&str>, executions: I) -> Result<()>
+where
+    I: IntoIterator<Item = (String, String, Duration)>,
+{
+    let mut history = load_history(collection_name)?;
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+
+    for (request, status, duration) in executions {
+        let duration_ms = duration.as_millis() as u64;
+        let mut past = history
+            .remove(&request)
+            .map(|e| e.executions)
+            .unwrap_or_default();
+
+        past.push(PastExecution {
+            environment: environment.map(str::to_string),
+            duration_ms,
+        });
+        let in_environment = past
+            .iter()
+            .filter(|x| x.environment.as_deref() == environment)
+            .count();
+        if in_environment > MAX_EXECUTIONS {
+            if let Some(i) = past
+                .iter()
+                .position(|x| x.environment.as_deref() == environment)
+            {
+                past.remove(i);
+            }
+        }
+
+        history.insert(
+            request,
+            HistoryEntry {
+                status,
+                timestamp: timestamp.clone(),
+                duration_ms,
+                executions: past,
+            },
+        );
+    }
+
+    let path = get_history_file_path(collection_name);
+    fs::write(&path, serde_json::to_string_pretty(&history)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))
+}
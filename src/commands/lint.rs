@@ -0,0 +1,89 @@
+use api_cli::error::{ApiClientError, Result};
+use api_cli::lint::{lint_collection, Diagnostic, Severity};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+
+use super::collection::find_collections;
+use super::environment::find_environments;
+use super::request::find_requests;
+use super::utils::{
+    ensure_collection_directory,
+    get_collection_file_path,
+    get_collections_directory,
+    get_environment_file_path,
+    get_request_file_path,
+};
+use super::LintArgs;
+
+pub fn run_lint(args: LintArgs) -> Result<()> {
+    let collections = match args.collection {
+        Some(c) => {
+            ensure_collection_directory(&c)?;
+            vec![c]
+        }
+        None => find_collections()?,
+    };
+
+    let mut diagnostics = Vec::new();
+    for collection in collections {
+        let environments = find_environments(collection.clone())?
+            .iter()
+            .map(|e| get_environment_file_path(&collection, e))
+            .collect::<Vec<_>>();
+        let requests = find_requests(collection.clone())?
+            .iter()
+            .map(|r| get_request_file_path(&collection, r))
+            .collect::<Result<Vec<_>>>()?;
+
+        diagnostics.extend(lint_collection(
+            &get_collection_file_path(&collection),
+            &environments,
+            &requests,
+        ));
+    }
+
+    for d in &diagnostics {
+        print_diagnostic(d);
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    println!("{} error(s), {} warning(s)", errors, warnings);
+
+    if errors > 0 {
+        return Err(ApiClientError::new_lint_error(errors));
+    }
+
+    Ok(())
+}
+
+fn print_diagnostic(diagnostic: &Diagnostic) {
+    let base_dir = get_collections_directory();
+    let path = diagnostic
+        .path
+        .strip_prefix(&base_dir)
+        .unwrap_or(&diagnostic.path)
+        .display();
+    let location = match diagnostic.line {
+        Some(l) => format!("{}:{}", path, l),
+        None => path.to_string(),
+    };
+
+    let severity = diagnostic.severity.to_string();
+    let severity = match diagnostic.severity {
+        Severity::Error => severity.if_supports_color(Stdout, |s| s.red()).to_string(),
+        Severity::Warning => severity
+            .if_supports_color(Stdout, |s| s.yellow())
+            .to_string(),
+    };
+
+    println!(
+        "{}: {}: {}",
+        location.if_supports_color(Stdout, |l| l.bold()),
+        severity,
+        diagnostic.message
+    );
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use api_cli::WIRE_LOG_TARGET;
+use log::{LevelFilter, Log, Metadata, Record};
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Logger printing the wire records of the requests according to the verbosity selected on the
+/// command line, and delegating the other records to `env_logger`, configured with `RUST_LOG`.
+struct CliLogger {
+    inner: env_logger::Logger,
+}
+
+impl CliLogger {
+    fn is_wire_record(metadata: &Metadata) -> bool {
+        metadata.target() == WIRE_LOG_TARGET && metadata.level() <= wire_level()
+    }
+}
+
+impl Log for CliLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        Self::is_wire_record(metadata) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if Self::is_wire_record(record.metadata()) {
+            eprintln!("{}", record.args());
+        } else {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+pub fn init_logger() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter();
+
+    log::set_boxed_logger(Box::new(CliLogger { inner })).expect("logger already initialized");
+    log::set_max_level(max_level);
+}
+
+/// Print the requests as they are sent with a verbosity of 1, and the connections and the
+/// response headers with a verbosity of 2 or more.
+pub(super) fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+
+    let level = wire_level();
+    if level > log::max_level() {
+        log::set_max_level(level);
+    }
+}
+
+fn wire_level() -> LevelFilter {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
@@ -0,0 +1,28 @@
+use std::net::SocketAddr;
+
+use api_cli::error::Result;
+use api_cli::mock::{MockServer, EXAMPLE_HEADER};
+use api_cli::RequestModel;
+
+use super::request::find_requests;
+use super::utils::{get_request_file_path, read_file};
+use super::MockArgs;
+
+pub async fn run_mock(args: MockArgs) -> Result<()> {
+    let mut requests = Vec::new();
+    for name in find_requests(args.collection.clone())? {
+        let request: RequestModel = read_file(&get_request_file_path(&args.collection, &name)?)?;
+        requests.push((name, request));
+    }
+
+    let addr = SocketAddr::new(args.host, args.port);
+    println!(
+        "Serving {} on http://{} (select an example with the {} header)",
+        args.collection, addr, EXAMPLE_HEADER
+    );
+
+    MockServer::new(requests)
+        .with_request_logger(|line| println!("{}", line))
+        .serve(addr)
+        .await
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use api_cli::error::Result;
+use api_cli::{ApiClientRequest, ApiResponse, NextPage, Pagination, Paginator};
+use serde_json::Value;
+
+use super::history::record_history;
+
+/// Items of all the pages of a list.
+pub(super) struct Pages {
+    pub(super) items: Vec<Value>,
+    pub(super) count: u32,
+    /// Sum of the latencies of the requests of the pages
+    pub(super) latency: Duration,
+}
+
+/// Request the pages following `first`, the response to the first page of a list, until the last
+/// one. The request of each page is built with `build`, and every response, `first` included, is
+/// checked with `check` before it is read, stopping at the first failure.
+pub(super) async fn fetch_all_pages<F, C>(
+    collection: &str,
+    environment: Option<&str>,
+    request: &str,
+    pagination: Pagination,
+    first: &ApiResponse,
+    build: F,
+    check: C,
+) -> Result<Pages>
+where
+    F: Fn(NextPage) -> Result<ApiClientRequest>,
+    C: Fn(&ApiResponse) -> Result<()>,
+{
+    let mut paginator = Paginator::new(pagination);
+    check(first)?;
+    let (mut items, mut next) = paginator.read_page(first)?;
+    let mut latency = first.latency();
+
+    while let Some(page) = next {
+        let res = build(page)?.execute_collected().await?;
+        record_history(
+            collection,
+            environment,
+            [(request.to_string(), res.status().to_string(), res.latency())],
+        );
+        latency += res.latency();
+
+        check(&res)?;
+        let (page_items, page_next) = paginator.read_page(&res)?;
+        items.extend(page_items);
+        next = page_next;
+    }
+
+    Ok(Pages {
+        items,
+        count: paginator.pages(),
+        latency,
+    })
+}
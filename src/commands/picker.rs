@@ -0,0 +1,50 @@
+use std::io::{self, IsTerminal};
+
+use api_cli::error::{ApiClientError, Result};
+use dialoguer::FuzzySelect;
+
+use super::collection::find_collections;
+use super::environment::find_environments;
+use super::request::find_requests;
+
+static NO_ENVIRONMENT: &str = "(none)";
+
+pub(super) fn pick_collection() -> Result<String> {
+    pick("collection", find_collections()?)
+}
+
+pub(super) fn pick_request(collection_name: &str) -> Result<String> {
+    pick("request", find_requests(collection_name.to_string())?)
+}
+
+/// Pick an environment, if the collection has any. Not using an environment is always an option.
+pub(super) fn pick_environment(collection_name: &str) -> Result<Option<String>> {
+    let environments = find_environments(collection_name.to_string())?;
+    if environments.is_empty() {
+        return Ok(None);
+    }
+
+    let mut items = vec![NO_ENVIRONMENT.to_string()];
+    items.extend(environments);
+
+    let env = pick("environment", items)?;
+
+    Ok(Some(env).filter(|e| e != NO_ENVIRONMENT))
+}
+
+/// Let the user fuzzy search through `items`. Fails if there is nothing to choose from or if there
+/// is no terminal to interact with.
+fn pick(name: &str, mut items: Vec<String>) -> Result<String> {
+    if items.is_empty() || !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(ApiClientError::new_missing_argument(name.to_string()));
+    }
+
+    let idx = FuzzySelect::new()
+        .with_prompt(format!("Select a {}", name))
+        .items(&items)
+        .default(0)
+        .interact()
+        .map_err(|dialoguer::Error::IO(e)| e)?;
+
+    Ok(items.swap_remove(idx))
+}
@@ -0,0 +1,76 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::net::SocketAddr;
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::record::{RecordedRequest, RecordingProxy};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::{Stderr, Stdout};
+
+use super::utils::{ensure_collection_directory, get_request_file_path};
+use super::RecordArgs;
+
+pub async fn run_record(args: RecordArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection)?;
+
+    let addr = SocketAddr::new(args.host, args.port);
+    println!(
+        "Recording the requests to {} on http://{} into {}",
+        args.target, addr, args.collection
+    );
+
+    let collection = args.collection;
+    RecordingProxy::new(args.target, move |recorded| {
+        let variables = recorded.variables.join(", ");
+        match save_recorded_request(&collection, recorded) {
+            Ok(name) => {
+                println!(
+                    "{}",
+                    format!("Saved {}", name).if_supports_color(Stdout, |s| s.dimmed())
+                );
+                // The credentials are not written to the collection.
+                if !variables.is_empty() {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "warning: {} uses the variables {} in place of its credentials",
+                            name, variables
+                        )
+                        .if_supports_color(Stderr, |s| s.yellow())
+                    );
+                }
+            }
+            Err(e) => eprintln!(
+                "{}",
+                format!("Unable to save the request: {}", e).if_supports_color(Stderr, |s| s.red())
+            ),
+        }
+    })?
+    .with_request_logger(|line| println!("{}", line))
+    .serve(addr)
+    .await
+}
+
+/// Write the request to a new file, named after it with a number appended if the name is taken.
+fn save_recorded_request(collection: &str, recorded: RecordedRequest) -> Result<String> {
+    let content = serde_yaml::to_string(&recorded.request)?;
+
+    let mut i = 1;
+    loop {
+        let name = match i {
+            1 => recorded.name.clone(),
+            _ => format!("{} {}", recorded.name, i),
+        };
+        let path = get_request_file_path(collection, &name)?;
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut f) => {
+                f.write_all(content.as_bytes())
+                    .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+                return Ok(name);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => i += 1,
+            Err(e) => return Err(ApiClientError::from_io_error_with_path(e, &path)),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::{ApiClientRequest, ApiResponse, ClientPool, TokenRefresh};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stderr;
+use reqwest::StatusCode;
+use reqwest_cookie_store::CookieStoreMutex;
+
+use super::history::record_history;
+use super::run::{apply_options, capture_variables};
+use super::utils::{get_tokens_file_path, load_request};
+use super::variables::save_runtime_variables;
+use super::RunArgs;
+
+/// What is needed to execute the token request of a collection.
+pub(super) struct RefreshContext<'a> {
+    pub(super) collection: &'a str,
+    pub(super) environment: Option<&'a str>,
+    pub(super) args: &'a RunArgs,
+    pub(super) cookie_store: &'a Arc<CookieStoreMutex>,
+    pub(super) clients: &'a ClientPool,
+}
+
+/// Execute a request, fetching a new bearer token with the token request of its auth first if the
+/// current one expired, or after the server rejected it. The request is rebuilt with `build` from
+/// the captured variables once the token is refreshed, and is retried once.
+pub(super) async fn execute_with_token_refresh<F>(
+    ctx: &RefreshContext<'_>,
+    req: ApiClientRequest,
+    captured: &mut HashMap<String, String>,
+    build: F,
+) -> Result<ApiResponse>
+where
+    F: Fn(&HashMap<String, String>) -> Result<ApiClientRequest>,
+{
+    let Some(refresh) = req.token_refresh().cloned() else {
+        return req.execute_collected().await;
+    };
+
+    if is_expired(ctx.collection, &refresh)? {
+        refresh_token(ctx, &refresh, captured).await?;
+        return build(captured)?.execute_collected().await;
+    }
+
+    let res = req.execute_collected().await?;
+    if res.status() != StatusCode::UNAUTHORIZED {
+        return Ok(res);
+    }
+
+    refresh_token(ctx, &refresh, captured).await?;
+    build(captured)?.execute_collected().await
+}
+
+/// Execute the token request and capture its variables, which are saved with the runtime
+/// variables of the collection.
+async fn refresh_token(
+    ctx: &RefreshContext<'_>,
+    refresh: &TokenRefresh,
+    captured: &mut HashMap<String, String>,
+) -> Result<()> {
+    let name = &refresh.request;
+    let req = load_request(
+        ctx.collection,
+        name,
+        ctx.environment,
+        ctx.args.env_file.as_deref(),
+    )?;
+    let captures = req.captures();
+    if captures.is_empty() {
+        return Err(ApiClientError::new_token_refresh_failed(
+            name.clone(),
+            "it captures no variable".to_string(),
+        ));
+    }
+
+    let res = apply_options(req, ctx.args)
+        .with_runtime_variables(captured.clone())
+        .with_cookie_store(Arc::clone(ctx.cookie_store))
+        .with_client_pool(ctx.clients.clone())
+        .execute_collected()
+        .await?;
+    record_history(
+        ctx.collection,
+        ctx.environment,
+        [(name.clone(), res.status().to_string(), res.latency())],
+    );
+    if res.status().is_client_error() || res.status().is_server_error() {
+        return Err(ApiClientError::new_token_refresh_failed(
+            name.clone(),
+            format!("unexpected status {}", res.status()),
+        ));
+    }
+    if !ctx.args.quiet {
+        eprintln!(
+            "{}",
+            format!("{} {}", name, res.status()).if_supports_color(Stderr, |s| s.dimmed())
+        );
+    }
+
+    let vars = capture_variables(&res, &captures)?;
+    save_runtime_variables(ctx.collection, vars.clone())?;
+    captured.extend(vars);
+
+    record_refresh(ctx.collection, name)
+}
+
+/// Whether the tokens of a token request are older than their lifetime. Tokens that were never
+/// fetched are considered expired, tokens without lifetime never expire.
+fn is_expired(collection_name: &str, refresh: &TokenRefresh) -> Result<bool> {
+    let Some(expires_in) = refresh.expires_in else {
+        return Ok(false);
+    };
+
+    let expired = load_refreshes(collection_name)?
+        .get(&refresh.request)
+        .and_then(|t| humantime::parse_rfc3339(t).ok())
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .is_none_or(|age| age >= expires_in);
+
+    Ok(expired)
+}
+
+/// RFC 3339 timestamps of the last execution of the token requests of a collection.
+fn load_refreshes(collection_name: &str) -> Result<BTreeMap<String, String>> {
+    let path = get_tokens_file_path(collection_name);
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data =
+        fs::read_to_string(&path).map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+    serde_json::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_json_error_with_path(e, &path))
+}
+
+fn record_refresh(collection_name: &str, request: &str) -> Result<()> {
+    let mut refreshes = load_refreshes(collection_name)?;
+    refreshes.insert(
+        request.to_string(),
+        humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+    );
+
+    let path = get_tokens_file_path(collection_name);
+    fs::write(&path, serde_json::to_string_pretty(&refreshes)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))
+}
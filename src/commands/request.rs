@@ -1,34 +1,84 @@
-use std::ffi::OsStr;
-use std::fs::{self, File};
+use std::fs;
 use std::path::Path;
 
 use api_cli::error::{ApiClientError, Result};
-use api_cli::RequestModel;
+use api_cli::{
+    to_curl_command,
+    HttpBody,
+    HttpJsonBody,
+    KeyValuePair,
+    RequestMetaModel,
+    RequestModel,
+    RequestName,
+    FOLDER_FILE_NAME,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tabled::settings::Style;
+use tabled::{Table, Tabled};
 
-use super::utils::{ensure_collection_directory, get_request_file_path, open_file_in_editor};
-use super::{RequestCmd, RequestCreateArgs, RequestEditArgs, RequestListArgs};
+use super::history::{load_history, HistoryEntry};
+use super::run::print_dry_run;
+use super::utils::{
+    confirm,
+    ensure_collection_directory,
+    get_request_file_path,
+    load_request,
+    open_file_in_editor,
+    read_file,
+    remove_empty_parents,
+};
+use super::{
+    RequestCmd,
+    RequestCreateArgs,
+    RequestDeleteArgs,
+    RequestEditArgs,
+    RequestExportCurlArgs,
+    RequestListArgs,
+    RequestRenameArgs,
+    RequestShowArgs,
+    RequestTemplate,
+};
 
 pub fn run_request_command(cmd: RequestCmd) -> Result<()> {
     match cmd {
         RequestCmd::Create(args) => create_request(args),
         RequestCmd::Edit(args) => edit_request(args),
         RequestCmd::List(args) => list_requests(args),
+        RequestCmd::Show(args) => show_request(args),
+        RequestCmd::ExportCurl(args) => export_curl(args),
+        RequestCmd::Delete(args) => delete_request(args),
+        RequestCmd::Rename(args) => rename_request(args),
     }
 }
 
 fn create_request(args: RequestCreateArgs) -> Result<()> {
     let collection_dir = ensure_collection_directory(&args.collection_name)?;
 
-    let request_path = get_request_file_path(&args.collection_name, &args.name);
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
 
     if request_path.exists() {
         return Err(ApiClientError::new_request_already_exists(args.name));
     }
 
-    fs::create_dir_all(request_path.parent().unwrap())?;
+    let content = match (args.template, &args.from) {
+        (Some(t), _) => template_content(t).to_string(),
+        (None, Some(from)) => {
+            let from_path = get_request_file_path(&args.collection_name, from)?;
+            if !from_path.exists() {
+                return Err(ApiClientError::new_request_not_found(from.clone()));
+            }
+
+            fs::read_to_string(&from_path)
+                .map_err(|e| ApiClientError::from_io_error_with_path(e, &from_path))?
+        }
+        (None, None) => serde_yaml::to_string(&RequestModel::default())?,
+    };
+    let content = apply_create_options(content, &args)?;
 
-    let writer = File::create(&request_path)?;
-    serde_yaml::to_writer(writer, &RequestModel::default())?;
+    fs::create_dir_all(request_path.parent().unwrap())?;
+    fs::write(&request_path, content)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &request_path))?;
 
     if args.edit {
         open_file_in_editor(&collection_dir, &request_path)?;
@@ -37,10 +87,51 @@ fn create_request(args: RequestCreateArgs) -> Result<()> {
     Ok(())
 }
 
+/// Apply the options of the command line defining the request on top of its initial content.
+fn apply_create_options(content: String, args: &RequestCreateArgs) -> Result<String> {
+    if args.method.is_none()
+        && args.url.is_none()
+        && args.headers.is_empty()
+        && args.body_json.is_none()
+    {
+        return Ok(content);
+    }
+
+    let mut request: RequestModel = serde_yaml::from_str(&content)?;
+
+    if let Some(m) = args.method {
+        request.http.method = m;
+    }
+    if let Some(u) = &args.url {
+        request.http.url = u.clone();
+    }
+    for (key, value) in &args.headers {
+        request.http.headers.push(KeyValuePair::new(key, value));
+    }
+    if let Some(json) = &args.body_json {
+        request.http.body = Some(HttpBody::Json(HttpJsonBody {
+            json: json.clone(),
+            content_type: None,
+        }));
+    }
+
+    Ok(serde_yaml::to_string(&request)?)
+}
+
+fn template_content(template: RequestTemplate) -> &'static str {
+    match template {
+        RequestTemplate::Get => include_str!("templates/get.yaml"),
+        RequestTemplate::JsonPost => include_str!("templates/json-post.yaml"),
+        RequestTemplate::Graphql => include_str!("templates/graphql.yaml"),
+        RequestTemplate::Form => include_str!("templates/form.yaml"),
+        RequestTemplate::Upload => include_str!("templates/upload.yaml"),
+    }
+}
+
 fn edit_request(args: RequestEditArgs) -> Result<()> {
     let collection_dir = ensure_collection_directory(&args.collection_name)?;
 
-    let request_path = get_request_file_path(&args.collection_name, &args.name);
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
 
     if !request_path.exists() {
         return Err(ApiClientError::new_request_not_found(args.name));
@@ -52,16 +143,189 @@ fn edit_request(args: RequestEditArgs) -> Result<()> {
 }
 
 fn list_requests(args: RequestListArgs) -> Result<()> {
-    let request_names = find_requests(args.collection_name)?;
+    ensure_collection_directory(&args.collection_name)?;
+
+    let mut history = load_history(&args.collection_name)?;
+
+    let rows: Vec<RequestRow> = find_requests_by_seq(&args.collection_name)?
+        .into_iter()
+        .map(|(name, meta)| {
+            let path = get_request_file_path(&args.collection_name, &name)?;
+            let request = read_file::<RequestModel>(&path).ok();
+            let last_run = history.remove(&name);
+
+            Ok(RequestRow {
+                method: request.as_ref().map(|r| r.method().to_string()),
+                url: request.as_ref().map(|r| r.url()),
+                name,
+                meta,
+                last_run,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|r| match &args.filter {
+            Some(f) => r.name.contains(f.as_str()) || r.url.as_deref().unwrap_or("").contains(f),
+            None => true,
+        })
+        .collect();
+
+    if args.json {
+        let requests: Vec<Value> = rows.iter().map(RequestRow::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&requests)?);
+
+        return Ok(());
+    }
 
-    for n in request_names {
-        println!("{}", n);
+    if rows.is_empty() {
+        return Ok(());
     }
 
+    let mut table = Table::new(rows.iter().map(RequestRow::to_table_row));
+    table.with(Style::modern());
+    println!("{}", table);
+
     Ok(())
 }
 
-fn find_requests(collection_name: String) -> Result<Vec<String>> {
+struct RequestRow {
+    name: String,
+    meta: RequestMetaModel,
+    /// Not set if the request can't be read
+    method: Option<String>,
+    url: Option<String>,
+    last_run: Option<HistoryEntry>,
+}
+
+#[derive(Tabled)]
+struct RequestTableRow {
+    name: String,
+    method: String,
+    url: String,
+    tags: String,
+    description: String,
+    #[tabled(rename = "last run")]
+    last_run: String,
+}
+
+impl RequestRow {
+    fn to_table_row(&self) -> RequestTableRow {
+        RequestTableRow {
+            name: self.name.clone(),
+            method: self.method.clone().unwrap_or_else(|| "-".to_string()),
+            url: self.url.clone().unwrap_or_else(|| "-".to_string()),
+            tags: self.meta.tags().join(", "),
+            description: self.meta.description().unwrap_or_default().to_string(),
+            last_run: self
+                .last_run
+                .as_ref()
+                .map(|h| h.status.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "method": self.method,
+            "url": self.url,
+            "tags": self.meta.tags(),
+            "description": self.meta.description(),
+            "last_run": self.last_run,
+        })
+    }
+}
+
+fn show_request(args: RequestShowArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
+    if !request_path.exists() {
+        return Err(ApiClientError::new_request_not_found(args.name));
+    }
+
+    if !args.resolved {
+        let content = fs::read_to_string(&request_path)
+            .map_err(|e| ApiClientError::from_io_error_with_path(e, &request_path))?;
+        print!("{}", content);
+
+        return Ok(());
+    }
+
+    let req = load_request(
+        &args.collection_name,
+        &args.name,
+        args.environment.as_deref(),
+        None,
+    )?;
+
+    let (request, masker) = req.prepare_masked()?;
+    print_dry_run(&request, args.mask_secrets.then_some(&masker));
+
+    Ok(())
+}
+
+fn export_curl(args: RequestExportCurlArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
+    if !request_path.exists() {
+        return Err(ApiClientError::new_request_not_found(args.name));
+    }
+
+    let req = load_request(
+        &args.collection_name,
+        &args.name,
+        args.environment.as_deref(),
+        None,
+    )?;
+
+    println!("{}", to_curl_command(&req.prepare()?));
+
+    Ok(())
+}
+
+fn delete_request(args: RequestDeleteArgs) -> Result<()> {
+    let collection_dir = ensure_collection_directory(&args.collection_name)?;
+
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
+    if !request_path.exists() {
+        return Err(ApiClientError::new_request_not_found(args.name));
+    }
+
+    if !args.force && !confirm(&format!("Delete request {}?", args.name))? {
+        return Ok(());
+    }
+
+    fs::remove_file(&request_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &request_path))?;
+    remove_empty_parents(request_path.parent().unwrap(), &collection_dir)?;
+
+    Ok(())
+}
+
+fn rename_request(args: RequestRenameArgs) -> Result<()> {
+    let collection_dir = ensure_collection_directory(&args.collection_name)?;
+
+    let request_path = get_request_file_path(&args.collection_name, &args.name)?;
+    if !request_path.exists() {
+        return Err(ApiClientError::new_request_not_found(args.name));
+    }
+
+    let new_request_path = get_request_file_path(&args.collection_name, &args.new_name)?;
+    if new_request_path.exists() {
+        return Err(ApiClientError::new_request_already_exists(args.new_name));
+    }
+
+    fs::create_dir_all(new_request_path.parent().unwrap())?;
+    fs::rename(&request_path, &new_request_path)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &request_path))?;
+    remove_empty_parents(request_path.parent().unwrap(), &collection_dir)?;
+
+    Ok(())
+}
+
+pub(super) fn find_requests(collection_name: String) -> Result<Vec<String>> {
     let collection_directory = ensure_collection_directory(&collection_name)?;
 
     let mut request_names =
@@ -71,6 +335,36 @@ fn find_requests(collection_name: String) -> Result<Vec<String>> {
     Ok(request_names)
 }
 
+/// The metadata of a request file, without the rest of its definition.
+#[derive(Deserialize)]
+struct RequestMetaFile {
+    #[serde(default)]
+    meta: RequestMetaModel,
+}
+
+/// Requests of a collection with their metadata, ordered by `seq` then by name. Requests without
+/// `seq`, or that can't be read, come after the others.
+pub(super) fn find_requests_by_seq(
+    collection_name: &str,
+) -> Result<Vec<(String, RequestMetaModel)>> {
+    let mut requests: Vec<(String, RequestMetaModel)> = find_requests(collection_name.to_string())?
+        .into_iter()
+        .map(|name| {
+            let path = get_request_file_path(collection_name, &name)?;
+            let meta = read_file::<RequestMetaFile>(&path)
+                .map(|f| f.meta)
+                .unwrap_or_default();
+
+            Ok((name, meta))
+        })
+        .collect::<Result<_>>()?;
+
+    // The sort is stable, so the requests with the same `seq` stay ordered by name.
+    requests.sort_by_key(|(_, m)| (m.seq().is_none(), m.seq()));
+
+    Ok(requests)
+}
+
 fn find_requests_in_directory(collection_dir: &Path, dir: &Path) -> Result<Vec<String>> {
     let mut request_names = Vec::new();
 
@@ -79,7 +373,7 @@ fn find_requests_in_directory(collection_dir: &Path, dir: &Path) -> Result<Vec<S
 
         // TODO: Put collection def somewhere else, or put requests in their own subfolder
         let name = path.file_name().unwrap();
-        if name == "collection.yaml" || name == "environments" {
+        if name == "collection.yaml" || name == "environments" || name == FOLDER_FILE_NAME {
             continue;
         }
 
@@ -88,20 +382,10 @@ fn find_requests_in_directory(collection_dir: &Path, dir: &Path) -> Result<Vec<S
             continue;
         }
 
-        if path.extension().unwrap_or(OsStr::new("")) != "yaml" {
-            continue;
+        let relative_path = path.strip_prefix(collection_dir).unwrap();
+        if let Some(name) = RequestName::from_path(relative_path) {
+            request_names.push(name.to_string());
         }
-
-        let name = path
-            .strip_prefix(collection_dir)
-            .unwrap()
-            .to_string_lossy()
-            .replace('/', ":")
-            .strip_suffix(".yaml")
-            .unwrap()
-            .to_string();
-
-        request_names.push(name);
     }
 
     Ok(request_names)
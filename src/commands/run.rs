@@ -1,30 +1,76 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::env;
 use std::fmt::Display;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 use std::str::FromStr;
-use std::time::{Duration, Instant};
-
-use api_cli::error::Result;
-use api_cli::{ApiClientRequest, CollectionModel, RequestModel};
-use colored_json::to_colored_json_auto;
-use jsonpath_rust::{find_slice, JsonPathInst};
-use log::debug;
-use owo_colors::Stream::Stdout;
+use std::sync::Arc;
+use std::time::Duration;
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::masking::{is_sensitive_header, Masker, MASK};
+use api_cli::{
+    format_markup,
+    hexdump,
+    highlight_markup,
+    ApiClientRequest,
+    ApiResponse,
+    Change,
+    ClientPool,
+    Difference,
+    ExampleModel,
+    ExpectedStatus,
+    GrpcResponse,
+    IpVersion,
+    JqFilter,
+    LoadReport,
+    ProxyConfig,
+    RateLimiter,
+    Redirect,
+    SavedResponse,
+    SchemaViolation,
+    SseStream,
+    StatusMatcher,
+    Tabular,
+    Timings,
+    TraceParent,
+};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use colored_json::to_colored_json;
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use jsonpath_rust::{find_slice, JsonPathInst, JsonPathValue};
+use owo_colors::Stream::{Stderr, Stdout};
 use owo_colors::{OwoColorize, Style as OwoStyle};
-use reqwest::Response;
-use serde_json::Value;
+use reqwest::header::HeaderMap;
+use reqwest::{Request, StatusCode};
+use reqwest_cookie_store::CookieStoreMutex;
+use serde_json::{json, Map, Value};
 use tabled::settings::object::Rows;
 use tabled::settings::{Disable, Style};
 use tabled::{Table, Tabled};
 use textwrap::{termwidth, Options};
+use tokio::time::Instant;
+use tonic::Code;
 
-use super::utils::{
-    get_collection_file_path,
-    get_environment_file_path,
-    get_request_file_path,
-    read_file,
-};
-use super::RunArgs;
+use super::cache::{load_cached_response, save_cached_response};
+use super::clipboard::copy_to_clipboard;
+use super::conditional::{load_validators, save_validators};
+use super::config::{config, json_color_mode};
+use super::cookies::{load_cookie_store, save_cookie_store};
+use super::history::{load_latencies, record_history};
+use super::logging::set_verbosity;
+use super::pagination::{fetch_all_pages, Pages};
+use super::picker::{pick_collection, pick_environment, pick_request};
+use super::refresh::{execute_with_token_refresh, RefreshContext};
+use super::runner::{run_collection, run_dataset, run_dependencies};
+use super::utils::{get_request_file_path, load_request, RUN_ID, RUN_TRACE};
+use super::variables::save_runtime_variables;
+use super::{OutputMode, RunArgs};
+
+/// Number of bytes of a binary body shown in the preview.
+const BINARY_PREVIEW_SIZE: usize = 256;
 
 #[derive(Tabled)]
 struct HeaderRow<'a, S: AsRef<str> + Display> {
@@ -32,49 +78,947 @@ struct HeaderRow<'a, S: AsRef<str> + Display> {
     pub(crate) value: S,
 }
 
+/// What is known of an execution besides its response, printed along with it.
+struct ExecutionDetails<'a> {
+    /// ID of the run, when it is sent in a header
+    request_id: Option<&'a str>,
+    /// ID of the trace propagated by the request
+    trace_id: Option<String>,
+    max_latency: Option<Duration>,
+    /// Latencies of the past executions of the request, oldest first
+    latencies: Vec<Duration>,
+    /// Whether the response is a `304 Not Modified` to a conditional request
+    not_modified: bool,
+    /// Whether the response was replayed from the cache instead of being received
+    replayed: bool,
+}
+
 pub async fn execute_request(args: RunArgs) -> Result<()> {
-    let collection_path = get_collection_file_path(&args.collection);
-    let collection: CollectionModel = read_file(collection_path.as_path())?;
-    debug!("Collection: {:#?}", collection);
+    set_verbosity(args.verbose);
+
+    // Only ask for an environment when the user is already picking what to run.
+    let collection_run = args.all || args.tag.is_some();
+    let interactive = args.collection.is_none() || (args.request.is_none() && !collection_run);
+    let collection = match &args.collection {
+        Some(c) => c.clone(),
+        None => match &config().default_collection {
+            Some(c) => c.clone(),
+            None => pick_collection()?,
+        },
+    };
+    let request = match &args.request {
+        Some(r) => Some(r.clone()),
+        None if collection_run => None,
+        None => Some(pick_request(&collection)?),
+    };
+    let environment = match &args.environment {
+        Some(e) => Some(e.clone()),
+        None if interactive => pick_environment(&collection)?,
+        None => None,
+    };
 
-    let request_path = get_request_file_path(&args.collection, &args.request);
-    let req: RequestModel = read_file(request_path.as_path())?;
-    debug!("Request: {:#?}", req);
+    let Some(request) = request else {
+        return run_collection(&collection, environment.as_deref(), &args).await;
+    };
+    if let Some(path) = &args.data {
+        return run_dataset(&collection, &request, environment.as_deref(), path, &args).await;
+    }
 
-    let mut req = ApiClientRequest::new(collection, req);
+    let req = load_request(
+        &collection,
+        &request,
+        environment.as_deref(),
+        args.env_file.as_deref(),
+    )?;
 
-    let global_variables: HashMap<String, String> = env::vars()
-        .filter(|(k, _)| k.starts_with("API_CLI_VAR_"))
-        .map(|(k, v)| (k.strip_prefix("API_CLI_VAR_").unwrap().to_string(), v))
-        .collect();
+    // Dependencies would have to be sent too, so the request runs with the last captured values.
+    let mut captured = match args.dry_run || args.offline {
+        true => HashMap::new(),
+        false => run_dependencies(&collection, &request, environment.as_deref(), &args).await?,
+    };
+    let mut captures = req.captures();
+    captures.extend(args.captures.iter().cloned());
+
+    let mut req = apply_options(req, &args).with_runtime_variables(captured.clone());
+    let max_latency = req.max_latency();
+    let expected_status = req.expected_status().cloned();
+    let request_id = req.request_id_header().map(|_| RUN_ID.as_str());
+    let validators = match args.conditional {
+        true => load_validators(&collection, &request, environment.as_deref())?,
+        false => None,
+    };
+    if let Some(v) = &validators {
+        req = req.with_validators(v.clone());
+    }
+    let pagination = match args.all_pages {
+        true => Some(req.pagination().cloned().ok_or_else(|| {
+            ApiClientError::new_pagination_error("the request has no pagination".to_string())
+        })?),
+        false => None,
+    };
+
+    if req.is_grpc() {
+        let unsupported = [
+            (args.dry_run, "--dry-run"),
+            (args.repeat.is_some(), "--repeat"),
+            (args.save_example.is_some(), "--save-example"),
+            (args.diff.is_some(), "--diff"),
+            (!args.captures.is_empty(), "--capture"),
+            (!args.expect_status.is_empty(), "--expect-status"),
+            (args.cache, "--cache"),
+            (args.offline, "--offline"),
+            (args.copy, "--copy"),
+        ];
+        if let Some((_, option)) = unsupported.iter().find(|(set, _)| *set) {
+            return Err(ApiClientError::new_grpc_option_not_supported(option));
+        }
+        save_runtime_variables(&collection, captured)?;
+
+        let res = req.execute_grpc().await?;
+        let details = ExecutionDetails {
+            request_id: None,
+            trace_id: None,
+            max_latency,
+            latencies: load_latencies(&collection, &request, environment.as_deref()),
+            not_modified: false,
+            replayed: false,
+        };
+        record_history(
+            &collection,
+            environment.as_deref(),
+            [(request, format!("{:?}", res.code()), res.latency())],
+        );
+
+        if args.status_only {
+            println!("{:?}", res.code());
+        } else if !args.quiet {
+            print_grpc_response(
+                &res,
+                args.output,
+                args.no_headers,
+                args.headers_only || args.no_body,
+                BodyFilter::from_args(&args).as_ref(),
+                &details,
+            )?;
+        }
+
+        // Like a non 2xx status for HTTP, quiet runs fail on any status other than `Ok`.
+        if args.quiet && res.code() != Code::Ok {
+            return Err(ApiClientError::new_grpc_call_failed(format!(
+                "{:?}",
+                res.code()
+            )));
+        }
+
+        return check_latency(res.latency(), max_latency);
+    }
+
+    if args.dry_run {
+        let (request, masker) = req.prepare_masked()?;
+        print_dry_run(&request, args.mask_secrets.then_some(&masker));
+
+        return Ok(());
+    }
+
+    if args.stream {
+        req = req.with_sse_stream();
+    }
+
+    let cookie_store = Arc::new(CookieStoreMutex::new(load_cookie_store(&collection)?));
+    // The pages and the token request reuse the connections of the request.
+    let clients = ClientPool::default();
+    req = req
+        .with_cookie_store(Arc::clone(&cookie_store))
+        .with_client_pool(clients.clone());
+
+    if req.is_sse_stream() {
+        let stream = req.execute_sse().await?;
+        print_stream(
+            stream,
+            args.output,
+            args.no_headers,
+            args.max_events,
+            args.max_duration,
+        )
+        .await?;
+
+        return save_cookie_store(&collection, &cookie_store.lock().unwrap());
+    }
+
+    if let Some(repeat) = args.repeat {
+        if let Some(limiter) = rate_limiter(&args) {
+            req = req.with_rate_limiter(Arc::new(limiter));
+        }
+        let report = req.execute_load(repeat, args.concurrency).await?;
+        save_cookie_store(&collection, &cookie_store.lock().unwrap())?;
+
+        return print_load_report(&report, args.output);
+    }
+
+    if let Some(path) = args
+        .output_file
+        .clone()
+        .or_else(|| req.save_path().map(Path::to_path_buf))
+    {
+        let progress = ProgressBar::new_spinner().with_style(
+            ProgressStyle::with_template("{spinner} {bytes} ({bytes_per_sec})")
+                .expect("invalid progress template"),
+        );
+
+        let res = req
+            .execute_to_file(&path, |size, total| {
+                if let (Some(t), None) = (total, progress.length()) {
+                    progress.set_length(t);
+                    progress.set_style(
+                        ProgressStyle::with_template(
+                            "{bar:40} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+                        )
+                        .expect("invalid progress template"),
+                    );
+                }
+                progress.set_position(size);
+            })
+            .await?;
+        progress.finish_and_clear();
+
+        save_cookie_store(&collection, &cookie_store.lock().unwrap())?;
+        let details = ExecutionDetails {
+            request_id,
+            trace_id: trace_parent(&args).map(TraceParent::trace_id),
+            max_latency,
+            latencies: load_latencies(&collection, &request, environment.as_deref()),
+            not_modified: false,
+            replayed: false,
+        };
+        record_history(
+            &collection,
+            environment.as_deref(),
+            [(request, res.status().to_string(), res.latency())],
+        );
+
+        if args.status_only {
+            println!("{}", res.status().as_u16());
+        } else if !args.quiet {
+            print_saved_response(&res, &args, &details)?;
+        }
+
+        check_status(res.status(), &args, expected_status.as_ref())?;
+        return check_latency(res.latency(), max_latency);
+    }
+
+    let example = match &args.diff {
+        Some(name) => match req.example(name) {
+            Some(e) => Some(e),
+            None => return Err(ApiClientError::new_example_not_found(name.clone())),
+        },
+        None => None,
+    };
+    let schema = req.response_schema()?;
 
-    req = req.with_global_variables(global_variables);
+    let ctx = RefreshContext {
+        collection: &collection,
+        environment: environment.as_deref(),
+        args: &args,
+        cookie_store: &cookie_store,
+        clients: &clients,
+    };
+    let res = match args.offline {
+        true => load_cached_response(&collection, &request, environment.as_deref())?,
+        false => {
+            execute_with_token_refresh(&ctx, req, &mut captured, |captured| {
+                let req = load_request(
+                    &collection,
+                    &request,
+                    environment.as_deref(),
+                    args.env_file.as_deref(),
+                )?;
+                let req = apply_options(req, &args)
+                    .with_runtime_variables(captured.clone())
+                    .with_cookie_store(Arc::clone(&cookie_store))
+                    .with_client_pool(clients.clone());
 
-    if let Some(e) = args.environment {
-        let environment_path = get_environment_file_path(&args.collection, &e);
-        let env = read_file(environment_path.as_path())?;
-        debug!("Environment: {:#?}", env);
+                Ok(match &validators {
+                    Some(v) => req.with_validators(v.clone()),
+                    None => req,
+                })
+            })
+            .await?
+        }
+    };
 
-        req = req.with_environment(env);
+    save_cookie_store(&collection, &cookie_store.lock().unwrap())?;
+    if args.conditional {
+        save_validators(&collection, &request, environment.as_deref(), &res)?;
+    }
+    if args.cache {
+        save_cached_response(&collection, &request, environment.as_deref(), &res)?;
+    }
+    let not_modified = validators.is_some() && res.status() == StatusCode::NOT_MODIFIED;
+    let details = ExecutionDetails {
+        request_id,
+        trace_id: trace_parent(&args).map(TraceParent::trace_id),
+        max_latency,
+        latencies: load_latencies(&collection, &request, environment.as_deref()),
+        not_modified,
+        replayed: args.offline,
     };
+    // A replayed response says nothing about how the request performs now.
+    if !args.offline {
+        record_history(
+            &collection,
+            environment.as_deref(),
+            [(request.clone(), res.status().to_string(), res.latency())],
+        );
+    }
+
+    if let Some(pagination) = pagination {
+        let pages = fetch_all_pages(
+            &collection,
+            environment.as_deref(),
+            &request,
+            pagination,
+            &res,
+            |page| {
+                let req = load_request(
+                    &collection,
+                    &request,
+                    environment.as_deref(),
+                    args.env_file.as_deref(),
+                )?;
 
-    let request_start = Instant::now();
-    let res = req.execute().await.expect("error performing request");
-    let request_duration = request_start.elapsed();
+                Ok(apply_options(req, &args)
+                    .with_runtime_variables(captured.clone())
+                    .with_cookie_store(Arc::clone(&cookie_store))
+                    .with_client_pool(clients.clone())
+                    .with_page(page))
+            },
+            |page| {
+                check_status(page.status(), &args, expected_status.as_ref())?;
+                check_latency(page.latency(), max_latency)
+            },
+        )
+        .await?;
+        save_cookie_store(&collection, &cookie_store.lock().unwrap())?;
+
+        if !args.quiet {
+            print_pages(&pages, &args)?;
+        }
+        return Ok(());
+    }
 
-    let mut request_results = vec![
-        ("Status", get_formatted_status(&res)),
-        ("Latency", get_formatted_latency(request_duration)),
+    if let Some(name) = &args.save_example {
+        let path = get_request_file_path(&collection, &request)?;
+        save_example(&path, ExampleModel::from_response(name.clone(), &res))?;
+        if !args.quiet {
+            eprintln!("Saved example {} to {}", name, path.display());
+        }
+    }
+
+    if args.copy {
+        copy_to_clipboard(&get_copied_body(
+            &res,
+            BodyFilter::from_args(&args).as_ref(),
+        )?)?;
+        if !args.quiet {
+            eprintln!("Copied the body to the clipboard");
+        }
+    }
+
+    if let Some(example) = example {
+        return print_differences(&example, &example.diff(&res));
+    }
+
+    // The exports are meant to be evaluated by a shell, so nothing else is printed.
+    if !args.export && !args.quiet {
+        print_collected_response(&res, &args, &details)?;
+    }
+
+    if !captures.is_empty() {
+        let vars = capture_variables(&res, &captures)?;
+        match args.export {
+            true => print_exports(&vars),
+            false => captured.extend(vars),
+        }
+    }
+    if !args.export {
+        save_runtime_variables(&collection, captured)?;
+    }
+
+    let latency = check_latency(res.latency(), max_latency);
+    if let Some(s) = schema {
+        if !not_modified {
+            print_violations(&s.validate_response(&res))?;
+        }
+    }
+    // The response being unchanged is the expected outcome of a conditional request.
+    if !not_modified {
+        check_status(res.status(), &args, expected_status.as_ref())?;
+    }
+
+    latency
+}
+
+fn print_collected_response(
+    res: &ApiResponse,
+    args: &RunArgs,
+    details: &ExecutionDetails,
+) -> Result<()> {
+    if args.status_only {
+        println!("{}", res.status().as_u16());
+        return Ok(());
+    }
+
+    let no_body = args.headers_only || args.no_body;
+
+    match args.output {
+        OutputMode::Table => {
+            if args.raw_headers {
+                print_raw_head(res);
+            }
+            print_table(
+                res,
+                args.no_headers || args.raw_headers,
+                no_body,
+                BodyFilter::from_args(args).as_ref(),
+                get_tabular(args).as_ref(),
+                args.hex,
+                details,
+            )
+        }
+        OutputMode::Body => {
+            if args.raw_headers {
+                print_raw_head(res);
+            } else if args.headers_only {
+                print_header_lines(res.headers());
+            }
+            if no_body {
+                return Ok(());
+            }
+            print_body(
+                res,
+                BodyFilter::from_args(args).as_ref(),
+                get_tabular(args).as_ref(),
+                args.hex,
+            )
+        }
+        OutputMode::Json => print_json(
+            res,
+            BodyFilter::from_args(args).as_ref(),
+            args.raw_headers,
+            args.no_headers,
+            no_body,
+        ),
+    }
+}
+
+/// Add an example to a request file, replacing any example with the same name. The first example
+/// is appended to the file, the file is rewritten as a yaml document otherwise, which keeps the
+/// fields unknown to the request model but not the formatting, and is refused if it has comments.
+fn save_example(path: &Path, example: ExampleModel) -> Result<()> {
+    let data =
+        fs::read_to_string(path).map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+    let mut doc: serde_yaml::Value = serde_yaml::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_yaml_error_with_path(e, path))?;
+    let Some(mapping) = doc.as_mapping_mut() else {
+        return Err(ApiClientError::new_invalid_request_file(path));
+    };
+
+    let value = serde_yaml::to_value(&example)?;
+    match mapping.get("examples") {
+        Some(e) if !e.is_sequence() => {
+            return Err(ApiClientError::new_example_not_saved(
+                path,
+                "`examples` is not a list",
+            ));
+        }
+        // The first example is appended to the file, which is kept as is.
+        None if !data.trim_start().starts_with('{') => {
+            let mut examples = serde_yaml::Mapping::new();
+            examples.insert("examples".into(), serde_yaml::Value::Sequence(vec![value]));
+            let separator = match data.is_empty() || data.ends_with('\n') {
+                true => "",
+                false => "\n",
+            };
+            let data = format!("{}{}{}", data, separator, serde_yaml::to_string(&examples)?);
+
+            return fs::write(path, data)
+                .map_err(|e| ApiClientError::from_io_error_with_path(e, path));
+        }
+        // Otherwise the document is rewritten, which would lose its comments.
+        _ if has_comments(&data) => {
+            return Err(ApiClientError::new_example_not_saved(
+                path,
+                "the file has comments that would be lost",
+            ));
+        }
+        _ => {}
+    }
+
+    let examples = mapping
+        .entry("examples".into())
+        .or_insert_with(|| serde_yaml::Value::Sequence(Vec::new()))
+        .as_sequence_mut()
+        .expect("examples is a list");
+    match examples
+        .iter_mut()
+        .find(|e| e.get("name").and_then(|n| n.as_str()) == Some(example.name()))
+    {
+        Some(e) => *e = value,
+        None => examples.push(value),
+    }
+
+    fs::write(path, serde_yaml::to_string(&doc)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, path))
+}
+
+/// Whether a yaml document may have comments, which start with a `#` at the start of a line or
+/// after a space. Strings with such characters are taken for comments too.
+fn has_comments(data: &str) -> bool {
+    data.lines()
+        .any(|l| l.trim_start().starts_with('#') || l.contains(" #"))
+}
+
+/// Print the differences between a response and an example, failing if there are any.
+fn print_differences(example: &ExampleModel, differences: &[Difference]) -> Result<()> {
+    if differences.is_empty() {
+        println!("No differences with example {}", example.name());
+        return Ok(());
+    }
+
+    println!("Differences with example {}:", example.name());
+    for d in differences {
+        let line = d.to_string();
+        let line = match d.change {
+            Change::Added(_) => line.if_supports_color(Stdout, |l| l.green()).to_string(),
+            Change::Removed(_) => line.if_supports_color(Stdout, |l| l.red()).to_string(),
+            Change::Changed { .. } => line.if_supports_color(Stdout, |l| l.yellow()).to_string(),
+        };
+        println!("  {}", line);
+    }
+
+    Err(ApiClientError::new_example_mismatch(
+        example.name().to_string(),
+        differences.len(),
+    ))
+}
+
+/// Extract values of the response body with json-paths. Strings are captured as is and other
+/// values as json.
+pub(super) fn capture_variables(
+    res: &ApiResponse,
+    captures: &[(String, String)],
+) -> Result<Vec<(String, String)>> {
+    let Some(body) = res.json() else {
+        let name = captures
+            .first()
+            .map(|(n, _)| n.as_str())
+            .unwrap_or_default();
+        return Err(ApiClientError::new_capture_error(
+            name,
+            "response body is not json".to_string(),
+        ));
+    };
+
+    captures
+        .iter()
+        .map(|(name, path)| {
+            let value = match find_json_path(path, body)?.into_iter().next() {
+                Some(Value::String(s)) => s,
+                Some(v) => v.to_string(),
+                None => {
+                    return Err(ApiClientError::new_capture_error(
+                        name,
+                        format!("no match for json-path `{}`", path),
+                    ))
+                }
+            };
+
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+fn print_exports(vars: &[(String, String)]) {
+    for (name, value) in vars {
+        println!("export {}='{}'", name, value.replace('\'', "'\\''"));
+    }
+}
+
+/// Fail unless the status is the one expected with `--expect-status` or by the request. With
+/// `--quiet`, a 2xx status is expected by default.
+fn check_status(
+    status: StatusCode,
+    args: &RunArgs,
+    expected: Option<&ExpectedStatus>,
+) -> Result<()> {
+    let expected = match (args.expect_status.as_slice(), expected) {
+        ([], Some(e)) => e.clone(),
+        ([], None) if args.quiet => ExpectedStatus::One(StatusMatcher::Class(2)),
+        ([], None) => return Ok(()),
+        (s, _) => ExpectedStatus::Any(s.to_vec()),
+    };
+
+    match expected.matches(status.as_u16()) {
+        true => Ok(()),
+        false => Err(ApiClientError::new_unexpected_status(
+            status,
+            expected.to_string(),
+        )),
+    }
+}
+
+/// Fail if the latency of a response is above the maximum of its request.
+fn check_latency(latency: Duration, max_latency: Option<Duration>) -> Result<()> {
+    match max_latency {
+        Some(m) if latency > m => Err(ApiClientError::new_latency_exceeded(latency, m)),
+        _ => Ok(()),
+    }
+}
+
+/// Print the locations of the response body that don't match the schema of the request, failing
+/// if there are any.
+fn print_violations(violations: &[SchemaViolation]) -> Result<()> {
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!("Response does not match its schema:");
+    for v in violations {
+        eprintln!("  {}", v.if_supports_color(Stderr, |v| v.red()));
+    }
+
+    Err(ApiClientError::new_schema_mismatch(violations.len()))
+}
+
+/// Apply the options of the command line that override the definition of the request.
+pub(super) fn apply_options(mut req: ApiClientRequest, args: &RunArgs) -> ApiClientRequest {
+    if !args.vars.is_empty() {
+        req = req.with_override_variables(args.vars.iter().cloned().collect());
+    }
+
+    if let Some(t) = args.timeout {
+        req = req.with_timeout(t);
+    }
+
+    if let Some(r) = args.retries {
+        req = req.with_retries(r);
+    }
+
+    if let Some(p) = &args.proxy {
+        req = req.with_proxy(ProxyConfig::new(p.clone()));
+    }
+
+    if args.no_follow {
+        req = req.with_follow_redirects(false);
+    }
+
+    if args.ipv4 {
+        req = req.with_ip_version(IpVersion::V4);
+    } else if args.ipv6 {
+        req = req.with_ip_version(IpVersion::V6);
+    }
+
+    if args.no_decompress {
+        req = req.with_decompression(false);
+    }
+
+    if let Some(h) = &args.request_id_header {
+        req = req.with_request_id_header(h.clone());
+    }
+
+    if let Some(parent) = trace_parent(args) {
+        req = req.with_trace_context(parent.clone(), args.tracestate.clone());
+    }
+
+    req
+}
+
+/// The limiter pacing the requests of a collection run or `--repeat`, from `--rate` and `--delay`.
+/// The slowest pace wins when both are set.
+pub(super) fn rate_limiter(args: &RunArgs) -> Option<RateLimiter> {
+    let interval = [
+        args.rate.map(|r| Duration::from_secs_f64(1.0 / r)),
+        args.delay,
+    ]
+    .into_iter()
+    .flatten()
+    .max()?;
+
+    Some(RateLimiter::new(interval))
+}
+
+/// The trace propagated by the requests, the given one or the one of the run.
+fn trace_parent(args: &RunArgs) -> Option<&TraceParent> {
+    args.traceparent
+        .as_ref()
+        .map(|p| p.as_ref().unwrap_or(&RUN_TRACE))
+}
+
+/// Print the status line and the headers like they are received, with one line per value. Header
+/// names are always lowercase as they are normalized by the http client.
+fn print_raw_head(res: &ApiResponse) {
+    println!("{:?} {}", res.version(), get_formatted_status(res.status()));
+    print_header_lines(res.headers());
+    println!();
+}
+
+/// Print the headers with one line per value.
+fn print_header_lines(headers: &HeaderMap) {
+    for (name, value) in headers {
+        println!(
+            "{}: {}",
+            name.as_str().if_supports_color(Stdout, |n| n.cyan()),
+            String::from_utf8_lossy(value.as_bytes())
+        );
+    }
+}
+
+/// Print the events of a stream as they arrive, until the server closes it or one of the limits
+/// is reached.
+async fn print_stream(
+    mut stream: SseStream,
+    output: OutputMode,
+    no_headers: bool,
+    max_events: Option<usize>,
+    max_duration: Option<Duration>,
+) -> Result<()> {
+    if let OutputMode::Table = output {
+        let mut stream_info = vec![("Status", get_formatted_status(stream.status()))];
+        if !no_headers {
+            if let Some(h) = get_formatted_headers(stream.headers()) {
+                stream_info.push(("Headers", h));
+            }
+        }
+
+        let mut table = Table::new(stream_info);
+        table
+            .with(Style::modern())
+            .with(Disable::row(Rows::first()));
+        println!("{}", table);
+    }
+
+    let deadline = max_duration.map(|d| Instant::now() + d);
+    let mut count = 0;
+
+    while max_events.is_none_or(|m| count < m) {
+        let event = match deadline {
+            Some(d) => match tokio::time::timeout_at(d, stream.next_event()).await {
+                Ok(e) => e?,
+                Err(_) => break,
+            },
+            None => stream.next_event().await?,
+        };
+
+        let event = match event {
+            Some(e) => e,
+            None => break,
+        };
+
+        match output {
+            OutputMode::Table => {
+                let name = event.event.as_deref().unwrap_or("message");
+                println!(
+                    "{} {}",
+                    format!("[{}]", name).if_supports_color(Stdout, |n| n.cyan()),
+                    event.data
+                );
+            }
+            OutputMode::Body => println!("{}", event.data),
+            OutputMode::Json => {
+                let output = json!({
+                    "event": event.event.as_deref().unwrap_or("message"),
+                    "id": event.id,
+                    "data": event.data,
+                });
+                println!("{}", serde_json::to_string(&output)?);
+            }
+        }
+        io::stdout().flush()?;
+
+        count += 1;
+    }
+
+    Ok(())
+}
+
+fn print_load_report(report: &LoadReport, output: OutputMode) -> Result<()> {
+    let percentiles = [
+        ("min", report.min()),
+        ("p50", report.percentile(50.0)),
+        ("p90", report.percentile(90.0)),
+        ("p99", report.percentile(99.0)),
+        ("max", report.max()),
+    ];
+
+    if let OutputMode::Json = output {
+        let latencies: Map<String, Value> = percentiles
+            .iter()
+            .map(|(name, d)| (name.to_string(), json!(d.map(as_millis))))
+            .collect();
+        let statuses: Map<String, Value> = report
+            .statuses()
+            .iter()
+            .map(|(s, c)| (s.to_string(), json!(c)))
+            .collect();
+
+        let output = json!({
+            "requests": report.requests(),
+            "errors": report.errors(),
+            "error_rate": report.error_rate(),
+            "duration_ms": as_millis(report.elapsed()),
+            "throughput": report.throughput(),
+            "latency_ms": latencies,
+            "statuses": statuses,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+
+        return Ok(());
+    }
+
+    let mut rows = vec![
+        ("Requests", report.requests().to_string()),
+        (
+            "Errors",
+            format!("{} ({:.1}%)", report.errors(), report.error_rate() * 100.0),
+        ),
+        ("Duration", format!("{:?}", report.elapsed())),
+        ("Throughput", format!("{:.1} req/s", report.throughput())),
     ];
 
-    if !args.no_headers {
-        if let Some(h) = get_formatted_headers(&res) {
+    let latency_rows: Vec<HeaderRow<String>> = percentiles
+        .iter()
+        .filter_map(|(name, d)| {
+            d.map(|d| HeaderRow {
+                name,
+                value: get_formatted_latency(d, None),
+            })
+        })
+        .collect();
+    if !latency_rows.is_empty() {
+        let mut latency_table = Table::new(latency_rows);
+        latency_table
+            .with(Style::modern())
+            .with(Disable::row(Rows::first()));
+        rows.push(("Latency", latency_table.to_string()));
+    }
+
+    if !report.statuses().is_empty() {
+        let statuses = report
+            .statuses()
+            .iter()
+            .map(|(s, c)| {
+                let status = StatusCode::from_u16(*s)
+                    .map(get_formatted_status)
+                    .unwrap_or_else(|_| s.to_string());
+                format!("{} × {}", status, c)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        rows.push(("Status", statuses));
+    }
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::modern())
+        .with(Disable::row(Rows::first()));
+    println!("{}", table);
+
+    Ok(())
+}
+
+/// Print the request as it would be sent. The secrets are hidden if a masker is given.
+pub(super) fn print_dry_run(request: &Request, masker: Option<&Masker>) {
+    let url = request.url().as_str();
+    println!(
+        "{} {}",
+        request
+            .method()
+            .as_str()
+            .if_supports_color(Stdout, |m| m.bold()),
+        masker.map_or(Cow::Borrowed(url), |m| m.mask(url))
+    );
+
+    for (name, value) in request.headers() {
+        let sensitive = is_sensitive_header(name, value);
+        let value = String::from_utf8_lossy(value.as_bytes());
+        let value = match masker {
+            Some(_) if sensitive => MASK.to_string(),
+            Some(m) => m.mask(&value).into_owned(),
+            None => value.into_owned(),
+        };
+
+        println!(
+            "{}: {}",
+            name.as_str().if_supports_color(Stdout, |n| n.cyan()),
+            value
+        );
+    }
+
+    let body = match request.body().and_then(|b| b.as_bytes()) {
+        Some(b) if !b.is_empty() => b,
+        _ => return,
+    };
+
+    println!();
+    if let Ok(v) = serde_json::from_slice::<Value>(body) {
+        let json = to_colored_json(&v, json_color_mode()).expect("error colorizing json");
+        println!(
+            "{}",
+            masker.map_or(Cow::Borrowed(json.as_str()), |m| m.mask(&json))
+        );
+    } else if let Ok(s) = std::str::from_utf8(body) {
+        println!("{}", masker.map_or(Cow::Borrowed(s), |m| m.mask(s)));
+    } else {
+        println!("<{} bytes of binary data>", body.len());
+    }
+}
+
+fn print_table(
+    res: &ApiResponse,
+    no_headers: bool,
+    no_body: bool,
+    filter: Option<&BodyFilter>,
+    tabular: Option<&Tabular>,
+    hex: bool,
+    details: &ExecutionDetails,
+) -> Result<()> {
+    let mut request_results = vec![("Status", get_formatted_status(res.status()))];
+    if let Some(id) = details.request_id {
+        request_results.push(("Request ID", id.to_string()));
+    }
+    if let Some(id) = &details.trace_id {
+        request_results.push(("Trace", get_formatted_trace(id)));
+    }
+    if details.not_modified {
+        request_results.push(("Cache", get_formatted_not_modified()));
+    }
+    if details.replayed {
+        request_results.push(("Cache", get_formatted_replayed()));
+    }
+    request_results.extend([
+        (
+            "Latency",
+            get_formatted_latency_with_trend(res.latency(), details),
+        ),
+        ("Size", get_formatted_size(res)),
+        (
+            "Timing",
+            get_formatted_timings(res.timings(), res.url().scheme() == "https"),
+        ),
+    ]);
+
+    if !res.redirects().is_empty() {
+        request_results.push(("Redirects", get_formatted_redirects(res.redirects())));
+    }
+
+    if !no_headers {
+        if let Some(h) = get_formatted_headers(res.headers()) {
             request_results.push(("Headers", h));
         }
     }
 
-    if !args.headers_only {
-        if let Some(b) = get_formatted_body(res, &args.json_path).await? {
+    if !no_body {
+        if let Some(b) = get_formatted_body(res, filter, tabular, hex)? {
             request_results.push(("Body", b));
         }
     }
@@ -88,8 +1032,346 @@ pub async fn execute_request(args: RunArgs) -> Result<()> {
     Ok(())
 }
 
-fn get_formatted_status(res: &Response) -> String {
-    res.status()
+/// Print the body as received, without any decoration, so it can be piped to other tools.
+fn print_body(
+    res: &ApiResponse,
+    filter: Option<&BodyFilter>,
+    tabular: Option<&Tabular>,
+    hex: bool,
+) -> Result<()> {
+    if let Some(tabular) = tabular {
+        println!("{}", get_tabular_body(res, filter, tabular)?);
+        return Ok(());
+    }
+
+    match filter {
+        _ if hex => println!("{}", hexdump(res.body())),
+        // Binary content is still written when redirected, e.g. to a file.
+        None if res.is_binary() && io::stdout().is_terminal() => {
+            eprintln!("{}", get_binary_body_message(res))
+        }
+        Some(filter) => {
+            let v: Value = serde_json::from_slice(res.body())?;
+            let matches = filter.apply(&v)?;
+            if matches.is_empty() {
+                eprintln!("No match for {}", filter);
+            }
+            for m in matches {
+                println!("{}", serde_json::to_string_pretty(&m)?);
+            }
+        }
+        None => io::stdout().write_all(res.body())?,
+    }
+
+    Ok(())
+}
+
+/// Print the items of all the pages of a list as a single json array.
+fn print_pages(pages: &Pages, args: &RunArgs) -> Result<()> {
+    let items = Value::Array(pages.items.clone());
+    let items = match BodyFilter::from_args(args) {
+        Some(filter) => Value::Array(filter.apply(&items)?),
+        None => items,
+    };
+    let tabular = get_tabular(args).map(|t| t.render(&items)).transpose()?;
+
+    match args.output {
+        OutputMode::Table => {
+            let mut rows = vec![
+                ("Pages", pages.count.to_string()),
+                ("Items", pages.items.len().to_string()),
+                ("Latency", get_formatted_latency(pages.latency, None)),
+            ];
+            if !args.no_body {
+                rows.push((
+                    "Body",
+                    tabular.unwrap_or_else(|| {
+                        to_colored_json(&items, json_color_mode()).expect("error colorizing json")
+                    }),
+                ));
+            }
+
+            let mut table = Table::new(rows);
+            table
+                .with(Style::modern())
+                .with(Disable::row(Rows::first()));
+            println!("{}", table);
+        }
+        OutputMode::Body => match tabular {
+            Some(t) => println!("{}", t),
+            None => println!("{}", serde_json::to_string_pretty(&items)?),
+        },
+        OutputMode::Json => {
+            let output = json!({
+                "pages": pages.count,
+                "latency_ms": pages.latency.as_secs_f64() * 1000.0,
+                "items": items,
+            });
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the response as a single json document containing the status, latency, headers and body.
+fn print_json(
+    res: &ApiResponse,
+    filter: Option<&BodyFilter>,
+    raw_headers: bool,
+    no_headers: bool,
+    no_body: bool,
+) -> Result<()> {
+    let status = res.status().as_u16();
+
+    let body = if let Some(docs) = res.json_lines() {
+        match filter {
+            Some(filter) => Value::Array(apply_to_all(filter, &docs)?),
+            None => Value::Array(docs),
+        }
+    } else if let Some(v) = res.json() {
+        match filter {
+            Some(filter) => Value::Array(filter.apply(v)?),
+            None => v.clone(),
+        }
+    } else if res.body().is_empty() {
+        Value::Null
+    } else if res.is_binary() {
+        Value::String(BASE64_STANDARD.encode(res.body()))
+    } else {
+        match res.text() {
+            Some(s) => Value::String(s.to_string()),
+            None => Value::Null,
+        }
+    };
+
+    let mut output = json!({
+        "status": status,
+        "latency_ms": res.latency().as_secs_f64() * 1000.0,
+        "size": res.body().len(),
+        "timings_ms": {
+            "dns": res.timings().dns.map(as_millis),
+            "connect": res.timings().connect.map(as_millis),
+            "ttfb": as_millis(res.timings().ttfb),
+            "download": as_millis(res.timings().download),
+        },
+        "redirects": res
+            .redirects()
+            .iter()
+            .map(|r| json!({"status": r.status.as_u16(), "location": r.location.as_str()}))
+            .collect::<Vec<_>>(),
+        "headers": headers_to_json(res.headers()),
+        "body": body,
+    });
+
+    if res.is_binary() && res.json().is_none() {
+        output["body_encoding"] = json!("base64");
+    }
+
+    // Keep every header value as a separate entry. The values of a header are grouped together, as
+    // the order in which the headers were received is not kept.
+    if raw_headers {
+        output["version"] = json!(format!("{:?}", res.version()));
+        output["headers"] = res
+            .headers()
+            .iter()
+            .map(|(k, v)| json!([k.as_str(), String::from_utf8_lossy(v.as_bytes())]))
+            .collect();
+    }
+
+    if let Some(o) = output.as_object_mut() {
+        if no_headers {
+            o.remove("headers");
+        }
+        if no_body {
+            o.remove("body");
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(())
+}
+
+/// Print the response of a request whose body was written to a file.
+fn print_saved_response(
+    res: &SavedResponse,
+    args: &RunArgs,
+    details: &ExecutionDetails,
+) -> Result<()> {
+    let saved = format!("Saved {} bytes to {}", res.size(), res.path().display());
+    let no_body = args.headers_only || args.no_body;
+
+    match args.output {
+        OutputMode::Table => {
+            let mut results = vec![("Status", get_formatted_status(res.status()))];
+            if let Some(id) = details.request_id {
+                results.push(("Request ID", id.to_string()));
+            }
+            if let Some(id) = &details.trace_id {
+                results.push(("Trace", get_formatted_trace(id)));
+            }
+            results.push((
+                "Latency",
+                get_formatted_latency_with_trend(res.latency(), details),
+            ));
+
+            if !args.no_headers {
+                if let Some(h) = get_formatted_headers(res.headers()) {
+                    results.push(("Headers", h));
+                }
+            }
+
+            if !no_body {
+                results.push(("Body", saved));
+            }
+
+            let mut table = Table::new(results);
+            table
+                .with(Style::modern())
+                .with(Disable::row(Rows::first()));
+            println!("{}", table);
+        }
+        OutputMode::Body => {
+            if args.headers_only {
+                print_header_lines(res.headers());
+            }
+            if !no_body {
+                eprintln!("{}", saved);
+            }
+        }
+        OutputMode::Json => {
+            let mut output = json!({
+                "status": res.status().as_u16(),
+                "latency_ms": res.latency().as_secs_f64() * 1000.0,
+                "headers": headers_to_json(res.headers()),
+                "file": res.path(),
+                "size": res.size(),
+            });
+
+            if let Some(o) = output.as_object_mut() {
+                if args.no_headers {
+                    o.remove("headers");
+                }
+                if no_body {
+                    o.remove("file");
+                    o.remove("size");
+                }
+            }
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the result of a gRPC call, in the same formats as an HTTP response.
+fn print_grpc_response(
+    res: &GrpcResponse,
+    output: OutputMode,
+    no_headers: bool,
+    no_body: bool,
+    filter: Option<&BodyFilter>,
+    details: &ExecutionDetails,
+) -> Result<()> {
+    let body = match (res.body(), filter) {
+        (Some(b), Some(filter)) => Some(Value::Array(filter.apply(b)?)),
+        (b, None) => b.cloned(),
+        (None, _) => None,
+    };
+
+    match output {
+        OutputMode::Table => {
+            let mut status = format!("{:?}", res.code());
+            if !res.message().is_empty() {
+                status = format!("{}: {}", status, res.message());
+            }
+            let status = status
+                .if_supports_color(Stdout, |s| match res.code() {
+                    Code::Ok => s.style(OwoStyle::new().green()),
+                    _ => s.style(OwoStyle::new().red()),
+                })
+                .to_string();
+
+            let mut results = vec![
+                ("Status", status),
+                (
+                    "Latency",
+                    get_formatted_latency_with_trend(res.latency(), details),
+                ),
+            ];
+
+            if !no_headers {
+                if let Some(h) = get_formatted_headers(res.metadata()) {
+                    results.push(("Metadata", h));
+                }
+            }
+
+            if !no_body {
+                if let Some(b) = &body {
+                    results.push((
+                        "Body",
+                        to_colored_json(b, json_color_mode()).expect("error colorizing json"),
+                    ));
+                }
+            }
+
+            let mut table = Table::new(results);
+            table
+                .with(Style::modern())
+                .with(Disable::row(Rows::first()));
+            println!("{}", table);
+        }
+        OutputMode::Body => {
+            if let Some(b) = &body {
+                println!("{}", serde_json::to_string_pretty(b)?);
+            }
+        }
+        OutputMode::Json => {
+            let output = json!({
+                "status": format!("{:?}", res.code()),
+                "message": res.message(),
+                "latency_ms": res.latency().as_secs_f64() * 1000.0,
+                "metadata": headers_to_json(res.metadata()),
+                "body": body,
+            });
+
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+    }
+
+    Ok(())
+}
+
+fn as_millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Convert headers to a json object, with an array for the headers that have multiple values.
+fn headers_to_json(headers: &HeaderMap) -> Map<String, Value> {
+    let mut map = Map::new();
+    for name in headers.keys() {
+        let mut values: Vec<Value> = headers
+            .get_all(name)
+            .iter()
+            .map(|v| Value::String(String::from_utf8_lossy(v.as_bytes()).to_string()))
+            .collect();
+
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Array(values)
+        };
+        map.insert(name.to_string(), value);
+    }
+
+    map
+}
+
+fn get_formatted_status(status: StatusCode) -> String {
+    status
         .if_supports_color(Stdout, |s| {
             let mut status_style = OwoStyle::new();
             status_style = match s.as_u16() {
@@ -105,24 +1387,133 @@ fn get_formatted_status(res: &Response) -> String {
         .to_string()
 }
 
-fn get_formatted_latency(latency: Duration) -> String {
-    let formatted_latency = format!("{:?}", latency);
+/// Format a latency, colored from its value or, if the request has one, from its maximum.
+fn get_formatted_latency(latency: Duration, max_latency: Option<Duration>) -> String {
+    let formatted_latency = match max_latency {
+        Some(m) if latency > m => format!("{:?} (max {:?})", latency, m),
+        _ => format!("{:?}", latency),
+    };
     formatted_latency
         .if_supports_color(Stdout, |d| {
             let mut status_style = OwoStyle::new();
-            status_style = match latency.as_secs_f64() {
-                1.0..=5.0 => status_style.yellow(),
-                5.0.. => status_style.red(),
-                _ => status_style.green(),
+            status_style = match (max_latency, latency.as_secs_f64()) {
+                (Some(m), _) if latency > m => status_style.red().bold(),
+                (Some(_), _) => status_style.green(),
+                (None, 1.0..=5.0) => status_style.yellow(),
+                (None, 5.0..) => status_style.red(),
+                (None, _) => status_style.green(),
             };
             d.style(status_style)
         })
         .to_string()
 }
 
-fn get_formatted_headers(res: &Response) -> Option<String> {
-    let headers = res.headers();
+/// Format a latency followed, if the request was executed before, by a sparkline and the minimum,
+/// average and maximum of its past latencies.
+fn get_formatted_latency_with_trend(latency: Duration, details: &ExecutionDetails) -> String {
+    let formatted_latency = get_formatted_latency(latency, details.max_latency);
+    let latencies = &details.latencies;
+
+    let (Some(min), Some(max)) = (latencies.iter().min(), latencies.iter().max()) else {
+        return formatted_latency;
+    };
+    let avg = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+    let trend = format!(
+        "{} min {:?} · avg {:?} · max {:?}",
+        sparkline(latencies),
+        *min,
+        Duration::from_millis(avg.as_millis() as u64),
+        *max
+    );
+
+    format!(
+        "{}  {}",
+        formatted_latency,
+        trend.if_supports_color(Stdout, |t| t.dimmed())
+    )
+}
+
+/// Draw durations as a line of bars whose heights go from the shortest to the longest one.
+fn sparkline(durations: &[Duration]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = durations.iter().min().copied().unwrap_or_default();
+    let max = durations.iter().max().copied().unwrap_or_default();
+    if max == min {
+        return BARS[0].to_string().repeat(durations.len());
+    }
+
+    let range = (max - min).as_secs_f64();
+    durations
+        .iter()
+        .map(|d| {
+            BARS[((*d - min).as_secs_f64() / range * (BARS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+/// Format the ID of a trace, followed by its link if the user configured one.
+fn get_formatted_trace(trace_id: &str) -> String {
+    match &config().trace_url {
+        Some(url) => format!("{}\n{}", trace_id, url.replace("{{traceId}}", trace_id)),
+        None => trace_id.to_string(),
+    }
+}
+
+fn get_formatted_not_modified() -> String {
+    "Not modified since the last response"
+        .if_supports_color(Stdout, |s| s.cyan())
+        .to_string()
+}
 
+fn get_formatted_replayed() -> String {
+    "Replayed from the cache"
+        .if_supports_color(Stdout, |s| s.cyan())
+        .to_string()
+}
+
+fn get_formatted_redirects(redirects: &[Redirect]) -> String {
+    redirects
+        .iter()
+        .map(|r| format!("{} → {}", get_formatted_status(r.status), r.location))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The TLS handshake is done by the connector of reqwest along with the TCP connection, and can't
+/// be timed separately.
+fn get_formatted_timings(timings: &Timings, tls: bool) -> String {
+    let mut rows = Vec::new();
+    if let Some(d) = timings.dns {
+        rows.push(HeaderRow {
+            name: "DNS",
+            value: format!("{:?}", d),
+        });
+    }
+    if let Some(c) = timings.connect {
+        rows.push(HeaderRow {
+            name: if tls { "Connect + TLS" } else { "Connect" },
+            value: format!("{:?}", c),
+        });
+    }
+    rows.push(HeaderRow {
+        name: "TTFB",
+        value: format!("{:?}", timings.ttfb),
+    });
+    rows.push(HeaderRow {
+        name: "Download",
+        value: format!("{:?}", timings.download),
+    });
+
+    let mut table = Table::new(rows);
+    table
+        .with(Style::modern())
+        .with(Disable::row(Rows::first()));
+
+    table.to_string()
+}
+
+fn get_formatted_headers(headers: &HeaderMap) -> Option<String> {
     if headers.is_empty() {
         return None;
     }
@@ -161,36 +1552,220 @@ fn get_formatted_headers(res: &Response) -> Option<String> {
     Some(table.to_string())
 }
 
-async fn get_formatted_body(res: Response, json_path: &Option<String>) -> Result<Option<String>> {
-    let resp_body = res.bytes().await.expect("error reading response body");
-    if resp_body.is_empty() {
+fn get_formatted_body(
+    res: &ApiResponse,
+    filter: Option<&BodyFilter>,
+    tabular: Option<&Tabular>,
+    hex: bool,
+) -> Result<Option<String>> {
+    if res.body().is_empty() {
         return Ok(None);
     }
 
+    if let Some(tabular) = tabular {
+        return get_tabular_body(res, filter, tabular).map(Some);
+    }
+
+    if hex {
+        return Ok(Some(hexdump(res.body())));
+    }
+
+    if res.is_binary() {
+        let preview = &res.body()[..res.body().len().min(BINARY_PREVIEW_SIZE)];
+        return Ok(Some(format!(
+            "{}\n\n{}",
+            get_binary_body_message(res),
+            hexdump(preview)
+        )));
+    }
+
     let width = termwidth() - 16; // Assumes "headers" is the longest in the first col.
 
-    if let Ok(v) = serde_json::from_slice::<Value>(&resp_body) {
-        let rendered_json = match json_path {
-            Some(json_path) => {
-                // TODO: Handle errors
-                let path = JsonPathInst::from_str(json_path).unwrap();
+    if let Some(docs) = res.json_lines() {
+        let docs = match filter {
+            Some(filter) => apply_to_all(filter, &docs)?,
+            None => docs,
+        };
+        if let (Some(filter), true) = (filter, docs.is_empty()) {
+            return Ok(Some(format!("No match for {}", filter)));
+        }
+        let rendered_json = docs
+            .iter()
+            .map(|v| to_colored_json(v, json_color_mode()).expect("error colorizing json"))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let body = textwrap::wrap(&rendered_json, Options::new(width).break_words(true));
 
-                find_slice(&path, &v)
-                    .into_iter()
-                    .map(|s| to_colored_json_auto(&s.to_data()).expect("error colorizing json"))
-                    .collect::<Vec<String>>()
-                    .join("\n")
-            }
-            None => to_colored_json_auto(&v).expect("error colorizing json"),
+        return Ok(Some(body.join("\n")));
+    } else if let Some(v) = res.json() {
+        let matches = match filter {
+            Some(filter) => filter.apply(v)?,
+            None => vec![v.clone()],
         };
+        if let (Some(filter), true) = (filter, matches.is_empty()) {
+            return Ok(Some(format!("No match for {}", filter)));
+        }
+        let rendered_json = matches
+            .iter()
+            .map(|v| to_colored_json(v, json_color_mode()).expect("error colorizing json"))
+            .collect::<Vec<String>>()
+            .join("\n");
 
         let body = textwrap::wrap(&rendered_json, Options::new(width).break_words(true));
 
         return Ok(Some(body.join("\n")));
-    } else if let Ok(s) = String::from_utf8(resp_body.into_iter().collect::<Vec<u8>>()) {
-        let body = textwrap::wrap(&s, Options::new(width));
+    } else if let (Some(markup), Some(s)) = (res.markup(), res.text()) {
+        let mut formatted = format_markup(s, markup);
+        if json_color_mode().use_color() {
+            formatted = highlight_markup(&formatted, markup);
+        }
+
+        let body = textwrap::wrap(&formatted, Options::new(width).break_words(true));
+        return Ok(Some(body.join("\n")));
+    } else if let Some(s) = res.text() {
+        let body = textwrap::wrap(s, Options::new(width));
         return Ok(Some(body.join("\n")));
     };
 
     Ok(None)
 }
+
+/// The text of the body, or of its matches, one per line. Matched strings are copied without
+/// their quotes, e.g. the value of a token.
+fn get_copied_body(res: &ApiResponse, filter: Option<&BodyFilter>) -> Result<String> {
+    let Some(filter) = filter else {
+        return match res.text() {
+            Some(s) if !res.is_binary() => Ok(s.to_string()),
+            _ => Err(ApiClientError::new_clipboard_error(
+                "the body is binary".to_string(),
+            )),
+        };
+    };
+
+    let v: Value = serde_json::from_slice(res.body())?;
+    let matches = filter.apply(&v)?;
+    if matches.is_empty() {
+        return Err(ApiClientError::new_clipboard_error(format!(
+            "no match for {}",
+            filter
+        )));
+    }
+
+    let lines = matches
+        .iter()
+        .map(|m| match m {
+            Value::String(s) => Ok(s.clone()),
+            m => serde_json::to_string_pretty(m),
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(lines.join("\n"))
+}
+
+fn get_tabular(args: &RunArgs) -> Option<Tabular> {
+    args.format.map(|f| Tabular::new(f, args.fields.clone()))
+}
+
+/// Render the json body in columns. A filter can select the array to display, e.g. `$.items`, or
+/// its items, e.g. `$.items[?(@.active)]`.
+fn get_tabular_body(
+    res: &ApiResponse,
+    filter: Option<&BodyFilter>,
+    tabular: &Tabular,
+) -> Result<String> {
+    let value = match (res.json_lines(), res.json()) {
+        (Some(docs), _) => Value::Array(docs),
+        (None, Some(v)) => v.clone(),
+        (None, None) => {
+            return Err(ApiClientError::new_not_tabular(
+                "it is not json".to_string(),
+            ))
+        }
+    };
+
+    let value = match filter {
+        Some(filter) => match filter.apply(&value)? {
+            mut matches if matches.len() == 1 && matches[0].is_array() => matches.remove(0),
+            matches => Value::Array(matches),
+        },
+        None => value,
+    };
+
+    tabular.render(&value)
+}
+
+fn get_formatted_size(res: &ApiResponse) -> String {
+    let size = HumanBytes(res.body().len() as u64);
+
+    match (res.content_encoding(), res.compressed_size()) {
+        (Some(encoding), Some(compressed)) => {
+            format!("{} ({} {})", size, HumanBytes(compressed), encoding)
+        }
+        (Some(encoding), None) => format!("{} ({}, not decoded)", size, encoding),
+        (None, _) => size.to_string(),
+    }
+}
+
+fn get_binary_body_message(res: &ApiResponse) -> String {
+    format!(
+        "Binary content of {}, use --output-file to save it or --hex to display it",
+        HumanBytes(res.body().len() as u64)
+    )
+}
+
+/// A filter selecting what is displayed of the json bodies.
+enum BodyFilter<'a> {
+    JsonPath(&'a str),
+    Jq(&'a JqFilter),
+}
+
+impl BodyFilter<'_> {
+    fn from_args(args: &RunArgs) -> Option<BodyFilter<'_>> {
+        match (&args.json_path, &args.jq) {
+            (Some(p), _) => Some(BodyFilter::JsonPath(p)),
+            (None, Some(f)) => Some(BodyFilter::Jq(f)),
+            (None, None) => None,
+        }
+    }
+
+    fn apply(&self, value: &Value) -> Result<Vec<Value>> {
+        match self {
+            BodyFilter::JsonPath(p) => find_json_path(p, value),
+            BodyFilter::Jq(f) => f.run(value),
+        }
+    }
+}
+
+impl Display for BodyFilter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyFilter::JsonPath(p) => write!(f, "json-path `{}`", p),
+            BodyFilter::Jq(jq) => write!(f, "jq filter `{}`", jq),
+        }
+    }
+}
+
+/// Apply the filter to each document and concatenate the results.
+fn apply_to_all(filter: &BodyFilter, docs: &[Value]) -> Result<Vec<Value>> {
+    let mut values = Vec::new();
+    for v in docs {
+        values.extend(filter.apply(v)?);
+    }
+
+    Ok(values)
+}
+
+fn find_json_path(json_path: &str, value: &Value) -> Result<Vec<Value>> {
+    let path = JsonPathInst::from_str(json_path)
+        .map_err(|e| ApiClientError::new_invalid_json_path(json_path, e.to_string()))?;
+
+    // A path that matches nothing yields a single null, which is not a match.
+    Ok(find_slice(&path, value)
+        .into_iter()
+        .filter_map(|s| match s {
+            JsonPathValue::NoValue => None,
+            s => Some(s.to_data()),
+        })
+        .collect())
+}
@@ -0,0 +1,427 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use api_cli::error::{ApiClientError, Result};
+use api_cli::report::{CaseOutcome, CaseResult, RunReport};
+use api_cli::{
+    execution_order,
+    load_dataset,
+    ClientPool,
+    ExpectedStatus,
+    RequestModel,
+    RequestName,
+};
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stderr;
+use owo_colors::Stream::Stdout;
+use reqwest::StatusCode;
+use reqwest_cookie_store::CookieStoreMutex;
+use tonic::Code;
+
+use super::cookies::{load_cookie_store, save_cookie_store};
+use super::history::record_history;
+use super::refresh::{execute_with_token_refresh, RefreshContext};
+use super::request::find_requests_by_seq;
+use super::run::{apply_options, capture_variables, rate_limiter};
+use super::utils::{get_request_file_path, load_request, read_file};
+use super::variables::save_runtime_variables;
+use super::{OutputMode, RunArgs};
+
+/// Execute the requests of a collection, or the ones with matching tags, one after the other, and
+/// report on their results. The dependencies of the requests are executed before them, and a
+/// request is skipped if one of its dependencies failed.
+pub(super) async fn run_collection(
+    collection: &str,
+    environment: Option<&str>,
+    args: &RunArgs,
+) -> Result<()> {
+    let cookie_store = Arc::new(CookieStoreMutex::new(load_cookie_store(collection)?));
+    let clients = ClientPool::default();
+    let mut report = RunReport::new(collection.to_string());
+    let start = Instant::now();
+
+    let requests = find_requests_by_seq(collection)?
+        .into_iter()
+        .filter(|(_, meta)| args.tag.as_ref().is_none_or(|t| t.matches(meta.tags())))
+        .map(|(name, _)| {
+            name.parse()
+                .map_err(ApiClientError::new_invalid_request_name)
+        })
+        .collect::<Result<Vec<RequestName>>>()?;
+
+    let mut dependencies = HashMap::new();
+    let requests = execution_order(&requests, |r| {
+        let path = get_request_file_path(collection, &r.to_string())?;
+        let deps = match read_file::<RequestModel>(&path) {
+            Ok(m) => m.dependencies()?,
+            // The requests that can't be read are reported as errors when they are executed.
+            Err(_) => Vec::new(),
+        };
+        dependencies.insert(r.clone(), deps.clone());
+
+        Ok(deps)
+    })?;
+
+    let rate_limiter = rate_limiter(args);
+    let mut captured = HashMap::new();
+    let mut failed = HashSet::new();
+    for name in requests {
+        let failed_dependency = dependencies
+            .get(&name)
+            .and_then(|deps| deps.iter().find(|d| failed.contains(*d)));
+
+        let case = match failed_dependency {
+            Some(d) => CaseResult {
+                name: name.to_string(),
+                status: None,
+                duration: Default::default(),
+                outcome: CaseOutcome::Skipped(format!("Dependency {} failed", d)),
+            },
+            None => {
+                if let Some(limiter) = &rate_limiter {
+                    limiter.acquire().await;
+                }
+                execute_case(
+                    collection,
+                    &name.to_string(),
+                    environment,
+                    args,
+                    &cookie_store,
+                    &clients,
+                    &mut captured,
+                )
+                .await
+            }
+        };
+
+        if !matches!(case.outcome, CaseOutcome::Passed) {
+            failed.insert(name);
+        }
+        if !matches!(args.output, OutputMode::Json) {
+            print_case(&case);
+        }
+        report.cases.push(case);
+    }
+
+    report.duration = start.elapsed();
+    save_cookie_store(collection, &cookie_store.lock().unwrap())?;
+    save_runtime_variables(collection, captured)?;
+    record_history(
+        collection,
+        environment,
+        report
+            .cases
+            .iter()
+            .filter_map(|c| Some((c.name.clone(), c.status.clone()?, c.duration))),
+    );
+
+    finish_run(&report, args)
+}
+
+/// Execute a request once per row of a dataset, with the values of the row as variables, and
+/// report on the result of each iteration. The dependencies of the request are executed once,
+/// before the first iteration.
+pub(super) async fn run_dataset(
+    collection: &str,
+    request: &str,
+    environment: Option<&str>,
+    path: &Path,
+    args: &RunArgs,
+) -> Result<()> {
+    let rows = load_dataset(path)?;
+    let captured = run_dependencies(collection, request, environment, args).await?;
+    let cookie_store = Arc::new(CookieStoreMutex::new(load_cookie_store(collection)?));
+    let clients = ClientPool::default();
+    let mut report = RunReport::new(request.to_string());
+    let start = Instant::now();
+
+    let rate_limiter = rate_limiter(args);
+    for (i, row) in rows.into_iter().enumerate() {
+        if let Some(limiter) = &rate_limiter {
+            limiter.acquire().await;
+        }
+
+        // The values of the row are only bound for its iteration.
+        let mut variables = captured.clone();
+        variables.extend(row);
+        let mut case = execute_case(
+            collection,
+            request,
+            environment,
+            args,
+            &cookie_store,
+            &clients,
+            &mut variables,
+        )
+        .await;
+        case.name = format!("{} #{}", request, i + 1);
+
+        if !matches!(args.output, OutputMode::Json) {
+            print_case(&case);
+        }
+        report.cases.push(case);
+    }
+
+    report.duration = start.elapsed();
+    save_cookie_store(collection, &cookie_store.lock().unwrap())?;
+
+    finish_run(&report, args)
+}
+
+/// Write the reports of a run and print its summary, failing if any of its requests failed.
+fn finish_run(report: &RunReport, args: &RunArgs) -> Result<()> {
+    for (format, path) in &args.report {
+        report.write(*format, path)?;
+    }
+
+    match args.output {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&report.to_json())?),
+        _ => print_summary(report),
+    }
+
+    if !report.is_success() {
+        return Err(ApiClientError::new_run_failed(
+            report.failed() + report.errors(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Execute the dependencies of a request, once each, and return the variables they captured.
+pub(super) async fn run_dependencies(
+    collection: &str,
+    request: &str,
+    environment: Option<&str>,
+    args: &RunArgs,
+) -> Result<HashMap<String, String>> {
+    let request: RequestName = request
+        .parse()
+        .map_err(ApiClientError::new_invalid_request_name)?;
+    let mut order = execution_order(&[request], |r| load_dependencies(collection, r))?;
+    // The request itself comes last.
+    order.pop();
+
+    let mut captured = HashMap::new();
+    if order.is_empty() {
+        return Ok(captured);
+    }
+
+    let cookie_store = Arc::new(CookieStoreMutex::new(load_cookie_store(collection)?));
+    let clients = ClientPool::default();
+    for name in order {
+        let name = name.to_string();
+        let req = load_request(collection, &name, environment, args.env_file.as_deref())?;
+        let captures = req.captures();
+        let expected_status = req.expected_status().cloned();
+        let req = apply_options(req, args)
+            .with_runtime_variables(captured.clone())
+            .with_cookie_store(Arc::clone(&cookie_store))
+            .with_client_pool(clients.clone());
+
+        let res = req.execute_collected().await?;
+        record_history(
+            collection,
+            environment,
+            [(name.clone(), res.status().to_string(), res.latency())],
+        );
+        if is_unexpected_status(res.status(), expected_status.as_ref()) {
+            return Err(ApiClientError::new_dependency_failed(
+                name,
+                format!("unexpected status {}", res.status()),
+            ));
+        }
+        if !args.quiet {
+            eprintln!(
+                "{}",
+                format!("{} {}", name, res.status()).if_supports_color(Stderr, |s| s.dimmed())
+            );
+        }
+
+        captured.extend(capture_variables(&res, &captures)?);
+    }
+    save_cookie_store(collection, &cookie_store.lock().unwrap())?;
+
+    Ok(captured)
+}
+
+fn load_dependencies(collection: &str, request: &RequestName) -> Result<Vec<RequestName>> {
+    let path = get_request_file_path(collection, &request.to_string())?;
+
+    read_file::<RequestModel>(&path)?.dependencies()
+}
+
+async fn execute_case(
+    collection: &str,
+    name: &str,
+    environment: Option<&str>,
+    args: &RunArgs,
+    cookie_store: &Arc<CookieStoreMutex>,
+    clients: &ClientPool,
+    captured: &mut HashMap<String, String>,
+) -> CaseResult {
+    let start = Instant::now();
+    let mut case = CaseResult {
+        name: name.to_string(),
+        status: None,
+        duration: Default::default(),
+        outcome: CaseOutcome::Passed,
+    };
+
+    let req = match load_request(collection, name, environment, args.env_file.as_deref()) {
+        Ok(r) => apply_options(r, args)
+            .with_runtime_variables(captured.clone())
+            .with_cookie_store(Arc::clone(cookie_store))
+            .with_client_pool(clients.clone()),
+        Err(e) => {
+            case.outcome = CaseOutcome::Error(e.to_string());
+            return case;
+        }
+    };
+
+    let captures = req.captures();
+    let max_latency = req.max_latency();
+    let expected_status = req.expected_status().cloned();
+    let schema = match req.response_schema() {
+        Ok(s) => s,
+        Err(e) => {
+            case.outcome = CaseOutcome::Error(e.to_string());
+            return case;
+        }
+    };
+
+    if req.is_sse_stream() {
+        case.outcome = CaseOutcome::Skipped("Streamed requests are not supported".to_string());
+        return case;
+    }
+
+    if req.is_grpc() {
+        match req.execute_grpc().await {
+            Ok(res) => {
+                case.status = Some(format!("{:?}", res.code()));
+
+                let mut failures = Vec::new();
+                if res.code() != Code::Ok {
+                    failures.push(format!("Unexpected status {:?}", res.code()));
+                }
+                failures.extend(latency_failure(res.latency(), max_latency));
+                if !failures.is_empty() {
+                    case.outcome = CaseOutcome::Failed(failures);
+                }
+            }
+            Err(e) => case.outcome = CaseOutcome::Error(e.to_string()),
+        }
+    } else {
+        let ctx = RefreshContext {
+            collection,
+            environment,
+            args,
+            cookie_store,
+            clients,
+        };
+        let res = execute_with_token_refresh(&ctx, req, captured, |captured| {
+            Ok(apply_options(
+                load_request(collection, name, environment, args.env_file.as_deref())?,
+                args,
+            )
+            .with_runtime_variables(captured.clone())
+            .with_cookie_store(Arc::clone(cookie_store))
+            .with_client_pool(clients.clone()))
+        })
+        .await;
+
+        match res {
+            Ok(res) => {
+                case.status = Some(res.status().to_string());
+
+                let mut failures = Vec::new();
+                if is_unexpected_status(res.status(), expected_status.as_ref()) {
+                    failures.push(match &expected_status {
+                        Some(e) => format!("Unexpected status {}, expected {}", res.status(), e),
+                        None => format!("Unexpected status {}", res.status()),
+                    });
+                }
+                failures.extend(latency_failure(res.latency(), max_latency));
+                if let Some(s) = &schema {
+                    failures.extend(s.validate_response(&res).iter().map(ToString::to_string));
+                }
+                if !captures.is_empty() {
+                    match capture_variables(&res, &captures) {
+                        Ok(vars) => captured.extend(vars),
+                        Err(e) => failures.push(e.to_string()),
+                    }
+                }
+                if !failures.is_empty() {
+                    case.outcome = CaseOutcome::Failed(failures);
+                }
+            }
+            Err(e) => case.outcome = CaseOutcome::Error(e.to_string()),
+        }
+    }
+
+    case.duration = start.elapsed();
+
+    case
+}
+
+/// Whether the status doesn't match the expected one, or is a client or server error if the request
+/// doesn't expect any.
+fn is_unexpected_status(status: StatusCode, expected: Option<&ExpectedStatus>) -> bool {
+    match expected {
+        Some(e) => !e.matches(status.as_u16()),
+        None => status.is_client_error() || status.is_server_error(),
+    }
+}
+
+fn latency_failure(latency: Duration, max_latency: Option<Duration>) -> Option<String> {
+    max_latency
+        .filter(|m| latency > *m)
+        .map(|m| format!("Latency of {:?} exceeds the maximum of {:?}", latency, m))
+}
+
+fn print_case(case: &CaseResult) {
+    let (symbol, detail) = match &case.outcome {
+        CaseOutcome::Passed => (
+            "✓".if_supports_color(Stdout, |s| s.green()).to_string(),
+            None,
+        ),
+        CaseOutcome::Failed(f) => (
+            "✗".if_supports_color(Stdout, |s| s.red()).to_string(),
+            Some(f.join(", ")),
+        ),
+        CaseOutcome::Error(e) => (
+            "✗".if_supports_color(Stdout, |s| s.red()).to_string(),
+            Some(e.clone()),
+        ),
+        CaseOutcome::Skipped(r) => (
+            "-".if_supports_color(Stdout, |s| s.yellow()).to_string(),
+            Some(r.clone()),
+        ),
+    };
+
+    let mut line = format!("{} {}", symbol, case.name);
+    if let Some(s) = &case.status {
+        line.push_str(&format!(" {}", s));
+    }
+    if !matches!(case.outcome, CaseOutcome::Skipped(_)) {
+        line.push_str(&format!(" ({} ms)", case.duration.as_millis()));
+    }
+    println!("{}", line);
+
+    if let Some(d) = detail {
+        println!("    {}", d.if_supports_color(Stdout, |s| s.dimmed()));
+    }
+}
+
+fn print_summary(report: &RunReport) {
+    println!(
+        "\n{} passed, {} failed, {} error(s), {} skipped in {} ms",
+        report.passed(),
+        report.failed(),
+        report.errors(),
+        report.skipped(),
+        report.duration.as_millis()
+    );
+}
@@ -0,0 +1,38 @@
+use std::io::{self, BufRead};
+
+use api_cli::error::Result;
+use api_cli::secrets::{delete_secret, get_secret, set_secret};
+
+use super::{SecretCmd, SecretNameArgs, SecretSetArgs};
+
+pub fn run_secret_command(cmd: SecretCmd) -> Result<()> {
+    match cmd {
+        SecretCmd::Set(args) => store_secret(args),
+        SecretCmd::Get(args) => show_secret(args),
+        SecretCmd::Delete(args) => remove_secret(args),
+    }
+}
+
+fn store_secret(args: SecretSetArgs) -> Result<()> {
+    let value = match args.value {
+        Some(v) => v,
+        None => {
+            // Read the value from stdin so it doesn't end up in the shell history
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+
+    set_secret(&args.name, &value)
+}
+
+fn show_secret(args: SecretNameArgs) -> Result<()> {
+    println!("{}", get_secret(&args.name)?);
+
+    Ok(())
+}
+
+fn remove_secret(args: SecretNameArgs) -> Result<()> {
+    delete_secret(&args.name)
+}
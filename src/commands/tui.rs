@@ -0,0 +1,571 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use api_cli::error::Result;
+use api_cli::{format_markup, ApiResponse, ClientPool, GrpcResponse};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use reqwest::header::HeaderMap;
+use reqwest_cookie_store::CookieStoreMutex;
+use serde_json::Value;
+
+use self::json::JsonView;
+use super::collection::find_collections;
+use super::cookies::{load_cookie_store, save_cookie_store};
+use super::environment::find_environments;
+use super::request::find_requests;
+use super::utils::{get_request_file_path, load_request};
+
+mod json;
+
+static NO_ENVIRONMENT: &str = "(none)";
+static HELP: &str = "q: quit | tab: next pane | enter: open/run/select/fold | r: run";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Tree,
+    Environments,
+    Response,
+}
+
+impl Pane {
+    fn next(self) -> Self {
+        match self {
+            Pane::Tree => Pane::Environments,
+            Pane::Environments => Pane::Response,
+            Pane::Response => Pane::Tree,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Pane::Tree => Pane::Response,
+            Pane::Environments => Pane::Tree,
+            Pane::Response => Pane::Environments,
+        }
+    }
+}
+
+enum TreeItem {
+    Collection(String),
+    Request { collection: String, name: String },
+}
+
+impl TreeItem {
+    fn collection(&self) -> &str {
+        match self {
+            TreeItem::Collection(c) => c,
+            TreeItem::Request { collection, .. } => collection,
+        }
+    }
+}
+
+struct ResponseView {
+    title: String,
+    headers: Vec<(String, String)>,
+    body: ResponseBody,
+    state: ListState,
+}
+
+enum ResponseBody {
+    Json(JsonView),
+    Text(Vec<String>),
+}
+
+impl ResponseView {
+    fn from_response(res: &ApiResponse) -> Self {
+        let json = res
+            .json_lines()
+            .map(Value::Array)
+            .or_else(|| res.json().cloned());
+        let body = match json {
+            Some(v) => ResponseBody::Json(JsonView::new(v)),
+            None => {
+                let text = match (res.markup(), res.text()) {
+                    (Some(markup), Some(s)) => format_markup(s, markup),
+                    _ => String::from_utf8_lossy(res.body()).into_owned(),
+                };
+                ResponseBody::Text(text.lines().map(String::from).collect())
+            }
+        };
+
+        Self::new(
+            format!("{} - {} ms", res.status(), res.latency().as_millis()),
+            res.headers(),
+            body,
+        )
+    }
+
+    fn from_grpc_response(res: &GrpcResponse) -> Self {
+        let body = match res.body() {
+            Some(v) => ResponseBody::Json(JsonView::new(v.clone())),
+            None => ResponseBody::Text(vec![res.message().to_string()]),
+        };
+
+        Self::new(
+            format!("{:?} - {} ms", res.code(), res.latency().as_millis()),
+            res.metadata(),
+            body,
+        )
+    }
+
+    fn error(message: String) -> Self {
+        Self::new(
+            "Error".to_string(),
+            &HeaderMap::new(),
+            ResponseBody::Text(message.lines().map(String::from).collect()),
+        )
+    }
+
+    fn new(title: String, headers: &HeaderMap, body: ResponseBody) -> Self {
+        let headers = headers
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.to_string(),
+                    String::from_utf8_lossy(v.as_bytes()).to_string(),
+                )
+            })
+            .collect();
+
+        Self {
+            title,
+            headers,
+            body,
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.body {
+            ResponseBody::Json(j) => j.lines().len(),
+            ResponseBody::Text(t) => t.len(),
+        }
+    }
+
+    fn toggle_fold(&mut self) {
+        if let (ResponseBody::Json(j), Some(idx)) = (&mut self.body, self.state.selected()) {
+            j.toggle(idx);
+        }
+    }
+}
+
+struct App {
+    focus: Pane,
+    collections: Vec<(String, Vec<String>)>,
+    expanded: HashSet<String>,
+    tree: Vec<TreeItem>,
+    tree_state: ListState,
+    /// Collection for which the environments are listed.
+    environments_collection: Option<String>,
+    environments: Vec<String>,
+    environments_state: ListState,
+    environment: Option<String>,
+    request_summary: String,
+    response: Option<ResponseView>,
+    status: String,
+    quit: bool,
+    /// Sessions of the collections, in each environment the requests were executed in.
+    sessions: HashMap<(String, Option<String>), Session>,
+}
+
+/// Cookies and clients kept between the requests of a collection, so their connections are reused.
+#[derive(Clone)]
+struct Session {
+    cookie_store: Arc<CookieStoreMutex>,
+    clients: ClientPool,
+}
+
+/// Browse the collections and execute their requests in an interactive interface.
+pub async fn run_tui() -> Result<()> {
+    let mut app = App::new()?;
+
+    let mut terminal = ratatui::init();
+    let res = app.run(&mut terminal).await;
+    ratatui::restore();
+
+    res
+}
+
+impl App {
+    fn new() -> Result<Self> {
+        let collections = find_collections()?
+            .into_iter()
+            .map(|c| {
+                let requests = find_requests(c.clone())?;
+                Ok((c, requests))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut app = Self {
+            focus: Pane::Tree,
+            collections,
+            expanded: HashSet::new(),
+            tree: Vec::new(),
+            tree_state: ListState::default().with_selected(Some(0)),
+            environments_collection: None,
+            environments: Vec::new(),
+            environments_state: ListState::default(),
+            environment: None,
+            request_summary: String::new(),
+            response: None,
+            status: HELP.to_string(),
+            quit: false,
+            sessions: HashMap::new(),
+        };
+        app.rebuild_tree();
+        app.selection_changed()?;
+
+        Ok(app)
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|f| self.draw(f))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.handle_key(key.code)? {
+                if let Some(TreeItem::Request { collection, name }) = self.selected_item() {
+                    let (collection, name) = (collection.clone(), name.clone());
+
+                    self.status = format!("Running {}/{}...", collection, name);
+                    terminal.draw(|f| self.draw(f))?;
+
+                    let environment = self.environment.clone();
+                    let response = match self.session(&collection, environment.as_deref()) {
+                        Ok(session) => {
+                            execute(&collection, &name, environment.as_deref(), &session).await
+                        }
+                        Err(e) => Err(e),
+                    }
+                    .unwrap_or_else(|e| ResponseView::error(e.to_string()));
+                    self.response = Some(response);
+                    self.status = HELP.to_string();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The session of a collection in an environment, started with the saved cookies.
+    fn session(&mut self, collection: &str, environment: Option<&str>) -> Result<Session> {
+        let key = (collection.to_string(), environment.map(str::to_string));
+        if let Some(s) = self.sessions.get(&key) {
+            return Ok(s.clone());
+        }
+
+        let session = Session {
+            cookie_store: Arc::new(CookieStoreMutex::new(load_cookie_store(collection)?)),
+            clients: ClientPool::default(),
+        };
+        self.sessions.insert(key, session.clone());
+
+        Ok(session)
+    }
+
+    /// Returns true if the selected request should be executed.
+    fn handle_key(&mut self, code: KeyCode) -> Result<bool> {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => self.focus = self.focus.next(),
+            KeyCode::BackTab => self.focus = self.focus.previous(),
+            KeyCode::Char('r') => return Ok(true),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1)?,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1)?,
+            KeyCode::PageUp => self.move_selection(-10)?,
+            KeyCode::PageDown => self.move_selection(10)?,
+            KeyCode::Enter | KeyCode::Char(' ') => return self.activate(),
+            _ => {}
+        }
+
+        Ok(false)
+    }
+
+    fn move_selection(&mut self, delta: isize) -> Result<()> {
+        let (state, len) = match self.focus {
+            Pane::Tree => (&mut self.tree_state, self.tree.len()),
+            Pane::Environments => (&mut self.environments_state, self.environments.len() + 1),
+            Pane::Response => match &mut self.response {
+                Some(r) => {
+                    let len = r.len();
+                    (&mut r.state, len)
+                }
+                None => return Ok(()),
+            },
+        };
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        let current = state.selected().unwrap_or(0);
+        state.select(Some(current.saturating_add_signed(delta).min(len - 1)));
+
+        if self.focus == Pane::Tree {
+            self.selection_changed()?;
+        }
+
+        Ok(())
+    }
+
+    fn activate(&mut self) -> Result<bool> {
+        match self.focus {
+            Pane::Tree => match self.selected_item() {
+                Some(TreeItem::Collection(c)) => {
+                    let c = c.clone();
+                    if !self.expanded.remove(&c) {
+                        self.expanded.insert(c);
+                    }
+                    self.rebuild_tree();
+                }
+                Some(TreeItem::Request { .. }) => return Ok(true),
+                None => {}
+            },
+            Pane::Environments => {
+                self.environment = self
+                    .environments_state
+                    .selected()
+                    .and_then(|i| i.checked_sub(1))
+                    .and_then(|i| self.environments.get(i).cloned());
+            }
+            Pane::Response => {
+                if let Some(r) = &mut self.response {
+                    r.toggle_fold();
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn selected_item(&self) -> Option<&TreeItem> {
+        self.tree_state.selected().and_then(|i| self.tree.get(i))
+    }
+
+    fn rebuild_tree(&mut self) {
+        self.tree = self
+            .collections
+            .iter()
+            .flat_map(|(collection, requests)| {
+                let requests = if self.expanded.contains(collection) {
+                    requests.as_slice()
+                } else {
+                    &[]
+                };
+
+                std::iter::once(TreeItem::Collection(collection.clone())).chain(
+                    requests.iter().map(|r| TreeItem::Request {
+                        collection: collection.clone(),
+                        name: r.clone(),
+                    }),
+                )
+            })
+            .collect();
+    }
+
+    /// Refresh the environments and the summary of the request when a new item is selected.
+    fn selection_changed(&mut self) -> Result<()> {
+        let Some(item) = self.selected_item() else {
+            return Ok(());
+        };
+
+        let collection = item.collection().to_string();
+        self.request_summary = match item {
+            TreeItem::Request { collection, name } => {
+                fs::read_to_string(get_request_file_path(collection, name)?)?
+            }
+            TreeItem::Collection(_) => String::new(),
+        };
+
+        if self.environments_collection.as_deref() != Some(collection.as_str()) {
+            self.environments = find_environments(collection.clone())?;
+            self.environments_state = ListState::default().with_selected(Some(0));
+            self.environment = None;
+            self.environments_collection = Some(collection);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+        let [tree, environments] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length((self.environments.len() as u16 + 3).min(10)),
+        ])
+        .areas(left);
+        let [request, response] =
+            Layout::vertical([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(right);
+
+        self.draw_tree(frame, tree);
+        self.draw_environments(frame, environments);
+        self.draw_request(frame, request);
+        self.draw_response(frame, response);
+
+        frame.render_widget(
+            Paragraph::new(self.status.as_str()).style(Style::new().fg(Color::DarkGray)),
+            status,
+        );
+    }
+
+    fn draw_tree(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .tree
+            .iter()
+            .map(|item| match item {
+                TreeItem::Collection(c) => {
+                    let marker = if self.expanded.contains(c) {
+                        "▾"
+                    } else {
+                        "▸"
+                    };
+                    ListItem::new(format!("{} {}", marker, c))
+                }
+                TreeItem::Request { name, .. } => ListItem::new(format!("    {}", name)),
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(self.block("Collections", Pane::Tree))
+            .highlight_style(highlight_style());
+
+        frame.render_stateful_widget(list, area, &mut self.tree_state);
+    }
+
+    fn draw_environments(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = std::iter::once(NO_ENVIRONMENT)
+            .chain(self.environments.iter().map(String::as_str))
+            .map(|e| {
+                let active = match &self.environment {
+                    Some(env) => env == e,
+                    None => e == NO_ENVIRONMENT,
+                };
+                ListItem::new(format!("{} {}", if active { "●" } else { " " }, e))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(self.block("Environment", Pane::Environments))
+            .highlight_style(highlight_style());
+
+        frame.render_stateful_widget(list, area, &mut self.environments_state);
+    }
+
+    fn draw_request(&self, frame: &mut Frame, area: Rect) {
+        let paragraph = Paragraph::new(self.request_summary.as_str())
+            .block(Block::bordered().title("Request"))
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(paragraph, area);
+    }
+
+    fn draw_response(&mut self, frame: &mut Frame, area: Rect) {
+        let block = self.block("Response", Pane::Response);
+
+        let Some(response) = &mut self.response else {
+            frame.render_widget(block, area);
+            return;
+        };
+
+        let block = block.title(Line::from(format!(" {} ", response.title)).right_aligned());
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [headers, body] = Layout::vertical([
+            Constraint::Length((response.headers.len() as u16 + 1).min(10)),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+
+        let header_lines: Vec<Line> = response
+            .headers
+            .iter()
+            .map(|(k, v)| {
+                Line::from(vec![
+                    Span::styled(format!("{}: ", k), Style::new().fg(Color::Cyan)),
+                    Span::raw(v.as_str()),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(header_lines), headers);
+
+        let items: Vec<ListItem> = match &response.body {
+            ResponseBody::Json(j) => j
+                .lines()
+                .iter()
+                .map(|l| {
+                    let marker = match (&l.fold_path, l.folded) {
+                        (Some(_), true) => "▸ ",
+                        (Some(_), false) => "▾ ",
+                        (None, _) => "  ",
+                    };
+                    ListItem::new(format!("{}{}{}", "  ".repeat(l.depth), marker, l.text))
+                })
+                .collect(),
+            ResponseBody::Text(t) => t.iter().map(|l| ListItem::new(l.as_str())).collect(),
+        };
+
+        let list = List::new(items).highlight_style(highlight_style());
+        frame.render_stateful_widget(list, body, &mut response.state);
+    }
+
+    fn block(&self, title: &'static str, pane: Pane) -> Block<'static> {
+        let block = Block::bordered().title(title);
+
+        if self.focus == pane {
+            block.border_style(Style::new().fg(Color::Yellow))
+        } else {
+            block
+        }
+    }
+}
+
+fn highlight_style() -> Style {
+    Style::new().add_modifier(Modifier::REVERSED)
+}
+
+async fn execute(
+    collection: &str,
+    request: &str,
+    environment: Option<&str>,
+    session: &Session,
+) -> Result<ResponseView> {
+    let req = load_request(collection, request, environment, None)?;
+
+    if req.is_grpc() {
+        let res = req.execute_grpc().await?;
+        return Ok(ResponseView::from_grpc_response(&res));
+    }
+
+    if req.is_sse_stream() {
+        return Ok(ResponseView::error(
+            "Streamed requests are not supported in the interactive interface".to_string(),
+        ));
+    }
+
+    let res = req
+        .with_cookie_store(Arc::clone(&session.cookie_store))
+        .with_client_pool(session.clients.clone())
+        .execute_collected()
+        .await?;
+
+    save_cookie_store(collection, &session.cookie_store.lock().unwrap())?;
+
+    Ok(ResponseView::from_response(&res))
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// A json document displayed one line per value, in which objects and arrays can be folded.
+pub(super) struct JsonView {
+    value: Value,
+    folded: HashSet<String>,
+    lines: Vec<JsonLine>,
+}
+
+pub(super) struct JsonLine {
+    pub(super) depth: usize,
+    pub(super) text: String,
+    /// Path of the object or array opened on this line, if any.
+    pub(super) fold_path: Option<String>,
+    pub(super) folded: bool,
+}
+
+impl JsonView {
+    pub(super) fn new(value: Value) -> Self {
+        let mut view = Self {
+            value,
+            folded: HashSet::new(),
+            lines: Vec::new(),
+        };
+        view.rebuild();
+
+        view
+    }
+
+    pub(super) fn lines(&self) -> &[JsonLine] {
+        &self.lines
+    }
+
+    /// Fold or unfold the object or array opened on `line`, if any.
+    pub(super) fn toggle(&mut self, line: usize) {
+        let Some(path) = self.lines.get(line).and_then(|l| l.fold_path.clone()) else {
+            return;
+        };
+
+        if !self.folded.remove(&path) {
+            self.folded.insert(path);
+        }
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        let mut lines = Vec::new();
+        push_value(
+            &mut lines,
+            &self.folded,
+            "$".to_string(),
+            None,
+            &self.value,
+            0,
+            false,
+        );
+
+        self.lines = lines;
+    }
+}
+
+/// Path, key and value of an element of an object or array.
+type Child<'a> = (String, Option<&'a str>, &'a Value);
+
+fn push_value(
+    lines: &mut Vec<JsonLine>,
+    folded: &HashSet<String>,
+    path: String,
+    key: Option<&str>,
+    value: &Value,
+    depth: usize,
+    trailing_comma: bool,
+) {
+    let prefix = key
+        .map(|k| format!("{}: ", Value::String(k.to_string())))
+        .unwrap_or_default();
+    let comma = if trailing_comma { "," } else { "" };
+
+    let (open, close, children): (&str, &str, Vec<Child>) = match value {
+        Value::Object(m) if !m.is_empty() => (
+            "{",
+            "}",
+            m.iter()
+                .map(|(k, v)| (format!("{}.{}", path, k), Some(k.as_str()), v))
+                .collect(),
+        ),
+        Value::Array(a) if !a.is_empty() => (
+            "[",
+            "]",
+            a.iter()
+                .enumerate()
+                .map(|(i, v)| (format!("{}[{}]", path, i), None, v))
+                .collect(),
+        ),
+        _ => {
+            lines.push(JsonLine {
+                depth,
+                text: format!("{}{}{}", prefix, value, comma),
+                fold_path: None,
+                folded: false,
+            });
+            return;
+        }
+    };
+
+    if folded.contains(&path) {
+        lines.push(JsonLine {
+            depth,
+            text: format!(
+                "{}{} … {} ({} items){}",
+                prefix,
+                open,
+                close,
+                children.len(),
+                comma
+            ),
+            fold_path: Some(path),
+            folded: true,
+        });
+        return;
+    }
+
+    lines.push(JsonLine {
+        depth,
+        text: format!("{}{}", prefix, open),
+        fold_path: Some(path),
+        folded: false,
+    });
+
+    let count = children.len();
+    for (i, (child_path, child_key, child)) in children.into_iter().enumerate() {
+        push_value(
+            lines,
+            folded,
+            child_path,
+            child_key,
+            child,
+            depth + 1,
+            i + 1 < count,
+        );
+    }
+
+    lines.push(JsonLine {
+        depth,
+        text: format!("{}{}", close, comma),
+        fold_path: None,
+        folded: false,
+    });
+}
@@ -1,12 +1,38 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
+use std::sync::Arc;
 use std::{env, fs};
 
 use api_cli::error::{ApiClientError, Result};
+use api_cli::{
+    ApiClientRequest,
+    CollectionModel,
+    EnvProvider,
+    FolderModel,
+    RequestModel,
+    RequestName,
+    TraceParent,
+    FOLDER_FILE_NAME,
+};
+use log::debug;
+use once_cell::sync::Lazy;
 use serde::Deserialize;
+use uuid::Uuid;
 
+use super::config::config;
+use super::variables::load_runtime_variables;
 use super::API_CLI_BASE_DIRECTORY;
 
+static GLOBAL_VARIABLE_PREFIX: &str = "API_CLI_VAR_";
+
+/// ID of the current run, shared by all the requests it executes so they can be correlated.
+pub static RUN_ID: Lazy<String> = Lazy::new(|| Uuid::new_v4().to_string());
+
+/// Trace of the current run, when one is propagated without being given.
+pub static RUN_TRACE: Lazy<TraceParent> = Lazy::new(TraceParent::new);
+
 pub fn read_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T> {
     let data: String = match fs::read_to_string(path) {
         Ok(d) => d,
@@ -19,6 +45,85 @@ pub fn read_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T> {
         .map_err(|e| ApiClientError::from_serde_yaml_error_with_path(e, path))
 }
 
+/// Load a request with its collection, the global variables and optionally an environment.
+pub fn load_request(
+    collection_name: &str,
+    request_name: &str,
+    environment_name: Option<&str>,
+    env_file: Option<&Path>,
+) -> Result<ApiClientRequest> {
+    let collection_path = get_collection_file_path(collection_name);
+    let collection: CollectionModel = read_file(collection_path.as_path())?;
+    debug!("Collection: {:#?}", collection);
+
+    let request_path = get_request_file_path(collection_name, request_name)?;
+    let req: RequestModel = read_file(request_path.as_path())?;
+    debug!("Request: {:#?}", req);
+
+    let collection_dir = collection_path
+        .parent()
+        .expect("collection file is in a directory")
+        .to_path_buf();
+    let mut req = ApiClientRequest::new(collection, req)
+        .with_collection_dir(collection_dir)
+        .with_request_file(request_path)
+        .with_request_id(RUN_ID.clone());
+
+    for folder_path in get_folder_file_paths(collection_name, request_name)? {
+        if folder_path.exists() {
+            let folder: FolderModel = read_file(folder_path.as_path())?;
+            debug!("Folder {}: {:#?}", folder_path.display(), folder);
+
+            req = req.with_folder(folder);
+        }
+    }
+
+    if let Some(t) = config().timeout {
+        req = req.with_default_timeout(t);
+    }
+
+    // The global variables come from the process environment, then `env_file`, then the `.env`
+    // file of the collection.
+    req = req.with_variable_provider(Arc::new(EnvProvider::new(GLOBAL_VARIABLE_PREFIX)));
+    if let Some(p) = env_file {
+        req = req.with_variable_provider(Arc::new(read_global_variables(p)?));
+    }
+    let collection_dotenv = get_dotenv_file_path(collection_name);
+    if collection_dotenv.exists() {
+        req = req.with_variable_provider(Arc::new(read_global_variables(&collection_dotenv)?));
+    }
+    req = req.with_runtime_variables(
+        load_runtime_variables(collection_name)?
+            .into_iter()
+            .collect(),
+    );
+
+    if let Some(e) = environment_name {
+        let environment_path = get_environment_file_path(collection_name, e);
+        let env = read_file(environment_path.as_path())?;
+        debug!("Environment: {:#?}", env);
+
+        req = req.with_environment(env);
+    };
+
+    Ok(req)
+}
+
+/// Get the global variables of a dotenv file, which are the variables prefixed with
+/// `API_CLI_VAR_`, without their prefix.
+fn read_global_variables(path: &Path) -> Result<HashMap<String, String>> {
+    Ok(read_dotenv_file(path)?
+        .into_iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix(GLOBAL_VARIABLE_PREFIX)?.to_string(), v)))
+        .collect())
+}
+
+fn read_dotenv_file(path: &Path) -> Result<Vec<(String, String)>> {
+    dotenvy::from_path_iter(path)
+        .and_then(|iter| iter.collect())
+        .map_err(|e| ApiClientError::from_dotenv_error_with_path(e, path))
+}
+
 pub fn get_collections_directory() -> PathBuf {
     PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str())
 }
@@ -40,20 +145,108 @@ pub fn get_environment_file_path(collection_name: &str, environment_name: &str)
     p
 }
 
-pub fn get_request_file_path(collection_name: &str, request_name: &str) -> PathBuf {
+pub fn get_dotenv_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".env");
+
+    p
+}
+
+pub fn get_cookie_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".cookies.json");
+
+    p
+}
+
+pub fn get_variables_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".vars.json");
+
+    p
+}
+
+pub fn get_tokens_file_path(collection_name: &str) -> PathBuf {
     let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
     p.push(collection_name);
-    // TODO: Use `:` everywhere
-    p.push(format!("{}.yaml", request_name.replace(':', "/")));
+    p.push(".tokens.json");
 
     p
 }
 
+pub fn get_history_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".history.json");
+
+    p
+}
+
+pub fn get_validators_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".validators.json");
+
+    p
+}
+
+pub fn get_responses_file_path(collection_name: &str) -> PathBuf {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(".responses.json");
+
+    p
+}
+
+/// Paths of the `folder.yaml` files that can configure a request, from the outermost folder to the
+/// innermost one.
+pub fn get_folder_file_paths(collection_name: &str, request_name: &str) -> Result<Vec<PathBuf>> {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+
+    let request_name = parse_request_name(request_name)?;
+    let mut folders: Vec<&str> = request_name.segments().collect();
+    folders.pop();
+
+    Ok(folders
+        .into_iter()
+        .map(|f| {
+            p.push(f);
+            p.join(FOLDER_FILE_NAME)
+        })
+        .collect())
+}
+
+pub fn get_request_file_path(collection_name: &str, request_name: &str) -> Result<PathBuf> {
+    let mut p = PathBuf::from(API_CLI_BASE_DIRECTORY.as_os_str());
+    p.push(collection_name);
+    p.push(parse_request_name(request_name)?.to_path());
+
+    Ok(p)
+}
+
+fn parse_request_name(request_name: &str) -> Result<RequestName> {
+    request_name
+        .parse()
+        .map_err(ApiClientError::new_invalid_request_name)
+}
+
 pub fn open_file_in_editor(collection_dir: &PathBuf, file_path: &PathBuf) -> Result<ExitStatus> {
-    let editor = env::var("EDITOR").unwrap_or("vi".to_string());
+    let editor = config()
+        .editor
+        .clone()
+        .or(env::var("EDITOR").ok())
+        .unwrap_or("vi".to_string());
+    // The editor can be configured with arguments, e.g. `code --wait`
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or("vi");
 
-    let status = Command::new(editor)
-        .args([file_path])
+    let status = Command::new(program)
+        .args(args)
+        .arg(file_path)
         .current_dir(collection_dir)
         .status()?;
 
@@ -73,3 +266,34 @@ pub(super) fn ensure_collection_directory(collection_name: &str) -> Result<PathB
 
     Ok(collection_directory)
 }
+
+/// Ask the user to confirm an action. Anything other than `y` or `yes` is a refusal.
+pub(super) fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Remove the empty directories between `dir` and `root`, `root` excluded.
+pub(super) fn remove_empty_parents(dir: &Path, root: &Path) -> Result<()> {
+    let mut dir = dir;
+
+    while dir != root && dir.starts_with(root) {
+        if fs::read_dir(dir)?.next().is_some() {
+            break;
+        }
+
+        fs::remove_dir(dir).map_err(|e| ApiClientError::from_io_error_with_path(e, dir))?;
+
+        dir = match dir.parent() {
+            Some(p) => p,
+            None => break,
+        };
+    }
+
+    Ok(())
+}
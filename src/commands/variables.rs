@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use api_cli::error::{ApiClientError, Result};
+
+use super::utils::{ensure_collection_directory, get_variables_file_path};
+use super::{VarCmd, VarNameArgs, VarSetArgs};
+
+pub fn run_var_command(cmd: VarCmd) -> Result<()> {
+    match cmd {
+        VarCmd::Set(args) => set_variable(args),
+        VarCmd::Get(args) => show_variable(args),
+        VarCmd::Unset(args) => unset_variable(args),
+    }
+}
+
+/// Load the runtime variables persisted for a collection, or nothing if none were set.
+pub(super) fn load_runtime_variables(collection_name: &str) -> Result<BTreeMap<String, String>> {
+    let path = get_variables_file_path(collection_name);
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data =
+        fs::read_to_string(&path).map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+    serde_json::from_str(&data)
+        .map_err(|e| ApiClientError::from_serde_json_error_with_path(e, &path))
+}
+
+/// Set runtime variables of a collection, keeping the other ones.
+pub(super) fn save_runtime_variables<I>(collection_name: &str, vars: I) -> Result<()>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut vars = vars.into_iter().peekable();
+    if vars.peek().is_none() {
+        return Ok(());
+    }
+
+    let mut variables = load_runtime_variables(collection_name)?;
+    variables.extend(vars);
+
+    write_runtime_variables(collection_name, &variables)
+}
+
+fn write_runtime_variables(collection_name: &str, vars: &BTreeMap<String, String>) -> Result<()> {
+    let path = get_variables_file_path(collection_name);
+    fs::write(&path, serde_json::to_string_pretty(vars)?)
+        .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))
+}
+
+fn set_variable(args: VarSetArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    save_runtime_variables(&args.collection_name, [(args.key, args.value)])
+}
+
+fn show_variable(args: VarNameArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    match load_runtime_variables(&args.collection_name)?.remove(&args.key) {
+        Some(v) => println!("{}", v),
+        None => return Err(ApiClientError::new_variable_not_set(args.key)),
+    }
+
+    Ok(())
+}
+
+fn unset_variable(args: VarNameArgs) -> Result<()> {
+    ensure_collection_directory(&args.collection_name)?;
+
+    let mut variables = load_runtime_variables(&args.collection_name)?;
+    if variables.remove(&args.key).is_none() {
+        return Err(ApiClientError::new_variable_not_set(args.key));
+    }
+
+    write_runtime_variables(&args.collection_name, &variables)
+}
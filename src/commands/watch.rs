@@ -0,0 +1,88 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use api_cli::error::Result;
+use owo_colors::OwoColorize;
+use owo_colors::Stream::Stdout;
+use ratatui::crossterm::cursor::MoveTo;
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{Clear, ClearType};
+
+use super::run::execute_request;
+use super::utils::{
+    get_collection_file_path,
+    get_dotenv_file_path,
+    get_environment_file_path,
+    get_folder_file_paths,
+    get_request_file_path,
+};
+use super::{RunArgs, WatchArgs};
+
+static POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Execute a request, then execute it again every time one of the files it is built from
+/// changes, until the process is stopped.
+pub async fn run_watch(args: WatchArgs) -> Result<()> {
+    let mut files = vec![
+        get_collection_file_path(&args.collection),
+        get_dotenv_file_path(&args.collection),
+        get_request_file_path(&args.collection, &args.request)?,
+    ];
+    files.extend(get_folder_file_paths(&args.collection, &args.request)?);
+    if let Some(e) = &args.environment {
+        files.push(get_environment_file_path(&args.collection, e));
+    }
+    if let Some(f) = &args.env_file {
+        files.push(f.clone());
+    }
+
+    let mut last_modified = modification_times(&files);
+
+    loop {
+        execute_request_once(&args).await;
+
+        while modification_times(&files) == last_modified {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        // Let editors finish writing before reading the files.
+        tokio::time::sleep(POLL_INTERVAL).await;
+        last_modified = modification_times(&files);
+    }
+}
+
+async fn execute_request_once(args: &WatchArgs) {
+    let _ = execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0));
+
+    let run_args = RunArgs {
+        collection: Some(args.collection.clone()),
+        request: Some(args.request.clone()),
+        environment: args.environment.clone(),
+        env_file: args.env_file.clone(),
+        output: args.output,
+        ..Default::default()
+    };
+
+    if let Err(e) = execute_request(run_args).await {
+        println!("{}", e.if_supports_color(Stdout, |e| e.red()));
+    }
+
+    println!(
+        "\n{}",
+        format!(
+            "Watching {}/{}, press Ctrl-C to stop",
+            args.collection, args.request
+        )
+        .if_supports_color(Stdout, |l| l.dimmed())
+    );
+    let _ = io::stdout().flush();
+}
+
+/// Modification time of each file, or `None` for the ones that don't exist.
+fn modification_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .collect()
+}
@@ -0,0 +1,66 @@
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+/// Validators of a response, sent back in a conditional request so the server answers with a
+/// `304 Not Modified` instead of the whole response when the resource did not change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    /// Value of the `ETag` header, sent back in `If-None-Match`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` header, sent back in `If-Modified-Since`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// The validators of a response, or `None` if it has neither an `ETag` nor a `Last-Modified`
+    /// header.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = Self {
+            etag: value(ETAG),
+            last_modified: value(LAST_MODIFIED),
+        };
+
+        match validators == Self::default() {
+            true => None,
+            false => Some(validators),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+    use super::Validators;
+
+    #[test]
+    fn test_validators_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("W/\"abc\""));
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        assert_eq!(
+            Validators::from_headers(&headers),
+            Some(Validators {
+                etag: Some("W/\"abc\"".to_string()),
+                last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validators_from_headers_without_validators() {
+        assert_eq!(Validators::from_headers(&HeaderMap::new()), None);
+    }
+}
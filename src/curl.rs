@@ -0,0 +1,102 @@
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use reqwest::{Method, Request};
+
+/// Format a request as an equivalent `curl` command.
+pub fn to_curl_command(request: &Request) -> String {
+    let mut parts = vec!["curl".to_string()];
+
+    let body = request
+        .body()
+        .and_then(|b| b.as_bytes())
+        .filter(|b| !b.is_empty());
+
+    if request.method() != Method::GET || body.is_some() {
+        parts.push(format!("-X {}", request.method()));
+    }
+
+    parts.push(shell_quote(request.url().as_str()));
+
+    for (name, value) in request.headers() {
+        let header = format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()));
+        parts.push(format!("-H {}", shell_quote(&header)));
+    }
+
+    if let Some(body) = body {
+        match std::str::from_utf8(body) {
+            Ok(s) => parts.push(format!("--data-raw {}", shell_quote(s))),
+            Err(_) => parts.push(format!(
+                "--data-binary @<(echo {} | base64 -d)",
+                BASE64_STANDARD.encode(body)
+            )),
+        }
+    }
+
+    parts.join(" \\\n  ")
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Client, Method};
+
+    use super::to_curl_command;
+
+    #[test]
+    fn test_get_request() {
+        let request = Client::new()
+            .get("https://api.example.org/users?page=2")
+            .header("Accept", "application/json")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            to_curl_command(&request),
+            "curl \\\n  'https://api.example.org/users?page=2' \\\n  -H 'accept: application/json'"
+        );
+    }
+
+    #[test]
+    fn test_request_with_body() {
+        let request = Client::new()
+            .request(Method::POST, "https://api.example.org/users")
+            .bearer_auth("token")
+            .json(&serde_json::json!({"name": "O'Brien"}))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            to_curl_command(&request),
+            concat!(
+                "curl \\\n",
+                "  -X POST \\\n",
+                "  'https://api.example.org/users' \\\n",
+                "  -H 'authorization: Bearer token' \\\n",
+                "  -H 'content-type: application/json' \\\n",
+                r#"  --data-raw '{"name":"O'\''Brien"}'"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_request_with_binary_body() {
+        let request = Client::new()
+            .request(Method::PUT, "https://api.example.org/upload")
+            .body(vec![0xff, 0x00, 0xfe])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            to_curl_command(&request),
+            concat!(
+                "curl \\\n",
+                "  -X PUT \\\n",
+                "  'https://api.example.org/upload' \\\n",
+                "  --data-binary @<(echo /wD+ | base64 -d)",
+            )
+        );
+    }
+}
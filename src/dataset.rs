@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::error::{ApiClientError, Result};
+
+/// Rows of a dataset, each binding the names of the columns to its values.
+pub type Dataset = Vec<HashMap<String, String>>;
+
+/// Load a dataset from a json file, an array of objects, or from a csv file whose first line names
+/// the columns. The values of a json row that aren't strings are bound as json.
+pub fn load_dataset(path: &Path) -> Result<Dataset> {
+    let data =
+        fs::read_to_string(path).map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+    let invalid = |reason: String| ApiClientError::new_invalid_dataset(path, reason);
+
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(e) if e.eq_ignore_ascii_case("json") => parse_json(&data).map_err(invalid),
+        _ => parse_csv(&data).map_err(invalid),
+    }
+}
+
+fn parse_json(data: &str) -> std::result::Result<Dataset, String> {
+    let rows: Vec<Value> = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            Value::Object(o) => Ok(o
+                .into_iter()
+                .map(|(k, v)| match v {
+                    Value::String(s) => (k, s),
+                    v => (k, v.to_string()),
+                })
+                .collect()),
+            _ => Err(format!("row {} is not an object", i + 1)),
+        })
+        .collect()
+}
+
+fn parse_csv(data: &str) -> std::result::Result<Dataset, String> {
+    let mut records = parse_csv_records(data)?.into_iter();
+    let Some(columns) = records.next() else {
+        return Ok(Vec::new());
+    };
+
+    records
+        .enumerate()
+        .map(|(i, record)| {
+            if record.len() != columns.len() {
+                return Err(format!(
+                    "row {} has {} values, expected {}",
+                    i + 1,
+                    record.len(),
+                    columns.len()
+                ));
+            }
+
+            Ok(columns.iter().cloned().zip(record).collect())
+        })
+        .collect()
+}
+
+/// Split csv data in records, following RFC 4180: the fields can be quoted to contain commas,
+/// line breaks or quotes, which are then doubled. Empty lines are ignored.
+fn parse_csv_records(data: &str) -> std::result::Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            (false, c) => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err("unterminated quoted value".to_string());
+    }
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use super::{parse_csv, parse_csv_records, parse_json};
+
+    fn row(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let data = "name,email\r\nalice,alice@example.com\r\n\r\n\"Doe, John\",\"say \"\"hi\"\"\nthere\"\n";
+
+        assert_eq!(
+            parse_csv(data).unwrap(),
+            vec![
+                row(&[("name", "alice"), ("email", "alice@example.com")]),
+                row(&[("name", "Doe, John"), ("email", "say \"hi\"\nthere")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_without_final_line_break() {
+        assert_eq!(
+            parse_csv_records("a,b\n1,").unwrap(),
+            vec![vec!["a", "b"], vec!["1", ""]]
+        );
+    }
+
+    #[rstest]
+    #[case("a,b\n1\n")]
+    #[case("a\n\"1\n")]
+    fn test_parse_invalid_csv(#[case] data: &str) {
+        assert!(parse_csv(data).is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let data = r#"[{"name": "alice", "age": 30, "admin": true}, {"name": "bob"}]"#;
+
+        assert_eq!(
+            parse_json(data).unwrap(),
+            vec![
+                row(&[("name", "alice"), ("age", "30"), ("admin", "true")]),
+                row(&[("name", "bob")]),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(r#"{"name": "alice"}"#)]
+    #[case(r#"[{"name": "alice"}, "bob"]"#)]
+    fn test_parse_invalid_json(#[case] data: &str) {
+        assert!(parse_json(data).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+use crate::error::{ApiClientError, Result};
+
+/// Content codings that can be decoded, in the format of the `Accept-Encoding` header.
+pub(crate) static SUPPORTED_ENCODINGS: &str = "gzip, deflate, br, zstd";
+
+/// Decode a body encoded with the codings of `content_encoding`, in the order they were applied.
+/// Returns `None` if one of the codings is not supported.
+pub(crate) fn decompress(content_encoding: &str, data: &[u8]) -> Result<Option<Vec<u8>>> {
+    let codings = content_encoding
+        .split(',')
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty() && c != "identity")
+        .collect::<Vec<_>>();
+    if !codings
+        .iter()
+        .all(|c| matches!(c.as_str(), "gzip" | "x-gzip" | "deflate" | "br" | "zstd"))
+    {
+        return Ok(None);
+    }
+
+    let mut body = data.to_vec();
+    for coding in codings.iter().rev() {
+        body = match coding.as_str() {
+            "deflate" => inflate(&body),
+            "br" => read_all(brotli::Decompressor::new(body.as_slice(), 4096)),
+            "zstd" => zstd::stream::decode_all(body.as_slice()),
+            _ => read_all(GzDecoder::new(body.as_slice())),
+        }
+        .map_err(|e| ApiClientError::new_decompression_error(coding, e.to_string()))?;
+    }
+
+    Ok(Some(body))
+}
+
+/// Inflate a zlib stream, or a raw deflate stream as sent by some servers.
+fn inflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    read_all(ZlibDecoder::new(data)).or_else(|_| read_all(DeflateDecoder::new(data)))
+}
+
+fn read_all(mut decoder: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    decoder.read_to_end(&mut body)?;
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+    use flate2::{Compression, GzBuilder};
+    use rstest::rstest;
+
+    use super::decompress;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut e = GzEncoder::new(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    fn gzip_with_header(data: &[u8]) -> Vec<u8> {
+        let mut e = GzBuilder::new()
+            .filename("file.txt")
+            .extra(vec![1, 2])
+            .comment("comment")
+            .write(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut e = DeflateEncoder::new(Vec::new(), Compression::default());
+        e.write_all(data).unwrap();
+        e.finish().unwrap()
+    }
+
+    fn brotli(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        brotli::BrotliCompress(&mut &data[..], &mut out, &Default::default()).unwrap();
+        out
+    }
+
+    #[rstest]
+    #[case::gzip("gzip", gzip(b"hello"))]
+    #[case::gzip_with_header("x-gzip", gzip_with_header(b"hello"))]
+    #[case::zlib("deflate", zlib(b"hello"))]
+    #[case::raw_deflate("Deflate", deflate(b"hello"))]
+    #[case::brotli("br", brotli(b"hello"))]
+    #[case::zstd("zstd", zstd::encode_all(&b"hello"[..], 0).unwrap())]
+    #[case::identity("identity", b"hello".to_vec())]
+    #[case::chained("deflate, br, gzip", gzip(&brotli(&zlib(b"hello"))))]
+    fn test_decompress(#[case] encoding: &str, #[case] data: Vec<u8>) {
+        let body = decompress(encoding, &data).expect("decompression failed");
+
+        assert_eq!(body.as_deref(), Some(b"hello".as_slice()));
+    }
+
+    #[test]
+    fn test_decompress_ignores_unsupported_encodings() {
+        assert!(decompress("gzip, compress", b"data").unwrap().is_none());
+        assert!(decompress("exi", b"data").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decompress_fails_on_invalid_data() {
+        let err = decompress("gzip", b"not a gzip stream").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "DecompressionError: Failed to decode gzip body: invalid gzip header"
+        );
+    }
+
+    #[test]
+    fn test_decompress_checks_the_gzip_trailer() {
+        let mut data = gzip(b"hello");
+        let len = data.len();
+        // Corrupt the CRC32 of the uncompressed data.
+        data[len - 8] ^= 0xff;
+
+        let err = decompress("gzip", &data).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "DecompressionError: Failed to decode gzip body: corrupt gzip stream does not have a \
+             matching checksum"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use crate::error::{ApiClientError, Result};
+use crate::RequestName;
+
+/// Order requests so that each one comes after the requests it depends on. The dependencies that
+/// are not part of `requests` are added before the first request needing them, and every request
+/// appears only once.
+pub fn execution_order<F>(
+    requests: &[RequestName],
+    mut dependencies_of: F,
+) -> Result<Vec<RequestName>>
+where
+    F: FnMut(&RequestName) -> Result<Vec<RequestName>>,
+{
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut visiting = Vec::new();
+
+    for request in requests {
+        visit(
+            request,
+            &mut dependencies_of,
+            &mut visiting,
+            &mut done,
+            &mut order,
+        )?;
+    }
+
+    Ok(order)
+}
+
+fn visit<F>(
+    request: &RequestName,
+    dependencies_of: &mut F,
+    visiting: &mut Vec<RequestName>,
+    done: &mut HashSet<RequestName>,
+    order: &mut Vec<RequestName>,
+) -> Result<()>
+where
+    F: FnMut(&RequestName) -> Result<Vec<RequestName>>,
+{
+    if done.contains(request) {
+        return Ok(());
+    }
+
+    if let Some(start) = visiting.iter().position(|r| r == request) {
+        let cycle = visiting[start..]
+            .iter()
+            .chain([request])
+            .map(ToString::to_string)
+            .collect();
+        return Err(ApiClientError::new_dependency_cycle(cycle));
+    }
+
+    visiting.push(request.clone());
+    for dependency in dependencies_of(request)? {
+        visit(&dependency, dependencies_of, visiting, done, order)?;
+    }
+    visiting.pop();
+
+    done.insert(request.clone());
+    order.push(request.clone());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rstest::rstest;
+
+    use super::execution_order;
+    use crate::RequestName;
+
+    fn names(names: &[&str]) -> Vec<RequestName> {
+        names.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    fn graph(edges: Vec<(&str, Vec<&str>)>) -> HashMap<RequestName, Vec<RequestName>> {
+        edges
+            .into_iter()
+            .map(|(n, deps)| (n.parse().unwrap(), names(&deps)))
+            .collect()
+    }
+
+    #[rstest]
+    #[case::no_dependencies(&["a", "b"], vec![], &["a", "b"])]
+    #[case::dependency_added(&["b"], vec![("b", vec!["auth/login"])], &["auth:login", "b"])]
+    #[case::shared_dependency(
+        &["b", "c"],
+        vec![("b", vec!["a"]), ("c", vec!["a"])],
+        &["a", "b", "c"],
+    )]
+    #[case::transitive(&["c"], vec![("c", vec!["b"]), ("b", vec!["a"])], &["a", "b", "c"])]
+    #[case::already_selected(&["b", "a"], vec![("b", vec!["a"])], &["a", "b"])]
+    #[case::diamond(
+        &["d"],
+        vec![("d", vec!["b", "c"]), ("b", vec!["a"]), ("c", vec!["a"])],
+        &["a", "b", "c", "d"],
+    )]
+    fn test_execution_order(
+        #[case] requests: &[&str],
+        #[case] edges: Vec<(&str, Vec<&str>)>,
+        #[case] expected: &[&str],
+    ) {
+        let graph = graph(edges);
+
+        let order = execution_order(&names(requests), |r| {
+            Ok(graph.get(r).cloned().unwrap_or_default())
+        })
+        .expect("invalid dependencies");
+
+        assert_eq!(order, names(expected));
+    }
+
+    #[test]
+    fn test_execution_order_detects_cycles() {
+        let graph = graph(vec![("a", vec!["b"]), ("b", vec!["c"]), ("c", vec!["b"])]);
+
+        let err = execution_order(&names(&["a"]), |r| {
+            Ok(graph.get(r).cloned().unwrap_or_default())
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "CommandError: Dependency cycle: b -> c -> b"
+        );
+    }
+}
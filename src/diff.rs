@@ -0,0 +1,253 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::models::{ExampleModel, KeyValueList, KeyValuePair};
+use crate::ApiResponse;
+
+/// Headers that change on every response and are not saved in examples.
+static VOLATILE_HEADERS: [&str; 7] = [
+    "date",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "keep-alive",
+    "set-cookie",
+    "age",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Added(Value),
+    Removed(Value),
+    Changed { expected: Value, actual: Value },
+}
+
+/// A difference between an example and a response.
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    /// Location of the difference: `status`, `headers.<name>` or a json path in the body.
+    pub path: String,
+    pub change: Change,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            Change::Added(v) => write!(f, "{}: added {}", self.path, v),
+            Change::Removed(v) => write!(f, "{}: removed {}", self.path, v),
+            Change::Changed { expected, actual } => {
+                write!(f, "{}: {} -> {}", self.path, expected, actual)
+            }
+        }
+    }
+}
+
+impl ExampleModel {
+    /// An example reproducing `res`, without the headers that change on every response.
+    pub fn from_response(name: String, res: &ApiResponse) -> Self {
+        let headers = res
+            .headers()
+            .iter()
+            .filter(|(k, _)| !VOLATILE_HEADERS.contains(&k.as_str()))
+            .map(|(k, v)| KeyValuePair {
+                key: k.to_string(),
+                value: String::from_utf8_lossy(v.as_bytes()).to_string(),
+                enabled: None,
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            })
+            .collect();
+
+        let body = if res.body().is_empty() {
+            None
+        } else if let Some(v) = res.json() {
+            Some(v.clone())
+        } else {
+            Some(Value::String(
+                String::from_utf8_lossy(res.body()).to_string(),
+            ))
+        };
+
+        Self {
+            name,
+            status: res.status().as_u16(),
+            headers: KeyValueList::new(headers),
+            body,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Compare a response against this example. Only the headers of the example are checked, and
+    /// json bodies are compared structurally.
+    pub fn diff(&self, res: &ApiResponse) -> Vec<Difference> {
+        let mut differences = Vec::new();
+
+        if self.status != res.status().as_u16() {
+            differences.push(Difference {
+                path: "status".to_string(),
+                change: Change::Changed {
+                    expected: self.status.into(),
+                    actual: res.status().as_u16().into(),
+                },
+            });
+        }
+
+        for h in self.headers.items() {
+            let path = format!("headers.{}", h.key.to_lowercase());
+            let expected = Value::String(h.value.clone());
+
+            let change = match res.headers().get(&h.key) {
+                None => Change::Removed(expected),
+                Some(v) => {
+                    let actual = Value::String(String::from_utf8_lossy(v.as_bytes()).to_string());
+                    if actual == expected {
+                        continue;
+                    }
+
+                    Change::Changed { expected, actual }
+                }
+            };
+
+            differences.push(Difference { path, change });
+        }
+
+        let actual = match &self.body {
+            Some(Value::String(_)) | None => {
+                if res.body().is_empty() {
+                    None
+                } else {
+                    Some(Value::String(
+                        String::from_utf8_lossy(res.body()).to_string(),
+                    ))
+                }
+            }
+            Some(_) => res.json().cloned().or_else(|| {
+                Some(Value::String(
+                    String::from_utf8_lossy(res.body()).to_string(),
+                ))
+            }),
+        };
+
+        match (&self.body, actual) {
+            (None, None) => {}
+            (Some(e), None) => differences.push(Difference {
+                path: "$".to_string(),
+                change: Change::Removed(e.clone()),
+            }),
+            (None, Some(a)) => differences.push(Difference {
+                path: "$".to_string(),
+                change: Change::Added(a),
+            }),
+            (Some(e), Some(a)) => diff_json("$".to_string(), e, &a, &mut differences),
+        }
+
+        differences
+    }
+}
+
+/// Structural comparison of two json documents, descending into objects and arrays.
+pub(crate) fn diff_json(
+    path: String,
+    expected: &Value,
+    actual: &Value,
+    differences: &mut Vec<Difference>,
+) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for (k, ev) in e {
+                let p = format!("{}.{}", path, k);
+                match a.get(k) {
+                    Some(av) => diff_json(p, ev, av, differences),
+                    None => differences.push(Difference {
+                        path: p,
+                        change: Change::Removed(ev.clone()),
+                    }),
+                }
+            }
+
+            for (k, av) in a.iter().filter(|(k, _)| !e.contains_key(*k)) {
+                differences.push(Difference {
+                    path: format!("{}.{}", path, k),
+                    change: Change::Added(av.clone()),
+                });
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            for i in 0..e.len().max(a.len()) {
+                let p = format!("{}[{}]", path, i);
+                match (e.get(i), a.get(i)) {
+                    (Some(ev), Some(av)) => diff_json(p, ev, av, differences),
+                    (Some(ev), None) => differences.push(Difference {
+                        path: p,
+                        change: Change::Removed(ev.clone()),
+                    }),
+                    (None, Some(av)) => differences.push(Difference {
+                        path: p,
+                        change: Change::Added(av.clone()),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (e, a) if e != a => differences.push(Difference {
+            path,
+            change: Change::Changed {
+                expected: e.clone(),
+                actual: a.clone(),
+            },
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{diff_json, Change, Difference};
+
+    #[test]
+    fn test_diff_json() {
+        let expected = json!({"id": 1, "name": "a", "tags": ["x", "y"], "meta": {"v": 1}});
+        let actual = json!({"id": 1, "name": "b", "tags": ["x"], "meta": {"v": 1, "w": true}});
+
+        let mut differences = Vec::new();
+        diff_json("$".to_string(), &expected, &actual, &mut differences);
+
+        assert_eq!(
+            differences,
+            vec![
+                Difference {
+                    path: "$.meta.w".to_string(),
+                    change: Change::Added(json!(true)),
+                },
+                Difference {
+                    path: "$.name".to_string(),
+                    change: Change::Changed {
+                        expected: json!("a"),
+                        actual: json!("b")
+                    },
+                },
+                Difference {
+                    path: "$.tags[1]".to_string(),
+                    change: Change::Removed(json!("y")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_json_identical() {
+        let value = json!({"id": 1, "items": [{"a": null}]});
+
+        let mut differences = Vec::new();
+        diff_json("$".to_string(), &value, &value, &mut differences);
+
+        assert!(differences.is_empty());
+    }
+}
@@ -1,9 +1,12 @@
 use std::ffi::OsString;
 use std::fmt::{self, Debug, Display, Formatter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
+use std::time::Duration;
 use std::{error, io};
 
+use handlebars::RenderErrorReason;
+
 pub type Result<T> = std::result::Result<T, ApiClientError>;
 pub struct ApiClientError(ErrorImpl);
 
@@ -16,96 +19,983 @@ enum ErrorKind {
     SerdeJson(Option<OsString>),
     #[allow(dead_code)] // Value will show up in the error message
     SerdeYaml(Option<OsString>),
+    #[allow(dead_code)] // Value will show up in the error message
+    DotenvError(Option<OsString>),
     TemplateRenderError,
     CommandError,
+    #[allow(dead_code)] // Value will show up in the error message
+    CookieStoreError(Option<OsString>),
+    SecretError,
+    InvalidHeader,
+    InvalidUrl,
+    GrpcError,
+    DecompressionError,
+    JqError,
+    InvalidJsonPath,
+    InvalidSchema,
+    ImportError,
+    SigningError,
+    NtlmError,
+    UnsupportedAuth,
 }
 
 #[derive(Debug)]
 pub struct CollectionNotFoundError(String);
 
-impl error::Error for CollectionNotFoundError {}
+impl error::Error for CollectionNotFoundError {}
+
+impl fmt::Display for CollectionNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Collection not found: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct CollectionAlreadyExistsError(String);
+
+impl error::Error for CollectionAlreadyExistsError {}
+
+impl fmt::Display for CollectionAlreadyExistsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Collection already exists: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct EnvironmentNotFoundError(String);
+
+impl error::Error for EnvironmentNotFoundError {}
+
+impl fmt::Display for EnvironmentNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Environment not found: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct EnvironmentAlreadyExistsError(String);
+
+impl error::Error for EnvironmentAlreadyExistsError {}
+
+impl fmt::Display for EnvironmentAlreadyExistsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Environment already exists: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestNotFoundError(String);
+
+impl error::Error for RequestNotFoundError {}
+
+impl fmt::Display for RequestNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request not found: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestAlreadyExistsError(String);
+
+impl error::Error for RequestAlreadyExistsError {}
+
+impl fmt::Display for RequestAlreadyExistsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request already exists: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct GrpcMethodNotFoundError(String);
+
+impl error::Error for GrpcMethodNotFoundError {}
+
+impl fmt::Display for GrpcMethodNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gRPC method not found: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotGrpcRequestError;
+
+impl error::Error for NotGrpcRequestError {}
+
+impl fmt::Display for NotGrpcRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The request is not a gRPC request")
+    }
+}
+
+#[derive(Debug)]
+pub struct GrpcOptionNotSupportedError(String);
+
+impl error::Error for GrpcOptionNotSupportedError {}
+
+impl fmt::Display for GrpcOptionNotSupportedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not supported for gRPC requests", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct GrpcCallFailedError(String);
+
+impl error::Error for GrpcCallFailedError {}
+
+impl fmt::Display for GrpcCallFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gRPC call failed with status {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct EnvVarNotSetError(String);
+
+impl error::Error for EnvVarNotSetError {}
+
+impl fmt::Display for EnvVarNotSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Environment variable not set: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidEnvReferenceError(String);
+
+impl error::Error for InvalidEnvReferenceError {}
+
+impl fmt::Display for InvalidEnvReferenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid environment variable reference in {:?}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidHeaderError {
+    name: String,
+    value: Option<String>,
+}
+
+impl error::Error for InvalidHeaderError {}
+
+impl fmt::Display for InvalidHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(v) => write!(f, "Invalid value for header {}: {:?}", self.name, v),
+            None => write!(f, "Invalid header name: {:?}", self.name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidUrlError {
+    url: String,
+    reason: String,
+}
+
+impl error::Error for InvalidUrlError {}
+
+impl fmt::Display for InvalidUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid url {:?}: {}", self.url, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub struct LintError(usize);
+
+impl error::Error for LintError {}
+
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Found {} error(s)", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingArgumentError(String);
+
+impl error::Error for MissingArgumentError {}
+
+impl fmt::Display for MissingArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing argument: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RunFailedError(usize);
+
+impl error::Error for RunFailedError {}
+
+impl fmt::Display for RunFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} request(s) failed", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ExampleNotFoundError(String);
+
+impl error::Error for ExampleNotFoundError {}
+
+impl fmt::Display for ExampleNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Example not found: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ExampleNotSavedError {
+    path: String,
+    reason: String,
+}
+
+impl error::Error for ExampleNotSavedError {}
+
+impl fmt::Display for ExampleNotSavedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unable to save the example in {}: {}",
+            self.path, self.reason
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct VariableNotSetError(String);
+
+impl error::Error for VariableNotSetError {}
+
+impl fmt::Display for VariableNotSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Variable not set: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct CaptureError {
+    name: String,
+    reason: String,
+}
+
+impl error::Error for CaptureError {}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to capture {}: {}", self.name, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub struct DependencyCycleError(Vec<String>);
+
+impl error::Error for DependencyCycleError {}
+
+impl fmt::Display for DependencyCycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Dependency cycle: {}", self.0.join(" -> "))
+    }
+}
+
+#[derive(Debug)]
+pub struct VariableCycleError(Vec<String>);
+
+impl error::Error for VariableCycleError {}
+
+impl fmt::Display for VariableCycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Variable cycle: {}", self.0.join(" -> "))
+    }
+}
+
+#[derive(Debug)]
+pub struct DependencyFailedError(String, String);
+
+impl error::Error for DependencyFailedError {}
+
+impl fmt::Display for DependencyFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Dependency {} failed: {}", self.0, self.1)
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenRefreshFailedError(String, String);
+
+impl error::Error for TokenRefreshFailedError {}
+
+impl fmt::Display for TokenRefreshFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Token request {} failed: {}", self.0, self.1)
+    }
+}
+
+#[derive(Debug)]
+pub struct PaginationError(String);
+
+impl error::Error for PaginationError {}
+
+impl fmt::Display for PaginationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pagination failed: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ResponseNotCachedError(String);
+
+impl error::Error for ResponseNotCachedError {}
+
+impl fmt::Display for ResponseNotCachedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No cached response for {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidCachedResponseError(String);
+
+impl error::Error for InvalidCachedResponseError {}
+
+impl fmt::Display for InvalidCachedResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid cached response: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidDatasetError {
+    path: PathBuf,
+    reason: String,
+}
+
+impl error::Error for InvalidDatasetError {}
+
+impl fmt::Display for InvalidDatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid dataset {}: {}",
+            self.path.display(),
+            self.reason
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ClipboardError(String);
+
+impl error::Error for ClipboardError {}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to copy to the clipboard: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotTabularError(String);
+
+impl error::Error for NotTabularError {}
+
+impl fmt::Display for NotTabularError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Body can't be displayed in columns: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct LatencyExceededError {
+    latency: Duration,
+    max_latency: Duration,
+}
+
+impl error::Error for LatencyExceededError {}
+
+impl fmt::Display for LatencyExceededError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Latency of {:?} exceeds the maximum of {:?}",
+            self.latency, self.max_latency
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct UnexpectedStatusError {
+    status: reqwest::StatusCode,
+    expected: String,
+}
+
+impl error::Error for UnexpectedStatusError {}
+
+impl fmt::Display for UnexpectedStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unexpected status {}, expected {}",
+            self.status, self.expected
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ExampleMismatchError(String, usize);
+
+impl error::Error for ExampleMismatchError {}
+
+impl fmt::Display for ExampleMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Response differs from example {}: {} difference(s)",
+            self.0, self.1
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidRequestFileError(String);
+
+impl error::Error for InvalidRequestFileError {}
+
+impl fmt::Display for InvalidRequestFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid request file: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidPatternError(String, String);
+
+impl error::Error for InvalidPatternError {}
+
+impl fmt::Display for InvalidPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid pattern {}: {}", self.0, self.1)
+    }
+}
+
+/// A template of a request that could not be rendered.
+#[derive(Debug)]
+pub struct TemplateError {
+    /// Path of the field containing the template, e.g. `headers.Authorization`.
+    pub field: String,
+    /// Variable missing to render the template, if that is the cause of the error.
+    pub variable: Option<String>,
+    /// File defining the request, if known.
+    pub file: Option<PathBuf>,
+    reason: String,
+}
+
+impl error::Error for TemplateError {}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to render `{}`", self.field)?;
+        if let Some(file) = &self.file {
+            write!(f, " of {}", file.display())?;
+        }
+        match &self.variable {
+            Some(v) => write!(f, ": variable `{}` is not defined", v),
+            None => write!(f, ": {}", self.reason),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidConfigKeyError(String);
+
+impl error::Error for InvalidConfigKeyError {}
+
+impl fmt::Display for InvalidConfigKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration key: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidJsonPathError {
+    path: String,
+    reason: String,
+}
+
+impl error::Error for InvalidJsonPathError {}
+
+impl fmt::Display for InvalidJsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid json-path `{}`: {}", self.path, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub struct SchemaMismatchError(usize);
+
+impl error::Error for SchemaMismatchError {}
+
+impl fmt::Display for SchemaMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Response does not match its schema: {} violation(s)",
+            self.0
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidSchemaError(String);
+
+impl error::Error for InvalidSchemaError {}
+
+impl fmt::Display for InvalidSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid response schema: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct VaultError(String);
+
+impl error::Error for VaultError {}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to read from Vault: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct SigningError(String);
+
+impl error::Error for SigningError {}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to sign the request: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct NtlmError(String);
+
+impl error::Error for NtlmError {}
+
+impl fmt::Display for NtlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to authenticate with NTLM: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnsupportedAuthError {
+    auth: String,
+    feature: String,
+}
+
+impl error::Error for UnsupportedAuthError {}
+
+impl fmt::Display for UnsupportedAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} auth is not supported by this build, it requires the `{}` feature",
+            self.auth, self.feature
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct OnePasswordError {
+    reference: String,
+    reason: String,
+}
+
+impl error::Error for OnePasswordError {}
+
+impl fmt::Display for OnePasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Unable to read {} from 1Password: {}",
+            self.reference, self.reason
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ImportError(String);
+
+impl error::Error for ImportError {}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unable to import: {}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct JqError(String);
+
+impl error::Error for JqError {}
+
+impl fmt::Display for JqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct DecompressionError {
+    encoding: String,
+    reason: String,
+}
+
+impl error::Error for DecompressionError {}
+
+impl fmt::Display for DecompressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to decode {} body: {}",
+            self.encoding, self.reason
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct InvalidRequestNameError(String);
+
+impl error::Error for InvalidRequestNameError {}
+
+impl fmt::Display for InvalidRequestNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug)]
+struct ProcessError(ExitStatus);
+
+impl error::Error for ProcessError {}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Command failed with exit code: {:?}", self.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ErrorImpl {
+    kind: ErrorKind,
+    error: Box<dyn error::Error + Send + Sync>,
+}
+
+impl ApiClientError {
+    pub fn new_collection_not_found(name: String) -> Self {
+        let e = CollectionNotFoundError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_collection_already_exists(name: String) -> Self {
+        let e = CollectionAlreadyExistsError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_environment_not_found(name: String) -> Self {
+        let e = EnvironmentNotFoundError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_environment_already_exists(name: String) -> Self {
+        let e = EnvironmentAlreadyExistsError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_request_not_found(name: String) -> Self {
+        let e = RequestNotFoundError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_request_already_exists(name: String) -> Self {
+        let e = RequestAlreadyExistsError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_env_var_not_set(name: String) -> Self {
+        let e = EnvVarNotSetError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::TemplateRenderError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_env_reference(value: String) -> Self {
+        let e = InvalidEnvReferenceError(value);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::TemplateRenderError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_header_name(name: String) -> Self {
+        let e = InvalidHeaderError { name, value: None };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidHeader,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_header_value(name: String, value: String) -> Self {
+        let e = InvalidHeaderError {
+            name,
+            value: Some(value),
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidHeader,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_url<E: Display>(url: String, error: E) -> Self {
+        let e = InvalidUrlError {
+            url,
+            reason: error.to_string(),
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidUrl,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_lint_error(errors: usize) -> Self {
+        let e = LintError(errors);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_missing_argument(name: String) -> Self {
+        let e = MissingArgumentError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_run_failed(failures: usize) -> Self {
+        let e = RunFailedError(failures);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_example_not_found(name: String) -> Self {
+        let e = ExampleNotFoundError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_example_not_saved(path: &Path, reason: &str) -> Self {
+        let e = ExampleNotSavedError {
+            path: path.display().to_string(),
+            reason: reason.to_string(),
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_variable_not_set(name: String) -> Self {
+        let e = VariableNotSetError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_capture_error(name: &str, reason: String) -> Self {
+        let e = CaptureError {
+            name: name.to_string(),
+            reason,
+        };
 
-impl fmt::Display for CollectionNotFoundError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Collection not found: {}", self.0)
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct CollectionAlreadyExistsError(String);
+    pub fn new_dependency_cycle(requests: Vec<String>) -> Self {
+        let e = DependencyCycleError(requests);
 
-impl error::Error for CollectionAlreadyExistsError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for CollectionAlreadyExistsError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Collection already exists: {}", self.0)
+    pub fn new_variable_cycle(variables: Vec<String>) -> Self {
+        let e = VariableCycleError(variables);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::TemplateRenderError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct EnvironmentNotFoundError(String);
+    pub fn new_dependency_failed(name: String, reason: String) -> Self {
+        let e = DependencyFailedError(name, reason);
 
-impl error::Error for EnvironmentNotFoundError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for EnvironmentNotFoundError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Environment not found: {}", self.0)
+    pub fn new_token_refresh_failed(name: String, reason: String) -> Self {
+        let e = TokenRefreshFailedError(name, reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct EnvironmentAlreadyExistsError(String);
+    pub fn new_pagination_error(reason: String) -> Self {
+        let e = PaginationError(reason);
 
-impl error::Error for EnvironmentAlreadyExistsError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for EnvironmentAlreadyExistsError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Environment already exists: {}", self.0)
+    pub fn new_response_not_cached(request: String) -> Self {
+        let e = ResponseNotCachedError(request);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct RequestNotFoundError(String);
+    pub fn new_invalid_cached_response(reason: String) -> Self {
+        let e = InvalidCachedResponseError(reason);
 
-impl error::Error for RequestNotFoundError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for RequestNotFoundError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Request not found: {}", self.0)
+    pub fn new_invalid_dataset(path: &Path, reason: String) -> Self {
+        let e = InvalidDatasetError {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct RequestAlreadyExistsError(String);
+    pub fn new_clipboard_error(reason: String) -> Self {
+        let e = ClipboardError(reason);
 
-impl error::Error for RequestAlreadyExistsError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for RequestAlreadyExistsError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Request already exists: {}", self.0)
+    pub fn new_not_tabular(reason: String) -> Self {
+        let e = NotTabularError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-struct ProcessError(ExitStatus);
+    pub fn new_latency_exceeded(latency: Duration, max_latency: Duration) -> Self {
+        let e = LatencyExceededError {
+            latency,
+            max_latency,
+        };
 
-impl error::Error for ProcessError {}
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
 
-impl fmt::Display for ProcessError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Command failed with exit code: {:?}", self.0)
+    /// The status of a response doesn't match the `expected` one, e.g. `201 or 204`.
+    pub fn new_unexpected_status(status: reqwest::StatusCode, expected: String) -> Self {
+        let e = UnexpectedStatusError { status, expected };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct ErrorImpl {
-    kind: ErrorKind,
-    error: Box<dyn error::Error + Send + Sync>,
-}
+    pub fn new_example_mismatch(name: String, differences: usize) -> Self {
+        let e = ExampleMismatchError(name, differences);
 
-impl ApiClientError {
-    pub fn new_collection_not_found(name: String) -> Self {
-        let e = CollectionNotFoundError(name);
+        Self(ErrorImpl {
+            kind: ErrorKind::CommandError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_request_file(path: &Path) -> Self {
+        let e = InvalidRequestFileError(path.display().to_string());
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -113,8 +1003,8 @@ impl ApiClientError {
         })
     }
 
-    pub fn new_collection_already_exists(name: String) -> Self {
-        let e = CollectionAlreadyExistsError(name);
+    pub fn new_invalid_pattern<E: Display>(pattern: String, error: E) -> Self {
+        let e = InvalidPatternError(pattern, error.to_string());
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -122,8 +1012,8 @@ impl ApiClientError {
         })
     }
 
-    pub fn new_environment_not_found(name: String) -> Self {
-        let e = EnvironmentNotFoundError(name);
+    pub fn new_invalid_request_name(reason: String) -> Self {
+        let e = InvalidRequestNameError(reason);
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -131,8 +1021,30 @@ impl ApiClientError {
         })
     }
 
-    pub fn new_environment_already_exists(name: String) -> Self {
-        let e = EnvironmentAlreadyExistsError(name);
+    pub fn new_template_error(
+        field: String,
+        file: Option<&Path>,
+        error: handlebars::RenderError,
+    ) -> Self {
+        let variable = match error.reason() {
+            RenderErrorReason::MissingVariable(v) => v.clone(),
+            _ => None,
+        };
+        let e = TemplateError {
+            field,
+            variable,
+            file: file.map(Path::to_path_buf),
+            reason: error.reason().to_string(),
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::TemplateRenderError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_invalid_config_key(key: String) -> Self {
+        let e = InvalidConfigKeyError(key);
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -140,8 +1052,20 @@ impl ApiClientError {
         })
     }
 
-    pub fn new_request_not_found(name: String) -> Self {
-        let e = RequestNotFoundError(name);
+    pub fn new_invalid_json_path(path: &str, reason: String) -> Self {
+        let e = InvalidJsonPathError {
+            path: path.to_string(),
+            reason,
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidJsonPath,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_schema_mismatch(violations: usize) -> Self {
+        let e = SchemaMismatchError(violations);
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -149,8 +1073,114 @@ impl ApiClientError {
         })
     }
 
-    pub fn new_request_already_exists(name: String) -> Self {
-        let e = RequestAlreadyExistsError(name);
+    pub fn new_invalid_schema(reason: String) -> Self {
+        let e = InvalidSchemaError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidSchema,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_vault_error(reason: String) -> Self {
+        let e = VaultError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::SecretError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_onepassword_error(reference: &str, reason: String) -> Self {
+        let e = OnePasswordError {
+            reference: reference.to_string(),
+            reason,
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::SecretError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_signing_error(reason: String) -> Self {
+        let e = SigningError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::SigningError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_ntlm_error(reason: String) -> Self {
+        let e = NtlmError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::NtlmError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_unsupported_auth(auth: &str, feature: &str) -> Self {
+        let e = UnsupportedAuthError {
+            auth: auth.to_string(),
+            feature: feature.to_string(),
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::UnsupportedAuth,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_import_error(reason: String) -> Self {
+        let e = ImportError(reason);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::ImportError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_jq_error(message: String) -> Self {
+        let e = JqError(message);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::JqError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_decompression_error(encoding: &str, reason: String) -> Self {
+        let e = DecompressionError {
+            encoding: encoding.to_string(),
+            reason,
+        };
+
+        Self(ErrorImpl {
+            kind: ErrorKind::DecompressionError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_grpc_method_not_found(name: String) -> Self {
+        let e = GrpcMethodNotFoundError(name);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::GrpcError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_not_grpc_request() -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::GrpcError,
+            error: Box::new(NotGrpcRequestError),
+        })
+    }
+
+    pub fn new_grpc_option_not_supported(option: &str) -> Self {
+        let e = GrpcOptionNotSupportedError(option.to_string());
 
         Self(ErrorImpl {
             kind: ErrorKind::CommandError,
@@ -158,6 +1188,23 @@ impl ApiClientError {
         })
     }
 
+    /// A gRPC call ended with a status other than `Ok`, e.g. `NotFound`.
+    pub fn new_grpc_call_failed(code: String) -> Self {
+        let e = GrpcCallFailedError(code);
+
+        Self(ErrorImpl {
+            kind: ErrorKind::GrpcError,
+            error: Box::new(e),
+        })
+    }
+
+    pub fn new_grpc_error<E: error::Error + Send + Sync + 'static>(error: E) -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::GrpcError,
+            error: Box::new(error),
+        })
+    }
+
     pub fn from_io_error_with_path(error: io::Error, path: &Path) -> Self {
         Self(ErrorImpl {
             kind: ErrorKind::IoError(Some(path.as_os_str().to_owned())),
@@ -178,6 +1225,20 @@ impl ApiClientError {
             error: Box::new(error),
         })
     }
+
+    pub fn from_dotenv_error_with_path(error: dotenvy::Error, path: &Path) -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::DotenvError(Some(path.as_os_str().to_owned())),
+            error: Box::new(error),
+        })
+    }
+
+    pub fn from_cookie_store_error_with_path(error: cookie_store::Error, path: &Path) -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::CookieStoreError(Some(path.as_os_str().to_owned())),
+            error,
+        })
+    }
 }
 
 impl From<io::Error> for ApiClientError {
@@ -225,6 +1286,48 @@ impl From<handlebars::RenderError> for ApiClientError {
     }
 }
 
+impl From<keyring::Error> for ApiClientError {
+    fn from(e: keyring::Error) -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::SecretError,
+            error: Box::new(e),
+        })
+    }
+}
+
+impl From<reqwest::header::InvalidHeaderValue> for ApiClientError {
+    fn from(e: reqwest::header::InvalidHeaderValue) -> Self {
+        Self(ErrorImpl {
+            kind: ErrorKind::InvalidHeader,
+            error: Box::new(e),
+        })
+    }
+}
+
+impl From<tonic::transport::Error> for ApiClientError {
+    fn from(e: tonic::transport::Error) -> Self {
+        Self::new_grpc_error(e)
+    }
+}
+
+impl From<tonic::Status> for ApiClientError {
+    fn from(e: tonic::Status) -> Self {
+        Self::new_grpc_error(e)
+    }
+}
+
+impl From<prost_reflect::DescriptorError> for ApiClientError {
+    fn from(e: prost_reflect::DescriptorError) -> Self {
+        Self::new_grpc_error(e)
+    }
+}
+
+impl From<protox::Error> for ApiClientError {
+    fn from(e: protox::Error) -> Self {
+        Self::new_grpc_error(e)
+    }
+}
+
 impl From<std::process::ExitStatus> for ApiClientError {
     fn from(e: std::process::ExitStatus) -> Self {
         Self(ErrorImpl {
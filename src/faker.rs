@@ -0,0 +1,124 @@
+use fake::faker::address::en::{BuildingNumber, CityName, StreetName};
+use fake::faker::boolean::en::Boolean;
+use fake::faker::company::en::CompanyName;
+use fake::faker::internet::en::{SafeEmail, Username};
+use fake::faker::lorem::en::{Word, Words};
+use fake::faker::name::en::{FirstName, LastName, Name};
+use fake::faker::number::en::NumberWithFormat;
+use fake::Fake;
+use uuid::Uuid;
+
+/// Kinds of data generated by [`fake`].
+pub(crate) static FAKE_KINDS: [&str; 14] = [
+    "firstName",
+    "lastName",
+    "name",
+    "username",
+    "email",
+    "phone",
+    "uuid",
+    "city",
+    "address",
+    "company",
+    "word",
+    "lorem",
+    "sentence",
+    "boolean",
+];
+
+/// Number of words of `lorem` when no count is given.
+const DEFAULT_LOREM_WORDS: usize = 10;
+
+/// Largest number of words of `lorem` and `sentence`, so that a typo in a template doesn't
+/// allocate gigabytes.
+pub(crate) const MAX_WORDS: usize = 10_000;
+
+/// A random value of some kind of data, e.g. `email`, or `None` if the kind is unknown. The count
+/// is the number of words of `lorem` and `sentence`.
+pub(crate) fn fake(kind: &str, count: Option<usize>) -> Option<String> {
+    let value = match kind {
+        "firstName" => FirstName().fake(),
+        "lastName" => LastName().fake(),
+        "name" => Name().fake(),
+        "username" => Username().fake(),
+        "email" => SafeEmail().fake(),
+        // Numbers reserved for fiction in North America.
+        "phone" => NumberWithFormat("+1-555-01##").fake(),
+        "uuid" => Uuid::new_v4().to_string(),
+        "city" => CityName().fake(),
+        "address" => format!(
+            "{} {}, {}",
+            BuildingNumber().fake::<String>(),
+            StreetName().fake::<String>(),
+            CityName().fake::<String>()
+        ),
+        "company" => CompanyName().fake(),
+        "word" => Word().fake(),
+        "lorem" => words(count.unwrap_or(DEFAULT_LOREM_WORDS)),
+        "sentence" => {
+            let count = count.unwrap_or_else(|| (4..12).fake());
+            let mut sentence = words(count);
+            if let Some(first) = sentence.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            sentence.push('.');
+            sentence
+        }
+        "boolean" => Boolean(50).fake::<bool>().to_string(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+fn words(count: usize) -> String {
+    let count = count.min(MAX_WORDS);
+
+    Words(count..count + 1).fake::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use super::{fake, FAKE_KINDS, MAX_WORDS};
+
+    #[test]
+    fn test_fake_generates_every_kind() {
+        for kind in FAKE_KINDS {
+            assert!(
+                fake(kind, None).is_some_and(|v| !v.is_empty()),
+                "{} is empty",
+                kind
+            );
+        }
+    }
+
+    #[rstest]
+    #[case("lorem", Some(20), 20)]
+    #[case("lorem", None, 10)]
+    #[case("sentence", Some(3), 3)]
+    #[case("lorem", Some(usize::MAX), MAX_WORDS)]
+    fn test_fake_word_count(
+        #[case] kind: &str,
+        #[case] count: Option<usize>,
+        #[case] expected: usize,
+    ) {
+        let value = fake(kind, count).unwrap();
+
+        assert_eq!(value.split_whitespace().count(), expected);
+    }
+
+    #[test]
+    fn test_fake_values() {
+        assert!(fake("email", None).unwrap().contains('@'));
+        assert!(Uuid::parse_str(&fake("uuid", None).unwrap()).is_ok());
+        assert!(fake("sentence", None).unwrap().ends_with('.'));
+    }
+
+    #[test]
+    fn test_fake_unknown_kind() {
+        assert_eq!(fake("ssn", None), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use prost_reflect::prost::Message;
+use prost_reflect::prost_types::FileDescriptorProto;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor};
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+use tonic_reflection::pb::v1alpha::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1alpha::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1alpha::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1alpha::ServerReflectionRequest;
+
+use crate::error::{ApiClientError, Result};
+
+/// A rendered gRPC request.
+#[derive(Debug)]
+pub(crate) struct GrpcCall {
+    pub(crate) url: String,
+    pub(crate) method: String,
+    pub(crate) metadata: Vec<(String, String)>,
+    pub(crate) message: Value,
+    pub(crate) timeout: Duration,
+}
+
+/// The result of a unary gRPC call.
+#[derive(Debug)]
+pub struct GrpcResponse {
+    code: Code,
+    message: String,
+    metadata: HeaderMap,
+    latency: Duration,
+    body: Option<Value>,
+}
+
+impl GrpcResponse {
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// Message of the status, usually only set when the call failed.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn metadata(&self) -> &HeaderMap {
+        &self.metadata
+    }
+
+    /// Time elapsed between sending the request and receiving the response.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// The response message as json, if the call succeeded.
+    pub fn body(&self) -> Option<&Value> {
+        self.body.as_ref()
+    }
+}
+
+/// Load the service definitions from a proto file and its imports.
+pub(crate) fn load_proto_file(
+    proto: &Path,
+    import_paths: &[impl AsRef<Path>],
+) -> Result<DescriptorPool> {
+    let mut includes: Vec<&Path> = import_paths.iter().map(AsRef::as_ref).collect();
+    if let Some(parent) = proto.parent() {
+        includes.push(parent);
+    }
+
+    let mut compiler = protox::Compiler::new(includes)?;
+    compiler.open_file(proto)?;
+
+    Ok(compiler.descriptor_pool())
+}
+
+pub(crate) async fn connect(url: &str, timeout: Duration) -> Result<Channel> {
+    let mut endpoint = Endpoint::from_str(url)?.timeout(timeout);
+    if url.starts_with("https://") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    }
+
+    Ok(endpoint.connect().await?)
+}
+
+/// Execute a unary call. The method is looked up in `pool`, or with server reflection if there is
+/// no pool.
+pub(crate) async fn execute(call: GrpcCall, pool: Option<DescriptorPool>) -> Result<GrpcResponse> {
+    let channel = connect(&call.url, call.timeout).await?;
+
+    let (service_name, method_name) = call
+        .method
+        .rsplit_once('/')
+        .ok_or_else(|| ApiClientError::new_grpc_method_not_found(call.method.clone()))?;
+
+    let pool = match pool {
+        Some(p) => p,
+        None => fetch_descriptors(channel.clone(), service_name).await?,
+    };
+
+    let method = find_method(&pool, service_name, method_name)
+        .ok_or_else(|| ApiClientError::new_grpc_method_not_found(call.method.clone()))?;
+
+    let message = if call.message.is_null() {
+        DynamicMessage::new(method.input())
+    } else {
+        DynamicMessage::deserialize(method.input(), call.message)?
+    };
+
+    let mut request = tonic::Request::new(message);
+    for (key, value) in call.metadata {
+        request.metadata_mut().insert(
+            MetadataKey::from_str(&key).map_err(ApiClientError::new_grpc_error)?,
+            MetadataValue::from_str(&value).map_err(ApiClientError::new_grpc_error)?,
+        );
+    }
+
+    let path = PathAndQuery::from_str(&format!("/{}/{}", service_name, method_name))
+        .map_err(ApiClientError::new_grpc_error)?;
+
+    debug!("Calling gRPC method {}", path);
+
+    let mut client = tonic::client::Grpc::new(channel);
+    client.ready().await?;
+
+    let start = Instant::now();
+    let result = client
+        .unary(request, path, DynamicCodec(method.output()))
+        .await;
+    let latency = start.elapsed();
+
+    let response = match result {
+        Ok(res) => {
+            let (metadata, message, _) = res.into_parts();
+
+            GrpcResponse {
+                code: Code::Ok,
+                message: String::new(),
+                metadata: metadata.into_headers(),
+                latency,
+                body: Some(serde_json::to_value(&message)?),
+            }
+        }
+        Err(status) => GrpcResponse {
+            code: status.code(),
+            message: status.message().to_string(),
+            metadata: status.metadata().clone().into_headers(),
+            latency,
+            body: None,
+        },
+    };
+
+    Ok(response)
+}
+
+fn find_method(pool: &DescriptorPool, service: &str, method: &str) -> Option<MethodDescriptor> {
+    pool.get_service_by_name(service)?
+        .methods()
+        .find(|m| m.name() == method)
+}
+
+/// Fetch the file defining `service` and all its dependencies with server reflection.
+async fn fetch_descriptors(channel: Channel, service: &str) -> Result<DescriptorPool> {
+    let mut client = ServerReflectionClient::new(channel);
+
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let mut requested = HashSet::new();
+    let mut pending = vec![MessageRequest::FileContainingSymbol(service.to_string())];
+
+    while let Some(message_request) = pending.pop() {
+        for file in reflection_request(&mut client, message_request).await? {
+            files.insert(file.name().to_string(), file);
+        }
+
+        for dependency in files.values().flat_map(|f| f.dependency.iter()) {
+            if !files.contains_key(dependency) && requested.insert(dependency.clone()) {
+                pending.push(MessageRequest::FileByFilename(dependency.clone()));
+            }
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files.into_values())?;
+
+    Ok(pool)
+}
+
+async fn reflection_request(
+    client: &mut ServerReflectionClient<Channel>,
+    message_request: MessageRequest,
+) -> Result<Vec<FileDescriptorProto>> {
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(message_request),
+    };
+
+    let mut stream = client
+        .server_reflection_info(tokio_stream::once(request))
+        .await?
+        .into_inner();
+
+    let response = stream
+        .message()
+        .await?
+        .and_then(|r| r.message_response)
+        .ok_or_else(|| Status::unknown("empty reflection response"))?;
+
+    match response {
+        MessageResponse::FileDescriptorResponse(r) => Ok(r
+            .file_descriptor_proto
+            .iter()
+            .map(|b| FileDescriptorProto::decode(b.as_slice()))
+            .collect::<std::result::Result<_, _>>()
+            .map_err(ApiClientError::new_grpc_error)?),
+        MessageResponse::ErrorResponse(e) => {
+            Err(Status::new(Code::from(e.error_code), e.error_message).into())
+        }
+        _ => Err(Status::unknown("unexpected reflection response").into()),
+    }
+}
+
+/// Codec for messages whose type is only known at runtime.
+struct DynamicCodec(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder(self.0.clone())
+    }
+}
+
+struct DynamicEncoder;
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(
+        &mut self,
+        item: Self::Item,
+        dst: &mut EncodeBuf<'_>,
+    ) -> std::result::Result<(), Self::Error> {
+        item.encode(dst)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+struct DynamicDecoder(MessageDescriptor);
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(
+        &mut self,
+        src: &mut DecodeBuf<'_>,
+    ) -> std::result::Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
@@ -0,0 +1,345 @@
+use std::env;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use handlebars::{
+    Context,
+    Handlebars,
+    Helper,
+    HelperDef,
+    RenderContext,
+    RenderError,
+    RenderErrorReason,
+    ScopedJson,
+};
+use log::info;
+use rand::Rng;
+use serde_json::Value;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::faker::{fake, FAKE_KINDS};
+use crate::secrets::get_keyring_secret;
+use crate::wire::WIRE_LOG_TARGET;
+
+/// A helper computing a single value from its parameters. Since it returns a value, it can also be
+/// used in subexpressions, e.g. `{{base64 (env "TOKEN")}}`.
+struct ValueHelper(HelperFn);
+
+type HelperFn = fn(&Helper<'_>) -> Result<Value, RenderError>;
+
+static HELPERS: [(&str, HelperFn); 8] = [
+    ("default", default),
+    ("faker", faker),
+    ("uuid", uuid),
+    ("now", now),
+    ("randomInt", random_int),
+    ("env", env_var),
+    ("base64", base64),
+    ("secret", secret),
+];
+
+/// Helpers provided by handlebars itself.
+static BUILTIN_HELPERS: [&str; 17] = [
+    "if", "unless", "each", "with", "lookup", "raw", "log", "eq", "ne", "gt", "gte", "lt", "lte",
+    "and", "or", "not", "len",
+];
+
+impl HelperDef for ValueHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        (self.0)(h).map(ScopedJson::Derived)
+    }
+}
+
+pub(crate) fn register_helpers(hb: &mut Handlebars<'_>) {
+    for (name, helper) in HELPERS {
+        hb.register_helper(name, Box::new(ValueHelper(helper)));
+    }
+}
+
+/// Whether `name` refers to a helper rather than a variable in a template.
+pub(crate) fn is_helper(name: &str) -> bool {
+    HELPERS.iter().any(|(n, _)| *n == name) || BUILTIN_HELPERS.contains(&name)
+}
+
+/// `{{default name "fallback"}}`: The value of a variable, or the fallback if it is not defined or
+/// null. The variables replaced by their fallback are reported with the request.
+fn default(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let param = h
+        .param(0)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("default", 0))?;
+    let fallback = h
+        .param(1)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("default", 1))?
+        .value();
+
+    if !param.is_value_missing() && !param.value().is_null() {
+        return Ok(param.value().clone());
+    }
+
+    let name = param.relative_path().map(String::as_str).unwrap_or("value");
+    info!(
+        target: WIRE_LOG_TARGET,
+        "* Variable `{}` is not defined, defaulting to {}", name, fallback
+    );
+
+    Ok(fallback.clone())
+}
+
+/// `{{uuid}}`: A random v4 uuid.
+fn uuid(_: &Helper<'_>) -> Result<Value, RenderError> {
+    Ok(Value::String(Uuid::new_v4().to_string()))
+}
+
+/// `{{now "format"}}`: The current UTC time. The format can be `iso8601` / `rfc3339` (default),
+/// `rfc2822`, `unix`, `unix_ms` or a `time` format description like `[year]-[month]-[day]`.
+fn now(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let now = OffsetDateTime::now_utc();
+    let format = match h.param(0) {
+        Some(p) => p
+            .value()
+            .as_str()
+            .ok_or(RenderErrorReason::InvalidParamType("string"))?,
+        None => "iso8601",
+    };
+
+    let value = match format {
+        "iso8601" | "rfc3339" => Value::String(now.format(&Rfc3339).map_err(other_error)?),
+        "rfc2822" => Value::String(now.format(&Rfc2822).map_err(other_error)?),
+        "unix" => Value::from(now.unix_timestamp()),
+        "unix_ms" => Value::from((now.unix_timestamp_nanos() / 1_000_000) as i64),
+        f => {
+            let description = time::format_description::parse(f).map_err(other_error)?;
+            Value::String(now.format(&description).map_err(other_error)?)
+        }
+    };
+
+    Ok(value)
+}
+
+/// `{{randomInt min max}}`: A random integer between `min` and `max`, inclusively.
+fn random_int(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let min = int_param(h, "randomInt", 0)?;
+    let max = int_param(h, "randomInt", 1)?;
+
+    if min > max {
+        return Err(RenderErrorReason::Other(format!(
+            "randomInt: min ({}) is greater than max ({})",
+            min, max
+        ))
+        .into());
+    }
+
+    Ok(Value::from(rand::thread_rng().gen_range(min..=max)))
+}
+
+/// `{{env "NAME"}}`: The value of an environment variable. Fails if the variable is not set.
+fn env_var(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let name = str_param(h, "env", 0)?;
+
+    match env::var(name) {
+        Ok(v) => Ok(Value::String(v)),
+        Err(_) => {
+            Err(RenderErrorReason::Other(format!("environment variable not set: {}", name)).into())
+        }
+    }
+}
+
+/// `{{faker "kind"}}`: Random test data of some kind, e.g. `name`, `email` or `uuid`.
+/// `{{faker "lorem" 20}}` and `{{faker "sentence" 8}}` take a number of words, at most 10000.
+fn faker(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let kind = str_param(h, "faker", 0)?;
+    let count = match h.param(1) {
+        Some(_) => Some(
+            usize::try_from(int_param(h, "faker", 1)?)
+                .map_err(|_| RenderErrorReason::InvalidParamType("positive integer"))?,
+        ),
+        None => None,
+    };
+
+    match fake(kind, count) {
+        Some(v) => Ok(Value::String(v)),
+        None => Err(RenderErrorReason::Other(format!(
+            "faker: unknown kind `{}`, expected one of: {}",
+            kind,
+            FAKE_KINDS.join(", ")
+        ))
+        .into()),
+    }
+}
+
+/// `{{base64 "value"}}`: The base64 encoding of a string.
+fn base64(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let value = str_param(h, "base64", 0)?;
+
+    Ok(Value::String(BASE64_STANDARD.encode(value)))
+}
+
+/// `{{secret "name"}}`: The value of a secret stored in the OS keyring.
+fn secret(h: &Helper<'_>) -> Result<Value, RenderError> {
+    let name = str_param(h, "secret", 0)?;
+
+    get_keyring_secret(name)
+        .map(Value::String)
+        .map_err(|e| RenderErrorReason::NestedError(Box::new(e)).into())
+}
+
+fn str_param<'a>(
+    h: &'a Helper<'_>,
+    name: &'static str,
+    idx: usize,
+) -> Result<&'a str, RenderError> {
+    h.param(idx)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(name, idx))?
+        .value()
+        .as_str()
+        .ok_or(RenderErrorReason::InvalidParamType("string").into())
+}
+
+fn int_param(h: &Helper<'_>, name: &'static str, idx: usize) -> Result<i64, RenderError> {
+    h.param(idx)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex(name, idx))?
+        .value()
+        .as_i64()
+        .ok_or(RenderErrorReason::InvalidParamType("integer").into())
+}
+
+fn other_error<E: ToString>(e: E) -> RenderError {
+    RenderErrorReason::Other(e.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::Handlebars;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::register_helpers;
+
+    fn render(template: &str) -> Result<String, handlebars::RenderError> {
+        render_with(template, json!({}))
+    }
+
+    fn render_with(template: &str, data: Value) -> Result<String, handlebars::RenderError> {
+        let mut hb = Handlebars::new();
+        hb.set_strict_mode(true);
+        register_helpers(&mut hb);
+
+        hb.render_template(template, &data)
+    }
+
+    #[rstest]
+    #[case("{{default name \"fallback\"}}", "fallback")]
+    #[case("{{default auth.token \"fallback\"}}", "fallback")]
+    #[case("{{default user \"fallback\"}}", "admin")]
+    #[case("{{default empty \"fallback\"}}", "")]
+    #[case("{{default missing user}}", "admin")]
+    #[case("{{base64 (default missing \"user:pass\")}}", "dXNlcjpwYXNz")]
+    fn test_default_helper(#[case] template: &str, #[case] expected: &str) {
+        let data = json!({"user": "admin", "empty": ""});
+
+        assert_eq!(render_with(template, data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_default_helper_requires_a_fallback() {
+        assert!(render("{{default name}}").is_err());
+    }
+
+    #[test]
+    fn test_uuid_helper() {
+        let value = render("{{uuid}}").unwrap();
+
+        assert!(Uuid::parse_str(&value).is_ok());
+        assert_ne!(value, render("{{uuid}}").unwrap());
+    }
+
+    #[rstest]
+    #[case("{{now}}", 20)]
+    #[case("{{now \"iso8601\"}}", 20)]
+    #[case("{{now \"unix\"}}", 10)]
+    #[case("{{now \"unix_ms\"}}", 13)]
+    #[case("{{now \"[year]-[month]-[day]\"}}", 10)]
+    fn test_now_helper(#[case] template: &str, #[case] min_len: usize) {
+        let value = render(template).unwrap();
+
+        assert!(value.len() >= min_len, "{} is too short", value);
+    }
+
+    #[test]
+    fn test_random_int_helper() {
+        for _ in 0..20 {
+            let value: i64 = render("{{randomInt 1 3}}").unwrap().parse().unwrap();
+            assert!((1..=3).contains(&value));
+        }
+    }
+
+    #[rstest]
+    #[case("{{randomInt 3 1}}")]
+    #[case("{{randomInt 1}}")]
+    #[case("{{randomInt \"a\" \"b\"}}")]
+    fn test_random_int_helper_with_invalid_params(#[case] template: &str) {
+        assert!(render(template).is_err());
+    }
+
+    #[test]
+    fn test_env_helper() {
+        std::env::set_var("API_CLI_TEST_ENV_HELPER", "some-value");
+
+        assert_eq!(
+            render("{{env \"API_CLI_TEST_ENV_HELPER\"}}").unwrap(),
+            "some-value"
+        );
+        assert!(render("{{env \"API_CLI_TEST_ENV_HELPER_MISSING\"}}").is_err());
+    }
+
+    #[test]
+    fn test_faker_helper() {
+        assert!(render("{{faker \"email\"}}").unwrap().contains('@'));
+        assert_eq!(
+            render("{{faker \"lorem\" 20}}")
+                .unwrap()
+                .split_whitespace()
+                .count(),
+            20
+        );
+    }
+
+    #[rstest]
+    #[case("{{faker \"ssn\"}}")]
+    #[case("{{faker}}")]
+    #[case("{{faker \"lorem\" -1}}")]
+    fn test_faker_helper_with_invalid_params(#[case] template: &str) {
+        assert!(render(template).is_err());
+    }
+
+    #[test]
+    fn test_base64_helper() {
+        assert_eq!(render("{{base64 \"user:pass\"}}").unwrap(), "dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn test_secret_helper_fails_on_missing_secret() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        assert!(render("{{secret \"api-cli-missing-secret\"}}").is_err());
+    }
+
+    #[test]
+    fn test_helpers_in_subexpressions() {
+        std::env::set_var("API_CLI_TEST_SUBEXPRESSION", "user:pass");
+
+        assert_eq!(
+            render("{{base64 (env \"API_CLI_TEST_SUBEXPRESSION\")}}").unwrap(),
+            "dXNlcjpwYXNz"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt::Write;
+
+const BYTES_PER_LINE: usize = 16;
+
+/// Format `data` like `hexdump -C`: the offset, the bytes in hexadecimal and their printable
+/// characters, 16 bytes per line.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+
+    for (idx, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let _ = write!(out, "{:08x} ", idx * BYTES_PER_LINE);
+        for i in 0..BYTES_PER_LINE {
+            if i % 8 == 0 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(out, "{:02x} ", b);
+                }
+                None => out.push_str("   "),
+            }
+        }
+
+        let chars: String = chunk
+            .iter()
+            .map(|&b| match b {
+                0x20..=0x7e => b as char,
+                _ => '.',
+            })
+            .collect();
+        let _ = writeln!(out, " |{}|", chars);
+    }
+
+    out.truncate(out.trim_end().len());
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hexdump;
+
+    #[test]
+    fn test_hexdump() {
+        let data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x01";
+
+        assert_eq!(
+            hexdump(data),
+            "00000000  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|\n\
+             00000010  00 01                                             |..|"
+        );
+    }
+
+    #[test]
+    fn test_hexdump_empty() {
+        assert_eq!(hexdump(b""), "");
+    }
+}
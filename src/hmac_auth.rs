@@ -0,0 +1,230 @@
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, DATE};
+use reqwest::Request;
+use sha2::{Digest, Sha256, Sha512};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::error::{ApiClientError, Result};
+use crate::models::{HmacAlgorithm, HmacEncoding};
+
+static DEFAULT_STRING_TO_SIGN: &str = "{method}\n{path}\n{date}\n{body_hash}";
+
+/// Rendered configuration of an `hmac` auth.
+#[derive(Debug)]
+pub(crate) struct HmacCredentials {
+    pub(crate) secret: String,
+    pub(crate) algorithm: HmacAlgorithm,
+    pub(crate) string_to_sign: Option<String>,
+    pub(crate) header: Option<String>,
+    pub(crate) prefix: Option<String>,
+    pub(crate) encoding: HmacEncoding,
+}
+
+/// Sign a fully built request with an HMAC of its method, path, date and body, added in the
+/// configured header.
+pub(crate) fn sign_request(
+    request: &mut Request,
+    credentials: &HmacCredentials,
+    now: OffsetDateTime,
+) -> Result<()> {
+    let string_to_sign = credentials
+        .string_to_sign
+        .as_deref()
+        .unwrap_or(DEFAULT_STRING_TO_SIGN);
+
+    let date = match request.headers().get(DATE) {
+        Some(d) => String::from_utf8_lossy(d.as_bytes()).into_owned(),
+        None if string_to_sign.contains("{date}") => {
+            let date = now
+                .format(format_description!(
+                    "[weekday repr:short], [day] [month repr:short] [year] \
+                     [hour]:[minute]:[second] GMT"
+                ))
+                .expect("invalid date format");
+            request
+                .headers_mut()
+                .insert(DATE, HeaderValue::from_str(&date)?);
+            date
+        }
+        None => String::new(),
+    };
+
+    let url = request.url();
+    let path = match url.query() {
+        Some(q) => format!("{}?{}", url.path(), q),
+        None => url.path().to_string(),
+    };
+    let body = request
+        .body()
+        .and_then(|b| b.as_bytes())
+        .unwrap_or_default();
+
+    let string_to_sign = string_to_sign
+        .replace("{method}", request.method().as_str())
+        .replace("{path}", &path)
+        .replace("{host}", url.host_str().unwrap_or_default())
+        .replace("{date}", &date)
+        .replace("{body_hash}", &hex_digest(credentials.algorithm, body));
+
+    let signature = hmac(
+        credentials.algorithm,
+        credentials.secret.as_bytes(),
+        string_to_sign.as_bytes(),
+    );
+    let signature = match credentials.encoding {
+        HmacEncoding::Hex => hex::encode(signature),
+        HmacEncoding::Base64 => BASE64_STANDARD.encode(signature),
+    };
+
+    let header = match &credentials.header {
+        Some(h) => HeaderName::try_from(h)
+            .map_err(|_| ApiClientError::new_invalid_header_name(h.clone()))?,
+        None => AUTHORIZATION,
+    };
+    let value = format!(
+        "{}{}",
+        credentials.prefix.as_deref().unwrap_or_default(),
+        signature
+    );
+    let mut value = HeaderValue::from_str(&value)?;
+    value.set_sensitive(true);
+    request.headers_mut().insert(header, value);
+
+    Ok(())
+}
+
+fn hex_digest(algorithm: HmacAlgorithm, data: &[u8]) -> String {
+    match algorithm {
+        HmacAlgorithm::Sha256 => hex::encode(Sha256::digest(data)),
+        HmacAlgorithm::Sha512 => hex::encode(Sha512::digest(data)),
+    }
+}
+
+fn hmac(algorithm: HmacAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HmacAlgorithm::Sha256 => {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any size");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        HmacAlgorithm::Sha512 => {
+            let mut mac =
+                Hmac::<Sha512>::new_from_slice(key).expect("hmac accepts keys of any size");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{AUTHORIZATION, DATE};
+    use rstest::rstest;
+    use time::macros::datetime;
+
+    use super::{sign_request, HmacCredentials};
+    use crate::models::{HmacAlgorithm, HmacEncoding};
+
+    fn credentials() -> HmacCredentials {
+        HmacCredentials {
+            secret: "secret".to_string(),
+            algorithm: HmacAlgorithm::Sha256,
+            string_to_sign: None,
+            header: None,
+            prefix: None,
+            encoding: HmacEncoding::Hex,
+        }
+    }
+
+    fn request() -> reqwest::Request {
+        reqwest::Client::new()
+            .post("https://api.example.com/users?page=2")
+            .body("{}")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sign_request_with_defaults() {
+        let mut request = request();
+
+        sign_request(
+            &mut request,
+            &credentials(),
+            datetime!(2024-03-01 12:30:00 UTC),
+        )
+        .unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers[DATE], "Fri, 01 Mar 2024 12:30:00 GMT");
+        assert_eq!(
+            headers[AUTHORIZATION],
+            "618fe11b2c6b4f6586f4cedcf27647eaa1205d907d4c128947bafeeb60a1e1e6"
+        );
+        assert!(headers[AUTHORIZATION].is_sensitive());
+    }
+
+    #[rstest]
+    #[case::sha256_hex(
+        HmacAlgorithm::Sha256,
+        HmacEncoding::Hex,
+        "HMAC k1:f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    )]
+    #[case::sha256_base64(
+        HmacAlgorithm::Sha256,
+        HmacEncoding::Base64,
+        "HMAC k1:97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg="
+    )]
+    #[case::sha512_hex(
+        HmacAlgorithm::Sha512,
+        HmacEncoding::Hex,
+        "HMAC k1:b42af09057bac1e2d41708e48a902e09b5ff7f12ab428a4fe86653c73dd248fb82f948a549f7b791a5b41915ee4d1ec3935357e4e2317250d0372afa2ebeeb3a"
+    )]
+    fn test_sign_request_with_custom_string(
+        #[case] algorithm: HmacAlgorithm,
+        #[case] encoding: HmacEncoding,
+        #[case] expected: &str,
+    ) {
+        let mut request = request();
+        let credentials = HmacCredentials {
+            algorithm,
+            encoding,
+            string_to_sign: Some("The quick brown fox jumps over the lazy dog".to_string()),
+            header: Some("X-Signature".to_string()),
+            prefix: Some("HMAC k1:".to_string()),
+            secret: "key".to_string(),
+        };
+
+        sign_request(&mut request, &credentials, datetime!(2024-03-01 0:00 UTC)).unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers["X-Signature"], expected);
+        assert!(headers.get(DATE).is_none());
+        assert!(headers.get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_sign_request_uses_existing_date_header() {
+        let mut request = request();
+        request
+            .headers_mut()
+            .insert(DATE, "Mon, 01 Jan 2024 00:00:00 GMT".parse().unwrap());
+        let credentials = HmacCredentials {
+            string_to_sign: Some("{date}|{host}".to_string()),
+            ..credentials()
+        };
+
+        sign_request(&mut request, &credentials, datetime!(2024-03-01 0:00 UTC)).unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers[DATE], "Mon, 01 Jan 2024 00:00:00 GMT");
+        assert_eq!(
+            headers[AUTHORIZATION],
+            "4d8334c079726a7d2676af0692653e54cf4a35712491d25bcfa9749e1e4d3d09"
+        );
+    }
+}
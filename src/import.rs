@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+pub use crate::import::bruno::import_bruno;
+pub use crate::import::insomnia::import_insomnia;
+use crate::models::{CollectionModel, EnvironmentModel, FolderModel, KeyValuePair, RequestModel};
+use crate::request_name::sanitize_segment;
+use crate::RequestName;
+
+mod bruno;
+mod insomnia;
+
+static NUNJUCKS_VARIABLE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*(?:_\.)?([\w.-]+)\s*\}\}").expect("invalid regex"));
+
+/// A collection converted from the export of another tool, to be written in the native layout.
+#[derive(Debug, Default)]
+pub struct ImportedCollection {
+    pub collection: CollectionModel,
+    /// Folders configuring their requests, by path relative to the collection directory.
+    pub folders: Vec<(PathBuf, FolderModel)>,
+    pub requests: Vec<(RequestName, RequestModel)>,
+    pub environments: Vec<(String, EnvironmentModel)>,
+    /// Parts of the export that couldn't be converted.
+    pub warnings: Vec<String>,
+}
+
+impl ImportedCollection {
+    /// Add a request, renaming it if its name is already taken in its folder.
+    fn add_request(&mut self, folders: &[String], name: &str, request: RequestModel) {
+        let taken = self
+            .requests
+            .iter()
+            .map(|(n, _)| n.to_string())
+            .collect::<HashSet<_>>();
+        let name = unique_name(folders, name, |n| !taken.contains(&n.to_string()));
+
+        self.requests.push((name, request));
+    }
+
+    fn add_environment(&mut self, name: &str, environment: EnvironmentModel) {
+        let mut name = sanitize_segment(name);
+        let base = name.clone();
+        let mut i = 1;
+        while self.environments.iter().any(|(n, _)| *n == name) {
+            i += 1;
+            name = format!("{} {}", base, i);
+        }
+
+        self.environments.push((name, environment));
+    }
+}
+
+/// A valid request name from the display names of a request and its folders, with a number
+/// appended if it is not `available`.
+fn unique_name<F>(folders: &[String], name: &str, available: F) -> RequestName
+where
+    F: Fn(&RequestName) -> bool,
+{
+    let folders = folders
+        .iter()
+        .map(|f| sanitize_segment(f))
+        .collect::<Vec<_>>();
+    let base = sanitize_segment(name);
+
+    let mut i = 1;
+    loop {
+        let candidate = match i {
+            1 => base.clone(),
+            _ => format!("{} {}", base, i),
+        };
+        let full = folders
+            .iter()
+            .cloned()
+            .chain([candidate])
+            .collect::<Vec<_>>()
+            .join("/");
+
+        // The names reserved for the collection files are renamed too.
+        if let Ok(n) = full.parse::<RequestName>() {
+            if available(&n) {
+                return n;
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Convert the `{{ _.name }}` variables of Insomnia and Bruno to handlebars ones.
+fn convert_template(template: &str) -> String {
+    NUNJUCKS_VARIABLE
+        .replace_all(template, "{{$1}}")
+        .into_owned()
+}
+
+fn pair(key: &str, value: &str, enabled: bool) -> KeyValuePair {
+    KeyValuePair {
+        key: convert_template(key),
+        value: convert_template(value),
+        enabled: (!enabled).then_some(false),
+        enabled_if: None,
+        encode: None,
+        secret: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::convert_template;
+
+    #[rstest]
+    #[case::insomnia("{{ _.base_url }}/users", "{{base_url}}/users")]
+    #[case::spaces("{{ token }}", "{{token}}")]
+    #[case::handlebars("{{host}}/{{id}}", "{{host}}/{{id}}")]
+    #[case::tag("{% uuid 'v4' %}", "{% uuid 'v4' %}")]
+    fn test_convert_template(#[case] template: &str, #[case] expected: &str) {
+        assert_eq!(convert_template(template), expected);
+    }
+}
@@ -0,0 +1,593 @@
+use std::fs;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use super::{convert_template, pair, ImportedCollection};
+use crate::error::{ApiClientError, Result};
+use crate::models::{
+    EnvironmentModel,
+    FolderModel,
+    GraphGLBody,
+    HttpAuth,
+    HttpAwsSigV4Auth,
+    HttpBasicAuth,
+    HttpBearerToken,
+    HttpBody,
+    HttpFormBody,
+    HttpGraphQLBody,
+    HttpJsonBody,
+    HttpMethod,
+    HttpParamsModel,
+    HttpRequestModel,
+    HttpTextBody,
+    KeyValueList,
+    KeyValuePair,
+    RequestMetaModel,
+    RequestModel,
+    RequestVarsModel,
+};
+
+static PROCESS_ENV: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*process\.env\.(\w+)\s*\}\}").expect("invalid regex"));
+
+static RESPONSE_BODY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^res\.body((?:\.\w+|\[\d+\])*)$").expect("invalid regex"));
+
+const METHODS: [&str; 7] = ["get", "post", "put", "delete", "patch", "options", "head"];
+
+/// A `name { ... }` or `name [ ... ]` block of a `.bru` file.
+#[derive(Debug)]
+struct Block {
+    name: String,
+    lines: Vec<String>,
+}
+
+impl Block {
+    /// Entries of a dictionary block, with whether they are enabled.
+    fn pairs(&self) -> Vec<(String, String, bool)> {
+        self.lines
+            .iter()
+            .filter_map(|l| {
+                let (key, value) = l.trim().split_once(':')?;
+                let (key, enabled) = match key.strip_prefix('~') {
+                    Some(k) => (k, false),
+                    None => (key, true),
+                };
+
+                Some((key.trim().to_string(), value.trim().to_string(), enabled))
+            })
+            .collect()
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        self.pairs()
+            .into_iter()
+            .find(|(k, _, _)| k == key)
+            .map(|(_, v, _)| v)
+    }
+
+    fn key_values(&self) -> KeyValueList {
+        KeyValueList::new(
+            self.pairs()
+                .iter()
+                .map(|(k, v, enabled)| pair(k, v, *enabled))
+                .collect(),
+        )
+    }
+
+    /// Entries of a list block, with whether they are enabled.
+    fn items(&self) -> Vec<(String, bool)> {
+        self.lines
+            .iter()
+            .map(|l| l.trim().trim_end_matches(','))
+            .filter(|l| !l.is_empty())
+            .map(|l| match l.strip_prefix('~') {
+                Some(l) => (l.to_string(), false),
+                None => (l.to_string(), true),
+            })
+            .collect()
+    }
+
+    /// Content of a text block, without the indentation of the block.
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.strip_prefix("  ").unwrap_or(l))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+struct BruFile(Vec<Block>);
+
+impl BruFile {
+    fn parse(content: &str) -> Self {
+        let mut blocks = Vec::new();
+        let mut current: Option<Block> = None;
+
+        for line in content.lines() {
+            if current.is_some() && (line == "}" || line == "]") {
+                blocks.extend(current.take());
+            } else if let Some(block) = current.as_mut() {
+                block.lines.push(line.to_string());
+            } else {
+                let line = line.trim_end();
+                if let Some(name) = line.strip_suffix('{').or(line.strip_suffix('[')) {
+                    current = Some(Block {
+                        name: name.trim().to_string(),
+                        lines: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        Self(blocks)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    fn block(&self, name: &str) -> Option<&Block> {
+        self.0.iter().find(|b| b.name == name)
+    }
+
+    fn key_values(&self, name: &str) -> KeyValueList {
+        self.block(name).map(Block::key_values).unwrap_or_default()
+    }
+
+    /// Blocks that have no equivalent, reported as warnings.
+    fn unsupported(&self, context: &str, warnings: &mut Vec<String>) {
+        for block in &self.0 {
+            let name = block.name.as_str();
+            if name.starts_with("script:") || name == "tests" || name.starts_with("assert") {
+                warnings.push(format!("{}: {} is not supported", context, name));
+            }
+        }
+    }
+}
+
+/// Convert a Bruno collection, from its directory or from a single `.bru` request file.
+pub fn import_bruno(path: &Path) -> Result<ImportedCollection> {
+    let mut imported = ImportedCollection::default();
+
+    if path.is_file() {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        add_request(&mut imported, &[], &name, &BruFile::read(path)?);
+
+        return Ok(imported);
+    }
+
+    if !path.join("bruno.json").is_file() {
+        return Err(ApiClientError::new_import_error(format!(
+            "{} is not a Bruno collection, bruno.json not found",
+            path.display()
+        )));
+    }
+
+    let collection_file = path.join("collection.bru");
+    if collection_file.is_file() {
+        let file = BruFile::read(&collection_file)?;
+        file.unsupported("collection", &mut imported.warnings);
+        imported.collection.headers = file.key_values("headers");
+        imported.collection.auth = folder_auth(&file, "collection", &mut imported.warnings);
+        imported.collection.vars = variables(file.block("vars:pre-request"));
+    }
+
+    for entry in sorted_entries(&path.join("environments"))? {
+        if entry.extension().is_some_and(|e| e == "bru") {
+            let name = entry.file_stem().unwrap_or_default().to_string_lossy();
+            imported.add_environment(&name, environment(&BruFile::read(&entry)?));
+        }
+    }
+
+    import_directory(&mut imported, path, &[])?;
+
+    Ok(imported)
+}
+
+fn import_directory(
+    imported: &mut ImportedCollection,
+    dir: &Path,
+    folders: &[String],
+) -> Result<()> {
+    let mut requests = Vec::new();
+
+    for entry in sorted_entries(dir)? {
+        let file_name = entry
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        if entry.is_dir() {
+            if file_name.starts_with('.')
+                || file_name == "node_modules"
+                || (folders.is_empty() && file_name == "environments")
+            {
+                continue;
+            }
+
+            let folders = [folders, &[file_name]].concat();
+            let folder_file = entry.join("folder.bru");
+            if folder_file.is_file() {
+                let file = BruFile::read(&folder_file)?;
+                let context = folders.join("/");
+                file.unsupported(&context, &mut imported.warnings);
+                let folder = FolderModel {
+                    headers: file.key_values("headers"),
+                    auth: folder_auth(&file, &context, &mut imported.warnings),
+                    vars: variables(file.block("vars:pre-request")),
+                };
+                if folder.auth.is_some() || !folder.headers.is_empty() || !folder.vars.is_empty() {
+                    let path = folders.iter().map(|f| super::sanitize_segment(f)).collect();
+                    imported.folders.push((path, folder));
+                }
+            }
+            import_directory(imported, &entry, &folders)?;
+        } else if entry.extension().is_some_and(|e| e == "bru")
+            && file_name != "folder.bru"
+            && file_name != "collection.bru"
+        {
+            let file = BruFile::read(&entry)?;
+            let seq = file
+                .block("meta")
+                .and_then(|m| m.get("seq"))
+                .and_then(|s| s.parse::<u32>().ok());
+            let name = entry
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            requests.push((seq, name, file));
+        }
+    }
+
+    requests.sort_by_key(|(seq, _, _)| seq.unwrap_or(u32::MAX));
+    for (_, name, file) in requests {
+        add_request(imported, folders, &name, &file);
+    }
+
+    Ok(())
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    Ok(entries)
+}
+
+fn add_request(
+    imported: &mut ImportedCollection,
+    folders: &[String],
+    file_name: &str,
+    file: &BruFile,
+) {
+    let meta = file.block("meta");
+    let name = meta
+        .and_then(|m| m.get("name"))
+        .unwrap_or(file_name.to_string());
+    let context = [folders, std::slice::from_ref(&name)].concat().join("/");
+    let warnings = &mut imported.warnings;
+
+    file.unsupported(&context, warnings);
+
+    let Some(method_block) = file.0.iter().find(|b| METHODS.contains(&b.name.as_str())) else {
+        warnings.push(format!(
+            "{}: no http method, the request is skipped",
+            context
+        ));
+        return;
+    };
+    let method = method_block.name.parse().unwrap_or(HttpMethod::Get);
+
+    let auth = match method_block.get("auth").as_deref() {
+        None | Some("inherit") => None,
+        Some(mode) => auth(mode, file, &context, warnings),
+    };
+    let body = match method_block.get("body").as_deref() {
+        None | Some("none") => None,
+        Some(mode) => body(mode, file, &context, warnings),
+    };
+
+    let request = RequestModel {
+        meta: RequestMetaModel {
+            name: Some(name.clone()),
+            seq: meta.and_then(|m| m.get("seq")).and_then(|s| s.parse().ok()),
+            description: file
+                .block("docs")
+                .map(Block::text)
+                .filter(|d| !d.is_empty()),
+            ..Default::default()
+        },
+        http: HttpRequestModel {
+            method,
+            url: convert_template(&method_block.get("url").unwrap_or_default()),
+            auth,
+            headers: file.key_values("headers"),
+            params: HttpParamsModel {
+                query: file.key_values("params:query"),
+                path: file.key_values("params:path"),
+            },
+            body,
+            ..Default::default()
+        },
+        vars: RequestVarsModel {
+            pre_request: file.key_values("vars:pre-request"),
+            post_request: captures(file.block("vars:post-response"), &context, warnings),
+        },
+        ..Default::default()
+    };
+
+    imported.add_request(folders, &name, request);
+}
+
+/// Authentication of the collection or of a folder, set by the `auth` block.
+fn folder_auth(file: &BruFile, context: &str, warnings: &mut Vec<String>) -> Option<HttpAuth> {
+    match file.block("auth").and_then(|a| a.get("mode")).as_deref() {
+        None | Some("none") | Some("inherit") => None,
+        Some(mode) => auth(mode, file, context, warnings),
+    }
+}
+
+fn auth(mode: &str, file: &BruFile, context: &str, warnings: &mut Vec<String>) -> Option<HttpAuth> {
+    let block = file.block(&format!("auth:{}", mode));
+    let field = |name: &str| convert_template(&block.and_then(|b| b.get(name)).unwrap_or_default());
+
+    match mode {
+        "none" => Some(HttpAuth::None),
+        "basic" => Some(HttpAuth::Basic(HttpBasicAuth {
+            username: field("username"),
+            password: field("password"),
+        })),
+        "bearer" => Some(HttpAuth::Bearer(HttpBearerToken {
+            token: field("token"),
+            refresh: None,
+        })),
+        "awsv4" => Some(HttpAuth::AwsSigV4(HttpAwsSigV4Auth {
+            access_key: field("accessKeyId"),
+            secret_key: field("secretAccessKey"),
+            region: field("region"),
+            service: field("service"),
+            session_token: Some(field("sessionToken")).filter(|t| !t.is_empty()),
+        })),
+        _ => {
+            warnings.push(format!("{}: unsupported auth {}", context, mode));
+            None
+        }
+    }
+}
+
+fn body(mode: &str, file: &BruFile, context: &str, warnings: &mut Vec<String>) -> Option<HttpBody> {
+    let text = |name: &str| {
+        convert_template(
+            &file
+                .block(&format!("body:{}", name))
+                .map(Block::text)
+                .unwrap_or_default(),
+        )
+    };
+    let text_body = |text: String, content_type: &str| {
+        HttpBody::Text(HttpTextBody {
+            text,
+            content_type: Some(content_type.to_string()),
+        })
+    };
+
+    match mode {
+        "json" => {
+            let text = text("json");
+            Some(match serde_json::from_str(&text) {
+                Ok(json) => HttpBody::Json(HttpJsonBody {
+                    json,
+                    content_type: None,
+                }),
+                // Json with variables outside of strings is kept as text.
+                Err(_) => text_body(text, "application/json"),
+            })
+        }
+        "text" => Some(text_body(text("text"), "text/plain")),
+        "xml" => Some(text_body(text("xml"), "application/xml")),
+        "formUrlEncoded" => Some(HttpBody::Form(HttpFormBody {
+            form: file.key_values("body:form-urlencoded"),
+        })),
+        "graphql" => {
+            let variables = serde_json::from_str::<Value>(&text("graphql:vars"))
+                .ok()
+                .and_then(|v| v.as_object().cloned())
+                .map(|v| v.into_iter().collect())
+                .unwrap_or_default();
+            Some(HttpBody::GraphQL(HttpGraphQLBody {
+                graphql: GraphGLBody {
+                    query: text("graphql"),
+                    variables,
+                },
+            }))
+        }
+        _ => {
+            warnings.push(format!("{}: unsupported body {}", context, mode));
+            None
+        }
+    }
+}
+
+/// Variables captured from the response, for the values read from the response body.
+fn captures(block: Option<&Block>, context: &str, warnings: &mut Vec<String>) -> KeyValueList {
+    let Some(block) = block else {
+        return KeyValueList::default();
+    };
+
+    let mut captures = Vec::new();
+    for (key, value, enabled) in block.pairs() {
+        match RESPONSE_BODY.captures(&value) {
+            Some(c) => captures.push(pair(&key, &format!("${}", &c[1]), enabled)),
+            None => warnings.push(format!(
+                "{}: unsupported post-response variable {}: {}",
+                context, key, value
+            )),
+        }
+    }
+
+    KeyValueList::new(captures)
+}
+
+/// Variables of the collection or of a folder, with references to the process environment
+/// converted.
+fn variables(block: Option<&Block>) -> KeyValueList {
+    KeyValueList::new(variable_pairs(block))
+}
+
+fn variable_pairs(block: Option<&Block>) -> Vec<KeyValuePair> {
+    block
+        .map(Block::pairs)
+        .unwrap_or_default()
+        .iter()
+        .map(|(k, v, enabled)| pair(k, &PROCESS_ENV.replace_all(v, "$${$1}"), *enabled))
+        .collect()
+}
+
+fn environment(file: &BruFile) -> EnvironmentModel {
+    let mut vars = variable_pairs(file.block("vars"));
+    // The values of secrets are not part of the export and must be filled in.
+    let secrets = file
+        .block("vars:secret")
+        .map(Block::items)
+        .unwrap_or_default();
+    vars.extend(secrets.iter().map(|(key, enabled)| KeyValuePair {
+        secret: Some(true),
+        ..pair(key, "", *enabled)
+    }));
+
+    EnvironmentModel {
+        vars: KeyValueList::new(vars),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::import_bruno;
+
+    fn write_collection(files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn test_import_bruno() {
+        let dir = write_collection(&[
+            ("bruno.json", r#"{"version": "1", "name": "api", "type": "collection"}"#),
+            (
+                "collection.bru",
+                "headers {\n  Accept: application/json\n}\n\nauth {\n  mode: bearer\n}\n\nauth:bearer {\n  token: {{token}}\n}\n",
+            ),
+            (
+                "environments/Local.bru",
+                "vars {\n  base_url: http://localhost\n  key: {{process.env.API_KEY}}\n}\nvars:secret [\n  token\n]\n",
+            ),
+            (
+                "users/Create user.bru",
+                "meta {\n  name: Create user\n  type: http\n  seq: 2\n}\n\npost {\n  url: {{base_url}}/users\n  body: json\n  auth: basic\n}\n\nheaders {\n  ~X-Debug: 1\n}\n\nauth:basic {\n  username: u\n  password: p\n}\n\nbody:json {\n  {\n    \"name\": \"{{name}}\"\n  }\n}\n\nvars:post-response {\n  user_id: res.body.id\n  status: res.status\n}\n\nscript:pre-request {\n  console.log(1)\n}\n",
+            ),
+            (
+                "users/List users.bru",
+                "meta {\n  name: List users\n  seq: 1\n}\n\nget {\n  url: {{base_url}}/users\n  auth: inherit\n}\n\nparams:query {\n  page: 1\n}\n\ndocs {\n  Lists the users.\n}\n",
+            ),
+        ]);
+
+        let imported = import_bruno(&dir).expect("import failed");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&imported.collection.headers).unwrap(),
+            json!([{"key": "Accept", "value": "application/json"}])
+        );
+        assert_eq!(
+            serde_json::to_value(&imported.collection.auth).unwrap(),
+            json!({"type": "bearer", "token": "{{token}}"})
+        );
+        assert_eq!(imported.environments.len(), 1);
+        assert_eq!(imported.environments[0].0, "Local");
+        assert_eq!(
+            serde_json::to_value(&imported.environments[0].1.vars).unwrap(),
+            json!([
+                {"key": "base_url", "value": "http://localhost"},
+                {"key": "key", "value": "${API_KEY}"},
+                {"key": "token", "value": "", "secret": true},
+            ])
+        );
+
+        let names = imported
+            .requests
+            .iter()
+            .map(|(n, _)| n.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["users:List users", "users:Create user"]);
+
+        let list = &imported.requests[0].1;
+        assert_eq!(list.meta.description.as_deref(), Some("Lists the users."));
+        assert!(list.http.auth.is_none());
+        assert_eq!(
+            serde_json::to_value(&list.http.params.query).unwrap(),
+            json!([{"key": "page", "value": "1"}])
+        );
+
+        let create = serde_json::to_value(&imported.requests[1].1).unwrap();
+        assert_eq!(create["http"]["method"], "POST");
+        assert_eq!(
+            create["http"]["headers"],
+            json!([{"key": "X-Debug", "value": "1", "enabled": false}])
+        );
+        assert_eq!(
+            create["http"]["auth"],
+            json!({"type": "basic", "username": "u", "password": "p"})
+        );
+        assert_eq!(create["http"]["body"]["json"], json!({"name": "{{name}}"}));
+        assert_eq!(
+            create["vars"]["post_request"],
+            json!([{"key": "user_id", "value": "$.id"}])
+        );
+
+        assert_eq!(
+            imported.warnings,
+            vec![
+                "users/Create user: script:pre-request is not supported",
+                "users/Create user: unsupported post-response variable status: res.status",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_bruno_requires_a_collection() {
+        let dir = write_collection(&[("readme.md", "")]);
+
+        let err = import_bruno(&dir).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.to_string().contains("bruno.json not found"));
+    }
+}
@@ -0,0 +1,447 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::{convert_template, pair, sanitize_segment, ImportedCollection};
+use crate::error::{ApiClientError, Result};
+use crate::models::{
+    EnvironmentModel,
+    FolderModel,
+    GraphGLBody,
+    HttpAuth,
+    HttpBasicAuth,
+    HttpBearerToken,
+    HttpBody,
+    HttpFormBody,
+    HttpGraphQLBody,
+    HttpJsonBody,
+    HttpMethod,
+    HttpParamsModel,
+    HttpRequestModel,
+    HttpTextBody,
+    KeyValueList,
+    RequestMetaModel,
+    RequestModel,
+};
+
+#[derive(Deserialize)]
+struct Export {
+    #[serde(rename = "__export_format")]
+    format: u32,
+    resources: Vec<Resource>,
+}
+
+#[derive(Deserialize)]
+struct Resource {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(rename = "_type")]
+    type_: String,
+    #[serde(rename = "parentId")]
+    parent_id: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(rename = "metaSortKey")]
+    sort_key: Option<f64>,
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    headers: Vec<Parameter>,
+    #[serde(default)]
+    parameters: Vec<Parameter>,
+    #[serde(default)]
+    body: Body,
+    #[serde(default)]
+    authentication: Map<String, Value>,
+    /// Variables of environments and folders
+    #[serde(alias = "environment", default)]
+    data: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Parameter {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    disabled: bool,
+    /// Set for the file fields of multipart forms.
+    #[serde(rename = "fileName")]
+    file_name: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct Body {
+    #[serde(rename = "mimeType")]
+    mime_type: Option<String>,
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<Parameter>,
+}
+
+/// Convert an Insomnia export, in the v4 json format. The base environment becomes the variables
+/// of the collection and its sub-environments the environments of the collection.
+pub fn import_insomnia(data: &str) -> Result<ImportedCollection> {
+    let export: Export = serde_json::from_str(data)?;
+    if export.format != 4 {
+        return Err(ApiClientError::new_import_error(format!(
+            "unsupported Insomnia export format {}",
+            export.format
+        )));
+    }
+
+    let mut imported = ImportedCollection::default();
+    let mut resources = export.resources;
+    resources.sort_by(|a, b| {
+        a.sort_key
+            .unwrap_or(0.0)
+            .total_cmp(&b.sort_key.unwrap_or(0.0))
+    });
+
+    let by_id: HashMap<&str, &Resource> = resources.iter().map(|r| (r.id.as_str(), r)).collect();
+    let folders_of = |resource: &Resource| {
+        let mut folders = Vec::new();
+        let mut parent = resource.parent_id.as_deref();
+        while let Some(p) = parent.and_then(|p| by_id.get(p)) {
+            if p.type_ != "request_group" {
+                break;
+            }
+            folders.insert(0, p.name.clone());
+            parent = p.parent_id.as_deref();
+        }
+
+        folders
+    };
+
+    let base_environments: Vec<&str> = resources
+        .iter()
+        .filter(|r| {
+            r.type_ == "environment"
+                && r.parent_id
+                    .as_deref()
+                    .and_then(|p| by_id.get(p))
+                    .is_none_or(|p| p.type_ == "workspace")
+        })
+        .map(|r| r.id.as_str())
+        .collect();
+
+    for resource in &resources {
+        match resource.type_.as_str() {
+            "environment" if base_environments.contains(&resource.id.as_str()) => {
+                imported.collection.vars = variables(&resource.data);
+            }
+            "environment" => imported.add_environment(
+                &resource.name,
+                EnvironmentModel {
+                    vars: variables(&resource.data),
+                    ..Default::default()
+                },
+            ),
+            "request_group" => {
+                let folder = FolderModel {
+                    headers: KeyValueList::default(),
+                    auth: auth(resource, &mut imported.warnings),
+                    vars: variables(&resource.data),
+                };
+                if folder.auth.is_some() || !folder.vars.is_empty() {
+                    let path: PathBuf = folders_of(resource)
+                        .iter()
+                        .chain([&resource.name])
+                        .map(|f| sanitize_segment(f))
+                        .collect();
+                    imported.folders.push((path, folder));
+                }
+            }
+            "request" => {
+                let request = request(resource, &mut imported.warnings);
+                imported.add_request(&folders_of(resource), &resource.name, request);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(imported)
+}
+
+fn request(resource: &Resource, warnings: &mut Vec<String>) -> RequestModel {
+    let method = match resource.method.parse() {
+        Ok(m) => m,
+        Err(_) => {
+            warnings.push(format!(
+                "{}: unsupported method {}, GET is used instead",
+                resource.name, resource.method
+            ));
+            HttpMethod::Get
+        }
+    };
+
+    let params = |list: &[Parameter]| {
+        KeyValueList::new(
+            list.iter()
+                .map(|p| pair(&p.name, &p.value, !p.disabled))
+                .collect(),
+        )
+    };
+
+    RequestModel {
+        meta: RequestMetaModel {
+            name: Some(resource.name.clone()),
+            description: (!resource.description.is_empty()).then(|| resource.description.clone()),
+            ..Default::default()
+        },
+        http: HttpRequestModel {
+            method,
+            url: convert_template(&resource.url),
+            auth: auth(resource, warnings),
+            headers: params(&resource.headers),
+            params: HttpParamsModel {
+                query: params(&resource.parameters),
+                ..Default::default()
+            },
+            body: body(resource, warnings),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn body(resource: &Resource, warnings: &mut Vec<String>) -> Option<HttpBody> {
+    let body = &resource.body;
+    let mime_type = body.mime_type.as_deref()?;
+    let text = convert_template(body.text.as_deref().unwrap_or_default());
+
+    match mime_type {
+        "application/json" => Some(match serde_json::from_str(&text) {
+            Ok(json) => HttpBody::Json(HttpJsonBody {
+                json,
+                content_type: None,
+            }),
+            // Json with templates outside of strings is kept as text.
+            Err(_) => HttpBody::Text(HttpTextBody {
+                text,
+                content_type: Some(mime_type.to_string()),
+            }),
+        }),
+        "application/graphql" => {
+            let graphql: Value = serde_json::from_str(&text).unwrap_or_default();
+            Some(HttpBody::GraphQL(HttpGraphQLBody {
+                graphql: GraphGLBody {
+                    query: graphql["query"].as_str().unwrap_or_default().to_string(),
+                    variables: graphql["variables"]
+                        .as_object()
+                        .map(|v| v.clone().into_iter().collect())
+                        .unwrap_or_default(),
+                },
+            }))
+        }
+        "application/x-www-form-urlencoded" => Some(HttpBody::Form(HttpFormBody {
+            form: KeyValueList::new(
+                body.params
+                    .iter()
+                    .map(|p| pair(&p.name, &p.value, !p.disabled))
+                    .collect(),
+            ),
+        })),
+        "multipart/form-data" => {
+            let files = body.params.iter().filter(|p| p.file_name.is_some()).count();
+            if files > 0 {
+                warnings.push(format!(
+                    "{}: multipart bodies are not supported, {} file field(s) were dropped",
+                    resource.name, files
+                ));
+            }
+            Some(HttpBody::Form(HttpFormBody {
+                form: KeyValueList::new(
+                    body.params
+                        .iter()
+                        .filter(|p| p.file_name.is_none())
+                        .map(|p| pair(&p.name, &p.value, !p.disabled))
+                        .collect(),
+                ),
+            }))
+        }
+        _ => Some(HttpBody::Text(HttpTextBody {
+            text,
+            content_type: Some(mime_type.to_string()),
+        })),
+    }
+}
+
+fn auth(resource: &Resource, warnings: &mut Vec<String>) -> Option<HttpAuth> {
+    let auth = &resource.authentication;
+    let field =
+        |name: &str| convert_template(auth.get(name).and_then(Value::as_str).unwrap_or_default());
+
+    if auth.get("disabled") == Some(&Value::Bool(true)) {
+        return None;
+    }
+
+    match auth.get("type").and_then(Value::as_str)? {
+        "basic" => Some(HttpAuth::Basic(HttpBasicAuth {
+            username: field("username"),
+            password: field("password"),
+        })),
+        "bearer" => Some(HttpAuth::Bearer(HttpBearerToken {
+            token: field("token"),
+            refresh: None,
+        })),
+        "none" => Some(HttpAuth::None),
+        t => {
+            warnings.push(format!(
+                "{}: unsupported authentication {}",
+                resource.name, t
+            ));
+            None
+        }
+    }
+}
+
+/// Variables of an environment, nested objects being flattened with dots as in Insomnia.
+fn variables(data: &Map<String, Value>) -> KeyValueList {
+    fn flatten(prefix: &str, data: &Map<String, Value>, vars: &mut Vec<(String, String)>) {
+        for (k, v) in data {
+            let key = match prefix {
+                "" => k.clone(),
+                p => format!("{}.{}", p, k),
+            };
+            match v {
+                Value::Object(o) => flatten(&key, o, vars),
+                Value::String(s) => vars.push((key, convert_template(s))),
+                v => vars.push((key, v.to_string())),
+            }
+        }
+    }
+
+    let mut vars = Vec::new();
+    flatten("", data, &mut vars);
+
+    KeyValueList::new(vars.iter().map(|(k, v)| pair(k, v, true)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::import_insomnia;
+
+    #[test]
+    fn test_import_insomnia() {
+        let export = json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                {"_id": "wrk_1", "_type": "workspace", "name": "Api"},
+                {
+                    "_id": "env_1", "_type": "environment", "parentId": "wrk_1",
+                    "name": "Base Environment", "data": {"base_url": "http://localhost"}
+                },
+                {
+                    "_id": "env_2", "_type": "environment", "parentId": "env_1",
+                    "name": "Production", "data": {"base_url": "https://api", "auth": {"user": "a"}}
+                },
+                {"_id": "fld_1", "_type": "request_group", "parentId": "wrk_1", "name": "Auth"},
+                {
+                    "_id": "req_1", "_type": "request", "parentId": "fld_1", "name": "Log in",
+                    "method": "POST", "url": "{{ _.base_url }}/login", "metaSortKey": -1,
+                    "headers": [{"name": "X-Trace", "value": "1", "disabled": true}],
+                    "body": {"mimeType": "application/json", "text": "{\"user\": \"{{ _.auth.user }}\"}"},
+                    "authentication": {"type": "basic", "username": "u", "password": "p"}
+                },
+                {
+                    "_id": "req_2", "_type": "request", "parentId": "wrk_1", "name": "Users: list",
+                    "method": "GET", "url": "{{ _.base_url }}/users", "metaSortKey": 1,
+                    "parameters": [{"name": "page", "value": "1"}],
+                    "authentication": {"type": "bearer", "token": "{{ _.token }}"}
+                },
+                {
+                    "_id": "req_3", "_type": "request", "parentId": "wrk_1", "name": "Upload",
+                    "method": "POST", "url": "http://localhost/upload", "metaSortKey": 2,
+                    "body": {"mimeType": "multipart/form-data", "params": [
+                        {"name": "file", "fileName": "/tmp/a"}, {"name": "title", "value": "a"}
+                    ]},
+                    "authentication": {"type": "oauth2"}
+                }
+            ]
+        });
+
+        let imported = import_insomnia(&export.to_string()).expect("import failed");
+
+        assert_eq!(
+            serde_yaml::to_value(&imported.collection.vars).unwrap(),
+            serde_yaml::from_str::<serde_yaml::Value>(
+                "[{key: base_url, value: 'http://localhost'}]"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            imported
+                .environments
+                .iter()
+                .map(|(n, e)| (n.as_str(), serde_json::to_value(&e.vars).unwrap()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "Production",
+                json!([{"key": "auth.user", "value": "a"}, {"key": "base_url", "value": "https://api"}])
+            )]
+        );
+
+        let requests = imported
+            .requests
+            .iter()
+            .map(|(n, r)| (n.to_string(), serde_json::to_value(&r.http).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            requests.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(),
+            vec!["Auth:Log in", "Users- list", "Upload"]
+        );
+        assert_eq!(requests[0].1["url"], "{{base_url}}/login");
+        assert_eq!(
+            requests[0].1["headers"],
+            json!([{"key": "X-Trace", "value": "1", "enabled": false}])
+        );
+        assert_eq!(
+            requests[0].1["body"],
+            json!({"type": "json", "json": {"user": "{{auth.user}}"}})
+        );
+        assert_eq!(
+            requests[0].1["auth"],
+            json!({"type": "basic", "username": "u", "password": "p"})
+        );
+        assert_eq!(
+            requests[1].1["params"]["query"],
+            json!([{"key": "page", "value": "1"}])
+        );
+        assert_eq!(
+            requests[1].1["auth"],
+            json!({"type": "bearer", "token": "{{token}}"})
+        );
+        assert_eq!(
+            requests[2].1["body"],
+            json!({"type": "form", "form": [{"key": "title", "value": "a"}]})
+        );
+
+        assert_eq!(
+            imported.warnings,
+            vec![
+                "Upload: unsupported authentication oauth2",
+                "Upload: multipart bodies are not supported, 1 file field(s) were dropped",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_insomnia_rejects_other_formats() {
+        let err = import_insomnia(r#"{"__export_format": 3, "resources": []}"#).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ImportError: Unable to import: unsupported Insomnia export format 3"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+use std::env;
+
+use crate::error::{ApiClientError, Result};
+
+/// Replace the references to process environment variables in a value.
+///
+/// `${NAME}` is replaced by the value of `NAME` and fails if it is not set, `${NAME:-default}`
+/// falls back to `default` instead. `$${` is an escaped `${`. Values without any reference are
+/// returned as is.
+pub(crate) fn interpolate_env(value: &str) -> Result<Cow<'_, str>> {
+    if !value.contains("${") {
+        return Ok(Cow::Borrowed(value));
+    }
+
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            result.push_str(&rest[..start - 1]);
+            result.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        result.push_str(&rest[..start]);
+
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            return Err(ApiClientError::new_invalid_env_reference(value.to_string()));
+        };
+
+        let expression = &after[..end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((n, d)) => (n, Some(d)),
+            None => (expression, None),
+        };
+
+        if name.is_empty() {
+            return Err(ApiClientError::new_invalid_env_reference(value.to_string()));
+        }
+
+        match (env::var(name), default) {
+            (Ok(v), _) => result.push_str(&v),
+            (Err(_), Some(d)) => result.push_str(d),
+            (Err(_), None) => {
+                return Err(ApiClientError::new_env_var_not_set(name.to_string()));
+            }
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(Cow::Owned(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use rstest::rstest;
+
+    use super::interpolate_env;
+
+    #[test]
+    fn test_interpolate_env_returns_plain_values_as_is() {
+        let value = interpolate_env("some-value {{var}}").unwrap();
+
+        assert!(matches!(value, Cow::Borrowed("some-value {{var}}")));
+    }
+
+    #[rstest]
+    #[case("${API_CLI_TEST_INTERPOLATION}", "value")]
+    #[case("Bearer ${API_CLI_TEST_INTERPOLATION}!", "Bearer value!")]
+    #[case(
+        "${API_CLI_TEST_INTERPOLATION}-${API_CLI_TEST_INTERPOLATION}",
+        "value-value"
+    )]
+    #[case("${API_CLI_TEST_UNSET:-fallback}", "fallback")]
+    #[case("${API_CLI_TEST_INTERPOLATION:-fallback}", "value")]
+    #[case("$${API_CLI_TEST_INTERPOLATION}", "${API_CLI_TEST_INTERPOLATION}")]
+    fn test_interpolate_env(#[case] value: &str, #[case] expected: &str) {
+        std::env::set_var("API_CLI_TEST_INTERPOLATION", "value");
+
+        assert_eq!(interpolate_env(value).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("${API_CLI_TEST_UNSET}")]
+    #[case("${API_CLI_TEST_INTERPOLATION")]
+    #[case("${}")]
+    fn test_interpolate_env_fails_on_invalid_reference(#[case] value: &str) {
+        assert!(interpolate_env(value).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::str::FromStr;
+
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{data, unwrap_valr, Compiler, Ctx, Filter, Vars};
+use jaq_json::Val;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::{ApiClientError, Result};
+
+type JqData = data::JustLut<Val>;
+
+/// A jq program applied to json documents, run by jaq with its standard library.
+///
+/// The program is compiled once when parsed to report its errors early, and again for every run
+/// as the compiled filters of jaq can't be cloned.
+#[derive(Debug, Clone)]
+pub struct JqFilter {
+    source: String,
+}
+
+impl FromStr for JqFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        compile(s)?;
+
+        Ok(Self {
+            source: s.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for JqFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl JqFilter {
+    /// Run the program on `input`, returning all its outputs.
+    pub fn run(&self, input: &Value) -> Result<Vec<Value>> {
+        let filter = compile(&self.source).map_err(ApiClientError::new_jq_error)?;
+        let input =
+            Val::deserialize(input).map_err(|e| ApiClientError::new_jq_error(e.to_string()))?;
+        let ctx = Ctx::<JqData>::new(&filter.lut, Vars::new([]));
+
+        filter
+            .id
+            .run((ctx, input))
+            .map(unwrap_valr)
+            .map(|v| match v {
+                Ok(v) => to_json(&v),
+                Err(e) => Err(ApiClientError::new_jq_error(e.to_string())),
+            })
+            .collect()
+    }
+}
+
+fn compile(source: &str) -> std::result::Result<Filter<JqData>, String> {
+    let defs = jaq_core::defs()
+        .chain(jaq_std::defs())
+        .chain(jaq_json::defs());
+    let funs = jaq_core::funs()
+        .chain(jaq_std::funs())
+        .chain(jaq_json::funs());
+
+    let arena = Arena::default();
+    let program = File {
+        code: source,
+        path: (),
+    };
+    let modules = Loader::new(defs).load(&arena, program).map_err(|errs| {
+        let messages: Vec<_> = errs
+            .into_iter()
+            .flat_map(|(_, e)| load_error_messages(e))
+            .collect();
+        messages.join(", ")
+    })?;
+
+    Compiler::<_, JqData>::default()
+        .with_funs(funs)
+        .compile(modules)
+        .map_err(|errs| {
+            let messages: Vec<_> = errs
+                .into_iter()
+                .flat_map(|(_, e)| e)
+                .map(|(name, undefined)| format!("undefined {} `{}`", undefined.as_str(), name))
+                .collect();
+            messages.join(", ")
+        })
+}
+
+fn load_error_messages(error: jaq_core::load::Error<&str>) -> Vec<String> {
+    use jaq_core::load::Error;
+
+    match error {
+        Error::Io(errs) => errs
+            .into_iter()
+            .map(|(path, e)| format!("unable to load `{}`: {}", path, e))
+            .collect(),
+        Error::Lex(errs) => errs
+            .into_iter()
+            .map(|(expected, found)| {
+                // The lexer reports the rest of the program, of which the first character failed.
+                let found = found.chars().next().map(String::from).unwrap_or_default();
+                expected_message(expected.as_str(), &found)
+            })
+            .collect(),
+        Error::Parse(errs) => errs
+            .into_iter()
+            .map(|(expected, found)| expected_message(expected.as_str(), found))
+            .collect(),
+    }
+}
+
+fn expected_message(expected: &str, found: &str) -> String {
+    match found {
+        "" => format!("expected {}, found end of filter", expected),
+        found => format!("expected {}, found `{}`", expected, found),
+    }
+}
+
+/// Values that can't be represented in json, like `nan` or byte strings, are errors.
+fn to_json(value: &Val) -> Result<Value> {
+    serde_json::from_str(&value.to_string()).map_err(|_| {
+        ApiClientError::new_jq_error(format!("{} can't be represented in json", value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    use super::JqFilter;
+
+    fn run(filter: &str, input: Value) -> Vec<Value> {
+        JqFilter::from_str(filter)
+            .expect("invalid filter")
+            .run(&input)
+            .expect("filter failed")
+    }
+
+    #[rstest]
+    #[case::identity(".", json!({"a": 1}), vec![json!({"a": 1})])]
+    #[case::field(".a.b", json!({"a": {"b": 2}}), vec![json!(2)])]
+    #[case::quoted_field(r#"."a-b""#, json!({"a-b": 1}), vec![json!(1)])]
+    #[case::missing_field(".a.b", json!({}), vec![json!(null)])]
+    #[case::index(".[1], .[-1]", json!([1, 2, 3]), vec![json!(2), json!(3)])]
+    #[case::slice(".[1:], .[:-1]", json!([1, 2, 3]), vec![json!([2, 3]), json!([1, 2])])]
+    #[case::iterate(".[] | .id", json!([{"id": 1}, {"id": 2}]), vec![json!(1), json!(2)])]
+    #[case::optional(".[] | .a?", json!([1, {"a": 2}]), vec![json!(2)])]
+    #[case::recurse("[.. | numbers]", json!({"a": [1, {"b": 2}]}), vec![json!([1, 2])])]
+    #[case::arithmetic(".a + 1, .a * 2 - 1, 7 % 3, 1 / 4", json!({"a": 2}), vec![json!(3), json!(3), json!(1), json!(0.25)])]
+    #[case::string_ops(r#""ab" * 2, "a,b" / ",""#, json!(null), vec![json!("abab"), json!(["a", "b"])])]
+    #[case::merge(". + {b: 2}, . * {a: {d: 4}}", json!({"a": {"c": 3}}), vec![json!({"a": {"c": 3}, "b": 2}), json!({"a": {"c": 3, "d": 4}})])]
+    #[case::comparison(".a == 1, .a < \"x\", null < false, [1] > [0, 5]", json!({"a": 1}), vec![json!(true), json!(true), json!(true), json!(true)])]
+    #[case::boolean("(true, false) and true, false or .a, (.a | not)", json!({"a": 1}), vec![json!(true), json!(false), json!(true), json!(false)])]
+    #[case::alternative(".a // \"default\", .b // \"default\"", json!({"a": false, "b": 2}), vec![json!("default"), json!(2)])]
+    #[case::map_select("map(select(.age > 30) | .name)", json!([{"name": "a", "age": 40}, {"name": "b", "age": 20}]), vec![json!(["a"])])]
+    #[case::object("{name, id: .user.id, (.k): 1, \"s\": 2} | keys", json!({"name": "n", "user": {"id": 3}, "k": "key"}), vec![json!(["id", "key", "name", "s"])])]
+    #[case::object_cartesian("{a: (1, 2)}", json!(null), vec![json!({"a": 1}), json!({"a": 2})])]
+    #[case::interpolation(r#""\(.first) \(.last // "?") has \(.n + 1) items""#, json!({"first": "Ada", "n": 2}), vec![json!("Ada ? has 3 items")])]
+    #[case::nested_interpolation(r#""\("(\(.a))")""#, json!({"a": 1}), vec![json!("(1)")])]
+    #[case::if_elif("if . > 2 then \"big\" elif . > 1 then \"medium\" else \"small\" end", json!(2), vec![json!("medium")])]
+    #[case::bindings(". as $x | [$x, $x * 2]", json!(2), vec![json!([2, 4])])]
+    #[case::reduce("reduce .[] as $x (0; . + $x)", json!([1, 2, 3]), vec![json!(6)])]
+    #[case::try_catch("try error(\"boom\") catch ., (try error(\"x\"))", json!(null), vec![json!("boom")])]
+    #[case::length("[.[] | length]", json!([[1, 2], "abc", {"a": 1}, null, -5]), vec![json!([2, 3, 1, 0, 5])])]
+    #[case::keys_has("keys, has(\"a\"), has(\"z\")", json!({"b": 1, "a": 2}), vec![json!(["a", "b"]), json!(true), json!(false)])]
+    #[case::sorting("sort, sort_by(-.), unique, min, max, reverse", json!([3, 1, 3]), vec![json!([1, 3, 3]), json!([3, 3, 1]), json!([1, 3]), json!(1), json!(3), json!([3, 1, 3])])]
+    #[case::group_by("group_by(.t) | map(length)", json!([{"t": "a"}, {"t": "b"}, {"t": "a"}]), vec![json!([2, 1])])]
+    #[case::entries("to_entries, with_entries({key, value: (.value + 1)})", json!({"a": 1}), vec![json!([{"key": "a", "value": 1}]), json!({"a": 2})])]
+    #[case::strings("split(\",\") | join(\"-\")", json!("a,b,c"), vec![json!("a-b-c")])]
+    #[case::regex("test(\"^A\"; \"i\"), gsub(\"(?<x>[0-9])\"; \"<\\(.x)>\")", json!("a1b2"), vec![json!(true), json!("a<1>b<2>")])]
+    #[case::add_any_all("add, any, all", json!([1, 2]), vec![json!(3), json!(true), json!(true)])]
+    #[case::range_limit("[range(3)], [limit(2; .[])], first(.[]), first, last", json!([4, 5, 6]), vec![json!([0, 1, 2]), json!([4, 5]), json!(4), json!(4), json!(6)])]
+    #[case::conversions("tostring, (\"12\" | tonumber), tojson, type", json!([1]), vec![json!("[1]"), json!(12), json!("[1]"), json!("array")])]
+    #[case::formats("@json, @base64 \"x\\(.[0])\", @uri \"q=\\(.[1])\"", json!(["a b", "x\"y", 1]), vec![json!("[\"a b\",\"x\\\"y\",1]"), json!("xYSBi"), json!("q=x%22y")])]
+    #[case::flatten("flatten, flatten(1)", json!([1, [2, [3]]]), vec![json!([1, 2, 3]), json!([1, 2, [3]])])]
+    #[case::contains("contains({a: [\"x\"]}), contains({b: 1})", json!({"a": ["xyz"]}), vec![json!(true), json!(false)])]
+    #[case::comments(".a # the a field", json!({"a": 1}), vec![json!(1)])]
+    fn test_jq_filter(#[case] filter: &str, #[case] input: Value, #[case] expected: Vec<Value>) {
+        assert_eq!(run(filter, input), expected);
+    }
+
+    #[rstest]
+    #[case::unbalanced("(.a", "expected closing parenthesis, found end of filter")]
+    #[case::unexpected(".a )", "expected token, found `)`")]
+    #[case::unterminated_string("\"abc", "expected closing quote, found end of filter")]
+    #[case::empty("  ", "expected term, found end of filter")]
+    #[case::invalid_char(".a ^ 1", "expected token, found `^`")]
+    #[case::undefined("foo(1)", "undefined filter `foo`")]
+    fn test_jq_filter_parse_errors(#[case] filter: &str, #[case] expected: &str) {
+        let err = JqFilter::from_str(filter).unwrap_err();
+
+        assert_eq!(err, expected);
+    }
+
+    #[rstest]
+    #[case::index(".a", json!(1), "JqError: cannot index 1 with \"a\"")]
+    #[case::iterate(".[]", json!(true), "JqError: cannot use true as iterable (array or object)")]
+    #[case::add(". + 1", json!("a"), "JqError: cannot calculate \"a\" + 1")]
+    #[case::error("error(\"custom\")", json!(null), "JqError: \"custom\"")]
+    #[case::not_json("nan", json!(null), "JqError: NaN can't be represented in json")]
+    fn test_jq_filter_runtime_errors(
+        #[case] filter: &str,
+        #[case] input: Value,
+        #[case] expected: &str,
+    ) {
+        let err = JqFilter::from_str(filter).unwrap().run(&input).unwrap_err();
+
+        assert_eq!(err.to_string(), expected);
+    }
+}
@@ -1,23 +1,173 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
 use handlebars::Handlebars;
 use log::{debug, info};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use reqwest::{Request, Response};
+use once_cell::sync::Lazy;
+use percent_encoding::utf8_percent_encode;
+use reqwest::header::{
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    ACCEPT,
+    ACCEPT_ENCODING,
+    CONTENT_TYPE,
+    IF_MODIFIED_SINCE,
+    IF_NONE_MATCH,
+    RETRY_AFTER,
+    USER_AGENT,
+};
+use reqwest::{Certificate, Client, ClientBuilder, Identity, NoProxy, Proxy, Request, Response};
+use reqwest_cookie_store::CookieStoreMutex;
 use serde_json::{Map, Value};
-
-use crate::error::Result;
-pub use crate::models::{CollectionModel, EnvironmentModel, RequestModel};
-use crate::models::{GraphGLBody, HttpAuth, HttpBody};
-
+use time::OffsetDateTime;
+
+pub use crate::builder::RequestBuilder;
+pub use crate::conditional::Validators;
+pub use crate::curl::to_curl_command;
+pub use crate::dataset::{load_dataset, Dataset};
+pub use crate::dependencies::execution_order;
+pub use crate::diff::{Change, Difference};
+use crate::error::{ApiClientError, Result};
+use crate::grpc::GrpcCall;
+pub use crate::grpc::GrpcResponse;
+pub use crate::hexdump::hexdump;
+use crate::hmac_auth::HmacCredentials;
+use crate::interpolation::interpolate_env;
+pub use crate::jq::JqFilter;
+use crate::lint::{referenced_paths, referenced_variables};
+pub use crate::load::LoadReport;
+pub use crate::markup::{format_markup, highlight_markup, Markup};
+use crate::masking::{is_sensitive_name, Masker};
+pub use crate::models::{
+    CollectionModel,
+    EnvironmentModel,
+    ExampleModel,
+    ExpectedStatus,
+    FolderModel,
+    FollowRedirects,
+    GraphGLBody,
+    GrpcRequestModel,
+    HmacAlgorithm,
+    HmacEncoding,
+    HttpAuth,
+    HttpAwsSigV4Auth,
+    HttpBasicAuth,
+    HttpBearerToken,
+    HttpBinaryBody,
+    HttpBody,
+    HttpFormBody,
+    HttpGraphQLBody,
+    HttpHmacAuth,
+    HttpJsonBody,
+    HttpMethod,
+    HttpNdjsonBody,
+    HttpNtlmAuth,
+    HttpParamsModel,
+    HttpRequestModel,
+    HttpTextBody,
+    IpVersion,
+    KeyValueList,
+    KeyValuePair,
+    Pagination,
+    PaginationStrategy,
+    ProxyConfig,
+    RequestMetaModel,
+    RequestModel,
+    RequestType,
+    RequestVarsModel,
+    ResponseSchemaModel,
+    RetryBackoff,
+    RetryPolicy,
+    SigningConfig,
+    StatusMatcher,
+    StreamMode,
+    TlsConfig,
+    TokenRefresh,
+    FOLDER_FILE_NAME,
+};
+#[cfg(feature = "ntlm")]
+use crate::ntlm::{NtlmCredentials, SharedNtlmCredentials};
+pub use crate::pagination::{NextPage, Paginator};
+pub use crate::providers::{EnvProvider, KeyringProvider, VariableProvider};
+pub use crate::rate_limit::RateLimiter;
+pub use crate::redirect::Redirect;
+pub use crate::replay::CachedResponse;
+pub use crate::request_name::{RequestName, REQUEST_NAME_SEPARATOR};
+pub use crate::response::{ApiResponse, SavedResponse};
+pub use crate::schema::{JsonSchema, SchemaViolation};
+use crate::sigv4::{AwsCredentials, URI_ENCODE_SET};
+pub use crate::sse::{SseEvent, SseStream};
+pub use crate::tabular::{Tabular, TabularFormat};
+pub use crate::tags::TagExpression;
+pub use crate::timing::Timings;
+use crate::timing::{HostOverride, RequestState, TimedConnectLayer, TimedResolver};
+pub use crate::trace::TraceParent;
+pub use crate::wire::WIRE_LOG_TARGET;
+
+mod builder;
+mod conditional;
+mod curl;
+mod dataset;
+mod decompression;
+mod dependencies;
+mod diff;
 pub mod error;
+mod faker;
+mod grpc;
+mod helpers;
+mod hexdump;
+mod hmac_auth;
+pub mod import;
+mod interpolation;
+mod jq;
+pub mod lint;
+mod load;
+mod markup;
+pub mod masking;
+pub mod mock;
 mod models;
+#[cfg(feature = "ntlm")]
+mod ntlm;
+mod onepassword;
+mod pagination;
+mod providers;
+mod rate_limit;
+pub mod record;
+mod redirect;
+mod replay;
+pub mod report;
+mod request_name;
+mod response;
+mod schema;
+pub mod secrets;
+mod signing;
+mod sigv4;
+mod sse;
+mod tabular;
+mod tags;
+mod timing;
+mod trace;
+mod vault;
+mod wire;
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+static TRACEPARENT: &str = "traceparent";
+static TRACESTATE: &str = "tracestate";
+/// Variable containing the ID of the run, when one is set.
+pub(crate) const REQUEST_ID_VARIABLE: &str = "requestId";
 
 #[derive(Debug)]
 pub struct ApiClientRequest {
@@ -25,7 +175,97 @@ pub struct ApiClientRequest {
     request: RequestModel,
     global_variables: Option<HashMap<String, String>>,
     override_variables: Option<HashMap<String, String>>,
+    runtime_variables: Option<HashMap<String, String>>,
+    request_id: Option<String>,
+    request_id_header: Option<String>,
+    trace_parent: Option<TraceParent>,
+    trace_state: Option<String>,
+    page: Option<NextPage>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    validators: Option<Validators>,
+    providers: Providers,
     environment: Option<EnvironmentModel>,
+    folders: Vec<FolderModel>,
+    collection_dir: Option<PathBuf>,
+    request_file: Option<PathBuf>,
+    cookie_store: Option<Arc<CookieStoreMutex>>,
+    timeout: Option<Duration>,
+    default_timeout: Duration,
+    retries: Option<u32>,
+    proxy: Option<ProxyConfig>,
+    sse: bool,
+    to_file: bool,
+    decompress: bool,
+    follow_redirects: Option<FollowRedirects>,
+    ip_version: Option<IpVersion>,
+    client: Option<ApiClient>,
+    client_pool: Option<ClientPool>,
+    state: RequestState,
+    #[cfg(feature = "ntlm")]
+    ntlm: SharedNtlmCredentials,
+}
+
+/// Client only used to build the requests, which are sent by the client of each request.
+static REQUEST_BUILDER_CLIENT: Lazy<Client> = Lazy::new(Client::new);
+
+/// An http client that can be shared between requests, so the connections are reused. The
+/// timings and redirects of the requests are recorded separately, even when they are concurrent.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    client: Client,
+}
+
+/// Clients shared by the requests of a run, e.g. of a collection, which are built as needed. The
+/// cookie store, proxy and tls configuration of the requests of a run are the same, so there is a
+/// client for each redirect and local address configuration.
+#[derive(Debug, Clone, Default)]
+pub struct ClientPool(Arc<Mutex<Vec<(ClientKey, ApiClient)>>>);
+
+type ClientKey = (Option<FollowRedirects>, Option<IpAddr>);
+
+/// Configuration of the connections of a client.
+#[derive(Default)]
+struct ClientOptions {
+    cookie_store: Option<Arc<CookieStoreMutex>>,
+    proxy: Option<ProxyConfig>,
+    tls: Option<TlsConfig>,
+    follow_redirects: Option<FollowRedirects>,
+    local_address: Option<IpAddr>,
+    ip_version: Option<IpVersion>,
+}
+
+impl ApiClient {
+    /// A client without cookie store, proxy or custom tls configuration.
+    pub fn new() -> Result<Self> {
+        Self::build(ClientOptions::default())
+    }
+
+    fn build(options: ClientOptions) -> Result<Self> {
+        let resolver = TimedResolver {
+            ip_version: options.ip_version,
+        };
+        let mut client_builder = reqwest::Client::builder()
+            .tls_info(true)
+            .redirect(redirect::policy(options.follow_redirects))
+            .dns_resolver(Arc::new(resolver))
+            .connector_layer(TimedConnectLayer)
+            .local_address(options.local_address);
+        if let Some(store) = options.cookie_store {
+            client_builder = client_builder.cookie_provider(store);
+        }
+
+        if let Some(p) = options.proxy {
+            client_builder = client_builder.proxy(build_proxy(p)?);
+        }
+
+        if let Some(t) = options.tls {
+            client_builder = configure_tls(client_builder, t)?;
+        }
+
+        Ok(Self {
+            client: client_builder.build()?,
+        })
+    }
 }
 
 impl ApiClientRequest {
@@ -35,7 +275,34 @@ impl ApiClientRequest {
             request,
             global_variables: None,
             override_variables: None,
+            runtime_variables: None,
+            request_id: None,
+            request_id_header: None,
+            trace_parent: None,
+            trace_state: None,
+            page: None,
+            rate_limiter: None,
+            validators: None,
+            providers: Providers::default(),
             environment: None,
+            folders: Vec::new(),
+            collection_dir: None,
+            request_file: None,
+            cookie_store: None,
+            timeout: None,
+            default_timeout: DEFAULT_TIMEOUT,
+            retries: None,
+            proxy: None,
+            sse: false,
+            to_file: false,
+            decompress: true,
+            follow_redirects: None,
+            ip_version: None,
+            client: None,
+            client_pool: None,
+            state: RequestState::default(),
+            #[cfg(feature = "ntlm")]
+            ntlm: SharedNtlmCredentials::default(),
         }
     }
 
@@ -49,122 +316,755 @@ impl ApiClientRequest {
         self
     }
 
+    /// Add variables set while working with the collection or captured from other requests,
+    /// taking precedence over the ones of the files but not over the override variables.
+    pub fn with_runtime_variables(mut self, vars: HashMap<String, String>) -> Self {
+        self.runtime_variables.get_or_insert_default().extend(vars);
+        self
+    }
+
+    /// Add a provider for the variables referenced by the templates that no other source defines.
+    /// Providers are asked in the order they are added, the first one defining a variable wins.
+    pub fn with_variable_provider(mut self, provider: Arc<dyn VariableProvider>) -> Self {
+        self.providers.0.push(provider);
+        self
+    }
+
     pub fn with_environment(mut self, env: EnvironmentModel) -> Self {
         self.environment = Some(env);
         self
     }
 
-    fn prepare(self) -> Result<Request> {
-        let hb = {
-            let mut hb = handlebars::Handlebars::new();
-            hb.set_strict_mode(true);
-            hb
+    /// Add the configuration of a folder containing the request. Folders are added from the
+    /// outermost to the innermost, each one taking precedence over the previous ones.
+    pub fn with_folder(mut self, folder: FolderModel) -> Self {
+        self.folders.push(folder);
+        self
+    }
+
+    /// Directory of the collection, against which the relative paths of the tls configuration of
+    /// the collection and its environments are resolved.
+    pub fn with_collection_dir(mut self, path: PathBuf) -> Self {
+        self.collection_dir = Some(path);
+        self
+    }
+
+    /// File defining the request, named in the errors of its templates.
+    pub fn with_request_file(mut self, path: PathBuf) -> Self {
+        self.request_file = Some(path);
+        self
+    }
+
+    /// Use the given cookie store to send and record cookies.
+    pub fn with_cookie_store(mut self, store: Arc<CookieStoreMutex>) -> Self {
+        self.cookie_store = Some(store);
+        self
+    }
+
+    /// Override the timeout defined in the request or the collection.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout used when none is defined in the request or the collection, 60 seconds by default.
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Override the number of times a failed request is retried.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Override the proxy defined in the environment or the collection.
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Override whether redirects are followed, as defined in the request or the collection.
+    pub fn with_follow_redirects(mut self, follow: bool) -> Self {
+        self.follow_redirects = Some(FollowRedirects::Enabled(follow));
+        self
+    }
+
+    /// Only connect to the addresses of the given protocol family.
+    pub fn with_ip_version(mut self, version: IpVersion) -> Self {
+        self.ip_version = Some(version);
+        self
+    }
+
+    /// Whether the gzip, deflate, brotli and zstd response bodies are decoded, which is the
+    /// default. The compressed bodies are still requested when disabled, to inspect them.
+    pub fn with_decompression(mut self, decompress: bool) -> Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Read the response as server-sent events, regardless of the stream mode of the request.
+    pub fn with_sse_stream(mut self) -> Self {
+        self.sse = true;
+        self
+    }
+
+    /// Whether the response should be read as server-sent events with `execute_sse`.
+    pub fn is_sse_stream(&self) -> bool {
+        self.sse || matches!(self.request.http.stream, Some(StreamMode::Sse))
+    }
+
+    /// File in which the response body should be saved, as defined by the request.
+    pub fn save_path(&self) -> Option<&Path> {
+        self.request.http.save.as_deref()
+    }
+
+    /// Schema the response body should match, as defined by the request. A schema file is read
+    /// relative to the directory of the request file.
+    pub fn response_schema(&self) -> Result<Option<JsonSchema>> {
+        let schema = match &self.request.http.response_schema {
+            None => return Ok(None),
+            Some(ResponseSchemaModel::Inline(s)) => s.clone(),
+            Some(ResponseSchemaModel::Path(p)) => {
+                let path = match self.request_file.as_deref().and_then(Path::parent) {
+                    Some(dir) => dir.join(p),
+                    None => p.clone(),
+                };
+                let data = fs::read_to_string(&path)
+                    .map_err(|e| ApiClientError::from_io_error_with_path(e, &path))?;
+
+                serde_yaml::from_str(&data)
+                    .map_err(|e| ApiClientError::from_serde_yaml_error_with_path(e, &path))?
+            }
+        };
+
+        JsonSchema::new(schema).map(Some)
+    }
+
+    /// Request refreshing the bearer token of the request, from its auth or the one it inherits.
+    pub fn token_refresh(&self) -> Option<&TokenRefresh> {
+        let auth = std::iter::once(self.request.http.auth.as_ref())
+            .chain(self.folders.iter().rev().map(|f| f.auth.as_ref()))
+            .chain([
+                self.environment.as_ref().and_then(|e| e.auth.as_ref()),
+                self.collection.auth.as_ref(),
+            ])
+            .flatten()
+            .find(|a| !matches!(a, HttpAuth::Inherit))?;
+
+        match auth {
+            HttpAuth::Bearer(b) => b.refresh.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Latency above which the request is considered failed, as defined by the request or its
+    /// collection.
+    pub fn max_latency(&self) -> Option<Duration> {
+        let max_latency = match &self.request.grpc {
+            Some(g) => g.max_latency,
+            None => self.request.http.max_latency,
+        };
+
+        max_latency.or(self.collection.max_latency)
+    }
+
+    /// Status the response must have for the request to succeed, if the request defines one.
+    pub fn expected_status(&self) -> Option<&ExpectedStatus> {
+        self.request.http.expected_status.as_ref()
+    }
+
+    /// How the following pages are requested, if the response is a paginated list.
+    pub fn pagination(&self) -> Option<&Pagination> {
+        self.request.http.pagination.as_ref()
+    }
+
+    /// Variables to capture from the response, with the json-path of their value in the body.
+    pub fn captures(&self) -> Vec<(String, String)> {
+        self.request.captures()
+    }
+
+    /// Example of the request with the given name, if any.
+    pub fn example(&self, name: &str) -> Option<ExampleModel> {
+        self.request
+            .examples
+            .iter()
+            .find(|e| e.name == name)
+            .cloned()
+    }
+
+    /// Whether the request is a gRPC call, to be sent with `execute_grpc`.
+    pub fn is_grpc(&self) -> bool {
+        self.request.grpc.is_some()
+    }
+
+    /// Send the request with a client shared with other requests, to reuse its connections. The
+    /// cookie store, proxy, tls, redirect and local address configuration of the request are
+    /// ignored in favor of the ones of the client.
+    pub fn with_client(mut self, client: ApiClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Send the request with a client of the pool, built with the configuration of the request if
+    /// there is none for its redirect and local address configuration yet.
+    pub fn with_client_pool(mut self, pool: ClientPool) -> Self {
+        self.client_pool = Some(pool);
+        self
+    }
+
+    /// Build a client with the cookie store, proxy, tls, redirect and local address configuration
+    /// of the request. It can be shared with other requests of the same collection with
+    /// `with_client`.
+    pub fn build_client(&mut self) -> Result<ApiClient> {
+        let (follow_redirects, local_address) = self.client_key();
+        let options = ClientOptions {
+            cookie_store: self.cookie_store.clone(),
+            proxy: self.take_proxy(),
+            tls: self.take_tls_config(),
+            follow_redirects,
+            local_address,
+            ip_version: self.ip_version,
+        };
+
+        ApiClient::build(options)
+    }
+
+    fn client_key(&self) -> ClientKey {
+        let follow_redirects = self
+            .follow_redirects
+            .or(self.request.http.follow_redirects)
+            .or(self.collection.follow_redirects);
+        let local_address = self
+            .request
+            .http
+            .local_address
+            .or(self.collection.local_address);
+
+        (follow_redirects, local_address)
+    }
+
+    fn take_client(&mut self) -> Result<ApiClient> {
+        if let Some(c) = self.client.take() {
+            return Ok(c);
+        }
+
+        let Some(pool) = self.client_pool.take() else {
+            return self.build_client();
         };
 
-        let global_vars = self.global_variables.unwrap_or_default();
-        let env = self.environment.unwrap_or_default();
-        let override_vars = self.override_variables.unwrap_or_default();
+        let key = self.client_key();
+        let mut clients = pool.0.lock().unwrap();
+        if let Some((_, c)) = clients.iter().find(|(k, _)| *k == key) {
+            return Ok(c.clone());
+        }
+
+        let client = self.build_client()?;
+        clients.push((key, client.clone()));
+
+        Ok(client)
+    }
+
+    fn take_proxy(&mut self) -> Option<ProxyConfig> {
+        self.proxy
+            .take()
+            .or(self.environment.as_mut().and_then(|e| e.proxy.take()))
+            .or(self.collection.proxy.take())
+    }
+
+    fn take_tls_config(&mut self) -> Option<TlsConfig> {
+        let mut tls = self
+            .environment
+            .as_mut()
+            .and_then(|e| e.tls.take())
+            .or(self.collection.tls.take())?;
+
+        if let Some(dir) = &self.collection_dir {
+            for path in [&mut tls.ca_cert, &mut tls.client_cert, &mut tls.client_key]
+                .into_iter()
+                .flatten()
+            {
+                *path = dir.join(&path);
+            }
+        }
+
+        Some(tls)
+    }
+
+    fn take_retry_policy(&mut self) -> Option<RetryPolicy> {
+        let mut policy = self
+            .request
+            .http
+            .retry
+            .take()
+            .or(self.collection.retry.take());
+
+        if let Some(r) = self.retries {
+            policy.get_or_insert_with(RetryPolicy::default).max_attempts = r + 1;
+        }
+
+        policy
+    }
+
+    /// Set the ID correlating the requests of a run, available as `{{requestId}}`.
+    pub fn with_request_id(mut self, id: String) -> Self {
+        self.request_id = Some(id);
+        self
+    }
+
+    /// Send the request ID in a header, instead of the one set by the collection.
+    pub fn with_request_id_header(mut self, name: String) -> Self {
+        self.request_id_header = Some(name);
+        self
+    }
+
+    /// The name of the header in which the request ID is sent, if there is one.
+    pub fn request_id_header(&self) -> Option<&str> {
+        self.request_id.as_ref()?;
+        self.request_id_header
+            .as_deref()
+            .or(self.collection.request_id_header.as_deref())
+    }
+
+    /// Propagate a trace to the server in the `traceparent` header, along with the vendor specific
+    /// `tracestate` if there is one.
+    pub fn with_trace_context(mut self, parent: TraceParent, state: Option<String>) -> Self {
+        self.trace_parent = Some(parent);
+        self.trace_state = state;
+        self
+    }
+
+    /// Wait for a token of the limiter before sending the request, or each of the requests of a
+    /// load test.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Make the request conditional, asking for the response only if it changed since the one the
+    /// validators are from. Conditional headers set by the request are kept.
+    pub fn with_validators(mut self, validators: Validators) -> Self {
+        self.validators = Some(validators);
+        self
+    }
+
+    /// Request another page of a paginated list.
+    pub fn with_page(mut self, page: NextPage) -> Self {
+        self.page = Some(page);
+        self
+    }
 
+    /// Merge the variables of all the sources, in increasing order of precedence. The secret
+    /// values, marked as such or looking sensitive from their name, are collected in a [`Masker`].
+    ///
+    /// The variables defined in the collection, the environment, the folders or the request can
+    /// reference other variables, e.g. `https://{{host}}/v2`, and are rendered once merged.
+    fn resolve_variables(&self) -> Result<(HashMap<String, String>, Masker)> {
         let mut variables = HashMap::new();
-        variables.extend(
-            global_vars
-                .iter()
-                .map(|(k, v)| (k.as_str(), v.as_str()))
-                .collect::<HashMap<&str, &str>>(),
-        );
-        variables.extend(self.collection.vars.as_map());
-        variables.extend(env.vars.as_map());
-        variables.extend(self.request.vars.pre_request.as_map());
-        variables.extend(
-            override_vars
-                .iter()
-                .map(|(k, v)| (k.as_str(), v.as_str()))
-                .collect::<HashMap<&str, &str>>(),
+        let mut secrets = Vec::new();
+        // Variables defined with a template, and whether they are secret
+        let mut templates = HashMap::new();
+
+        let mut add = |key: &str, value: String, secret: bool, defined: bool| {
+            let secret = secret || is_sensitive_name(key);
+            if defined && value.contains("{{") {
+                templates.insert(key.to_string(), secret);
+            } else {
+                templates.remove(key);
+                if secret {
+                    secrets.push(value.clone());
+                }
+            }
+            variables.insert(key.to_string(), value);
+        };
+
+        if let Some(id) = &self.request_id {
+            add(REQUEST_ID_VARIABLE, id.clone(), false, false);
+        }
+        if let Some(vars) = &self.global_variables {
+            for (k, v) in vars {
+                add(k, v.clone(), false, false);
+            }
+        }
+        for i in self.collection.vars.items() {
+            add(&i.key, i.value.clone(), i.is_secret(), true);
+        }
+        if let Some(env) = &self.environment {
+            for i in env.vars.items() {
+                let value = interpolate_env(&i.value)?;
+                let resolved = secrets::resolve_value(&value)?;
+                // Values read from a secret store are always secret, and never templates.
+                let from_keyring = matches!(resolved, Cow::Owned(_));
+                add(
+                    &i.key,
+                    resolved.into_owned(),
+                    i.is_secret() || from_keyring,
+                    !from_keyring,
+                );
+            }
+        }
+        for folder in &self.folders {
+            for i in folder.vars.items() {
+                add(&i.key, i.value.clone(), i.is_secret(), true);
+            }
+        }
+        for i in self.request.vars.pre_request.items() {
+            add(&i.key, i.value.clone(), i.is_secret(), true);
+        }
+        if let Some(vars) = &self.runtime_variables {
+            for (k, v) in vars {
+                add(k, v.clone(), false, false);
+            }
+        }
+        if let Some(vars) = &self.override_variables {
+            for (k, v) in vars {
+                add(k, v.clone(), false, false);
+            }
+        }
+
+        if !templates.is_empty() {
+            let rendered = Variables::new(variables, &self.providers);
+            {
+                let r = Renderer::new(&rendered, None);
+                let mut pending: HashSet<String> = templates.keys().cloned().collect();
+                let mut names: Vec<&String> = templates.keys().collect();
+                names.sort();
+                for name in names {
+                    r.render_variable(name, &mut pending, &mut Vec::new())?;
+                }
+            }
+
+            secrets.extend(rendered.secrets.take());
+            variables = rendered.values.into_inner();
+            for (name, secret) in templates {
+                if secret {
+                    secrets.push(variables[&name].clone());
+                }
+            }
+        }
+
+        let masker = Masker::new(secrets);
+        debug!(
+            "Request variables: {:#?}",
+            masker.mask_variables(&variables)
         );
 
-        debug!("Request variables: {:#?}", variables);
+        Ok((variables, masker))
+    }
+
+    /// Render the request with all its variables, without sending it.
+    pub fn prepare(self) -> Result<Request> {
+        Ok(self.prepare_masked()?.0)
+    }
+
+    /// Render the request like [`prepare`](Self::prepare), along with a [`Masker`] hiding the
+    /// secrets it contains. The values of the headers containing a secret are marked as
+    /// sensitive.
+    pub fn prepare_masked(self) -> Result<(Request, Masker)> {
+        let sse = self.is_sse_stream();
+        let streamed = sse || self.to_file;
+
+        let (variables, mut masker) = self.resolve_variables()?;
+        let variables = Variables::new(variables, &self.providers);
+        // The templates of the collection, environment and folders are not in the request file.
+        let shared = Renderer::new(&variables, None);
+        let r = Renderer::new(&variables, self.request_file.as_deref());
+
+        let url = r.render("url", &self.request.http.url)?;
+        let path_params = self
+            .request
+            .http
+            .params
+            .get_path_params()
+            .into_iter()
+            .map(|(k, v)| {
+                let field = format!("params.path.{}", k);
+                Ok((r.render(&field, k)?, r.render(&field, v)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let url = substitute_path_params(&url, &path_params);
+
+        let method = reqwest::Method::from(&self.request.http.method);
+        let mut url =
+            reqwest::Url::parse(&url).map_err(|e| ApiClientError::new_invalid_url(url, e))?;
+
+        let host = match (
+            &self.request.http.host,
+            self.environment.as_ref().and_then(|e| e.host.as_ref()),
+        ) {
+            (Some(h), _) => Some(r.render("host", h)?),
+            (None, Some(h)) => Some(shared.render("environment.host", h)?),
+            (None, None) => None,
+        };
+        *self.state.host_override.lock().unwrap() =
+            host.map(|h| override_host(&mut url, h)).transpose()?;
+
+        let encode_query = self.collection.encode_query.unwrap_or(true);
+        for p in self.request.http.params.get_query_params() {
+            let field = format!("params.query.{}", p.key);
+            if !r.is_enabled(&field, p)? {
+                continue;
+            }
+            let key = r.render(&field, &p.key)?;
+            let value = r.render(&field, &p.value)?;
+            if p.is_secret() {
+                masker.extend([value.clone()]);
+            }
 
-        let url = hb.render_template(&self.request.http.url, &variables)?;
+            if p.encode.unwrap_or(encode_query) {
+                url.query_pairs_mut().append_pair(&key, &value);
+            } else {
+                append_raw_query_param(&mut url, &key, &value);
+            }
+        }
 
-        let method =
-            reqwest::Method::from_str(self.request.http.method.as_str()).expect("invalid method");
-        let url = reqwest::Url::parse(&url).expect("invalid url");
+        match &self.page {
+            Some(NextPage::Url(u)) => url = u.clone(),
+            Some(NextPage::Param { name, value }) => replace_query_param(&mut url, name, value),
+            None => {}
+        }
 
         let headers = {
-            let mut h = HeaderMap::new();
+            let mut render_headers = |r: &Renderer, prefix: &str, list: &KeyValueList| {
+                let mut headers = HeaderMap::new();
+                for i in list.items() {
+                    let field = format!("{}headers.{}", prefix, i.key);
+                    if !r.is_enabled(&field, i)? {
+                        continue;
+                    }
+                    let key = r.render(&field, &i.key)?;
+                    let val = r.render(&field, &i.value)?;
+                    if i.is_secret() {
+                        masker.extend([val.clone()]);
+                    }
 
-            for i in self.collection.headers.items() {
-                let key = hb.render_template(&i.key, &variables)?;
-                let val = hb.render_template(&i.value, &variables)?;
+                    let (name, value) = parse_header(key, val)?;
+                    headers.append(name, value);
+                }
 
-                // TODO: Handle error
-                h.insert(
-                    HeaderName::from_str(&key).expect("invalid header name"),
-                    HeaderValue::from_str(&val).expect("invalid header value"),
-                );
+                Ok::<_, ApiClientError>(headers)
+            };
+
+            let disabled = self
+                .collection
+                .headers_disabled
+                .iter()
+                .chain(&self.request.http.headers_disabled)
+                .map(|n| {
+                    HeaderName::try_from(n)
+                        .map_err(|_| ApiClientError::new_invalid_header_name(n.clone()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut h = render_headers(&shared, "collection.", &self.collection.headers)?;
+
+            // Headers of the environment, folders and request replace the ones with the same name
+            // defined before them.
+            if let Some(env) = &self.environment {
+                h.extend(render_headers(&shared, "environment.", &env.headers)?);
+            }
+
+            for folder in &self.folders {
+                h.extend(render_headers(&shared, "folder.", &folder.headers)?);
+            }
+
+            for name in &disabled {
+                h.remove(name);
+            }
+
+            h.extend(render_headers(&r, "", &self.request.http.headers)?);
+
+            let user_agent = match (&self.request.http.user_agent, &self.collection.user_agent) {
+                (Some(ua), _) => Some(r.render("user_agent", ua)?),
+                (None, Some(ua)) => Some(shared.render("collection.user_agent", ua)?),
+                (None, None) => None,
+            };
+            match user_agent {
+                Some(ua) => {
+                    let (name, value) = parse_header(USER_AGENT.to_string(), ua)?;
+                    h.insert(name, value);
+                }
+                None if !h.contains_key(USER_AGENT) && !disabled.contains(&USER_AGENT) => {
+                    h.insert(USER_AGENT, HeaderValue::from_static(APP_USER_AGENT));
+                }
+                None => {}
+            }
+
+            if let (Some(name), Some(id)) = (self.request_id_header(), &self.request_id) {
+                let (name, value) = parse_header(name.to_string(), id.clone())?;
+                if !h.contains_key(&name) && !disabled.contains(&name) {
+                    h.insert(name, value);
+                }
+            }
+
+            if let Some(v) = &self.validators {
+                let conditions = [
+                    (IF_NONE_MATCH, &v.etag),
+                    (IF_MODIFIED_SINCE, &v.last_modified),
+                ];
+                for (name, value) in conditions {
+                    if let (Some(value), false) = (value, h.contains_key(&name)) {
+                        let (name, value) = parse_header(name.to_string(), value.clone())?;
+                        h.insert(name, value);
+                    }
+                }
             }
 
-            for i in self.request.http.headers.items() {
-                let key = hb.render_template(&i.key, &variables)?;
-                let val = hb.render_template(&i.value, &variables)?;
+            if let Some(parent) = &self.trace_parent {
+                let (name, value) = parse_header(TRACEPARENT.to_string(), parent.to_string())?;
+                h.insert(name, value);
 
-                // TODO: Handle error
+                if let Some(state) = &self.trace_state {
+                    let (name, value) = parse_header(TRACESTATE.to_string(), state.clone())?;
+                    h.insert(name, value);
+                }
+            }
+
+            if let Some(ct) = self
+                .request
+                .http
+                .body
+                .as_ref()
+                .and_then(|b| b.content_type())
+            {
+                let val = r.render("body.content_type", ct)?;
+
+                let (name, value) = parse_header(CONTENT_TYPE.to_string(), val)?;
+                h.insert(name, value);
+            }
+
+            if sse && !h.contains_key(ACCEPT) && !disabled.contains(&ACCEPT) {
+                h.insert(ACCEPT, HeaderValue::from_static("text/event-stream"));
+            }
+
+            // Streamed bodies are not decoded.
+            if !streamed && !h.contains_key(ACCEPT_ENCODING) && !disabled.contains(&ACCEPT_ENCODING)
+            {
                 h.insert(
-                    HeaderName::from_str(&key).expect("invalid header name"),
-                    HeaderValue::from_str(&val).expect("invalid header value"),
+                    ACCEPT_ENCODING,
+                    HeaderValue::from_static(decompression::SUPPORTED_ENCODINGS),
                 );
             }
 
             h
         };
 
-        let mut req = reqwest::Client::new()
-            .request(method, url)
-            .headers(headers)
-            .query(&self.request.http.params.get_query_params());
+        let has_content_type = headers.contains_key(CONTENT_TYPE);
+        let mut req = REQUEST_BUILDER_CLIENT.request(method, url).headers(headers);
 
-        if let Some(auth) = self.request.http.auth.or(self.collection.auth) {
+        let mut aws_credentials = None;
+        let mut hmac_credentials = None;
+        let auth_renderer = match self.request.http.auth {
+            None | Some(HttpAuth::Inherit) => &shared,
+            Some(_) => &r,
+        };
+        let inherited_auth = self.folders.into_iter().fold(
+            resolve_auth(self.environment.and_then(|e| e.auth), self.collection.auth),
+            |auth, folder| resolve_auth(folder.auth, auth),
+        );
+        if let Some(auth) = resolve_auth(self.request.http.auth, inherited_auth) {
             req = match auth {
-                HttpAuth::None => req,
+                HttpAuth::None | HttpAuth::Inherit => req,
                 HttpAuth::Basic(b) => {
-                    let username = hb.render_template(&b.username, &variables)?;
-                    let password = Some(hb.render_template(&b.password, &variables)?);
+                    let username = auth_renderer.render("auth.username", &b.username)?;
+                    let password = Some(auth_renderer.render("auth.password", &b.password)?);
 
                     req.basic_auth(username, password)
                 }
                 HttpAuth::Bearer(t) => {
-                    let token = hb.render_template(&t.token, &variables)?;
+                    let token = auth_renderer.render("auth.token", &t.token)?;
                     req.bearer_auth(token)
                 }
+                HttpAuth::AwsSigV4(a) => {
+                    // The signature covers the final request, it is computed once it is built.
+                    aws_credentials = Some(AwsCredentials {
+                        access_key: auth_renderer.render("auth.access_key", &a.access_key)?,
+                        secret_key: auth_renderer.render("auth.secret_key", &a.secret_key)?,
+                        region: auth_renderer.render("auth.region", &a.region)?,
+                        service: auth_renderer.render("auth.service", &a.service)?,
+                        session_token: a
+                            .session_token
+                            .map(|t| auth_renderer.render("auth.session_token", &t))
+                            .transpose()?,
+                    });
+                    req
+                }
+                HttpAuth::Hmac(h) => {
+                    // Like sigv4, the signature covers the final request.
+                    hmac_credentials = Some(HmacCredentials {
+                        secret: auth_renderer.render("auth.secret", &h.secret)?,
+                        algorithm: h.algorithm,
+                        string_to_sign: h
+                            .string_to_sign
+                            .map(|s| auth_renderer.render("auth.string_to_sign", &s))
+                            .transpose()?,
+                        header: h.header,
+                        prefix: h
+                            .prefix
+                            .map(|p| auth_renderer.render("auth.prefix", &p))
+                            .transpose()?,
+                        encoding: h.encoding,
+                    });
+                    req
+                }
+                #[cfg(feature = "ntlm")]
+                HttpAuth::Ntlm(a) => start_ntlm(req, a, auth_renderer, &self.ntlm)?,
+                #[cfg(not(feature = "ntlm"))]
+                HttpAuth::Ntlm(_) => {
+                    return Err(ApiClientError::new_unsupported_auth("NTLM", "ntlm"));
+                }
             }
         }
 
         if let Some(body) = self.request.http.body {
             req = match body {
                 HttpBody::Text(t) => {
-                    let text = hb.render_template(&t.text, &variables)?;
-                    req.header("Content-Type", "text/plain").body(text)
+                    let text = r.render("body.text", &t.text)?;
+                    if !has_content_type {
+                        req = req.header(CONTENT_TYPE, "text/plain");
+                    }
+
+                    req.body(text)
                 }
                 HttpBody::Json(j) => {
                     // TODO: Find a better way than re/deserializing.
                     let json_str = serde_json::to_string(&j.json)?;
-                    let json_str = hb.render_template(&json_str, &variables)?;
+                    let json_str = r.render("body.json", &json_str)?;
                     let json: Value = serde_json::from_str(&json_str)?;
 
                     req.json(&json)
                 }
+                HttpBody::Ndjson(n) => {
+                    let mut body = String::new();
+                    for (idx, doc) in n.ndjson.into_iter().enumerate() {
+                        let json_str = serde_json::to_string(&doc)?;
+                        let json_str = r.render(&format!("body.ndjson[{}]", idx), &json_str)?;
+                        let json: Value = serde_json::from_str(&json_str)?;
+
+                        body.push_str(&serde_json::to_string(&json)?);
+                        body.push('\n');
+                    }
+
+                    if !has_content_type {
+                        req = req.header(CONTENT_TYPE, "application/x-ndjson");
+                    }
+
+                    req.body(body)
+                }
                 HttpBody::GraphQL(g) => {
-                    let query = hb.render_template(&g.graphql.query, &variables)?;
+                    let query = r.render("body.graphql.query", &g.graphql.query)?;
 
                     let variables = {
                         let mut vars = HashMap::new();
 
                         for (k, v) in g.graphql.variables.into_iter() {
-                            let key = hb.render_template(&k, &variables)?;
+                            let field = format!("body.graphql.variables.{}", k);
+                            let key = r.render(&field, &k)?;
 
                             // let value = serde_json::to_string(v)?;
                             // let value = hb.render_template(&value, &variables)?;
-                            let value = apply_template(&hb, v, &variables)?;
+                            let value = apply_template(&r, &field, v)?;
 
                             vars.insert(key, value);
                         }
@@ -177,19 +1077,23 @@ impl ApiClientRequest {
                     req.json(&payload)
                 }
                 HttpBody::Binary(b) => {
-                    let body = hb.render_template(&b.binary, &variables)?;
+                    let body = r.render("body.binary", &b.binary)?;
+
+                    if !has_content_type {
+                        req = req.header(CONTENT_TYPE, "application/x-www-form-urlencoded");
+                    }
 
                     // TODO Manage Error
-                    req.header("Content-Type", "application/x-www-form-urlencoded")
-                        .body(BASE64_STANDARD.decode(body).expect("invalid base64"))
+                    req.body(BASE64_STANDARD.decode(body).expect("invalid base64"))
                 }
                 HttpBody::Form(f) => {
-                    let mut form = HashMap::new();
+                    let mut form = Vec::new();
                     for i in f.form.items() {
-                        form.insert(
-                            hb.render_template(&i.key, &variables)?,
-                            hb.render_template(&i.value, &variables)?,
-                        );
+                        let field = format!("body.form.{}", i.key);
+                        if !r.is_enabled(&field, i)? {
+                            continue;
+                        }
+                        form.push((r.render(&field, &i.key)?, r.render(&field, &i.value)?));
                     }
 
                     req.form(&form)
@@ -197,57 +1101,669 @@ impl ApiClientRequest {
             }
         }
 
-        req = req.timeout(Duration::from_secs(60));
+        let timeout = self
+            .timeout
+            .or(self.request.http.timeout)
+            .or(self.collection.timeout);
+        // The timeout covers the whole body, streams are only limited by an explicit timeout.
+        match timeout {
+            Some(t) => req = req.timeout(t),
+            None if !streamed => req = req.timeout(self.default_timeout),
+            None => {}
+        }
 
-        Ok(req.build()?)
-    }
+        let signing_command = match (self.request.http.signing, self.collection.signing) {
+            (Some(s), _) => Some(render_command(&r, &s.command)?),
+            (None, Some(s)) => Some(render_command(&shared, &s.command)?),
+            (None, None) => None,
+        };
 
-    pub async fn execute(self) -> Result<Response> {
-        let request = self.prepare()?;
+        masker.extend(variables.secrets.take());
 
-        info!("{} {}", request.method(), request.url());
+        let mut request = req.build()?;
+        if let Some(credentials) = aws_credentials {
+            sigv4::sign_request(&mut request, &credentials, OffsetDateTime::now_utc())?;
+        }
+        if let Some(credentials) = hmac_credentials {
+            hmac_auth::sign_request(&mut request, &credentials, OffsetDateTime::now_utc())?;
+        }
+        if let Some(command) = signing_command {
+            signing::sign_request(&mut request, &command)?;
+        }
 
-        let client = reqwest::Client::builder()
-            .user_agent(APP_USER_AGENT)
-            .build()?;
-        let resp = client.execute(request).await?;
+        for value in request.headers_mut().values_mut() {
+            if value.to_str().is_ok_and(|v| masker.contains_secret(v)) {
+                value.set_sensitive(true);
+            }
+        }
 
-        Ok(resp)
+        Ok((request, masker))
     }
-}
 
-fn apply_template(
-    hb: &Handlebars<'_>,
-    value: Value,
-    variables: &HashMap<&str, &str>,
-) -> Result<Value> {
-    let value = match value {
-        Value::Object(o) => {
-            let m = o
-                .into_iter()
-                .map(|(k, v)| {
-                    let rendered = apply_template(hb, v, variables)?;
-                    Ok((k, rendered))
-                })
-                .collect::<Result<Map<String, Value>>>()?;
+    pub async fn execute(mut self) -> Result<Response> {
+        let client = self.take_client()?.client;
+        *self.state.timings.lock().unwrap() = Timings::default();
+        self.state.redirects.lock().unwrap().clear();
+        let retry_policy = self.take_retry_policy();
+        let state = self.state.clone();
+        let timings = Arc::clone(&self.state.timings);
+        let rate_limiter = self.rate_limiter.clone();
+        #[cfg(feature = "ntlm")]
+        let ntlm = Arc::clone(&self.ntlm);
+        let (request, masker) = self.prepare_masked()?;
+
+        if let Some(limiter) = rate_limiter {
+            limiter.acquire().await;
+        }
 
-            Value::Object(m)
+        info!(
+            "{} {}",
+            request.method(),
+            masker.mask(request.url().as_str())
+        );
+        wire::log_request(&request, &masker);
+
+        #[cfg(feature = "ntlm")]
+        let ntlm_credentials = ntlm.lock().unwrap().take();
+        #[cfg(feature = "ntlm")]
+        if let Some(credentials) = ntlm_credentials {
+            let response = state
+                .scope(ntlm::execute(&client, request, &credentials, retry_policy))
+                .await?;
+            wire::log_response(&response, &timings.lock().unwrap());
+
+            return Ok(response);
         }
-        Value::Array(a) => {
-            let arr = a
-                .into_iter()
-                .map(|v| {
-                    let rendered = apply_template(hb, v, variables)?;
-                    Ok(rendered)
+
+        let response = match retry_policy {
+            Some(p) => state.scope(send_with_retries(&client, request, &p)).await?,
+            None => state.scope(client.execute(request)).await?,
+        };
+
+        wire::log_response(&response, &timings.lock().unwrap());
+
+        Ok(response)
+    }
+
+    /// Execute the request and read the whole response body.
+    pub async fn execute_collected(self) -> Result<ApiResponse> {
+        let timings = Arc::clone(&self.state.timings);
+        let redirects = Arc::clone(&self.state.redirects);
+        let decompress = self.decompress;
+
+        let start = Instant::now();
+        let res = self.execute().await?;
+        let latency = start.elapsed();
+
+        let timings = *timings.lock().unwrap();
+        let redirects = redirects.lock().unwrap().clone();
+        ApiResponse::collect(res, latency, timings, redirects, decompress).await
+    }
+
+    /// Execute the request `repeat` times with up to `concurrency` requests in flight, and report
+    /// on the latencies and errors. The request is rendered once, failed requests are not retried.
+    pub async fn execute_load(mut self, repeat: usize, concurrency: usize) -> Result<LoadReport> {
+        let client = self.take_client()?.client;
+        let rate_limiter = self.rate_limiter.clone();
+        let (request, masker) = self.prepare_masked()?;
+
+        info!(
+            "{} {} ({} times, {} concurrent)",
+            request.method(),
+            masker.mask(request.url().as_str()),
+            repeat,
+            concurrency
+        );
+
+        Ok(load::run(client, request, repeat, concurrency, rate_limiter).await)
+    }
+
+    /// Execute a unary gRPC call. Fails if the request is not a gRPC request.
+    pub async fn execute_grpc(mut self) -> Result<GrpcResponse> {
+        let Some(grpc) = self.request.grpc.take() else {
+            return Err(ApiClientError::new_not_grpc_request());
+        };
+
+        let (variables, _) = self.resolve_variables()?;
+        let variables = Variables::new(variables, &self.providers);
+        let r = Renderer::new(&variables, self.request_file.as_deref());
+
+        let metadata = grpc
+            .metadata
+            .items()
+            .map(|i| {
+                let field = format!("grpc.metadata.{}", i.key);
+                Ok((r.render(&field, &i.key)?, r.render(&field, &i.value)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let call = GrpcCall {
+            url: r.render("grpc.url", &grpc.url)?,
+            method: r.render("grpc.method", &grpc.method)?,
+            metadata,
+            message: apply_template(&r, "grpc.message", grpc.message)?,
+            timeout: self
+                .timeout
+                .or(grpc.timeout)
+                .or(self.collection.timeout)
+                .unwrap_or(self.default_timeout),
+        };
+
+        let pool = match grpc.proto {
+            Some(p) => {
+                let dir = self.collection_dir.as_deref().unwrap_or(Path::new(""));
+                let import_paths: Vec<_> = grpc.import_paths.iter().map(|i| dir.join(i)).collect();
+                Some(grpc::load_proto_file(&dir.join(p), &import_paths)?)
+            }
+            None => None,
+        };
+
+        grpc::execute(call, pool).await
+    }
+
+    /// Execute the request and write the response body to a file as it is received, without
+    /// buffering it in memory. `on_progress` is called after each chunk with the number of bytes
+    /// written and the expected total, if known.
+    pub async fn execute_to_file<F>(
+        mut self,
+        path: &Path,
+        mut on_progress: F,
+    ) -> Result<SavedResponse>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        self.to_file = true;
+
+        let start = Instant::now();
+        let mut res = self.execute().await?;
+        let latency = start.elapsed();
+
+        let total = res.content_length();
+        let mut writer = File::create(path)
+            .map(BufWriter::new)
+            .map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+
+        let mut size = 0;
+        while let Some(chunk) = res.chunk().await? {
+            writer
+                .write_all(&chunk)
+                .map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+            size += chunk.len() as u64;
+            on_progress(size, total);
+        }
+        writer
+            .flush()
+            .map_err(|e| ApiClientError::from_io_error_with_path(e, path))?;
+
+        Ok(SavedResponse::new(&res, latency, path, size))
+    }
+
+    /// Execute the request and read the response as a stream of server-sent events.
+    pub async fn execute_sse(self) -> Result<SseStream> {
+        let res = self.with_sse_stream().execute().await?;
+
+        Ok(SseStream::new(res))
+    }
+}
+
+async fn send_with_retries(
+    client: &Client,
+    request: Request,
+    policy: &RetryPolicy,
+) -> reqwest::Result<Response> {
+    let mut attempt = 1;
+    loop {
+        let req = request.try_clone().expect("request is not cloneable");
+        let result = client.execute(req).await;
+
+        let should_retry = match &result {
+            Ok(resp) => policy.on_status.contains(&resp.status().as_u16()),
+            Err(e) => policy.on_connection_error && (e.is_connect() || e.is_timeout()),
+        };
+
+        if !should_retry || attempt >= policy.max_attempts {
+            return result;
+        }
+
+        let retry_after = result
+            .as_ref()
+            .ok()
+            .and_then(|resp| retry_after(resp.headers(), SystemTime::now()));
+        let delay = match retry_after {
+            Some(d) if d > policy.max_retry_after => {
+                info!(
+                    "Attempt {}/{} failed, the server asked to retry in {:?}, retrying in {:?}",
+                    attempt, policy.max_attempts, d, policy.max_retry_after
+                );
+                policy.max_retry_after
+            }
+            Some(d) => {
+                info!(
+                    "Attempt {}/{} failed, retrying in {:?} as asked by the server",
+                    attempt, policy.max_attempts, d
+                );
+                d
+            }
+            None => {
+                let d = policy.delay_for_attempt(attempt);
+                info!(
+                    "Attempt {}/{} failed, retrying in {:?}",
+                    attempt, policy.max_attempts, d
+                );
+                d
+            }
+        };
+        tokio::time::sleep(delay).await;
+
+        attempt += 1;
+    }
+}
+
+/// The delay asked by the `Retry-After` header of a response, either a number of seconds or the date
+/// to retry at, e.g. `120` or `Wed, 21 Oct 2015 07:28:00 GMT`. A date in the past is no delay.
+fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = httpdate::parse_http_date(value).ok()?;
+            Some(date.duration_since(now).unwrap_or_default())
+        }
+    }
+}
+
+/// The auth of a request or folder, falling back to the one of its parent if it is not defined or
+/// set to `inherit`.
+fn resolve_auth(auth: Option<HttpAuth>, parent_auth: Option<HttpAuth>) -> Option<HttpAuth> {
+    match auth {
+        None | Some(HttpAuth::Inherit) => parent_auth,
+        auth => auth,
+    }
+}
+
+/// Replace the host name of the url, returning the address of the original one to connect to.
+fn override_host(url: &mut reqwest::Url, host: String) -> Result<HostOverride> {
+    let address = url
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    url.set_host(Some(&host))
+        .map_err(|e| ApiClientError::new_invalid_url(host.clone(), e))?;
+
+    Ok(HostOverride { host, address })
+}
+
+/// Start the handshake of an `ntlm` auth, completed when the request is executed.
+#[cfg(feature = "ntlm")]
+fn start_ntlm(
+    req: reqwest::RequestBuilder,
+    auth: HttpNtlmAuth,
+    r: &Renderer,
+    ntlm: &SharedNtlmCredentials,
+) -> Result<reqwest::RequestBuilder> {
+    let credentials = NtlmCredentials::new(
+        r.render("auth.username", &auth.username)?,
+        r.render("auth.password", &auth.password)?,
+        auth.domain
+            .map(|d| r.render("auth.domain", &d))
+            .transpose()?,
+    );
+    let header = ntlm::negotiate_header()?;
+    *ntlm.lock().unwrap() = Some(credentials);
+
+    Ok(req.header(reqwest::header::AUTHORIZATION, header))
+}
+
+fn render_command(r: &Renderer, command: &[String]) -> Result<Vec<String>> {
+    command
+        .iter()
+        .enumerate()
+        .map(|(idx, arg)| r.render(&format!("signing.command[{}]", idx), arg))
+        .collect()
+}
+
+/// Replace the `:name` segments and the `{name}` placeholders of the path of `url` by the
+/// percent-encoded value of the matching parameter. The query and fragment are left untouched.
+fn substitute_path_params(url: &str, params: &[(String, String)]) -> String {
+    if params.is_empty() {
+        return url.to_string();
+    }
+
+    let (path, rest) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+
+    let path = path
+        .split('/')
+        .map(|segment| {
+            if let Some((_, value)) = params
+                .iter()
+                .find(|(name, _)| segment.strip_prefix(':') == Some(name))
+            {
+                return utf8_percent_encode(value, URI_ENCODE_SET).to_string();
+            }
+
+            params
+                .iter()
+                .fold(segment.to_string(), |segment, (name, value)| {
+                    segment.replace(
+                        &format!("{{{}}}", name),
+                        &utf8_percent_encode(value, URI_ENCODE_SET).to_string(),
+                    )
+                })
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("{}{}", path, rest)
+}
+
+/// Append a query parameter to `url` as is. Only the characters that can't appear in a query are
+/// encoded, so values that are already percent-encoded are sent unchanged.
+fn append_raw_query_param(url: &mut reqwest::Url, key: &str, value: &str) {
+    let query = match url.query() {
+        Some(q) if !q.is_empty() => format!("{}&{}={}", q, key, value),
+        _ => format!("{}={}", key, value),
+    };
+
+    url.set_query(Some(&query));
+}
+
+/// Set the value of a query parameter, replacing all the values it had.
+fn replace_query_param(url: &mut reqwest::Url, name: &str, value: &str) {
+    let others: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(k, _)| k != name)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+
+    let mut pairs = url.query_pairs_mut();
+    pairs.clear().extend_pairs(others).append_pair(name, value);
+}
+
+/// Parse a rendered header, failing with the offending name or value.
+fn parse_header(name: String, value: String) -> Result<(HeaderName, HeaderValue)> {
+    let header_name = match HeaderName::from_str(&name) {
+        Ok(n) => n,
+        Err(_) => return Err(ApiClientError::new_invalid_header_name(name)),
+    };
+
+    match HeaderValue::from_str(&value) {
+        Ok(v) => Ok((header_name, v)),
+        Err(_) => Err(ApiClientError::new_invalid_header_value(name, value)),
+    }
+}
+
+fn new_template_engine() -> Handlebars<'static> {
+    let mut hb = Handlebars::new();
+    hb.set_strict_mode(true);
+    helpers::register_helpers(&mut hb);
+
+    hb
+}
+
+/// Variable providers of a request, which are not printed with it.
+#[derive(Clone, Default)]
+struct Providers(Vec<Arc<dyn VariableProvider>>);
+
+impl fmt::Debug for Providers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Providers({})", self.0.len())
+    }
+}
+
+/// Variables of a request, completed by its providers with the variables referenced by the
+/// templates as they are rendered.
+struct Variables<'a> {
+    values: RefCell<HashMap<String, String>>,
+    providers: &'a Providers,
+    /// Values of the secret providers, to hide
+    secrets: RefCell<Vec<String>>,
+}
+
+impl<'a> Variables<'a> {
+    fn new(values: HashMap<String, String>, providers: &'a Providers) -> Self {
+        Self {
+            values: RefCell::new(values),
+            providers,
+            secrets: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Ask the providers for the variables of a template that are not defined yet.
+    fn provide(&self, template: &str) {
+        if self.providers.0.is_empty() {
+            return;
+        }
+
+        for key in referenced_variables(template) {
+            if is_defined(&self.values.borrow(), &key) {
+                continue;
+            }
+
+            let provided = self
+                .providers
+                .0
+                .iter()
+                .find_map(|p| p.get(&key).map(|v| (v, p.is_secret())));
+            if let Some((value, secret)) = provided {
+                if secret {
+                    self.secrets.borrow_mut().push(value.clone());
+                }
+                self.values.borrow_mut().insert(key, value);
+            }
+        }
+    }
+}
+
+/// Renders the templates of a request with its variables, naming the field being rendered when it
+/// fails.
+struct Renderer<'a> {
+    hb: Handlebars<'static>,
+    variables: &'a Variables<'a>,
+    file: Option<&'a Path>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(variables: &'a Variables<'a>, file: Option<&'a Path>) -> Self {
+        Self {
+            hb: new_template_engine(),
+            variables,
+            file,
+        }
+    }
+
+    fn render(&self, field: &str, template: &str) -> Result<String> {
+        self.variables.provide(template);
+
+        self.hb
+            .render_template(template, &template_data(&self.variables.values.borrow()))
+            .map_err(|e| ApiClientError::new_template_error(field.to_string(), self.file, e))
+    }
+
+    /// Whether a header, parameter or form field is enabled, from its `enabled_if` template.
+    fn is_enabled(&self, field: &str, item: &KeyValuePair) -> Result<bool> {
+        match &item.enabled_if {
+            Some(condition) => Ok(is_truthy(
+                &self.render(&format!("{}.enabled_if", field), condition)?,
+            )),
+            None => Ok(true),
+        }
+    }
+
+    /// Render a variable defined with a template, after the `pending` variables it references.
+    /// `stack` holds the variables being rendered, to detect the ones referencing themselves.
+    fn render_variable(
+        &self,
+        name: &str,
+        pending: &mut HashSet<String>,
+        stack: &mut Vec<String>,
+    ) -> Result<()> {
+        if let Some(i) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[i..].to_vec();
+            cycle.push(name.to_string());
+            return Err(ApiClientError::new_variable_cycle(cycle));
+        }
+        if !pending.contains(name) {
+            return Ok(());
+        }
+
+        let template = self.variables.values.borrow()[name].clone();
+        stack.push(name.to_string());
+        for path in referenced_paths(&template) {
+            // Either the variable itself, a variable it contains or its namespace.
+            let mut references: Vec<String> = pending
+                .iter()
+                .filter(|p| {
+                    *p == &path || is_nested_in(p, &path) || is_nested_in(&path, p.as_str())
+                })
+                .cloned()
+                .collect();
+            references.sort();
+            for r in references {
+                self.render_variable(&r, pending, stack)?;
+            }
+        }
+        stack.pop();
+
+        let value = self.render(&format!("vars.{}", name), &template)?;
+        self.variables
+            .values
+            .borrow_mut()
+            .insert(name.to_string(), value);
+        pending.remove(name);
+
+        Ok(())
+    }
+}
+
+/// Whether a rendered condition holds: anything but an empty string, `false`, `0`, `no` or `off`.
+fn is_truthy(value: &str) -> bool {
+    let value = value.trim().to_lowercase();
+
+    !matches!(value.as_str(), "" | "false" | "0" | "no" | "off")
+}
+
+/// Whether a variable, or variables nested in it like `auth.token` in `auth`, are defined.
+fn is_defined(values: &HashMap<String, String>, key: &str) -> bool {
+    values.contains_key(key) || values.keys().any(|k| is_nested_in(k, key))
+}
+
+/// Whether a variable is nested in a namespace, like `auth.token` in `auth`.
+fn is_nested_in(name: &str, namespace: &str) -> bool {
+    name.strip_prefix(namespace)
+        .is_some_and(|r| r.starts_with('.'))
+}
+
+/// The variables as the data of the templates, the variables with a dotted name being nested in
+/// objects so `{{auth.token}}` renders the `auth.token` variable. A variable named like the
+/// namespace of other variables is replaced by them.
+fn template_data(values: &HashMap<String, String>) -> Value {
+    let mut keys: Vec<&String> = values.keys().collect();
+    // A namespace sorts before the variables it contains, which then replace it.
+    keys.sort();
+
+    let mut data = Map::new();
+    for key in keys {
+        let value = Value::String(values[key].clone());
+        let path: Vec<&str> = key.split('.').collect();
+        let (Some((name, parents)), false) = (path.split_last(), path.contains(&"")) else {
+            data.insert(key.clone(), value);
+            continue;
+        };
+
+        let mut object = &mut data;
+        for parent in parents {
+            let entry = object
+                .entry(parent.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            object = entry.as_object_mut().expect("namespace is an object");
+        }
+        object.insert(name.to_string(), value);
+    }
+
+    Value::Object(data)
+}
+
+fn build_proxy(config: ProxyConfig) -> Result<Proxy> {
+    let url = interpolate_env(&config.url)?;
+    debug!("Using proxy: {}", url);
+
+    let mut proxy = Proxy::all(url.as_ref())?;
+
+    if let Some(username) = config.username {
+        let password = config.password.unwrap_or_default();
+        proxy = proxy.basic_auth(&interpolate_env(&username)?, &interpolate_env(&password)?);
+    }
+
+    if !config.no_proxy.is_empty() {
+        proxy = proxy.no_proxy(NoProxy::from_string(&config.no_proxy.join(",")));
+    }
+
+    Ok(proxy)
+}
+
+fn configure_tls(mut builder: ClientBuilder, config: TlsConfig) -> Result<ClientBuilder> {
+    if let Some(path) = config.ca_cert {
+        let pem = read_tls_file(&path)?;
+        for cert in Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if let Some(cert_path) = config.client_cert {
+        let cert = read_tls_file(&cert_path)?;
+
+        let identity = match config.client_key {
+            Some(key_path) => Identity::from_pkcs8_pem(&cert, &read_tls_file(&key_path)?)?,
+            None => {
+                let password = config.password.unwrap_or_default();
+                Identity::from_pkcs12_der(&cert, &interpolate_env(&password)?)?
+            }
+        };
+
+        builder = builder.identity(identity);
+    }
+
+    if config.insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder)
+}
+
+fn read_tls_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| ApiClientError::from_io_error_with_path(e, path))
+}
+
+/// Render the strings of a json value, `field` being the path of the value in the request.
+fn apply_template(r: &Renderer, field: &str, value: Value) -> Result<Value> {
+    let value = match value {
+        Value::Object(o) => {
+            let m = o
+                .into_iter()
+                .map(|(k, v)| {
+                    let rendered = apply_template(r, &format!("{}.{}", field, k), v)?;
+                    Ok((k, rendered))
                 })
+                .collect::<Result<Map<String, Value>>>()?;
+
+            Value::Object(m)
+        }
+        Value::Array(a) => {
+            let arr = a
+                .into_iter()
+                .enumerate()
+                .map(|(idx, v)| apply_template(r, &format!("{}[{}]", field, idx), v))
                 .collect::<Result<Vec<Value>>>()?;
 
             Value::Array(arr)
         }
-        Value::String(s) => {
-            let s = hb.render_template(&s, &variables)?;
-            Value::String(s)
-        }
+        Value::String(s) => Value::String(r.render(field, &s)?),
         _ => value,
     };
 
@@ -257,35 +1773,79 @@ fn apply_template(
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::path::PathBuf;
     use std::str::FromStr;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant, SystemTime};
 
     use base64::prelude::BASE64_STANDARD;
     use base64::Engine;
     use once_cell::sync::Lazy;
+    use reqwest::header::{HeaderMap, HeaderValue};
     use reqwest::StatusCode;
+    use reqwest_cookie_store::CookieStoreMutex;
     use rstest::rstest;
-    use serde_json::{Map, Number, Value};
+    use serde_json::{json, Map, Number, Value};
+    use tonic::transport::server::TcpIncoming;
+    use tonic::Code;
     use wiremock::{http, matchers, Match, Mock, MockServer, Request, ResponseTemplate};
 
+    use super::{is_truthy, retry_after, substitute_path_params, template_data};
     use crate::models::{
+        ExpectedStatus,
+        FollowRedirects,
         GraphGLBody,
+        GrpcRequestModel,
+        HmacAlgorithm,
+        HmacEncoding,
         HttpAuth,
+        HttpAwsSigV4Auth,
         HttpBasicAuth,
         HttpBearerToken,
         HttpBinaryBody,
         HttpBody,
         HttpFormBody,
         HttpGraphQLBody,
+        HttpHmacAuth,
         HttpJsonBody,
         HttpMethod,
+        HttpNdjsonBody,
+        HttpNtlmAuth,
         HttpParamsModel,
         HttpRequestModel,
         HttpTextBody,
+        IpVersion,
         KeyValueList,
         KeyValuePair,
+        Pagination,
+        PaginationStrategy,
         RequestVarsModel,
+        ResponseSchemaModel,
+        RetryBackoff,
+        RetryPolicy,
+        SigningConfig,
+        StatusMatcher,
+        StreamMode,
+        TlsConfig,
+        TokenRefresh,
+    };
+    use crate::{
+        ApiClient,
+        ApiClientRequest,
+        ClientPool,
+        CollectionModel,
+        EnvironmentModel,
+        FolderModel,
+        Markup,
+        Paginator,
+        ProxyConfig,
+        RateLimiter,
+        RequestModel,
+        SseEvent,
+        TraceParent,
+        Validators,
+        VariableProvider,
     };
-    use crate::{ApiClientRequest, CollectionModel, RequestModel};
 
     static TRACING: Lazy<()> = Lazy::new(|| {
         env_logger::init();
@@ -329,6 +1889,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn request_meta_round_trips() {
+        let yaml = "meta:\n  name: Get user\n  type: http\n  seq: 2\n  description: Fetch the current user\n  tags:\n  - users\n";
+
+        let request: RequestModel =
+            serde_yaml::from_str(&format!("{}http:\n  method: GET\n  url: x\n", yaml))
+                .expect("invalid request");
+
+        assert_eq!(request.meta().name(), Some("Get user"));
+        assert_eq!(request.meta().seq(), Some(2));
+        assert_eq!(request.meta().description(), Some("Fetch the current user"));
+        assert_eq!(request.meta().tags(), ["users"]);
+
+        let serialized = serde_yaml::to_string(&request).unwrap();
+        assert!(serialized.starts_with(yaml), "{}", serialized);
+
+        let serialized = serde_yaml::to_string(&RequestModel::default()).unwrap();
+        assert!(!serialized.contains("meta"), "{}", serialized);
+    }
+
     #[tokio::test]
     async fn api_client_performs_basic_request() {
         let test_server = spawn_mock_server().await;
@@ -344,7 +1924,7 @@ mod tests {
                 url: test_server.base_url,
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -353,63 +1933,139 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn api_client_uses_specified_path() {
-        let path = "/foo/bar/baz";
-
+    async fn api_client_collects_response() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::path(path))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::CREATED)
+                    .insert_header("X-Test-Header", "some-test-value")
+                    .set_body_json(serde_json::json!({"id": 123})),
+            )
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
-                url: format!("{}{}", test_server.base_url, path),
+                method: HttpMethod::Post,
+                url: test_server.base_url,
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
 
-        api_request.execute().await.expect("request failed");
+        let res = api_request
+            .execute_collected()
+            .await
+            .expect("request failed");
+
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers()["X-Test-Header"], "some-test-value");
+        assert_eq!(res.text(), Some(r#"{"id":123}"#));
+        assert_eq!(res.json(), Some(&serde_json::json!({"id": 123})));
+    }
+
+    #[tokio::test]
+    async fn api_client_diffs_response_with_saved_example() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .insert_header("X-Test-Header", "some-test-value")
+                    .set_body_json(json!({"id": 123, "name": "test"})),
+            )
+            .up_to_n_times(1)
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .insert_header("X-Test-Header", "other-value")
+                    .set_body_json(json!({"id": 123, "tags": []})),
+            )
+            .mount(&test_server.mock)
+            .await;
+
+        let request = || RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url.clone(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request())
+            .execute_collected()
+            .await
+            .unwrap();
+        let example = crate::ExampleModel::from_response("ok".to_string(), &res);
+
+        assert!(example.headers.items().all(|h| h.key != "date"));
+        assert!(example.diff(&res).is_empty());
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request())
+            .execute_collected()
+            .await
+            .unwrap();
+        let paths: Vec<String> = example.diff(&res).into_iter().map(|d| d.path).collect();
+
+        assert_eq!(paths, vec!["headers.x-test-header", "$.name", "$.tags"]);
     }
 
     #[rstest]
-    #[case(HttpMethod::Get)]
-    #[case(HttpMethod::Post)]
-    #[case(HttpMethod::Delete)]
+    #[case::ndjson("application/x-ndjson", Some(vec![json!({"id": 1}), json!({"id": 2})]))]
+    #[case::jsonl("application/jsonl; charset=utf-8", Some(vec![json!({"id": 1}), json!({"id": 2})]))]
+    #[case::other("text/plain", None)]
     #[tokio::test]
-    async fn api_client_uses_specified_method(#[case] method: HttpMethod) {
+    async fn api_client_parses_json_lines(
+        #[case] content_type: &str,
+        #[case] expected: Option<Vec<Value>>,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::method(method.as_str()))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_raw("{\"id\":1}\n\n{\"id\":2}\n", content_type),
+            )
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                method,
                 url: test_server.base_url,
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute_collected()
+            .await
+            .expect("request failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(res.json_lines(), expected);
     }
 
+    #[rstest]
+    #[case::text("text/plain", b"hello".to_vec(), false)]
+    #[case::invalid_utf8("text/plain", vec![0xff, 0xfe, 0x00], true)]
+    #[case::nul_byte("application/octet-stream", b"a\0b".to_vec(), true)]
+    #[case::image("image/png; charset=binary", b"PNG".to_vec(), true)]
+    #[case::svg("image/svg+xml", b"<svg/>".to_vec(), false)]
+    #[case::empty("application/octet-stream", Vec::new(), false)]
     #[tokio::test]
-    async fn api_client_sends_headers() {
+    async fn api_client_detects_binary_bodies(
+        #[case] content_type: &str,
+        #[case] body: Vec<u8>,
+        #[case] expected: bool,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::header("X-Test-Header-1", "some-test-value"))
-            .and(matchers::header("X-Test-Header-2", "other-test-value"))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_raw(body, content_type))
             .expect(1)
             .mount(&test_server.mock)
             .await;
@@ -417,41 +2073,41 @@ mod tests {
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                headers: KeyValueList::new(vec![
-                    KeyValuePair {
-                        key: "X-Test-Header-1".to_string(),
-                        value: "some-test-value".to_string(),
-                        enabled: Some(true),
-                    },
-                    KeyValuePair {
-                        key: "X-Test-Header-2".to_string(),
-                        value: "other-test-value".to_string(),
-                        enabled: Some(true),
-                    },
-                ]),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute_collected()
+            .await
+            .expect("request failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(res.is_binary(), expected);
     }
 
+    #[rstest]
+    #[case::decompressed(true, b"hello".to_vec(), Some(13))]
+    #[case::raw(false, vec![120, 156, 203, 72, 205, 201, 201, 7, 0, 6, 44, 2, 21], None)]
     #[tokio::test]
-    async fn api_client_ignores_disabled_headers() {
+    async fn api_client_decompresses_bodies(
+        #[case] decompress: bool,
+        #[case] expected_body: Vec<u8>,
+        #[case] expected_compressed_size: Option<u64>,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::header(
-            "explicit-enabled",
-            "explicit-enabled-value",
-        ))
-        .and(matchers::header(
-            "implicit-enabled",
-            "implicit-enabled-value",
+        Mock::given(matchers::headers(
+            "accept-encoding",
+            vec!["gzip", "deflate", "br", "zstd"],
         ))
-        .and(HeaderIsMissingMatcher("disabled".try_into().unwrap()))
-        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .respond_with(
+            ResponseTemplate::new(StatusCode::OK)
+                .insert_header("content-encoding", "deflate")
+                .set_body_raw(
+                    vec![120, 156, 203, 72, 205, 201, 201, 7, 0, 6, 44, 2, 21],
+                    "text/plain",
+                ),
+        )
         .expect(1)
         .mount(&test_server.mock)
         .await;
@@ -459,39 +2115,39 @@ mod tests {
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                headers: KeyValueList::new(vec![
-                    KeyValuePair {
-                        key: "explicit-enabled".to_string(),
-                        value: "explicit-enabled-value".to_string(),
-                        enabled: Some(true),
-                    },
-                    KeyValuePair {
-                        key: "implicit-enabled".to_string(),
-                        value: "implicit-enabled-value".to_string(),
-                        enabled: None,
-                    },
-                    KeyValuePair {
-                        key: "disabled".to_string(),
-                        value: "disabled-value".to_string(),
-                        enabled: Some(false),
-                    },
-                ]),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_decompression(decompress)
+            .execute_collected()
+            .await
+            .expect("request failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(res.body(), expected_body);
+        assert_eq!(res.content_encoding(), Some("deflate"));
+        assert_eq!(res.compressed_size(), expected_compressed_size);
     }
 
+    #[rstest]
+    #[case::xml("application/xml", Some(Markup::Xml))]
+    #[case::atom("application/atom+xml; charset=utf-8", Some(Markup::Xml))]
+    #[case::html("text/html; charset=utf-8", Some(Markup::Html))]
+    #[case::xhtml("application/xhtml+xml", Some(Markup::Html))]
+    #[case::yaml("application/yaml", Some(Markup::Yaml))]
+    #[case::json("application/json", None)]
     #[tokio::test]
-    async fn api_client_sends_query_params() {
+    async fn api_client_detects_markup_bodies(
+        #[case] content_type: &str,
+        #[case] expected: Option<Markup>,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::query_param("param1", "value1"))
-            .and(matchers::query_param("param2", "value2"))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_raw(b"<a/>".to_vec(), content_type),
+            )
             .expect(1)
             .mount(&test_server.mock)
             .await;
@@ -499,264 +2155,283 @@ mod tests {
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                params: HttpParamsModel {
-                    query: KeyValueList::new(vec![
-                        KeyValuePair {
-                            key: "param1".to_string(),
-                            value: "value1".to_string(),
-                            enabled: Some(true),
-                        },
-                        KeyValuePair {
-                            key: "param2".to_string(),
-                            value: "value2".to_string(),
-                            enabled: Some(true),
-                        },
-                    ]),
-                },
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute_collected()
+            .await
+            .expect("request failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(res.markup(), expected);
     }
 
+    #[rstest]
+    #[case::valid(json!({"id": 1, "name": "a"}), vec![])]
+    #[case::invalid(
+        json!({"id": "1"}),
+        vec!["$: \"name\" is a required property", "$.id: \"1\" is not of type \"integer\""],
+    )]
     #[tokio::test]
-    async fn api_client_ignores_disabled_query_params() {
+    async fn api_client_validates_response_schema(
+        #[case] body: Value,
+        #[case] expected: Vec<&str>,
+        #[values(
+            ResponseSchemaModel::Path(PathBuf::from("schemas/user.json")),
+            ResponseSchemaModel::Inline(json!({
+                "properties": {"id": {"type": "integer"}, "name": {"type": "string"}},
+                "required": ["id", "name"],
+            })),
+        )]
+        schema: ResponseSchemaModel,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::query_param(
-            "explicit-enabled",
-            "explicit-enabled-value",
-        ))
-        .and(matchers::query_param(
-            "implicit-enabled",
-            "implicit-enabled-value",
-        ))
-        .and(matchers::query_param_is_missing("disabled"))
-        .respond_with(ResponseTemplate::new(StatusCode::OK))
-        .expect(1)
-        .mount(&test_server.mock)
-        .await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(body))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                params: HttpParamsModel {
-                    query: KeyValueList::new(vec![
-                        KeyValuePair {
-                            key: "explicit-enabled".to_string(),
-                            value: "explicit-enabled-value".to_string(),
-                            enabled: Some(true),
-                        },
-                        KeyValuePair {
-                            key: "implicit-enabled".to_string(),
-                            value: "implicit-enabled-value".to_string(),
-                            enabled: None,
-                        },
-                        KeyValuePair {
-                            key: "disabled".to_string(),
-                            value: "disabled-value".to_string(),
-                            enabled: Some(false),
-                        },
-                    ]),
-                },
+                response_schema: Some(schema),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
-
-        api_request.execute().await.expect("request failed");
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_request_file(PathBuf::from("tests/request.yaml"));
+        let schema = req
+            .response_schema()
+            .expect("invalid schema")
+            .expect("no schema");
+        let res = req.execute_collected().await.expect("request failed");
+
+        let violations = schema
+            .validate_response(&res)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(violations, expected);
     }
 
-    #[rstest]
-    #[case::none(HttpAuth::None, None)]
-    #[case::basic(
-        HttpAuth::Basic(HttpBasicAuth{username: "user".to_string(), password: "pass".to_string()}),
-        Some("Basic dXNlcjpwYXNz"),
-    )]
-    #[case::bearer(
-        HttpAuth::Bearer(HttpBearerToken{token: "bearer-token".to_string()}),
-        Some("Bearer bearer-token"),
-    )]
     #[tokio::test]
-    async fn api_client_sends_auth(#[case] auth: HttpAuth, #[case] expected: Option<&str>) {
+    async fn api_client_records_timings() {
         let test_server = spawn_mock_server().await;
-
-        let mock = match expected {
-            Some(a) => Mock::given(matchers::header("Authorization", a)),
-            None => Mock::given(HeaderIsMissingMatcher("Authorization".try_into().unwrap())),
-        };
-
-        mock.respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_string("some-body"))
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                url: test_server.base_url,
-                auth: Some(auth),
+                url: test_server.base_url.replace("127.0.0.1", "localhost"),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
 
-        api_request.execute().await.expect("request failed");
+        let res = api_request
+            .execute_collected()
+            .await
+            .expect("request failed");
+
+        let timings = res.timings();
+        assert!(timings.dns.is_some());
+        assert!(timings.connect.is_some());
+        assert_eq!(timings.ttfb, res.latency());
     }
 
     #[tokio::test]
-    async fn test_client_sends_text_body() {
-        let body = "some text value";
-
+    async fn api_client_reuses_connections_of_shared_client() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_string(body))
-            .and(matchers::header("Content-Type", "text/plain"))
-            .and(matchers::header("Content-Length", body.len()))
+        Mock::given(matchers::any())
             .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
+            .expect(2)
             .mount(&test_server.mock)
             .await;
 
-        let request = RequestModel {
-            http: HttpRequestModel {
-                url: test_server.base_url,
-                body: Some(HttpBody::Text(HttpTextBody {
-                    text: body.to_string(),
-                })),
+        let client = ApiClient::new().expect("invalid client");
+
+        let mut connects = Vec::new();
+        for _ in 0..2 {
+            let request = RequestModel {
+                http: HttpRequestModel {
+                    url: test_server.base_url.clone(),
+                    ..Default::default()
+                },
                 ..Default::default()
-            },
-            vars: Default::default(),
+            };
+
+            let res = ApiClientRequest::new(CollectionModel::default(), request)
+                .with_client(client.clone())
+                .execute_collected()
+                .await
+                .expect("request failed");
+
+            connects.push(res.timings().connect);
+        }
+
+        assert!(connects[0].is_some());
+        assert!(connects[1].is_none());
+    }
+
+    #[tokio::test]
+    async fn api_client_records_redirects_of_concurrent_requests_separately() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::path("/first"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::FOUND)
+                    .insert_header("Location", "/final")
+                    .set_delay(Duration::from_millis(50)),
+            )
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::path("/final"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_delay(Duration::from_millis(50)),
+            )
+            .mount(&test_server.mock)
+            .await;
+
+        let client = ApiClient::new().expect("invalid client");
+        let execute = |path: &str| {
+            let request = RequestModel {
+                http: HttpRequestModel {
+                    url: format!("{}{}", test_server.base_url, path),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            ApiClientRequest::new(CollectionModel::default(), request)
+                .with_client(client.clone())
+                .execute_collected()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let (redirected, direct) = tokio::join!(execute("/first"), execute("/final"));
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(redirected.expect("request failed").redirects().len(), 1);
+        assert!(direct.expect("request failed").redirects().is_empty());
     }
 
+    #[rstest]
+    #[case::same_config(None, false)]
+    #[case::other_config(Some(FollowRedirects::Enabled(false)), true)]
     #[tokio::test]
-    async fn test_client_sends_json_body() {
-        let body: Value = serde_json::from_str(
-            r#"
-        {
-            "name": "some-name",
-            "flag": true,
-            "id": 123,
-            "data": {
-                "foo": "bar"
-            }
+    async fn api_client_reuses_clients_of_pool(
+        #[case] follow_redirects: Option<FollowRedirects>,
+        #[case] expect_new_connection: bool,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(2)
+            .mount(&test_server.mock)
+            .await;
+
+        let pool = ClientPool::default();
+        let mut connects = Vec::new();
+        for follow_redirects in [None, follow_redirects] {
+            let request = RequestModel {
+                http: HttpRequestModel {
+                    url: test_server.base_url.clone(),
+                    follow_redirects,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let res = ApiClientRequest::new(CollectionModel::default(), request)
+                .with_client_pool(pool.clone())
+                .execute_collected()
+                .await
+                .expect("request failed");
+
+            connects.push(res.timings().connect);
         }
-        "#,
-        )
-        .unwrap();
 
+        assert!(connects[0].is_some());
+        assert_eq!(connects[1].is_some(), expect_new_connection);
+    }
+
+    #[tokio::test]
+    async fn api_client_executes_load() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_json(&body))
-            .and(matchers::header("Content-Type", "application/json"))
-            // TODO: Check len
-            // .and(matchers::header("Content-Length", body.len()))
+        Mock::given(matchers::any())
             .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
+            .up_to_n_times(8)
+            .expect(8)
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::INTERNAL_SERVER_ERROR))
+            .expect(2)
             .mount(&test_server.mock)
             .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                url: test_server.base_url,
-                body: Some(HttpBody::Json(HttpJsonBody { json: body })),
+                url: test_server.base_url.clone(),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
 
-        api_request.execute().await.expect("request failed");
+        let report = api_request
+            .execute_load(10, 3)
+            .await
+            .expect("request failed");
+
+        assert_eq!(report.requests(), 10);
+        assert_eq!(report.errors(), 2);
+        assert_eq!(report.statuses().get(&200), Some(&8));
+        assert!(report.percentile(50.0).is_some());
     }
 
     #[tokio::test]
-    async fn test_client_sends_graphql_body() {
-        let query = r#"
-            query ($user: String!, $filter: Filter!) {
-              user(login: $login) {
-                login
-                name
-                company
-                location
-                repos(filter: $filter) {
-                  nodes {
-                    name
-                  }
-                }
-              }
-            }
-        "#;
-
-        let mut filter = Map::new();
-        filter.insert("language".to_string(), Value::String("python".to_string()));
-        filter.insert(
-            "min_stars".to_string(),
-            Value::Number(Number::from_str("420").expect("unable to parse number")),
-        );
-
-        let mut variables = Map::new();
-        variables.insert("login".to_string(), Value::String("some-name".to_string()));
-        variables.insert("filter".to_string(), Value::Object(filter));
-
-        let mut body = HashMap::new();
-        body.insert("query", Value::String(query.to_string()));
-        body.insert("variables", Value::Object(variables.clone()));
-
+    async fn api_client_executes_load_at_limited_rate() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_json(&body))
-            .and(matchers::header("Content-Type", "application/json"))
-            // TODO: Check len
-            // .and(matchers::header("Content-Length", body.len()))
+        Mock::given(matchers::any())
             .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
+            .expect(4)
             .mount(&test_server.mock)
             .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                url: test_server.base_url,
-                body: Some(HttpBody::GraphQL(HttpGraphQLBody {
-                    graphql: GraphGLBody {
-                        query: query.to_string(),
-                        variables: variables.into_iter().collect::<HashMap<String, Value>>(),
-                    },
-                })),
+                url: test_server.base_url.clone(),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let report = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_rate_limiter(Arc::new(RateLimiter::per_second(20.0)))
+            .execute_load(4, 4)
+            .await
+            .expect("request failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(report.requests(), 4);
+        assert!(report.elapsed() >= Duration::from_millis(150));
     }
 
     #[tokio::test]
-    async fn test_client_sends_binary_body() {
-        let body: Vec<u8> = vec![
-            0xa3, 0x2d, 0x30, 0x1f, 0xc9, 0x5f, 0xc1, 0xdf, 0x9f, 0x8e, 0x1d, 0xff, 0x56, 0xb7,
-            0xef, 0xac, 0x0f, 0x4f, 0xe6, 0x62, 0x82, 0x91, 0xbc, 0xb9, 0xb9, 0x4a, 0x20, 0xfa,
-            0x68, 0x3c, 0x18, 0x8e,
-        ];
+    async fn api_client_saves_response_to_file() {
+        let body: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
 
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_bytes(body.clone()))
-            .and(matchers::header(
-                "Content-Type",
-                "application/x-www-form-urlencoded",
-            ))
-            .and(matchers::header("Content-Length", body.len()))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_bytes(body.clone()))
             .expect(1)
             .mount(&test_server.mock)
             .await;
@@ -764,50 +2439,40 @@ mod tests {
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                body: Some(HttpBody::Binary(HttpBinaryBody {
-                    binary: BASE64_STANDARD.encode(body),
-                })),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let path = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        let mut progress = Vec::new();
 
-        api_request.execute().await.expect("request failed");
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = api_request
+            .execute_to_file(&path, |size, total| progress.push((size, total)))
+            .await
+            .expect("request failed");
+
+        let saved = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.size(), body.len() as u64);
+        assert_eq!(saved, body);
+        assert_eq!(
+            progress.last(),
+            Some(&(body.len() as u64, Some(body.len() as u64)))
+        );
     }
 
     #[tokio::test]
-    async fn test_client_sends_form_body() {
-        let form = vec![
-            KeyValuePair {
-                key: "name".to_string(),
-                value: "Firstname Lastname".to_string(),
-                enabled: Some(true),
-            },
-            KeyValuePair {
-                key: "email".to_string(),
-                value: "firstname.lastname@example.org".to_string(),
-                enabled: Some(true),
-            },
-        ];
-
-        let mut expected_data = HashMap::new();
-        expected_data.insert("name".to_string(), "Firstname Lastname".to_string());
-        expected_data.insert(
-            "email".to_string(),
-            "firstname.lastname@example.org".to_string(),
-        );
-        let expected_len = serde_urlencoded::to_string(&expected_data).unwrap().len();
-
+    async fn api_client_streams_sse_events() {
         let test_server = spawn_mock_server().await;
-        Mock::given(FormDataMatcher(expected_data))
-            .and(matchers::header(
-                "Content-Type",
-                "application/x-www-form-urlencoded",
+        Mock::given(matchers::header("Accept", "text/event-stream"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_raw(
+                "event: greeting\nid: 1\ndata: hello\n\n: keep-alive\n\ndata: world\n\n",
+                "text/event-stream",
             ))
-            .and(matchers::header("Content-Length", expected_len))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
             .expect(1)
             .mount(&test_server.mock)
             .await;
@@ -815,51 +2480,46 @@ mod tests {
         let request = RequestModel {
             http: HttpRequestModel {
                 url: test_server.base_url,
-                body: Some(HttpBody::Form(HttpFormBody {
-                    form: KeyValueList::new(form),
-                })),
+                stream: Some(StreamMode::Sse),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        assert!(api_request.is_sse_stream());
 
-        api_request.execute().await.expect("request failed");
+        let mut stream = api_request.execute_sse().await.expect("request failed");
+
+        let mut events = Vec::new();
+        while let Some(e) = stream.next_event().await.expect("error reading stream") {
+            events.push(e);
+        }
+
+        assert_eq!(stream.status(), StatusCode::OK);
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("greeting".to_string()),
+                    id: Some("1".to_string()),
+                    data: "hello".to_string(),
+                },
+                SseEvent {
+                    event: None,
+                    id: None,
+                    data: "world".to_string(),
+                },
+            ]
+        );
     }
 
     #[tokio::test]
-    async fn test_client_ignores_disabled_form_body() {
-        let form = vec![
-            KeyValuePair {
-                key: "findme1".to_string(),
-                value: "".to_string(),
-                enabled: Some(true),
-            },
-            KeyValuePair {
-                key: "findme2".to_string(),
-                value: "".to_string(),
-                enabled: None,
-            },
-            KeyValuePair {
-                key: "ignoreme".to_string(),
-                value: "".to_string(),
-                enabled: Some(false),
-            },
-        ];
-        let mut expected_data = HashMap::new();
-        expected_data.insert("findme1".to_string(), "".to_string());
-        expected_data.insert("findme2".to_string(), "".to_string());
-        let expected_len = serde_urlencoded::to_string(&expected_data).unwrap().len();
+    async fn api_client_uses_specified_path() {
+        let path = "/foo/bar/baz";
 
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::any())
-            .and(FormDataMatcher(expected_data))
-            .and(matchers::header(
-                "Content-Type",
-                "application/x-www-form-urlencoded",
-            ))
-            .and(matchers::header("Content-Length", expected_len))
+        Mock::given(matchers::path(path))
             .respond_with(ResponseTemplate::new(StatusCode::OK))
             .expect(1)
             .mount(&test_server.mock)
@@ -867,13 +2527,11 @@ mod tests {
 
         let request = RequestModel {
             http: HttpRequestModel {
-                url: test_server.base_url,
-                body: Some(HttpBody::Form(HttpFormBody {
-                    form: KeyValueList::new(form),
-                })),
+                method: HttpMethod::Get,
+                url: format!("{}{}", test_server.base_url, path),
                 ..Default::default()
             },
-            vars: Default::default(),
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -881,27 +2539,26 @@ mod tests {
         api_request.execute().await.expect("request failed");
     }
 
+    #[rstest]
+    #[case(HttpMethod::Get)]
+    #[case(HttpMethod::Post)]
+    #[case(HttpMethod::Delete)]
     #[tokio::test]
-    async fn test_client_applies_templating_to_url() {
+    async fn api_client_uses_specified_method(#[case] method: HttpMethod) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::any())
+        Mock::given(matchers::method(method.as_str()))
             .respond_with(ResponseTemplate::new(StatusCode::OK))
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
-        let variables = [("url", test_server.base_url)];
-
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
-                url: "{{url}}".to_string(),
-                ..Default::default()
-            },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                method,
+                url: test_server.base_url,
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -910,33 +2567,39 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_client_applies_templating_to_basic_auth() {
+    async fn api_client_sends_headers() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::header(
-            "Authorization",
-            "Basic YS11c2VybmFtZTphLXBhc3N3b3Jk",
-        ))
-        .respond_with(ResponseTemplate::new(StatusCode::OK))
-        .expect(1)
-        .mount(&test_server.mock)
-        .await;
-
-        let variables = [("username", "a-username"), ("password", "a-password")];
+        Mock::given(matchers::header("X-Test-Header-1", "some-test-value"))
+            .and(matchers::header("X-Test-Header-2", "other-test-value"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
 
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
                 url: test_server.base_url,
-                auth: Some(HttpAuth::Basic(HttpBasicAuth {
-                    username: "{{username}}".to_string(),
-                    password: "{{password}}".to_string(),
-                })),
-                ..Default::default()
-            },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                headers: KeyValueList::new(vec![
+                    KeyValuePair {
+                        key: "X-Test-Header-1".to_string(),
+                        value: "some-test-value".to_string(),
+                        enabled: Some(true),
+                        enabled_if: None,
+                        encode: None,
+                        secret: None,
+                    },
+                    KeyValuePair {
+                        key: "X-Test-Header-2".to_string(),
+                        value: "other-test-value".to_string(),
+                        enabled: Some(true),
+                        enabled_if: None,
+                        encode: None,
+                        secret: None,
+                    },
+                ]),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -945,34 +2608,54 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_client_applies_templating_to_bearer_auth() {
-        let token = "mmOjB9WuQXBxCFwvxn7v7qWn3sonKzIy";
-
+    async fn api_client_ignores_disabled_headers() {
         let test_server = spawn_mock_server().await;
         Mock::given(matchers::header(
-            "Authorization",
-            format!("Bearer {}", token),
+            "explicit-enabled",
+            "explicit-enabled-value",
+        ))
+        .and(matchers::header(
+            "implicit-enabled",
+            "implicit-enabled-value",
         ))
+        .and(HeaderIsMissingMatcher("disabled".try_into().unwrap()))
         .respond_with(ResponseTemplate::new(StatusCode::OK))
         .expect(1)
         .mount(&test_server.mock)
         .await;
 
-        let variables = [("token", token)];
-
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
                 url: test_server.base_url,
-                auth: Some(HttpAuth::Bearer(HttpBearerToken {
-                    token: "{{token}}".to_string(),
-                })),
-                ..Default::default()
-            },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                headers: KeyValueList::new(vec![
+                    KeyValuePair {
+                        key: "explicit-enabled".to_string(),
+                        value: "explicit-enabled-value".to_string(),
+                        enabled: Some(true),
+                        enabled_if: None,
+                        encode: None,
+                        secret: None,
+                    },
+                    KeyValuePair {
+                        key: "implicit-enabled".to_string(),
+                        value: "implicit-enabled-value".to_string(),
+                        enabled: None,
+                        enabled_if: None,
+                        encode: None,
+                        secret: None,
+                    },
+                    KeyValuePair {
+                        key: "disabled".to_string(),
+                        value: "disabled-value".to_string(),
+                        enabled: Some(false),
+                        enabled_if: None,
+                        encode: None,
+                        secret: None,
+                    },
+                ]),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -980,108 +2663,260 @@ mod tests {
         api_request.execute().await.expect("request failed");
     }
 
-    #[tokio::test]
-    async fn test_client_applies_templating_to_headers() {
-        let header_name = "X-Test-Header";
-        let header_value = "some-test-value";
+    #[test]
+    fn api_client_sends_repeated_headers() {
+        let collection = CollectionModel {
+            headers: KeyValueList::from([("X-Tag", "collection"), ("X-Other", "collection")]),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("X-Tag", "a"), ("X-Tag", "b")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
 
-        let test_server = spawn_mock_server().await;
-        Mock::given(matchers::header(header_name, header_value))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
-            .mount(&test_server.mock)
-            .await;
+        let req = ApiClientRequest::new(collection, request)
+            .prepare()
+            .expect("prepare failed");
 
-        let variables = [("header_name", header_name), ("header_value", header_value)];
+        let tags: Vec<_> = req.headers().get_all("X-Tag").iter().collect();
+        assert_eq!(tags, ["a", "b"]);
+        assert_eq!(req.headers()["X-Other"], "collection");
+    }
 
+    #[test]
+    fn api_client_sends_repeated_query_params() {
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
-                url: test_server.base_url,
-                headers: KeyValueList::from([("{{header_name}}", "{{header_value}}")]),
-                ..Default::default()
-            },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                url: "http://localhost?tag=a".to_string(),
+                params: HttpParamsModel {
+                    query: KeyValueList::from([("tag", "b"), ("tag", "c")]),
+                    ..Default::default()
+                },
                 ..Default::default()
             },
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .expect("prepare failed");
 
-        api_request.execute().await.expect("request failed");
+        assert_eq!(req.url().query(), Some("tag=a&tag=b&tag=c"));
     }
 
-    #[tokio::test]
-    async fn test_client_applies_templating_to_text_body() {
-        let key = "some-test-key";
-        let value = "some-test-value";
+    #[test]
+    fn api_client_sends_repeated_form_fields() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Post,
+                url: "http://localhost".to_string(),
+                body: Some(HttpBody::Form(HttpFormBody {
+                    form: KeyValueList::from([("tag", "a"), ("tag", "b")]),
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
 
-        let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_string(format!("{} / {}", key, value)))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
-            .mount(&test_server.mock)
-            .await;
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .expect("prepare failed");
 
-        let variables = [("key", key), ("value", value)];
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap();
+        assert_eq!(body, b"tag=a&tag=b");
+    }
+
+    #[test]
+    fn api_client_sends_form_fields_in_declaration_order() {
+        let fields = [
+            ("grant_type", "authorization_code"),
+            ("scope", "read"),
+            ("code", "{{code}}"),
+            ("client_id", "some-client"),
+            ("scope", "write"),
+            ("redirect_uri", "http://localhost/callback"),
+        ];
 
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
-                url: test_server.base_url,
-                body: Some(HttpBody::Text(HttpTextBody {
-                    text: "{{key}} / {{value}}".to_string(),
+                method: HttpMethod::Post,
+                url: "http://localhost".to_string(),
+                body: Some(HttpBody::Form(HttpFormBody {
+                    form: KeyValueList::from(fields),
                 })),
                 ..Default::default()
             },
             vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                pre_request: KeyValueList::from([("code", "some-code")]),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .expect("prepare failed");
 
-        api_request.execute().await.expect("request failed");
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(body),
+            "grant_type=authorization_code&scope=read&code=some-code&client_id=some-client\
+             &scope=write&redirect_uri=http%3A%2F%2Flocalhost%2Fcallback"
+        );
     }
 
-    #[tokio::test]
-    async fn test_client_applies_templating_to_json_body() {
-        let key = "some-test-key";
-        let value = "some-test-value";
+    #[rstest]
+    #[case::text_default(HttpBody::Text(HttpTextBody { text: "a".to_string(), content_type: None }), None, "text/plain")]
+    #[case::text_header(HttpBody::Text(HttpTextBody { text: "a".to_string(), content_type: None }), Some("text/csv"), "text/csv")]
+    #[case::text_explicit(
+        HttpBody::Text(HttpTextBody { text: "a".to_string(), content_type: Some("text/csv".to_string()) }),
+        Some("text/plain"),
+        "text/csv",
+    )]
+    #[case::json_default(HttpBody::Json(HttpJsonBody { json: Value::Null, content_type: None }), None, "application/json")]
+    #[case::json_header(
+        HttpBody::Json(HttpJsonBody { json: Value::Null, content_type: None }),
+        Some("application/vnd.api+json"),
+        "application/vnd.api+json",
+    )]
+    #[case::json_explicit(
+        HttpBody::Json(HttpJsonBody { json: Value::Null, content_type: Some("application/x-ndjson".to_string()) }),
+        None,
+        "application/x-ndjson",
+    )]
+    #[case::binary_explicit(
+        HttpBody::Binary(HttpBinaryBody { binary: "".to_string(), content_type: Some("image/png".to_string()) }),
+        None,
+        "image/png",
+    )]
+    fn api_client_sets_content_type(
+        #[case] body: HttpBody,
+        #[case] header: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let headers = match header {
+            Some(h) => KeyValueList::from([("Content-Type", h)]),
+            None => KeyValueList::default(),
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Post,
+                url: "http://localhost".to_string(),
+                headers,
+                body: Some(body),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
 
-        let variables = [("key", key), ("value", value)];
-        let expected_body = HashMap::from(variables);
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .expect("prepare failed");
+
+        let content_types: Vec<_> = req.headers().get_all("Content-Type").iter().collect();
+        assert_eq!(content_types, [expected]);
+    }
 
+    #[tokio::test]
+    async fn api_client_sends_query_params() {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_json(expected_body))
+        Mock::given(matchers::query_param("param1", "value1"))
+            .and(matchers::query_param("param2", "value2"))
             .respond_with(ResponseTemplate::new(StatusCode::OK))
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
-        let body: Value = serde_json::from_str(
-            r#"
-        {
-            "key": "{{key}}",
-            "value": "{{value}}"
-        }
-        "#,
-        )
-        .unwrap();
-
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
                 url: test_server.base_url,
-                body: Some(HttpBody::Json(HttpJsonBody { json: body })),
+                params: HttpParamsModel {
+                    query: KeyValueList::new(vec![
+                        KeyValuePair {
+                            key: "param1".to_string(),
+                            value: "value1".to_string(),
+                            enabled: Some(true),
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                        KeyValuePair {
+                            key: "param2".to_string(),
+                            value: "value2".to_string(),
+                            enabled: Some(true),
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                    ]),
+                    ..Default::default()
+                },
                 ..Default::default()
             },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn api_client_ignores_disabled_query_params() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::query_param(
+            "explicit-enabled",
+            "explicit-enabled-value",
+        ))
+        .and(matchers::query_param(
+            "implicit-enabled",
+            "implicit-enabled-value",
+        ))
+        .and(matchers::query_param_is_missing("disabled"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                params: HttpParamsModel {
+                    query: KeyValueList::new(vec![
+                        KeyValuePair {
+                            key: "explicit-enabled".to_string(),
+                            value: "explicit-enabled-value".to_string(),
+                            enabled: Some(true),
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                        KeyValuePair {
+                            key: "implicit-enabled".to_string(),
+                            value: "implicit-enabled-value".to_string(),
+                            enabled: None,
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                        KeyValuePair {
+                            key: "disabled".to_string(),
+                            value: "disabled-value".to_string(),
+                            enabled: Some(false),
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                    ]),
+                    ..Default::default()
+                },
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -1089,71 +2924,37 @@ mod tests {
         api_request.execute().await.expect("request failed");
     }
 
+    #[rstest]
+    #[case::none(HttpAuth::None, None)]
+    #[case::basic(
+        HttpAuth::Basic(HttpBasicAuth{username: "user".to_string(), password: "pass".to_string()}),
+        Some("Basic dXNlcjpwYXNz"),
+    )]
+    #[case::bearer(
+        HttpAuth::Bearer(HttpBearerToken{token: "bearer-token".to_string(), refresh: None}),
+        Some("Bearer bearer-token"),
+    )]
     #[tokio::test]
-    async fn test_client_applies_templating_to_graphql_body() {
-        let key = "some-test-key";
-        let value = "some-test-value";
+    async fn api_client_sends_auth(#[case] auth: HttpAuth, #[case] expected: Option<&str>) {
+        let test_server = spawn_mock_server().await;
 
-        let variables = [("key", key), ("value", value)];
-        let expected_body: Value = serde_json::from_str(
-            r#"
-            {
-                "query": "query { resolver { some-test-key } }",
-                "variables": {
-                    "some-test-key": "some-test-value",
-                    "number": 123,
-                    "struct": {
-                        "embed": {
-                            "key": "some-test-key",
-                            "value": "some-test-value"
-                        }
-                    }
-                }
-            }
-        "#,
-        )
-        .unwrap();
+        let mock = match expected {
+            Some(a) => Mock::given(matchers::header("Authorization", a)),
+            None => Mock::given(HeaderIsMissingMatcher("Authorization".try_into().unwrap())),
+        };
 
-        let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_json(expected_body))
-            .respond_with(ResponseTemplate::new(StatusCode::OK))
+        mock.respond_with(ResponseTemplate::new(StatusCode::OK))
             .expect(1)
             .mount(&test_server.mock)
             .await;
 
-        let query = "query { resolver { {{key}} } }";
-        let query_vars: HashMap<String, Value> = serde_json::from_str(
-            r#"
-        {
-            "{{key}}": "{{value}}",
-            "number": 123,
-            "struct": {
-                "embed": {
-                    "key": "{{key}}",
-                    "value": "{{value}}"
-                }
-            }
-        }
-        "#,
-        )
-        .unwrap();
-
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
                 url: test_server.base_url,
-                body: Some(HttpBody::GraphQL(HttpGraphQLBody {
-                    graphql: GraphGLBody {
-                        query: query.to_string(),
-                        variables: query_vars,
-                    },
-                })),
-                ..Default::default()
-            },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+                auth: Some(auth),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
@@ -1161,73 +2962,2982 @@ mod tests {
         api_request.execute().await.expect("request failed");
     }
 
-    #[tokio::test]
-    async fn test_client_applies_templating_to_binary_body() {
-        let data = "c29tZS1kYXRh"; // "some-data"
+    #[rstest]
+    #[case::header_name(
+        "{{host}}",
+        "X-Bad Name",
+        "value",
+        "Invalid header name: \"X-Bad Name\""
+    )]
+    #[case::header_value(
+        "{{host}}",
+        "X-Header",
+        "line\nbreak",
+        "Invalid value for header X-Header: \"line\\nbreak\""
+    )]
+    #[case::url("not a url", "X-Header", "value", "Invalid url \"not a url\"")]
+    fn api_client_returns_error_on_invalid_request(
+        #[case] url: &str,
+        #[case] header_name: &str,
+        #[case] header_value: &str,
+        #[case] expected: &str,
+    ) {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: url.to_string(),
+                headers: KeyValueList::from([(header_name, header_value)]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
 
-        let variables = [("data", data)];
+        let api_request =
+            ApiClientRequest::new(CollectionModel::default(), request).with_override_variables(
+                HashMap::from([("host".to_string(), "http://localhost".to_string())]),
+            );
+
+        let err = api_request
+            .prepare()
+            .expect_err("request should be invalid");
+
+        assert!(
+            err.to_string().contains(expected),
+            "unexpected error: {}",
+            err
+        );
+    }
 
+    #[rstest]
+    #[case::default(None, StatusCode::OK, 2)]
+    #[case::disabled(Some(FollowRedirects::Enabled(false)), StatusCode::FOUND, 0)]
+    #[case::max(Some(FollowRedirects::Max(1)), StatusCode::OK, 1)]
+    #[tokio::test]
+    async fn api_client_follows_redirects(
+        #[case] follow_redirects: Option<FollowRedirects>,
+        #[case] expected_status: StatusCode,
+        #[case] expected_redirects: usize,
+    ) {
         let test_server = spawn_mock_server().await;
-        Mock::given(matchers::body_bytes("some-data".as_bytes()))
+        Mock::given(matchers::path("/first"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::FOUND).insert_header("Location", "/second"),
+            )
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::path("/second"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::MOVED_PERMANENTLY)
+                    .insert_header("Location", "/final"),
+            )
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::path("/final"))
             .respond_with(ResponseTemplate::new(StatusCode::OK))
-            .expect(1)
             .mount(&test_server.mock)
             .await;
 
+        let url = match follow_redirects {
+            Some(FollowRedirects::Max(_)) => format!("{}/second", test_server.base_url),
+            _ => format!("{}/first", test_server.base_url),
+        };
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
-                url: test_server.base_url,
-                body: Some(HttpBody::Binary(HttpBinaryBody {
-                    binary: "{{data}}".to_string(),
-                })),
+                url,
+                follow_redirects,
                 ..Default::default()
             },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+            ..Default::default()
+        };
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute_collected()
+            .await
+            .expect("request failed");
+
+        assert_eq!(res.status(), expected_status);
+        assert_eq!(res.redirects().len(), expected_redirects);
+        if let Some(last) = res.redirects().last() {
+            assert_eq!(last.location.path(), "/final");
+        }
+    }
+
+    #[tokio::test]
+    async fn api_client_fails_on_too_many_redirects() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::FOUND).insert_header("Location", "/loop"),
+            )
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            follow_redirects: Some(FollowRedirects::Max(3)),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url.clone(),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
-        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+        let res = ApiClientRequest::new(collection, request).execute().await;
 
-        api_request.execute().await.expect("request failed");
+        assert!(res.is_err());
     }
 
+    #[rstest]
+    #[case::not_defined(None, Some("Basic dXNlcjpwYXNz"))]
+    #[case::inherit(Some(HttpAuth::Inherit), Some("Basic dXNlcjpwYXNz"))]
+    #[case::none(Some(HttpAuth::None), None)]
+    #[case::overridden(
+        Some(HttpAuth::Bearer(HttpBearerToken{token: "bearer-token".to_string(), refresh: None})),
+        Some("Bearer bearer-token"),
+    )]
     #[tokio::test]
-    async fn test_client_applies_templating_to_form_body() {
-        let key = "some-test-key";
-        let value = "some-test-value";
+    async fn api_client_inherits_collection_auth(
+        #[case] auth: Option<HttpAuth>,
+        #[case] expected: Option<&str>,
+    ) {
+        let test_server = spawn_mock_server().await;
 
-        let variables = [("key", key), ("value", value)];
+        let mock = match expected {
+            Some(a) => Mock::given(matchers::header("Authorization", a)),
+            None => Mock::given(HeaderIsMissingMatcher("Authorization".try_into().unwrap())),
+        };
 
-        let test_server = spawn_mock_server().await;
-        Mock::given(FormDataMatcher(HashMap::from([(
-            key.to_string(),
-            value.to_string(),
-        )])))
-        .respond_with(ResponseTemplate::new(StatusCode::OK))
-        .expect(1)
-        .mount(&test_server.mock)
-        .await;
+        mock.respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            auth: Some(HttpAuth::Basic(HttpBasicAuth {
+                username: "user".to_string(),
+                password: "pass".to_string(),
+            })),
+            ..Default::default()
+        };
 
         let request = RequestModel {
             http: HttpRequestModel {
-                method: HttpMethod::Get,
                 url: test_server.base_url,
-                body: Some(HttpBody::Form(HttpFormBody {
-                    form: KeyValueList::from([("{{key}}", "{{value}}")]),
-                })),
+                auth,
                 ..Default::default()
             },
-            vars: RequestVarsModel {
-                pre_request: KeyValueList::from(variables),
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_text_body() {
+        let body = "some text value";
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_string(body))
+            .and(matchers::header("Content-Type", "text/plain"))
+            .and(matchers::header("Content-Length", body.len()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::Text(HttpTextBody {
+                    text: body.to_string(),
+                    content_type: None,
+                })),
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let api_request = ApiClientRequest::new(CollectionModel::default(), request);
 
         api_request.execute().await.expect("request failed");
     }
+
+    #[tokio::test]
+    async fn test_client_sends_json_body() {
+        let body: Value = serde_json::from_str(
+            r#"
+        {
+            "name": "some-name",
+            "flag": true,
+            "id": 123,
+            "data": {
+                "foo": "bar"
+            }
+        }
+        "#,
+        )
+        .unwrap();
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_json(&body))
+            .and(matchers::header("Content-Type", "application/json"))
+            // TODO: Check len
+            // .and(matchers::header("Content-Length", body.len()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::Json(HttpJsonBody {
+                    json: body,
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_graphql_body() {
+        let query = r#"
+            query ($user: String!, $filter: Filter!) {
+              user(login: $login) {
+                login
+                name
+                company
+                location
+                repos(filter: $filter) {
+                  nodes {
+                    name
+                  }
+                }
+              }
+            }
+        "#;
+
+        let mut filter = Map::new();
+        filter.insert("language".to_string(), Value::String("python".to_string()));
+        filter.insert(
+            "min_stars".to_string(),
+            Value::Number(Number::from_str("420").expect("unable to parse number")),
+        );
+
+        let mut variables = Map::new();
+        variables.insert("login".to_string(), Value::String("some-name".to_string()));
+        variables.insert("filter".to_string(), Value::Object(filter));
+
+        let mut body = HashMap::new();
+        body.insert("query", Value::String(query.to_string()));
+        body.insert("variables", Value::Object(variables.clone()));
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_json(&body))
+            .and(matchers::header("Content-Type", "application/json"))
+            // TODO: Check len
+            // .and(matchers::header("Content-Length", body.len()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::GraphQL(HttpGraphQLBody {
+                    graphql: GraphGLBody {
+                        query: query.to_string(),
+                        variables: variables.into_iter().collect::<HashMap<String, Value>>(),
+                    },
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_binary_body() {
+        let body: Vec<u8> = vec![
+            0xa3, 0x2d, 0x30, 0x1f, 0xc9, 0x5f, 0xc1, 0xdf, 0x9f, 0x8e, 0x1d, 0xff, 0x56, 0xb7,
+            0xef, 0xac, 0x0f, 0x4f, 0xe6, 0x62, 0x82, 0x91, 0xbc, 0xb9, 0xb9, 0x4a, 0x20, 0xfa,
+            0x68, 0x3c, 0x18, 0x8e,
+        ];
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_bytes(body.clone()))
+            .and(matchers::header(
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            ))
+            .and(matchers::header("Content-Length", body.len()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::Binary(HttpBinaryBody {
+                    binary: BASE64_STANDARD.encode(body),
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_form_body() {
+        let form = vec![
+            KeyValuePair {
+                key: "name".to_string(),
+                value: "Firstname Lastname".to_string(),
+                enabled: Some(true),
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            },
+            KeyValuePair {
+                key: "email".to_string(),
+                value: "firstname.lastname@example.org".to_string(),
+                enabled: Some(true),
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            },
+        ];
+
+        let mut expected_data = HashMap::new();
+        expected_data.insert("name".to_string(), "Firstname Lastname".to_string());
+        expected_data.insert(
+            "email".to_string(),
+            "firstname.lastname@example.org".to_string(),
+        );
+        let expected_len = serde_urlencoded::to_string(&expected_data).unwrap().len();
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(FormDataMatcher(expected_data))
+            .and(matchers::header(
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            ))
+            .and(matchers::header("Content-Length", expected_len))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::Form(HttpFormBody {
+                    form: KeyValueList::new(form),
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_ignores_disabled_form_body() {
+        let form = vec![
+            KeyValuePair {
+                key: "findme1".to_string(),
+                value: "".to_string(),
+                enabled: Some(true),
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            },
+            KeyValuePair {
+                key: "findme2".to_string(),
+                value: "".to_string(),
+                enabled: None,
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            },
+            KeyValuePair {
+                key: "ignoreme".to_string(),
+                value: "".to_string(),
+                enabled: Some(false),
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            },
+        ];
+        let mut expected_data = HashMap::new();
+        expected_data.insert("findme1".to_string(), "".to_string());
+        expected_data.insert("findme2".to_string(), "".to_string());
+        let expected_len = serde_urlencoded::to_string(&expected_data).unwrap().len();
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .and(FormDataMatcher(expected_data))
+            .and(matchers::header(
+                "Content-Type",
+                "application/x-www-form-urlencoded",
+            ))
+            .and(matchers::header("Content-Length", expected_len))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: test_server.base_url,
+                body: Some(HttpBody::Form(HttpFormBody {
+                    form: KeyValueList::new(form),
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_url() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let variables = [("url", test_server.base_url)];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "{{url}}".to_string(),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_basic_auth() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header(
+            "Authorization",
+            "Basic YS11c2VybmFtZTphLXBhc3N3b3Jk",
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let variables = [("username", "a-username"), ("password", "a-password")];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                auth: Some(HttpAuth::Basic(HttpBasicAuth {
+                    username: "{{username}}".to_string(),
+                    password: "{{password}}".to_string(),
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_bearer_auth() {
+        let token = "mmOjB9WuQXBxCFwvxn7v7qWn3sonKzIy";
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header(
+            "Authorization",
+            format!("Bearer {}", token),
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let variables = [("token", token)];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                auth: Some(HttpAuth::Bearer(HttpBearerToken {
+                    token: "{{token}}".to_string(),
+                    refresh: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_signs_request_with_aws_sigv4() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/eu-west-1/execute-api/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=[0-9a-f]{64}$",
+        ))
+        .and(matchers::header_exists("x-amz-date"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let variables = [("access_key", "AKIDEXAMPLE"), ("region", "eu-west-1")];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Post,
+                url: test_server.base_url,
+                auth: Some(HttpAuth::AwsSigV4(HttpAwsSigV4Auth {
+                    access_key: "{{access_key}}".to_string(),
+                    secret_key: "secret".to_string(),
+                    region: "{{region}}".to_string(),
+                    service: "execute-api".to_string(),
+                    session_token: None,
+                })),
+                body: Some(HttpBody::Text(HttpTextBody {
+                    text: "some-body".to_string(),
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_signs_request_with_hmac() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header_regex(
+            "X-Signature",
+            r"^k1:[A-Za-z0-9+/]{43}=$",
+        ))
+        .and(matchers::header_exists("Date"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Post,
+                url: test_server.base_url,
+                auth: Some(HttpAuth::Hmac(HttpHmacAuth {
+                    secret: "{{hmac_secret}}".to_string(),
+                    algorithm: HmacAlgorithm::Sha256,
+                    string_to_sign: None,
+                    header: Some("X-Signature".to_string()),
+                    prefix: Some("{{key_id}}:".to_string()),
+                    encoding: HmacEncoding::Base64,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("hmac_secret", "s3cr3t"), ("key_id", "k1")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[cfg(feature = "ntlm")]
+    #[tokio::test]
+    async fn test_client_starts_ntlm_handshake() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAB",
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                auth: Some(HttpAuth::Ntlm(HttpNtlmAuth {
+                    username: "CORP\\{{user}}".to_string(),
+                    password: "pass".to_string(),
+                    domain: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("user", "alice")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[cfg(not(feature = "ntlm"))]
+    #[test]
+    fn test_prepare_rejects_ntlm_without_feature() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost".to_string(),
+                auth: Some(HttpAuth::Ntlm(HttpNtlmAuth {
+                    username: "user".to_string(),
+                    password: "pass".to_string(),
+                    domain: None,
+                })),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "UnsupportedAuth: NTLM auth is not supported by this build, it requires the `ntlm` \
+             feature"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_client_signs_request_with_collection_signing_command() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header("X-Signature", "signed-with-k1"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            signing: Some(SigningConfig {
+                command: vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    r#"printf '{"headers": {"X-Signature": "signed-with-%s"}}' "$0""#.to_string(),
+                    "{{key_id}}".to_string(),
+                ],
+            }),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("key_id", "k1")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_headers() {
+        let header_name = "X-Test-Header";
+        let header_value = "some-test-value";
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header(header_name, header_value))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let variables = [("header_name", header_name), ("header_value", header_value)];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                headers: KeyValueList::from([("{{header_name}}", "{{header_value}}")]),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[rstest]
+    #[case::request(Some("{{name}}.example.test"), None)]
+    #[case::environment(None, Some("{{name}}.example.test"))]
+    #[tokio::test]
+    async fn test_client_sends_overridden_host_to_the_address_of_the_url(
+        #[case] request_host: Option<&str>,
+        #[case] env_host: Option<&str>,
+    ) {
+        let test_server = spawn_mock_server().await;
+        let port = test_server.mock.address().port();
+        Mock::given(matchers::header(
+            "Host",
+            format!("api.example.test:{}", port),
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                host: request_host.map(str::to_string),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let environment = EnvironmentModel {
+            vars: KeyValueList::from([("name", "api")]),
+            host: env_host.map(str::to_string),
+            ..Default::default()
+        };
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .execute()
+            .await
+            .expect("request failed");
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.url().as_str(),
+            format!("http://api.example.test:{}/", port)
+        );
+    }
+
+    /// Request all the pages of `url`, returning their items and the number of pages.
+    async fn fetch_all_pages(url: &str, pagination: Pagination) -> (Vec<Value>, u32) {
+        let mut paginator = Paginator::new(pagination.clone());
+        let mut items = Vec::new();
+        let mut page = None;
+
+        loop {
+            let request = RequestModel {
+                http: HttpRequestModel {
+                    method: HttpMethod::Get,
+                    url: url.to_string(),
+                    pagination: Some(pagination.clone()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut req = ApiClientRequest::new(CollectionModel::default(), request);
+            if let Some(p) = page.take() {
+                req = req.with_page(p);
+            }
+
+            let res = req.execute_collected().await.expect("request failed");
+            let (page_items, next) = paginator.read_page(&res).expect("invalid page");
+            items.extend(page_items);
+
+            match next {
+                Some(n) => page = Some(n),
+                None => return (items, paginator.pages()),
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::page(
+        PaginationStrategy::Page { param: "page".to_string(), start: 1 },
+        &["page=1&limit=2", "limit=2&page=2", "limit=2&page=3"]
+    )]
+    #[case::offset(
+        PaginationStrategy::Offset { param: "offset".to_string(), start: 0 },
+        &["page=1&limit=2", "page=1&limit=2&offset=2", "page=1&limit=2&offset=3"]
+    )]
+    #[tokio::test]
+    async fn test_paginate_with_query_param(
+        #[case] strategy: PaginationStrategy,
+        #[case] queries: &'static [&'static str],
+    ) {
+        let test_server = spawn_mock_server().await;
+        for (query, items) in queries.iter().zip([json!([1, 2]), json!([3]), json!([])]) {
+            Mock::given(matchers::method("GET"))
+                .and(move |r: &Request| r.url.query() == Some(*query))
+                .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(items))
+                .expect(1)
+                .mount(&test_server.mock)
+                .await;
+        }
+
+        let pagination = Pagination {
+            next: strategy,
+            items: None,
+            max_pages: None,
+        };
+        let url = format!("{}/items?page=1&limit=2", test_server.base_url);
+        let (items, pages) = fetch_all_pages(&url, pagination).await;
+
+        assert_eq!(items, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(pages, 3);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_with_cursor() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::query_param_is_missing("cursor"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!({"data": [1, 2], "next": "abc"})),
+            )
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::query_param("cursor", "abc"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!({"data": [3], "next": null})),
+            )
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let pagination = Pagination {
+            next: PaginationStrategy::Cursor {
+                path: "$.next".to_string(),
+                param: "cursor".to_string(),
+            },
+            items: Some("$.data".to_string()),
+            max_pages: None,
+        };
+        let (items, pages) = fetch_all_pages(&test_server.base_url, pagination).await;
+
+        assert_eq!(items, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(pages, 2);
+    }
+
+    #[rstest]
+    #[case::all_pages(None, 3, 3)]
+    #[case::max_pages(Some(2), 2, 2)]
+    #[tokio::test]
+    async fn test_paginate_with_link_header(
+        #[case] max_pages: Option<u32>,
+        #[case] expected_items: usize,
+        #[case] expected_pages: u32,
+    ) {
+        let test_server = spawn_mock_server().await;
+        for page in 1..=3 {
+            let mut template = ResponseTemplate::new(StatusCode::OK).set_body_json(json!([page]));
+            if page < 3 {
+                template = template
+                    .insert_header("Link", format!(r#"</items?page={}>; rel="next""#, page + 1));
+            }
+            Mock::given(matchers::query_param("page", page.to_string()))
+                .respond_with(template)
+                .mount(&test_server.mock)
+                .await;
+        }
+
+        let pagination = Pagination {
+            next: PaginationStrategy::Link,
+            items: None,
+            max_pages,
+        };
+        let url = format!("{}/items?page=1", test_server.base_url);
+        let (items, pages) = fetch_all_pages(&url, pagination).await;
+
+        assert_eq!(items.len(), expected_items);
+        assert_eq!(pages, expected_pages);
+    }
+
+    #[rstest]
+    #[case::ipv4(IpVersion::V4, true)]
+    #[case::ipv6(IpVersion::V6, false)]
+    #[tokio::test]
+    async fn test_client_connects_to_addresses_of_ip_version(
+        #[case] version: IpVersion,
+        #[case] expected_success: bool,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: format!("http://localhost:{}", test_server.mock.address().port()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_ip_version(version)
+            .execute()
+            .await;
+
+        assert_eq!(res.is_ok(), expected_success);
+    }
+
+    #[rstest]
+    #[case::collection(Some("127.0.0.1"), None, true)]
+    #[case::request(Some("192.0.2.1"), Some("127.0.0.1"), true)]
+    #[case::unassigned(None, Some("192.0.2.1"), false)]
+    #[tokio::test]
+    async fn test_client_connects_from_local_address(
+        #[case] collection_address: Option<&str>,
+        #[case] request_address: Option<&str>,
+        #[case] expected_success: bool,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::method("GET"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            local_address: collection_address.map(|a| a.parse().unwrap()),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                local_address: request_address.map(|a| a.parse().unwrap()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let res = ApiClientRequest::new(collection, request).execute().await;
+
+        assert_eq!(res.is_ok(), expected_success);
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_query_params() {
+        let param_name = "user-id";
+        let param_value = "some-test-value";
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::query_param(param_name, param_value))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let variables = [("param_name", param_name), ("param_value", param_value)];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                params: HttpParamsModel {
+                    query: KeyValueList::from([("{{param_name}}", "{{param_value}}")]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[rstest]
+    #[case("http://localhost:8080/users/:id", "http://localhost:8080/users/42")]
+    #[case("http://localhost/users/{id}/posts", "http://localhost/users/42/posts")]
+    #[case(
+        "http://localhost/users/:id?sort=:id",
+        "http://localhost/users/42?sort=:id"
+    )]
+    #[case(
+        "http://localhost/files/{name}.txt",
+        "http://localhost/files/a%20b%2Fc.txt"
+    )]
+    #[case(
+        "http://localhost/users/:identifier",
+        "http://localhost/users/:identifier"
+    )]
+    fn test_substitute_path_params(#[case] url: &str, #[case] expected: &str) {
+        let params = vec![
+            ("id".to_string(), "42".to_string()),
+            ("name".to_string(), "a b/c".to_string()),
+        ];
+
+        assert_eq!(substitute_path_params(url, &params), expected);
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_path_params() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::path("/users/some%20user/posts/1"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: format!("{}/users/:user/posts/{{post}}", test_server.base_url),
+                params: HttpParamsModel {
+                    path: KeyValueList::from([("user", "{{user}}"), ("post", "1")]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("user", "some user")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[rstest]
+    #[case::encoded_by_default(None, None, "a=x+y%2Fz&b=%253D")]
+    #[case::encoded_param(Some(false), Some(true), "a=x%20y/z&b=%253D")]
+    #[case::raw_param(None, Some(false), "a=x+y%2Fz&b=%3D")]
+    #[case::raw_collection(Some(false), None, "a=x%20y/z&b=%3D")]
+    fn test_prepare_encodes_query_params(
+        #[case] encode_query: Option<bool>,
+        #[case] encode_param: Option<bool>,
+        #[case] expected: &str,
+    ) {
+        let collection = CollectionModel {
+            encode_query,
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                params: HttpParamsModel {
+                    query: KeyValueList::new(vec![
+                        KeyValuePair {
+                            key: "a".to_string(),
+                            value: "x y/z".to_string(),
+                            enabled: None,
+                            enabled_if: None,
+                            encode: None,
+                            secret: None,
+                        },
+                        KeyValuePair {
+                            key: "b".to_string(),
+                            value: "%3D".to_string(),
+                            enabled: None,
+                            enabled_if: None,
+                            encode: encode_param,
+                            secret: None,
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().query(), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_client_fails_on_undefined_variable_in_query_params() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                params: HttpParamsModel {
+                    query: KeyValueList::from([("user", "{{undefined}}")]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        assert!(api_request.prepare().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_text_body() {
+        let key = "some-test-key";
+        let value = "some-test-value";
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_string(format!("{} / {}", key, value)))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let variables = [("key", key), ("value", value)];
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                body: Some(HttpBody::Text(HttpTextBody {
+                    text: "{{key}} / {{value}}".to_string(),
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_json_body() {
+        let key = "some-test-key";
+        let value = "some-test-value";
+
+        let variables = [("key", key), ("value", value)];
+        let expected_body = HashMap::from(variables);
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_json(expected_body))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let body: Value = serde_json::from_str(
+            r#"
+        {
+            "key": "{{key}}",
+            "value": "{{value}}"
+        }
+        "#,
+        )
+        .unwrap();
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                body: Some(HttpBody::Json(HttpJsonBody {
+                    json: body,
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[test]
+    fn test_client_applies_templating_to_ndjson_body() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Post,
+                url: "http://localhost".to_string(),
+                body: Some(HttpBody::Ndjson(HttpNdjsonBody {
+                    ndjson: vec![json!({"key": "{{key}}"}), json!([1, "{{value}}"])],
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("key", "some-key"), ("value", "some-value")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["Content-Type"], "application/x-ndjson");
+        assert_eq!(
+            req.body().and_then(|b| b.as_bytes()).unwrap(),
+            b"{\"key\":\"some-key\"}\n[1,\"some-value\"]\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_graphql_body() {
+        let key = "some-test-key";
+        let value = "some-test-value";
+
+        let variables = [("key", key), ("value", value)];
+        let expected_body: Value = serde_json::from_str(
+            r#"
+            {
+                "query": "query { resolver { some-test-key } }",
+                "variables": {
+                    "some-test-key": "some-test-value",
+                    "number": 123,
+                    "struct": {
+                        "embed": {
+                            "key": "some-test-key",
+                            "value": "some-test-value"
+                        }
+                    }
+                }
+            }
+        "#,
+        )
+        .unwrap();
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_json(expected_body))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let query = "query { resolver { {{key}} } }";
+        let query_vars: HashMap<String, Value> = serde_json::from_str(
+            r#"
+        {
+            "{{key}}": "{{value}}",
+            "number": 123,
+            "struct": {
+                "embed": {
+                    "key": "{{key}}",
+                    "value": "{{value}}"
+                }
+            }
+        }
+        "#,
+        )
+        .unwrap();
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                body: Some(HttpBody::GraphQL(HttpGraphQLBody {
+                    graphql: GraphGLBody {
+                        query: query.to_string(),
+                        variables: query_vars,
+                    },
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_binary_body() {
+        let data = "c29tZS1kYXRh"; // "some-data"
+
+        let variables = [("data", data)];
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::body_bytes("some-data".as_bytes()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                body: Some(HttpBody::Binary(HttpBinaryBody {
+                    binary: "{{data}}".to_string(),
+                    content_type: None,
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_applies_templating_to_form_body() {
+        let key = "some-test-key";
+        let value = "some-test-value";
+
+        let variables = [("key", key), ("value", value)];
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(FormDataMatcher(HashMap::from([(
+            key.to_string(),
+            value.to_string(),
+        )])))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&test_server.mock)
+        .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                body: Some(HttpBody::Form(HttpFormBody {
+                    form: KeyValueList::from([("{{key}}", "{{value}}")]),
+                })),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from(variables),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_cookies_from_cookie_store() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::path("/login"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).insert_header("Set-Cookie", "session=abc123"),
+            )
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::path("/profile"))
+            .and(matchers::header("Cookie", "session=abc123"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let cookie_store = Arc::new(CookieStoreMutex::default());
+
+        for path in ["/login", "/profile"] {
+            let request = RequestModel {
+                http: HttpRequestModel {
+                    method: HttpMethod::Get,
+                    url: format!("{}{}", test_server.base_url, path),
+                    ..Default::default()
+                },
+                vars: Default::default(),
+                ..Default::default()
+            };
+
+            let api_request = ApiClientRequest::new(CollectionModel::default(), request)
+                .with_cookie_store(Arc::clone(&cookie_store));
+
+            api_request.execute().await.expect("request failed");
+        }
+
+        let store = cookie_store.lock().unwrap();
+        let cookies: Vec<_> = store.iter_unexpired().map(|c| c.name_value()).collect();
+        assert_eq!(cookies, vec![("session", "abc123")]);
+    }
+
+    #[rstest]
+    #[case::request_timeout(None, Some(100), None, None, false)]
+    #[case::collection_timeout(Some(100), None, None, None, false)]
+    #[case::request_overrides_collection(Some(100), Some(5000), None, None, true)]
+    #[case::override_timeout(None, Some(5000), Some(100), None, false)]
+    #[case::override_overrides_request(None, Some(100), Some(5000), None, true)]
+    #[case::default_timeout(None, None, None, Some(100), false)]
+    #[case::collection_overrides_default(Some(5000), None, None, Some(100), true)]
+    #[tokio::test]
+    async fn test_client_applies_timeout(
+        #[case] collection_timeout: Option<u64>,
+        #[case] request_timeout: Option<u64>,
+        #[case] override_timeout: Option<u64>,
+        #[case] default_timeout: Option<u64>,
+        #[case] expect_success: bool,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_delay(Duration::from_millis(500)),
+            )
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            timeout: collection_timeout.map(Duration::from_millis),
+            ..Default::default()
+        };
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                timeout: request_timeout.map(Duration::from_millis),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut api_request = ApiClientRequest::new(collection, request);
+        if let Some(t) = override_timeout {
+            api_request = api_request.with_timeout(Duration::from_millis(t));
+        }
+        if let Some(t) = default_timeout {
+            api_request = api_request.with_default_timeout(Duration::from_millis(t));
+        }
+
+        let res = api_request.execute().await;
+        assert_eq!(res.is_ok(), expect_success);
+    }
+
+    #[rstest]
+    #[case::succeeds_after_retries(3, StatusCode::OK, 3)]
+    #[case::gives_up_after_max_attempts(2, StatusCode::SERVICE_UNAVAILABLE, 2)]
+    #[tokio::test]
+    async fn test_client_retries_failed_requests(
+        #[case] max_attempts: u32,
+        #[case] expected_status: StatusCode,
+        #[case] expected_calls: u64,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::SERVICE_UNAVAILABLE))
+            .up_to_n_times(2)
+            .expect(expected_calls.min(2))
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(expected_calls.saturating_sub(2))
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                retry: Some(RetryPolicy {
+                    max_attempts,
+                    backoff: RetryBackoff::Fixed,
+                    delay: Duration::from_millis(10),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        let res = api_request.execute().await.expect("request failed");
+        assert_eq!(res.status(), expected_status);
+    }
+
+    #[tokio::test]
+    async fn test_client_does_not_retry_by_default() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::SERVICE_UNAVAILABLE))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        let res = api_request.execute().await.expect("request failed");
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_client_retries_override_collection_policy() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::BAD_GATEWAY))
+            .expect(3)
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            retry: Some(RetryPolicy {
+                max_attempts: 5,
+                delay: Duration::from_millis(10),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request).with_retries(2);
+
+        let res = api_request.execute().await.expect("request failed");
+        assert_eq!(res.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[rstest]
+    #[case::fixed(RetryBackoff::Fixed, [100, 100, 100])]
+    #[case::exponential(RetryBackoff::Exponential, [100, 200, 400])]
+    fn test_retry_policy_backoff(#[case] backoff: RetryBackoff, #[case] expected: [u64; 3]) {
+        let policy = RetryPolicy {
+            backoff,
+            delay: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let delays: Vec<Duration> = (1..=3).map(|a| policy.delay_for_attempt(a)).collect();
+        assert_eq!(delays, expected.map(Duration::from_millis).to_vec(),);
+    }
+
+    #[rstest]
+    #[case::seconds("120", Some(120))]
+    #[case::date("Wed, 21 Oct 2015 07:30:00 GMT", Some(120))]
+    #[case::date_in_the_past("Wed, 21 Oct 2015 07:00:00 GMT", Some(0))]
+    #[case::invalid("soon", None)]
+    fn test_retry_after(#[case] value: &str, #[case] expected: Option<u64>) {
+        let mut headers = HeaderMap::new();
+        headers.insert("Retry-After", HeaderValue::from_str(value).unwrap());
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+        assert_eq!(
+            retry_after(&headers, now),
+            expected.map(Duration::from_secs)
+        );
+        assert_eq!(retry_after(&HeaderMap::new(), SystemTime::now()), None);
+    }
+
+    #[rstest]
+    #[case::honored(Duration::from_secs(5), Duration::from_secs(1))]
+    #[case::capped(Duration::from_millis(100), Duration::from_millis(100))]
+    #[tokio::test]
+    async fn test_client_waits_for_retry_after(
+        #[case] max_retry_after: Duration,
+        #[case] expected_wait: Duration,
+    ) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(
+                ResponseTemplate::new(StatusCode::TOO_MANY_REQUESTS)
+                    .insert_header("Retry-After", "1"),
+            )
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                retry: Some(RetryPolicy {
+                    delay: Duration::from_millis(10),
+                    max_retry_after,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        let start = Instant::now();
+        let res = api_request.execute().await.expect("request failed");
+        let elapsed = start.elapsed();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(elapsed >= expected_wait);
+        assert!(elapsed < expected_wait + Duration::from_millis(900));
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_request_through_proxy() {
+        let proxy_server = spawn_mock_server().await;
+        Mock::given(matchers::path("/foo"))
+            .and(matchers::header(
+                "Proxy-Authorization",
+                "Basic dXNlcjpwYXNz",
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&proxy_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://api.example.org/foo".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let proxy = ProxyConfig {
+            url: proxy_server.base_url,
+            username: Some("user".to_string()),
+            password: Some("pass".to_string()),
+            no_proxy: vec![],
+        };
+
+        let api_request =
+            ApiClientRequest::new(CollectionModel::default(), request).with_proxy(proxy);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[tokio::test]
+    async fn test_client_bypasses_proxy_for_no_proxy_hosts() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let environment = EnvironmentModel {
+            proxy: Some(ProxyConfig {
+                url: "http://proxy.invalid:3128".to_string(),
+                username: None,
+                password: None,
+                no_proxy: vec!["127.0.0.1".to_string()],
+            }),
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[rstest]
+    #[case::runtime(None, "runtime")]
+    #[case::overridden(Some("override"), "override")]
+    fn test_prepare_merges_runtime_variables(
+        #[case] override_value: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost/{{value}}".to_string(),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("value", "request")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let environment = EnvironmentModel {
+            vars: KeyValueList::from([("value", "environment")]),
+            ..Default::default()
+        };
+
+        let mut api_request = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .with_runtime_variables(HashMap::from([(
+                "value".to_string(),
+                "runtime".to_string(),
+            )]));
+        if let Some(v) = override_value {
+            api_request = api_request
+                .with_override_variables(HashMap::from([("value".to_string(), v.to_string())]));
+        }
+
+        let request = api_request.prepare().expect("request should be valid");
+
+        assert_eq!(request.url().path(), format!("/{}", expected));
+    }
+
+    #[rstest]
+    #[case::first_provider(Some("first"), None, "first")]
+    #[case::request_variable(Some("first"), Some("request"), "request")]
+    #[case::next_provider(None, None, "second")]
+    fn test_prepare_asks_variable_providers(
+        #[case] first_value: Option<&str>,
+        #[case] request_value: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost/{{value}}".to_string(),
+                ..Default::default()
+            },
+            vars: RequestVarsModel {
+                pre_request: request_value
+                    .map(|v| KeyValueList::from([("value", v)]))
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let first: HashMap<String, String> = first_value
+            .map(|v| ("value".to_string(), v.to_string()))
+            .into_iter()
+            .collect();
+        let second = HashMap::from([("value".to_string(), "second".to_string())]);
+
+        let request = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_variable_provider(Arc::new(first))
+            .with_variable_provider(Arc::new(second))
+            .prepare()
+            .expect("request should be valid");
+
+        assert_eq!(request.url().path(), format!("/{}", expected));
+    }
+
+    #[test]
+    fn test_prepare_masks_values_of_secret_providers() {
+        struct SecretProvider;
+
+        impl VariableProvider for SecretProvider {
+            fn get(&self, key: &str) -> Option<String> {
+                (key == "token").then(|| "provided-token".to_string())
+            }
+
+            fn is_secret(&self) -> bool {
+                true
+            }
+        }
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("Authorization", "Bearer {{token}}")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (request, masker) = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_variable_provider(Arc::new(SecretProvider))
+            .prepare_masked()
+            .expect("request should be valid");
+
+        let header = &request.headers()["Authorization"];
+        assert_eq!(header, "Bearer provided-token");
+        assert!(header.is_sensitive());
+        assert_eq!(masker.mask("Bearer provided-token"), "Bearer ********");
+    }
+
+    #[rstest]
+    #[case::none(None, None, None)]
+    #[case::collection(Some(200), None, Some(200))]
+    #[case::request(None, Some(100), Some(100))]
+    #[case::request_over_collection(Some(200), Some(100), Some(100))]
+    fn test_max_latency(
+        #[case] collection_max: Option<u64>,
+        #[case] request_max: Option<u64>,
+        #[case] expected: Option<u64>,
+    ) {
+        let collection = CollectionModel {
+            max_latency: collection_max.map(Duration::from_millis),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                max_latency: request_max.map(Duration::from_millis),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request);
+
+        assert_eq!(
+            api_request.max_latency(),
+            expected.map(Duration::from_millis)
+        );
+    }
+
+    #[rstest]
+    #[case::code("404", 404, true)]
+    #[case::other_code("404", 200, false)]
+    #[case::class("2xx", 204, true)]
+    #[case::uppercase_class("4XX", 422, true)]
+    #[case::other_class("2xx", 301, false)]
+    #[case::list("[404, 5xx]", 503, true)]
+    #[case::not_in_list("[404, 5xx]", 422, false)]
+    fn test_expected_status_matches(
+        #[case] yaml: &str,
+        #[case] status: u16,
+        #[case] expected: bool,
+    ) {
+        let expected_status: ExpectedStatus = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(expected_status.matches(status), expected);
+    }
+
+    #[rstest]
+    #[case::class("6xx")]
+    #[case::code("99")]
+    #[case::text("ok")]
+    fn test_expected_status_rejects_invalid_values(#[case] yaml: &str) {
+        let err = serde_yaml::from_str::<ExpectedStatus>(yaml).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("expected a code like 404 or a class like 2xx"));
+    }
+
+    #[test]
+    fn test_expected_status_serialization() {
+        let expected_status =
+            ExpectedStatus::Any(vec![StatusMatcher::Code(404), StatusMatcher::Class(5)]);
+
+        let yaml = serde_yaml::to_string(&expected_status).unwrap();
+
+        assert_eq!(yaml, "- 404\n- 5xx\n");
+        assert_eq!(expected_status.to_string(), "404 or 5xx");
+    }
+
+    #[test]
+    fn test_retry_policy_keeps_empty_on_status() {
+        let policy: RetryPolicy = serde_yaml::from_str("on_status: []").unwrap();
+
+        let yaml = serde_yaml::to_string(&policy).unwrap();
+        let policy: RetryPolicy = serde_yaml::from_str(&yaml).unwrap();
+
+        assert!(policy.on_status.is_empty(), "{}", yaml);
+    }
+
+    #[rstest]
+    #[case::inherited(None, Some("auth/login"))]
+    #[case::explicitly_inherited(Some(HttpAuth::Inherit), Some("auth/login"))]
+    #[case::overridden(Some(HttpAuth::None), None)]
+    fn test_token_refresh(#[case] request_auth: Option<HttpAuth>, #[case] expected: Option<&str>) {
+        let collection = CollectionModel {
+            auth: Some(HttpAuth::Bearer(HttpBearerToken {
+                token: "{{token}}".to_string(),
+                refresh: Some(TokenRefresh {
+                    request: "auth/login".to_string(),
+                    expires_in: None,
+                }),
+            })),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                auth: request_auth,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request);
+
+        assert_eq!(
+            api_request.token_refresh().map(|r| r.request.as_str()),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_interpolates_process_env_vars_in_environment() {
+        std::env::set_var("API_CLI_TEST_ENV_TOKEN", "some-token");
+
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::header("Authorization", "Bearer some-token"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                headers: KeyValueList::from([("Authorization", "Bearer {{token}}")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let environment = EnvironmentModel {
+            vars: KeyValueList::from([("token", "${API_CLI_TEST_ENV_TOKEN}")]),
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment);
+
+        api_request.execute().await.expect("request failed");
+    }
+
+    #[rstest]
+    #[case::environment_auth(None, Some("env-token"), "Bearer env-token")]
+    #[case::request_auth(Some("request-token"), Some("env-token"), "Bearer request-token")]
+    #[case::collection_auth(None, None, "Bearer collection-token")]
+    fn test_prepare_merges_environment_headers_and_auth(
+        #[case] request_token: Option<&str>,
+        #[case] env_token: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let bearer = |token: &str| {
+            HttpAuth::Bearer(HttpBearerToken {
+                token: token.to_string(),
+                refresh: None,
+            })
+        };
+
+        let collection = CollectionModel {
+            headers: KeyValueList::from([("X-Env", "collection"), ("X-Collection", "collection")]),
+            auth: Some(bearer("collection-token")),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("X-Request", "request")]),
+                auth: request_token.map(bearer),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let environment = EnvironmentModel {
+            vars: KeyValueList::from([("name", "staging")]),
+            headers: KeyValueList::from([("X-Env", "{{name}}"), ("X-Request", "env")]),
+            auth: env_token.map(bearer),
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .with_environment(environment)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["X-Collection"], "collection");
+        assert_eq!(req.headers()["X-Env"], "staging");
+        assert_eq!(req.headers()["X-Request"], "request");
+        assert_eq!(req.headers()["Authorization"], expected);
+    }
+
+    #[test]
+    fn test_prepare_drops_disabled_headers() {
+        let collection = CollectionModel {
+            headers: KeyValueList::from([("X-Collection", "collection"), ("Accept", "text/plain")]),
+            headers_disabled: vec!["accept-encoding".to_string()],
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("X-Request", "request")]),
+                headers_disabled: vec![
+                    "Accept".to_string(),
+                    "X-Request".to_string(),
+                    "User-Agent".to_string(),
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["X-Collection"], "collection");
+        assert_eq!(req.headers()["X-Request"], "request");
+        assert!(req.headers().get("Accept").is_none());
+        assert!(req.headers().get("Accept-Encoding").is_none());
+        assert!(req.headers().get("User-Agent").is_none());
+    }
+
+    #[rstest]
+    #[case::default(None, None, None, crate::APP_USER_AGENT)]
+    #[case::header(None, None, Some("header/1.0"), "header/1.0")]
+    #[case::collection(Some("{{name}}/1.0"), None, None, "collection/1.0")]
+    #[case::request(
+        Some("{{name}}/1.0"),
+        Some("request/2.0"),
+        Some("header/1.0"),
+        "request/2.0"
+    )]
+    fn test_prepare_sets_user_agent(
+        #[case] collection_user_agent: Option<&str>,
+        #[case] request_user_agent: Option<&str>,
+        #[case] header: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let collection = CollectionModel {
+            vars: KeyValueList::from([("name", "collection")]),
+            user_agent: collection_user_agent.map(str::to_string),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers: header
+                    .map(|h| KeyValueList::from([("User-Agent", h)]))
+                    .unwrap_or_default(),
+                user_agent: request_user_agent.map(str::to_string),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["User-Agent"], expected);
+    }
+
+    #[rstest]
+    #[case::no_header(None, None, None, None)]
+    #[case::collection(Some("X-Request-Id"), None, None, Some("run-1"))]
+    #[case::option(Some("X-Request-Id"), Some("X-Correlation-Id"), None, None)]
+    #[case::request(Some("X-Request-Id"), None, Some("custom"), Some("custom"))]
+    fn test_prepare_sends_request_id(
+        #[case] collection_header: Option<&str>,
+        #[case] header: Option<&str>,
+        #[case] request_header: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let collection = CollectionModel {
+            request_id_header: collection_header.map(str::to_string),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost/{{requestId}}".to_string(),
+                headers: request_header
+                    .map(|h| KeyValueList::from([("X-Request-Id", h)]))
+                    .unwrap_or_default(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut req =
+            ApiClientRequest::new(collection, request).with_request_id("run-1".to_string());
+        if let Some(h) = header {
+            req = req.with_request_id_header(h.to_string());
+        }
+        let req = req.prepare().expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "http://localhost/run-1");
+        assert_eq!(
+            req.headers()
+                .get("X-Request-Id")
+                .map(|v| v.to_str().unwrap()),
+            expected
+        );
+        if header.is_some() {
+            assert_eq!(req.headers()["X-Correlation-Id"], "run-1");
+        }
+    }
+
+    #[rstest]
+    #[case::parent_only(None)]
+    #[case::with_state(Some("vendor=abc"))]
+    fn test_prepare_sends_trace_context(#[case] state: Option<&str>) {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("traceparent", "replaced")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let parent: TraceParent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+            .parse()
+            .unwrap();
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_trace_context(parent, state.map(str::to_string))
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(
+            req.headers()["traceparent"],
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+        assert_eq!(
+            req.headers().get("tracestate").map(|v| v.to_str().unwrap()),
+            state
+        );
+    }
+
+    #[test]
+    fn test_prepare_sends_conditional_headers() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("If-Modified-Since", "kept")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let validators = Validators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+        };
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_validators(validators)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["if-none-match"], "\"abc\"");
+        assert_eq!(req.headers()["if-modified-since"], "kept");
+    }
+
+    #[test]
+    fn test_prepare_rejects_invalid_disabled_header() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                headers_disabled: vec!["X A".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = ApiClientRequest::new(CollectionModel::default(), request)
+            .prepare()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "InvalidHeader: Invalid header name: \"X A\""
+        );
+    }
+
+    #[rstest]
+    #[case::url(
+        "url: http://{{host}}",
+        "Failed to render `url` of request.yaml: variable `host` is not defined"
+    )]
+    #[case::header(
+        "url: http://localhost\n  headers:\n    - key: X-Id\n      value: '{{id}}'",
+        "Failed to render `headers.X-Id` of request.yaml: variable `id` is not defined"
+    )]
+    #[case::json_body(
+        "url: http://localhost\n  body:\n    type: json\n    json:\n      users:\n        - name: '{{name}}'",
+        "Failed to render `body.json` of request.yaml: variable `name` is not defined"
+    )]
+    #[case::graphql_variable(
+        "url: http://localhost\n  body:\n    type: graphql\n    graphql:\n      query: q\n      variables:\n        filter:\n          ids: ['{{id}}']",
+        "Failed to render `body.graphql.variables.filter.ids[0]` of request.yaml: variable `id` is not defined"
+    )]
+    fn test_prepare_names_the_field_of_template_errors(#[case] http: &str, #[case] expected: &str) {
+        let request: RequestModel =
+            serde_yaml::from_str(&format!("http:\n  method: GET\n  {}\n", http))
+                .expect("invalid request");
+
+        let err = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_request_file(PathBuf::from("request.yaml"))
+            .prepare()
+            .expect_err("prepare should fail");
+
+        assert!(err.to_string().ends_with(expected), "{}", err);
+    }
+
+    #[test]
+    fn test_prepare_names_the_source_of_inherited_template_errors() {
+        let collection = CollectionModel {
+            headers: KeyValueList::from([("X-Token", "{{token}}")]),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = ApiClientRequest::new(collection, request)
+            .with_request_file(PathBuf::from("request.yaml"))
+            .prepare()
+            .expect_err("prepare should fail");
+
+        assert!(
+            err.to_string().ends_with(
+                "Failed to render `collection.headers.X-Token`: variable `token` is not defined"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_prepare_masks_secret_variables() {
+        let environment: EnvironmentModel = serde_yaml::from_str(
+            r#"
+            vars:
+              - key: user_id
+                value: abc-123
+                secret: true
+              - key: api_token
+                value: xyz-789
+              - key: region
+                value: us-east-1
+            "#,
+        )
+        .unwrap();
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost/users/{{user_id}}?region={{region}}".to_string(),
+                headers: KeyValueList::from([
+                    ("X-Key", "{{api_token}}"),
+                    ("X-Region", "{{region}}"),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (req, masker) = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .prepare_masked()
+            .expect("prepare failed");
+
+        assert_eq!(
+            masker.mask(req.url().as_str()),
+            "http://localhost/users/********?region=us-east-1"
+        );
+        assert!(req.headers()["X-Key"].is_sensitive());
+        assert!(!req.headers()["X-Region"].is_sensitive());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_does_not_render_values_of_secret_stores() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/v1/secret/app"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!({"data": {"password": "p{{a}}ss"}})),
+            )
+            .mount(&server)
+            .await;
+        std::env::set_var("VAULT_ADDR", server.uri());
+        std::env::set_var("VAULT_TOKEN", "root");
+        let environment: EnvironmentModel = serde_yaml::from_str(
+            r#"
+            vars:
+              - key: password
+                value: vault:secret/app#password
+            "#,
+        )
+        .unwrap();
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost".to_string(),
+                headers: KeyValueList::from([("X-Password", "{{password}}")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.headers()["X-Password"], "p{{a}}ss");
+    }
+
+    #[test]
+    fn test_environment_variables_can_be_numbers_or_booleans() {
+        let environment: EnvironmentModel = serde_yaml::from_str(
+            r#"
+            vars:
+              - key: port
+                value: 8080
+              - key: verbose
+                value: true
+              - key: ratio
+                value: 1.5
+              - key: limits
+                value:
+                  retries: 3
+                  strict: false
+            "#,
+        )
+        .unwrap();
+
+        let vars: Vec<_> = environment
+            .vars
+            .items()
+            .map(|v| (v.key.as_str(), v.value.as_str()))
+            .collect();
+
+        assert_eq!(
+            vars,
+            vec![
+                ("port", "8080"),
+                ("verbose", "true"),
+                ("ratio", "1.5"),
+                ("limits.retries", "3"),
+                ("limits.strict", "false"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prepare_renders_namespaced_variables() {
+        let environment: EnvironmentModel = serde_yaml::from_str(
+            r#"
+            vars:
+              - key: auth
+                value:
+                  token: abc-123
+                  user: admin
+              - key: hosts
+                value:
+                  billing:
+                    url: http://billing
+            "#,
+        )
+        .unwrap();
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "{{hosts.billing.url}}/users/{{auth.user}}".to_string(),
+                headers: KeyValueList::from([
+                    ("X-Token", "{{auth.token}}"),
+                    ("X-Stage", "{{stage}}"),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .with_override_variables(HashMap::from([("stage".to_string(), "prod".to_string())]))
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "http://billing/users/admin");
+        assert_eq!(req.headers()["X-Token"], "abc-123");
+        assert_eq!(req.headers()["X-Stage"], "prod");
+    }
+
+    #[test]
+    fn test_prepare_renders_variables_referencing_variables() {
+        let collection = CollectionModel {
+            vars: KeyValueList::from([
+                ("api_url", "{{base_url}}/v2"),
+                ("base_url", "https://{{host}}"),
+                ("host", "collection"),
+            ]),
+            ..Default::default()
+        };
+        let environment = EnvironmentModel {
+            vars: KeyValueList::from([("host", "{{region}}.example.com"), ("region", "eu")]),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "{{api_url}}/users".to_string(),
+                headers: KeyValueList::from([("X-Captured", "{{captured}}")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .with_environment(environment)
+            .with_runtime_variables(HashMap::from([(
+                "captured".to_string(),
+                "{{not a template}}".to_string(),
+            )]))
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "https://eu.example.com/v2/users");
+        assert_eq!(req.headers()["X-Captured"], "{{not a template}}");
+    }
+
+    #[test]
+    fn test_prepare_rejects_variable_cycles() {
+        let collection = CollectionModel {
+            vars: KeyValueList::from([("a", "{{b}}"), ("b", "x{{c}}"), ("c", "{{a}}")]),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "http://localhost/{{a}}".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = ApiClientRequest::new(collection, request)
+            .prepare()
+            .expect_err("prepare should fail");
+
+        assert!(
+            err.to_string()
+                .ends_with("Variable cycle: a -> b -> c -> a"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_prepare_skips_items_not_enabled() {
+        let request: RequestModel = serde_yaml::from_str(
+            r#"
+            http:
+              method: POST
+              url: http://localhost
+              headers:
+                - key: X-Debug
+                  value: "1"
+                  enabled_if: "{{debug}}"
+                - key: X-Staging
+                  value: "1"
+                  enabled_if: "{{eq stage \"staging\"}}"
+              params:
+                query:
+                  - key: verbose
+                    value: "true"
+                    enabled_if: "{{debug}}"
+                  - key: page
+                    value: "1"
+              body:
+                type: form
+                form:
+                  - key: name
+                    value: api
+                  - key: trace
+                    value: "on"
+                    enabled_if: "{{default trace \"no\"}}"
+            "#,
+        )
+        .unwrap();
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_override_variables(HashMap::from([
+                ("debug".to_string(), "true".to_string()),
+                ("stage".to_string(), "prod".to_string()),
+            ]))
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "http://localhost/?verbose=true&page=1");
+        assert_eq!(req.headers()["X-Debug"], "1");
+        assert!(!req.headers().contains_key("X-Staging"));
+        assert_eq!(
+            req.body().and_then(|b| b.as_bytes()),
+            Some(&b"name=api"[..])
+        );
+    }
+
+    #[rstest]
+    #[case("true", true)]
+    #[case("yes", true)]
+    #[case("1", true)]
+    #[case("", false)]
+    #[case(" False ", false)]
+    #[case("0", false)]
+    #[case("off", false)]
+    fn test_is_truthy(#[case] value: &str, #[case] expected: bool) {
+        assert_eq!(is_truthy(value), expected);
+    }
+
+    #[test]
+    fn test_template_data_nests_dotted_variables() {
+        let values = HashMap::from([
+            ("auth".to_string(), "replaced".to_string()),
+            ("auth.token".to_string(), "abc".to_string()),
+            ("auth.user.name".to_string(), "admin".to_string()),
+            ("trailing.".to_string(), "kept".to_string()),
+        ]);
+
+        assert_eq!(
+            template_data(&values),
+            json!({
+                "auth": {"token": "abc", "user": {"name": "admin"}},
+                "trailing.": "kept",
+            })
+        );
+    }
+
+    #[test]
+    fn test_prepare_merges_folders() {
+        let bearer = |token: &str| {
+            HttpAuth::Bearer(HttpBearerToken {
+                token: token.to_string(),
+                refresh: None,
+            })
+        };
+
+        let collection = CollectionModel {
+            headers: KeyValueList::from([("X-Level", "collection")]),
+            auth: Some(bearer("collection-token")),
+            vars: KeyValueList::from([("host", "http://collection"), ("path", "collection")]),
+            ..Default::default()
+        };
+        let outer = FolderModel {
+            headers: KeyValueList::from([("X-Level", "outer"), ("X-Outer", "{{path}}")]),
+            auth: Some(bearer("{{path}}-token")),
+            vars: KeyValueList::from([("host", "http://outer"), ("path", "outer")]),
+        };
+        let inner = FolderModel {
+            headers: KeyValueList::from([("X-Level", "inner")]),
+            auth: Some(HttpAuth::Inherit),
+            vars: KeyValueList::from([("path", "inner")]),
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "{{host}}/{{path}}".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(collection, request)
+            .with_folder(outer)
+            .with_folder(inner)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "http://outer/inner");
+        assert_eq!(req.headers()["X-Level"], "inner");
+        assert_eq!(req.headers()["X-Outer"], "inner");
+        assert_eq!(req.headers()["Authorization"], "Bearer inner-token");
+    }
+
+    #[test]
+    fn test_prepare_folders_override_environment() {
+        let environment = EnvironmentModel {
+            headers: KeyValueList::from([("X-Level", "environment")]),
+            vars: KeyValueList::from([("host", "http://environment")]),
+            ..Default::default()
+        };
+        let folder = FolderModel {
+            headers: KeyValueList::from([("X-Level", "folder")]),
+            vars: KeyValueList::from([("host", "http://folder")]),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: "{{host}}/".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let req = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .with_folder(folder)
+            .prepare()
+            .expect("prepare failed");
+
+        assert_eq!(req.url().as_str(), "http://folder/");
+        assert_eq!(req.headers()["X-Level"], "folder");
+    }
+
+    #[rstest]
+    #[case::ca_cert(
+        TlsConfig { ca_cert: Some(PathBuf::from("./tests/certs/client.pem")), ..Default::default() },
+        true,
+    )]
+    #[case::pem_identity(
+        TlsConfig {
+            client_cert: Some(PathBuf::from("./tests/certs/client.pem")),
+            client_key: Some(PathBuf::from("./tests/certs/client.key")),
+            ..Default::default()
+        },
+        true,
+    )]
+    #[case::pkcs12_identity(
+        TlsConfig {
+            client_cert: Some(PathBuf::from("./tests/certs/client.p12")),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        },
+        true,
+    )]
+    #[case::insecure(TlsConfig { insecure: true, ..Default::default() }, true)]
+    #[case::missing_ca_cert(
+        TlsConfig { ca_cert: Some(PathBuf::from("./tests/certs/missing.pem")), ..Default::default() },
+        false,
+    )]
+    #[case::invalid_pkcs12_password(
+        TlsConfig {
+            client_cert: Some(PathBuf::from("./tests/certs/client.p12")),
+            password: Some("invalid".to_string()),
+            ..Default::default()
+        },
+        false,
+    )]
+    #[tokio::test]
+    async fn test_client_applies_tls_config(#[case] tls: TlsConfig, #[case] expect_success: bool) {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .mount(&test_server.mock)
+            .await;
+
+        let collection = CollectionModel {
+            tls: Some(tls),
+            ..Default::default()
+        };
+
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let api_request = ApiClientRequest::new(collection, request);
+
+        let res = api_request.execute().await;
+        assert_eq!(res.is_ok(), expect_success);
+    }
+
+    #[tokio::test]
+    async fn test_client_resolves_tls_paths_from_collection_dir() {
+        let test_server = spawn_mock_server().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&test_server.mock)
+            .await;
+
+        let environment = EnvironmentModel {
+            tls: Some(TlsConfig {
+                client_cert: Some(PathBuf::from("certs/client.pem")),
+                client_key: Some(PathBuf::from("certs/client.key")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let request = RequestModel {
+            http: HttpRequestModel {
+                method: HttpMethod::Get,
+                url: test_server.base_url,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        ApiClientRequest::new(CollectionModel::default(), request)
+            .with_environment(environment)
+            .with_collection_dir(PathBuf::from("tests"))
+            .execute()
+            .await
+            .expect("request failed");
+    }
+
+    async fn spawn_grpc_server() -> String {
+        let (mut reporter, health_service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("api-cli", tonic_health::ServingStatus::NotServing)
+            .await;
+
+        let reflection_service = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+            .build_v1alpha()
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(health_service)
+                .add_service(reflection_service)
+                .serve_with_incoming(incoming),
+        );
+
+        url
+    }
+
+    fn grpc_request(url: String, proto: Option<&str>, service: &str) -> RequestModel {
+        RequestModel {
+            grpc: Some(GrpcRequestModel {
+                url,
+                method: "grpc.health.v1.Health/Check".to_string(),
+                proto: proto.map(|p| {
+                    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+                    path.push(p);
+                    path
+                }),
+                import_paths: Vec::new(),
+                metadata: KeyValueList::from([("x-test-header", "some-test-value")]),
+                message: serde_json::json!({ "service": "{{service}}" }),
+                timeout: None,
+                max_latency: None,
+            }),
+            vars: RequestVarsModel {
+                pre_request: KeyValueList::from([("service", service)]),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case::reflection(None, "", Code::Ok, Some(serde_json::json!({"status": "SERVING"})))]
+    #[case::proto_file(
+        Some("tests/protos/health.proto"),
+        "api-cli",
+        Code::Ok,
+        Some(serde_json::json!({"status": "NOT_SERVING"})),
+    )]
+    #[case::error_status(None, "unknown-service", Code::NotFound, None)]
+    #[tokio::test]
+    async fn api_client_executes_grpc_call(
+        #[case] proto: Option<&str>,
+        #[case] service: &str,
+        #[case] expected_code: Code,
+        #[case] expected_body: Option<Value>,
+    ) {
+        let url = spawn_grpc_server().await;
+
+        let api_request = ApiClientRequest::new(
+            CollectionModel::default(),
+            grpc_request(url, proto, service),
+        );
+        assert!(api_request.is_grpc());
+
+        let res = api_request.execute_grpc().await.expect("request failed");
+
+        assert_eq!(res.code(), expected_code);
+        assert_eq!(res.body(), expected_body.as_ref());
+    }
+
+    #[tokio::test]
+    async fn api_client_fails_on_unknown_grpc_method() {
+        let url = spawn_grpc_server().await;
+
+        let mut request = grpc_request(url, None, "");
+        request.grpc.as_mut().unwrap().method = "grpc.health.v1.Health/Unknown".to_string();
+
+        let api_request = ApiClientRequest::new(CollectionModel::default(), request);
+
+        assert!(api_request.execute_grpc().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn api_client_fails_on_grpc_call_of_http_request() {
+        let request = RequestModel {
+            http: HttpRequestModel {
+                url: "http://localhost".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let err = ApiClientRequest::new(CollectionModel::default(), request)
+            .execute_grpc()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "GrpcError: The request is not a gRPC request"
+        );
+    }
+
+    #[tokio::test]
+    async fn api_client_loads_proto_from_collection_dir() {
+        let url = spawn_grpc_server().await;
+        let mut request = grpc_request(url, None, "api-cli");
+        if let Some(grpc) = request.grpc.as_mut() {
+            grpc.proto = Some(PathBuf::from("protos/health.proto"));
+            grpc.import_paths = vec![PathBuf::from("protos")];
+        }
+
+        let res = ApiClientRequest::new(CollectionModel::default(), request)
+            .with_collection_dir(PathBuf::from("tests"))
+            .execute_grpc()
+            .await
+            .expect("request failed");
+
+        assert_eq!(
+            res.body(),
+            Some(&serde_json::json!({"status": "NOT_SERVING"}))
+        );
+    }
+
+    #[rstest]
+    #[case::http("http:\n  method: GET\n  url: http://localhost", true)]
+    #[case::grpc("grpc:\n  url: http://localhost:50051\n  method: a.B/C", true)]
+    #[case::neither("vars:\n  pre_request: []", false)]
+    fn test_request_requires_http_unless_grpc(#[case] yaml: &str, #[case] valid: bool) {
+        let request = serde_yaml::from_str::<RequestModel>(yaml);
+
+        match valid {
+            true => assert!(request.is_ok(), "{:?}", request),
+            false => assert!(request
+                .unwrap_err()
+                .to_string()
+                .contains("missing field `http`")),
+        }
+    }
 }
@@ -0,0 +1,786 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fmt, fs};
+
+use serde::de::DeserializeOwned;
+
+use crate::helpers::is_helper;
+use crate::models::{
+    CollectionModel,
+    EnvironmentModel,
+    FolderModel,
+    HttpAuth,
+    HttpBearerToken,
+    KeyValueList,
+    RequestModel,
+    FOLDER_FILE_NAME,
+};
+use crate::{RequestName, REQUEST_ID_VARIABLE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in one of the files of a collection.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// Line of the problem, starting at 1, if it could be located.
+    pub line: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Check the files of a collection for schema errors, unknown fields, empty urls, duplicate
+/// variables, unknown dependencies and variables used in templates that are not defined anywhere.
+pub fn lint_collection(
+    collection_path: &Path,
+    environment_paths: &[PathBuf],
+    request_paths: &[PathBuf],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let collection = parse_file::<CollectionModel>(collection_path, &mut diagnostics);
+    let environments: Vec<(String, EnvironmentModel)> = environment_paths
+        .iter()
+        .filter_map(|p| {
+            let (source, env) = parse_file::<EnvironmentModel>(p, &mut diagnostics)?;
+            check_duplicate_variables(p, &source, &env.vars, &mut diagnostics);
+
+            let name = p.file_stem().unwrap_or_default().to_string_lossy();
+            Some((name.to_string(), env))
+        })
+        .collect();
+
+    let mut scope = Scope {
+        defined: HashSet::from([REQUEST_ID_VARIABLE.to_string()]),
+        environments: environments
+            .iter()
+            .map(|(name, env)| (name.clone(), variable_names(&env.vars)))
+            .collect(),
+    };
+
+    let collection_dir = collection_path.parent().unwrap_or(Path::new(""));
+    let mut folders = HashMap::new();
+    let requests = request_dependencies(collection_dir, request_paths);
+
+    if let Some((source, collection)) = &collection {
+        check_duplicate_variables(collection_path, source, &collection.vars, &mut diagnostics);
+
+        scope.defined.extend(variable_names(&collection.vars));
+        scope.defined.extend(captured_by_token_request(
+            collection.auth.as_ref(),
+            collection_dir,
+            &requests,
+        ));
+        check_templates(collection_path, source, &scope, &mut diagnostics);
+    }
+
+    for path in request_paths {
+        let folder_vars = match path.parent() {
+            Some(dir) => {
+                folder_variables(collection_dir, dir, &scope, &mut folders, &mut diagnostics)
+            }
+            None => HashSet::new(),
+        };
+
+        let Some((source, request)) = parse_file::<RequestModel>(path, &mut diagnostics) else {
+            continue;
+        };
+
+        if request.grpc.is_none() && request.http.url.trim().is_empty() {
+            diagnostics.push(Diagnostic {
+                path: path.clone(),
+                line: find_key_line(&source, "url"),
+                severity: Severity::Error,
+                message: "The url is empty".to_string(),
+            });
+        }
+
+        let pre_request = &request.vars.pre_request;
+        check_duplicate_variables(path, &source, pre_request, &mut diagnostics);
+
+        for dependency in &request.depends_on {
+            let message = match dependency.parse::<RequestName>() {
+                Ok(d) if requests.contains_key(&collection_dir.join(d.to_path())) => continue,
+                Ok(d) => format!("Unknown dependency `{}`", d),
+                Err(e) => e,
+            };
+            diagnostics.push(Diagnostic {
+                path: path.clone(),
+                line: find_value_line(&source, dependency),
+                severity: Severity::Error,
+                message,
+            });
+        }
+
+        if let Some(HttpAuth::Bearer(HttpBearerToken {
+            refresh: Some(refresh),
+            ..
+        })) = &request.http.auth
+        {
+            let message = match refresh.request.parse::<RequestName>() {
+                Ok(r) if requests.contains_key(&collection_dir.join(r.to_path())) => None,
+                Ok(r) => Some(format!("Unknown token request `{}`", r)),
+                Err(e) => Some(e),
+            };
+            if let Some(message) = message {
+                diagnostics.push(Diagnostic {
+                    path: path.clone(),
+                    line: find_value_line(&source, &refresh.request),
+                    severity: Severity::Error,
+                    message,
+                });
+            }
+        }
+
+        let mut request_scope = scope.clone();
+        request_scope.defined.extend(folder_vars);
+        request_scope.defined.extend(captured_by_token_request(
+            request.http.auth.as_ref(),
+            collection_dir,
+            &requests,
+        ));
+        request_scope.defined.extend(variable_names(pre_request));
+        request_scope
+            .defined
+            .extend(captured_by_dependencies(path, &requests));
+        check_templates(path, &source, &request_scope, &mut diagnostics);
+    }
+
+    diagnostics
+}
+
+/// Variables defined by the folder files of `dir` and of its parents, up to the collection
+/// directory. Each folder file is checked the first time it is found.
+fn folder_variables(
+    collection_dir: &Path,
+    dir: &Path,
+    scope: &Scope,
+    folders: &mut HashMap<PathBuf, HashSet<String>>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> HashSet<String> {
+    if dir == collection_dir || !dir.starts_with(collection_dir) {
+        return HashSet::new();
+    }
+    if let Some(vars) = folders.get(dir) {
+        return vars.clone();
+    }
+
+    let mut vars = match dir.parent() {
+        Some(parent) => folder_variables(collection_dir, parent, scope, folders, diagnostics),
+        None => HashSet::new(),
+    };
+
+    let path = dir.join(FOLDER_FILE_NAME);
+    if path.exists() {
+        if let Some((source, folder)) = parse_file::<FolderModel>(&path, diagnostics) {
+            check_duplicate_variables(&path, &source, &folder.vars, diagnostics);
+            vars.extend(variable_names(&folder.vars));
+
+            let mut folder_scope = scope.clone();
+            folder_scope.defined.extend(vars.iter().cloned());
+            check_templates(&path, &source, &folder_scope, diagnostics);
+        }
+    }
+
+    folders.insert(dir.to_path_buf(), vars.clone());
+
+    vars
+}
+
+/// Dependencies and captured variables of the requests that can be parsed, by path.
+fn request_dependencies(
+    collection_dir: &Path,
+    request_paths: &[PathBuf],
+) -> HashMap<PathBuf, (Vec<PathBuf>, HashSet<String>)> {
+    request_paths
+        .iter()
+        .filter_map(|p| {
+            let source = fs::read_to_string(p).ok()?;
+            let request: RequestModel = serde_yaml::from_str(&source).ok()?;
+            let dependencies = request
+                .dependencies()
+                .unwrap_or_default()
+                .iter()
+                .map(|d| collection_dir.join(d.to_path()))
+                .collect();
+            let captured = variable_names(&request.vars.post_request);
+
+            Some((p.clone(), (dependencies, captured)))
+        })
+        .collect()
+}
+
+/// Variables captured by the dependencies of a request, direct or not.
+fn captured_by_dependencies(
+    path: &Path,
+    requests: &HashMap<PathBuf, (Vec<PathBuf>, HashSet<String>)>,
+) -> HashSet<String> {
+    let mut captured = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut pending = requests
+        .get(path)
+        .map(|(deps, _)| deps.clone())
+        .unwrap_or_default();
+
+    while let Some(dependency) = pending.pop() {
+        if !seen.insert(dependency.clone()) {
+            continue;
+        }
+        if let Some((deps, vars)) = requests.get(&dependency) {
+            captured.extend(vars.iter().cloned());
+            pending.extend(deps.iter().cloned());
+        }
+    }
+
+    captured
+}
+
+/// Variables captured by the token request refreshing the bearer token of an auth.
+fn captured_by_token_request(
+    auth: Option<&HttpAuth>,
+    collection_dir: &Path,
+    requests: &HashMap<PathBuf, (Vec<PathBuf>, HashSet<String>)>,
+) -> HashSet<String> {
+    let Some(HttpAuth::Bearer(HttpBearerToken {
+        refresh: Some(refresh),
+        ..
+    })) = auth
+    else {
+        return HashSet::new();
+    };
+
+    refresh
+        .request
+        .parse::<RequestName>()
+        .ok()
+        .and_then(|r| requests.get(&collection_dir.join(r.to_path())))
+        .map(|(_, captured)| captured.clone())
+        .unwrap_or_default()
+}
+
+/// Variables that can be used in the templates of a file.
+#[derive(Clone)]
+struct Scope {
+    /// Defined regardless of the environment.
+    defined: HashSet<String>,
+    environments: Vec<(String, HashSet<String>)>,
+}
+
+fn parse_file<T: DeserializeOwned>(
+    path: &Path,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<(String, T)> {
+    let source = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            diagnostics.push(Diagnostic {
+                path: path.to_path_buf(),
+                line: None,
+                severity: Severity::Error,
+                message: format!("Unable to read file: {}", e),
+            });
+            return None;
+        }
+    };
+
+    let mut unknown_fields = Vec::new();
+    let result = serde_ignored::deserialize(serde_yaml::Deserializer::from_str(&source), |p| {
+        unknown_fields.push(p.to_string())
+    });
+
+    match result {
+        Ok(value) => {
+            for field in unknown_fields {
+                let key = field.rsplit('.').next().unwrap_or(&field);
+                diagnostics.push(Diagnostic {
+                    path: path.to_path_buf(),
+                    line: find_key_line(&source, key),
+                    severity: Severity::Warning,
+                    message: format!("Unknown field `{}`", field),
+                });
+            }
+
+            Some((source, value))
+        }
+        Err(e) => {
+            diagnostics.push(Diagnostic {
+                path: path.to_path_buf(),
+                line: e.location().map(|l| l.line()),
+                severity: Severity::Error,
+                message: e.to_string(),
+            });
+
+            None
+        }
+    }
+}
+
+fn check_duplicate_variables(
+    path: &Path,
+    source: &str,
+    vars: &KeyValueList,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+
+    for key in vars.items().map(|i| i.key.as_str()) {
+        if !seen.insert(key) && reported.insert(key) {
+            diagnostics.push(Diagnostic {
+                path: path.to_path_buf(),
+                line: find_value_line(source, key),
+                severity: Severity::Warning,
+                message: format!("Variable `{}` is defined more than once", key),
+            });
+        }
+    }
+}
+
+fn check_templates(path: &Path, source: &str, scope: &Scope, diagnostics: &mut Vec<Diagnostic>) {
+    for (idx, line) in source.lines().enumerate() {
+        // Variables with a fallback don't have to be defined.
+        let variables: BTreeSet<String> = variable_paths(line, false)
+            .iter()
+            .map(|p| root_variable(p))
+            .collect();
+
+        for var in variables {
+            if scope.defined.contains(&var) {
+                continue;
+            }
+
+            let missing_from: Vec<&str> = scope
+                .environments
+                .iter()
+                .filter(|(_, vars)| !vars.contains(&var))
+                .map(|(name, _)| name.as_str())
+                .collect();
+
+            let (severity, message) = if missing_from.len() == scope.environments.len() {
+                (Severity::Error, format!("Undefined variable `{}`", var))
+            } else if !missing_from.is_empty() {
+                (
+                    Severity::Warning,
+                    format!(
+                        "Variable `{}` is not defined in environments: {}",
+                        var,
+                        missing_from.join(", ")
+                    ),
+                )
+            } else {
+                continue;
+            };
+
+            diagnostics.push(Diagnostic {
+                path: path.to_path_buf(),
+                line: Some(idx + 1),
+                severity,
+                message,
+            });
+        }
+    }
+}
+
+/// Names of the variables referenced in the templates of a line. Helpers and literal parameters
+/// are ignored, only the root of a path is returned, e.g. `user` for `{{user.name}}`.
+pub(crate) fn referenced_variables(line: &str) -> Vec<String> {
+    referenced_paths(line)
+        .iter()
+        .map(|p| root_variable(p))
+        .collect()
+}
+
+/// Paths of the variables referenced by the templates of a line, e.g. `user.id`.
+pub(crate) fn referenced_paths(line: &str) -> Vec<String> {
+    variable_paths(line, true)
+}
+
+fn root_variable(path: &str) -> String {
+    path.split('.').next().unwrap_or(path).to_string()
+}
+
+/// Paths of the variables referenced by the templates of a line, including the `optional` ones
+/// given a fallback with the `default` helper or not.
+fn variable_paths(line: &str, optional: bool) -> Vec<String> {
+    let mut variables = Vec::new();
+    let mut rest = line;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let expression = after[..end].trim_matches(['{', '}', '~']).trim();
+        rest = &after[end + 2..];
+
+        // Comments, partials, closing blocks and else clauses don't reference any variable.
+        if expression.starts_with(['!', '>', '/']) || expression == "else" {
+            continue;
+        }
+        let expression = expression.trim_start_matches(['#', '^']);
+
+        let tokens: Vec<&str> = expression
+            .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        for (i, token) in tokens.iter().enumerate() {
+            if is_helper(token) || is_literal(token) {
+                continue;
+            }
+            if !optional && i > 0 && tokens[i - 1] == "default" {
+                continue;
+            }
+
+            let path = token
+                .split('[')
+                .next()
+                .unwrap_or(token)
+                .trim_end_matches('.');
+            let root = path.split('.').next().unwrap_or(path);
+            if !root.is_empty() && root != "this" {
+                variables.push(path.to_string());
+            }
+        }
+    }
+
+    variables
+}
+
+fn is_literal(token: &str) -> bool {
+    token.starts_with(['"', '\'', '@'])
+        || token.contains('=')
+        || token.parse::<f64>().is_ok()
+        || matches!(token, "true" | "false" | "null")
+}
+
+/// Names of the variables, the nested ones like `auth.token` defining their namespace.
+fn variable_names(vars: &KeyValueList) -> HashSet<String> {
+    vars.items()
+        .map(|i| i.key.split('.').next().unwrap_or(&i.key).to_string())
+        .collect()
+}
+
+/// First line defining `key` in a yaml document, either as `key:` or `- key:`.
+fn find_key_line(source: &str, key: &str) -> Option<usize> {
+    let pattern = format!("{}:", key);
+
+    source
+        .lines()
+        .position(|l| {
+            let l = l.trim_start().trim_start_matches("- ");
+            l.starts_with(&pattern)
+        })
+        .map(|i| i + 1)
+}
+
+/// Last line containing `value`, used to locate the entries of key/value lists.
+fn find_value_line(source: &str, value: &str) -> Option<usize> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| l.contains(value))
+        .last()
+        .map(|(i, _)| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{
+        lint_collection,
+        referenced_paths,
+        referenced_variables,
+        variable_paths,
+        Severity,
+    };
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_referenced_variables() {
+        assert_eq!(
+            referenced_variables(
+                r#"url: "{{host}}/users/{{user.id}}?t={{now "unix"}}&s={{base64 (env "X")}}&k={{secret name}}""#
+            ),
+            vec!["host", "user", "name"]
+        );
+        assert_eq!(
+            referenced_variables("{{#if debug}}{{! comment}}{{/if}}{{uuid}}"),
+            vec!["debug"]
+        );
+    }
+
+    #[test]
+    fn test_referenced_paths() {
+        assert_eq!(
+            referenced_paths("{{hosts.billing}}/{{items.[0]}}/{{user}}"),
+            vec!["hosts.billing", "items", "user"]
+        );
+    }
+
+    #[test]
+    fn test_variable_paths_with_fallback() {
+        let line = r#"{{default page "1"}}/{{base64 (default user.name "anonymous")}}/{{id}}"#;
+
+        assert_eq!(variable_paths(line, true), vec!["page", "user.name", "id"]);
+        assert_eq!(variable_paths(line, false), vec!["id"]);
+    }
+
+    #[test]
+    fn test_lint_collection() {
+        let dir = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir(&dir).unwrap();
+        let dir = dir.as_path();
+
+        let collection = write(
+            dir,
+            "collection.yaml",
+            "vars:\n  - key: host\n    value: http://localhost\n  - key: host\n    value: x\n",
+        );
+        let environments = vec![
+            write(dir, "dev.yaml", "vars:\n  - key: token\n    value: abc\n"),
+            write(dir, "prod.yaml", "vars: []\n"),
+        ];
+        let requests = vec![
+            write(
+                dir,
+                "ok.yaml",
+                "http:\n  method: GET\n  url: \"{{host}}/ok\"\n",
+            ),
+            write(
+                dir,
+                "bad.yaml",
+                "http:\n  method: GET\n  url: \"\"\n  headers:\n    - key: Authorization\n      value: \"Bearer {{token}} {{missing}}\"\n  unknown: 1\n",
+            ),
+            write(dir, "invalid.yaml", "http:\n  method: FETCH\n  url: x\n"),
+        ];
+
+        let diagnostics = lint_collection(&collection, &environments, &requests);
+        let found: Vec<(String, Option<usize>, Severity)> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.path.file_name().unwrap().to_string_lossy().to_string(),
+                    d.line,
+                    d.severity,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                ("collection.yaml".to_string(), Some(4), Severity::Warning),
+                ("bad.yaml".to_string(), Some(7), Severity::Warning),
+                ("bad.yaml".to_string(), Some(3), Severity::Error),
+                ("bad.yaml".to_string(), Some(6), Severity::Error),
+                ("bad.yaml".to_string(), Some(6), Severity::Warning),
+                ("invalid.yaml".to_string(), Some(2), Severity::Error),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_lint_collection_uses_folder_variables() {
+        let dir = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("users/admin")).unwrap();
+        let dir = dir.as_path();
+
+        let collection = write(dir, "collection.yaml", "vars: []\n");
+        write(
+            &dir.join("users"),
+            "folder.yaml",
+            "vars:\n  - key: base\n    value: http://localhost/users\n",
+        );
+        write(
+            &dir.join("users/admin"),
+            "folder.yaml",
+            "headers:\n  - key: X-Admin\n    value: \"{{base}} {{role}}\"\n",
+        );
+        let requests = vec![
+            write(
+                &dir.join("users/admin"),
+                "list.yaml",
+                "http:\n  method: GET\n  url: \"{{base}}/admin\"\n",
+            ),
+            write(
+                dir,
+                "root.yaml",
+                "http:\n  method: GET\n  url: \"{{base}}\"\n",
+            ),
+        ];
+
+        let diagnostics = lint_collection(&collection, &[], &requests);
+        let found: Vec<(String, Option<usize>, String)> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.path
+                        .strip_prefix(dir)
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                    d.line,
+                    d.message.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "users/admin/folder.yaml".to_string(),
+                    Some(3),
+                    "Undefined variable `role`".to_string()
+                ),
+                (
+                    "root.yaml".to_string(),
+                    Some(3),
+                    "Undefined variable `base`".to_string()
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_lint_collection_uses_variables_captured_by_dependencies() {
+        let dir = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("auth")).unwrap();
+        let dir = dir.as_path();
+
+        let collection = write(dir, "collection.yaml", "vars: []\n");
+        let requests = vec![
+            write(
+                &dir.join("auth"),
+                "login.yaml",
+                "http:\n  method: POST\n  url: http://localhost/login\nvars:\n  post-request:\n    - key: token\n      value: $.token\n",
+            ),
+            write(
+                dir,
+                "me.yaml",
+                "http:\n  method: GET\n  url: \"http://localhost/me?t={{token}}\"\ndepends_on: [auth/login]\n",
+            ),
+            write(
+                dir,
+                "other.yaml",
+                "http:\n  method: GET\n  url: \"http://localhost/{{token}}\"\ndepends_on:\n  - me\n  - logout\n",
+            ),
+            write(
+                dir,
+                "orphan.yaml",
+                "http:\n  method: GET\n  url: \"http://localhost/{{token}}\"\n",
+            ),
+        ];
+
+        let diagnostics = lint_collection(&collection, &[], &requests);
+        let found: Vec<(String, Option<usize>, String)> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.path.file_name().unwrap().to_string_lossy().to_string(),
+                    d.line,
+                    d.message.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "other.yaml".to_string(),
+                    Some(6),
+                    "Unknown dependency `logout`".to_string()
+                ),
+                (
+                    "orphan.yaml".to_string(),
+                    Some(3),
+                    "Undefined variable `token`".to_string()
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_lint_collection_uses_variables_captured_by_token_requests() {
+        let dir = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("auth")).unwrap();
+        let dir = dir.as_path();
+
+        let collection = write(
+            dir,
+            "collection.yaml",
+            "auth:\n  type: bearer\n  token: \"{{token}}\"\n  refresh:\n    request: auth/login\n",
+        );
+        let requests = vec![
+            write(
+                &dir.join("auth"),
+                "login.yaml",
+                "http:\n  method: POST\n  url: http://localhost/login\nvars:\n  post-request:\n    - key: token\n      value: $.token\n",
+            ),
+            write(
+                dir,
+                "admin.yaml",
+                "http:\n  method: GET\n  url: http://localhost/admin\n  auth:\n    type: bearer\n    token: \"{{admin_token}}\"\n    refresh:\n      request: auth/admin\n",
+            ),
+        ];
+
+        let diagnostics = lint_collection(&collection, &[], &requests);
+        let found: Vec<(String, Option<usize>, String)> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.path.file_name().unwrap().to_string_lossy().to_string(),
+                    d.line,
+                    d.message.clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "admin.yaml".to_string(),
+                    Some(8),
+                    "Unknown token request `auth:admin`".to_string()
+                ),
+                (
+                    "admin.yaml".to_string(),
+                    Some(6),
+                    "Undefined variable `admin_token`".to_string()
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::debug;
+use reqwest::{Client, Request, StatusCode};
+use tokio::task::JoinSet;
+
+use crate::RateLimiter;
+
+/// Aggregated results of executing the same request many times.
+#[derive(Debug)]
+pub struct LoadReport {
+    /// Latencies of the requests that received a response, sorted.
+    latencies: Vec<Duration>,
+    statuses: BTreeMap<u16, usize>,
+    failures: usize,
+    elapsed: Duration,
+}
+
+impl LoadReport {
+    fn new(results: Vec<RequestResult>, elapsed: Duration) -> Self {
+        let mut latencies = Vec::with_capacity(results.len());
+        let mut statuses = BTreeMap::new();
+        let mut failures = 0;
+
+        for result in results {
+            match result {
+                Ok((status, latency)) => {
+                    latencies.push(latency);
+                    *statuses.entry(status.as_u16()).or_default() += 1;
+                }
+                Err(e) => {
+                    debug!("Request failed: {}", e);
+                    failures += 1;
+                }
+            }
+        }
+
+        latencies.sort();
+
+        Self {
+            latencies,
+            statuses,
+            failures,
+            elapsed,
+        }
+    }
+
+    /// Number of requests sent.
+    pub fn requests(&self) -> usize {
+        self.latencies.len() + self.failures
+    }
+
+    /// Number of requests that failed or received a 4xx or 5xx response.
+    pub fn errors(&self) -> usize {
+        let error_statuses: usize = self
+            .statuses
+            .iter()
+            .filter(|(s, _)| **s >= 400)
+            .map(|(_, c)| c)
+            .sum();
+
+        self.failures + error_statuses
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        match self.requests() {
+            0 => 0.0,
+            n => self.errors() as f64 / n as f64,
+        }
+    }
+
+    /// Number of requests per second.
+    pub fn throughput(&self) -> f64 {
+        self.requests() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Total time spent executing the requests.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Number of responses received for each status code.
+    pub fn statuses(&self) -> &BTreeMap<u16, usize> {
+        &self.statuses
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.latencies.first().copied()
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.latencies.last().copied()
+    }
+
+    /// Latency under which `p` percent of the responses were received, using the nearest-rank
+    /// method. `None` if no response was received.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+
+        let rank = (p / 100.0 * self.latencies.len() as f64).ceil() as usize;
+
+        Some(self.latencies[rank.clamp(1, self.latencies.len()) - 1])
+    }
+}
+
+type RequestResult = std::result::Result<(StatusCode, Duration), reqwest::Error>;
+
+/// Send `request` `repeat` times, with `concurrency` workers sharing the client and its
+/// connections, and the rate limiter if there is one.
+pub(crate) async fn run(
+    client: Client,
+    request: Request,
+    repeat: usize,
+    concurrency: usize,
+    rate_limiter: Option<Arc<RateLimiter>>,
+) -> LoadReport {
+    let next = Arc::new(AtomicUsize::new(0));
+    let request = Arc::new(request);
+
+    let start = Instant::now();
+    let mut workers = JoinSet::new();
+    for _ in 0..concurrency.clamp(1, repeat.max(1)) {
+        let client = client.clone();
+        let request = Arc::clone(&request);
+        let next = Arc::clone(&next);
+        let rate_limiter = rate_limiter.clone();
+
+        workers.spawn(async move {
+            let mut results = Vec::new();
+
+            while next.fetch_add(1, Ordering::Relaxed) < repeat {
+                if let Some(limiter) = &rate_limiter {
+                    limiter.acquire().await;
+                }
+                let req = request.try_clone().expect("request is not cloneable");
+                results.push(send(&client, req).await);
+            }
+
+            results
+        });
+    }
+
+    let mut results = Vec::with_capacity(repeat);
+    while let Some(r) = workers.join_next().await {
+        results.extend(r.expect("load worker panicked"));
+    }
+
+    LoadReport::new(results, start.elapsed())
+}
+
+/// Send the request and read the whole body, so the latency covers the full response.
+async fn send(client: &Client, request: Request) -> RequestResult {
+    let start = Instant::now();
+    let res = client.execute(request).await?;
+    let status = res.status();
+    res.bytes().await?;
+
+    Ok((status, start.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use super::LoadReport;
+
+    fn report(latencies_ms: &[u64], status: StatusCode) -> LoadReport {
+        let results = latencies_ms
+            .iter()
+            .map(|l| Ok((status, Duration::from_millis(*l))))
+            .collect();
+
+        LoadReport::new(results, Duration::from_secs(2))
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let latencies: Vec<u64> = (1..=100).rev().collect();
+        let report = report(&latencies, StatusCode::OK);
+
+        assert_eq!(report.min(), Some(Duration::from_millis(1)));
+        assert_eq!(report.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(report.percentile(90.0), Some(Duration::from_millis(90)));
+        assert_eq!(report.percentile(99.0), Some(Duration::from_millis(99)));
+        assert_eq!(report.max(), Some(Duration::from_millis(100)));
+        assert_eq!(report.throughput(), 50.0);
+        assert_eq!(report.errors(), 0);
+    }
+
+    #[test]
+    fn test_error_statuses_are_errors() {
+        let report = report(&[10, 20], StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(report.requests(), 2);
+        assert_eq!(report.errors(), 2);
+        assert_eq!(report.error_rate(), 1.0);
+        assert_eq!(report.statuses().get(&503), Some(&2));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = LoadReport::new(vec![], Duration::from_secs(1));
+
+        assert_eq!(report.percentile(50.0), None);
+        assert_eq!(report.error_rate(), 0.0);
+    }
+}
@@ -1,12 +1,26 @@
 use api_cli::error::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 use commands::{
     execute_request,
     generate_shell_completion,
+    hold_clipboard_content,
+    init_logger,
+    load_config,
     run_collection_command,
+    run_config_command,
+    run_cookies_command,
     run_environment_command,
+    run_grep,
+    run_lint,
+    run_mock,
+    run_record,
     run_request_command,
+    run_secret_command,
     run_shell,
+    run_tui,
+    run_var_command,
+    run_watch,
     Cli,
     Command,
 };
@@ -15,16 +29,35 @@ mod commands;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    env_logger::init();
+    if hold_clipboard_content() {
+        return Ok(());
+    }
+
+    init_logger();
+
+    // The defaults of some arguments come from the configuration.
+    load_config();
+
+    CompleteEnv::with_factory(Cli::command).complete();
 
     let cli = Cli::parse();
 
     match cli.command {
-        Command::Run(args) => execute_request(args).await,
+        Command::Run(args) => execute_request(*args).await,
         Command::Completion(args) => generate_shell_completion(args.shell),
         Command::Collection(cmd) => run_collection_command(cmd),
         Command::Environment(cmd) => run_environment_command(cmd),
         Command::Request(cmd) => run_request_command(cmd),
+        Command::Cookies(cmd) => run_cookies_command(cmd),
+        Command::Secret(cmd) => run_secret_command(cmd),
+        Command::Var(cmd) => run_var_command(cmd),
+        Command::Lint(args) => run_lint(args),
+        Command::Grep(args) => run_grep(args),
+        Command::Mock(args) => run_mock(args).await,
+        Command::Record(args) => run_record(args).await,
+        Command::Watch(args) => run_watch(args).await,
+        Command::Config(cmd) => run_config_command(cmd),
         Command::Cd => run_shell(),
+        Command::Tui => run_tui().await,
     }
 }
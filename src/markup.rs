@@ -0,0 +1,355 @@
+use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
+use regex::Regex;
+
+const INDENT: &str = "  ";
+
+/// HTML elements that have no content and thus no end tag.
+static HTML_VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// HTML elements whose content is kept as is.
+static HTML_RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "script", "style", "textarea"];
+
+static ATTRIBUTE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"([^\s=/>]+)(\s*=\s*(?:"[^"]*"|'[^']*'|[^\s>]+))?"#).expect("invalid regex")
+});
+
+static YAML_KEY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(\s*(?:-\s+)*)("[^"]*"|'[^']*'|[^\s#'"\-?:,\[\]{}][^#:]*?|\?)(\s*:)(\s|$)"#)
+        .expect("invalid regex")
+});
+
+/// Markup languages of the response bodies that can be pretty-printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Xml,
+    Html,
+    Yaml,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// Comments, CDATA sections, doctypes and processing instructions.
+    Special(&'a str),
+    StartTag(&'a str, &'a str),
+    EmptyTag(&'a str),
+    EndTag(&'a str, &'a str),
+    Text(&'a str),
+    /// Content of an HTML raw text element.
+    Raw(&'a str),
+}
+
+/// Indent the elements of an XML or HTML document, one per line. YAML documents are returned
+/// as is, their layout being already meaningful.
+pub fn format_markup(text: &str, markup: Markup) -> String {
+    if markup == Markup::Yaml {
+        return text.trim_end().to_string();
+    }
+
+    let tokens = tokenize(text, markup == Markup::Html);
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut idx = 0;
+
+    while idx < tokens.len() {
+        let indent = INDENT.repeat(depth);
+        match tokens[idx] {
+            Token::StartTag(tag, name) => {
+                // Elements with only text, or nothing, are kept on a single line.
+                match (tokens.get(idx + 1), tokens.get(idx + 2)) {
+                    (Some(Token::EndTag(end, n)), _) if n.eq_ignore_ascii_case(name) => {
+                        lines.push(format!("{}{}{}", indent, tag, end));
+                        idx += 2;
+                        continue;
+                    }
+                    (Some(Token::Text(t)), Some(Token::EndTag(end, n)))
+                        if n.eq_ignore_ascii_case(name) && !t.trim().contains('\n') =>
+                    {
+                        lines.push(format!("{}{}{}{}", indent, tag, t.trim(), end));
+                        idx += 3;
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                lines.push(format!("{}{}", indent, tag));
+                if !(markup == Markup::Html && is_void_element(name)) {
+                    depth += 1;
+                }
+            }
+            Token::EndTag(tag, _) => {
+                depth = depth.saturating_sub(1);
+                lines.push(format!("{}{}", INDENT.repeat(depth), tag));
+            }
+            Token::EmptyTag(tag) | Token::Special(tag) => lines.push(format!("{}{}", indent, tag)),
+            Token::Text(t) => lines.extend(
+                t.lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(|l| format!("{}{}", indent, l)),
+            ),
+            Token::Raw(t) => {
+                if !t.trim().is_empty() {
+                    lines.push(t.trim_matches('\n').trim_end().to_string());
+                }
+            }
+        }
+        idx += 1;
+    }
+
+    lines.join("\n")
+}
+
+/// Color the tags, attributes and comments of an XML or HTML document, or the keys and comments
+/// of a YAML document, with ANSI escape codes.
+pub fn highlight_markup(text: &str, markup: Markup) -> String {
+    match markup {
+        Markup::Yaml => text
+            .lines()
+            .map(highlight_yaml_line)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Markup::Xml | Markup::Html => tokenize(text, markup == Markup::Html)
+            .into_iter()
+            .map(|t| match t {
+                Token::Special(s) => s.bright_black().to_string(),
+                Token::StartTag(s, _) | Token::EmptyTag(s) | Token::EndTag(s, _) => {
+                    highlight_tag(s)
+                }
+                Token::Text(s) | Token::Raw(s) => s.to_string(),
+            })
+            .collect(),
+    }
+}
+
+fn tokenize(text: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+            rest = &rest[start..];
+        }
+
+        let len = if rest.starts_with("<!--") {
+            find_end(rest, "-->")
+        } else if rest.starts_with("<![CDATA[") {
+            find_end(rest, "]]>")
+        } else if rest.starts_with("<?") {
+            find_end(rest, "?>")
+        } else {
+            find_tag_end(rest)
+        };
+        let tag = &rest[..len];
+        rest = &rest[len..];
+
+        if tag.starts_with("<!") || tag.starts_with("<?") {
+            tokens.push(Token::Special(tag));
+        } else if let Some(name) = tag.strip_prefix("</") {
+            tokens.push(Token::EndTag(tag, tag_name(name)));
+        } else if tag.ends_with("/>") {
+            tokens.push(Token::EmptyTag(tag));
+        } else {
+            let name = tag_name(&tag[1..]);
+            if name.is_empty() {
+                // A lone `<` is text.
+                tokens.push(Token::Text(tag));
+                continue;
+            }
+            tokens.push(Token::StartTag(tag, name));
+
+            if html && is_raw_text_element(name) {
+                let end = find_raw_text_end(rest, name);
+                if end > 0 {
+                    tokens.push(Token::Raw(&rest[..end]));
+                }
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Length of `text` up to and including `end`, or the whole `text` if it is unterminated.
+fn find_end(text: &str, end: &str) -> usize {
+    text.find(end).map(|i| i + end.len()).unwrap_or(text.len())
+}
+
+/// Length of the tag at the start of `text`, ignoring the `>` in quoted attribute values.
+fn find_tag_end(text: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in text.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            // An unterminated tag stops at the next one.
+            (None, '<') => return i,
+            _ => {}
+        }
+    }
+
+    text.len()
+}
+
+fn find_raw_text_end(text: &str, name: &str) -> usize {
+    let end_tag = format!("</{}", name.to_ascii_lowercase());
+    text.to_ascii_lowercase()
+        .find(&end_tag)
+        .unwrap_or(text.len())
+}
+
+fn tag_name(tag: &str) -> &str {
+    let end = tag
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(tag.len());
+
+    &tag[..end]
+}
+
+fn is_void_element(name: &str) -> bool {
+    HTML_VOID_ELEMENTS.contains(&name.to_lowercase().as_str())
+}
+
+fn is_raw_text_element(name: &str) -> bool {
+    HTML_RAW_TEXT_ELEMENTS.contains(&name.to_lowercase().as_str())
+}
+
+fn highlight_tag(tag: &str) -> String {
+    let (open, inner) = match tag.strip_prefix("</") {
+        Some(inner) => ("</", inner),
+        None => ("<", &tag[1..]),
+    };
+    let (inner, close) = match inner.strip_suffix("/>") {
+        Some(inner) => (inner, "/>"),
+        None => match inner.strip_suffix('>') {
+            Some(inner) => (inner, ">"),
+            None => (inner, ""),
+        },
+    };
+    let name = tag_name(inner);
+    let attributes = ATTRIBUTE_REGEX.replace_all(&inner[name.len()..], |c: &regex::Captures| {
+        let value = c.get(2).map(|v| v.as_str()).unwrap_or_default();
+        match value.split_once('=') {
+            Some((eq, v)) => format!("{}{}={}", (&c[1]).cyan(), eq, v.green()),
+            None => (&c[1]).cyan().to_string(),
+        }
+    });
+
+    format!(
+        "{}{}{}{}",
+        open.bright_black(),
+        name.blue().bold(),
+        attributes,
+        close.bright_black()
+    )
+}
+
+fn highlight_yaml_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') || trimmed == "---" || trimmed == "..." {
+        return line.bright_black().to_string();
+    }
+
+    let (content, comment) = split_yaml_comment(line);
+    let content = match YAML_KEY_REGEX.captures(content) {
+        Some(c) => {
+            let key_end = c.get(0).map(|m| m.end()).unwrap_or_default();
+            format!(
+                "{}{}{}{}{}",
+                &c[1],
+                (&c[2]).blue().bold(),
+                &c[3],
+                &c[4],
+                &content[key_end..]
+            )
+        }
+        None => content.to_string(),
+    };
+
+    match comment {
+        Some(comment) => format!("{}{}", content, comment.bright_black()),
+        None => content,
+    }
+}
+
+/// Split a YAML line before its trailing comment, a `#` preceded by a space outside of quotes.
+fn split_yaml_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '#') if previous.is_whitespace() => return (&line[..i], Some(&line[i..])),
+            _ => {}
+        }
+        previous = c;
+    }
+
+    (line, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use ansi_str::AnsiStr;
+    use rstest::rstest;
+
+    use super::{format_markup, highlight_markup, Markup};
+
+    #[rstest]
+    #[case::nested_elements(
+        r#"<?xml version="1.0"?><a><b id="1">text</b><c/><d></d><e><f>x</f></e></a>"#,
+        "<?xml version=\"1.0\"?>\n<a>\n  <b id=\"1\">text</b>\n  <c/>\n  <d></d>\n  <e>\n    <f>x</f>\n  </e>\n</a>"
+    )]
+    #[case::comments_and_cdata(
+        "<a>\n  <!-- note -->\n  <![CDATA[<raw>]]>\n</a>",
+        "<a>\n  <!-- note -->\n  <![CDATA[<raw>]]>\n</a>"
+    )]
+    #[case::mixed_content(
+        "<p>before<b>bold</b>after</p>",
+        "<p>\n  before\n  <b>bold</b>\n  after\n</p>"
+    )]
+    #[case::quoted_gt("<a title=\"a > b\"><b/></a>", "<a title=\"a > b\">\n  <b/>\n</a>")]
+    #[case::unbalanced("</a><b>", "</a>\n<b>")]
+    fn test_format_xml(#[case] xml: &str, #[case] expected: &str) {
+        assert_eq!(format_markup(xml, Markup::Xml), expected);
+    }
+
+    #[test]
+    fn test_format_html() {
+        let html = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><script>if (a < b) {\n  go();\n}</script></head><body><br><p>Hi</p></body></html>";
+
+        assert_eq!(
+            format_markup(html, Markup::Html),
+            "<!DOCTYPE html>\n<html>\n  <head>\n    <meta charset=\"utf-8\">\n    <script>\nif (a < b) {\n  go();\n}\n    </script>\n  </head>\n  <body>\n    <br>\n    <p>Hi</p>\n  </body>\n</html>"
+        );
+    }
+
+    #[test]
+    fn test_format_yaml_keeps_the_document() {
+        let yaml = "a:\n  - b: 1 # comment\n";
+
+        assert_eq!(format_markup(yaml, Markup::Yaml), "a:\n  - b: 1 # comment");
+    }
+
+    #[rstest]
+    #[case(Markup::Xml, "<a x=\"1\" y><!-- c --><b/>text</a>")]
+    #[case(Markup::Html, "<p class=a>x<br></p>")]
+    #[case(Markup::Yaml, "---\na: 1 # c\n- b: \"x # y\"\nplain text")]
+    fn test_highlight_markup_only_adds_colors(#[case] markup: Markup, #[case] text: &str) {
+        let highlighted = highlight_markup(text, markup);
+
+        assert_ne!(highlighted, text);
+        assert_eq!(highlighted.ansi_strip(), text);
+    }
+}
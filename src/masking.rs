@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderName, HeaderValue, AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION};
+
+/// Replacement of the sensitive values.
+pub static MASK: &str = "********";
+
+/// Characters escaped in urls and form bodies, like `application/x-www-form-urlencoded` does.
+const URL_ENCODED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'*')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_');
+
+/// Parts of the names of the variables and headers whose value is considered sensitive.
+static SENSITIVE_NAME_PARTS: [&str; 9] = [
+    "authorization",
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "api_key",
+    "api-key",
+    "credential",
+];
+
+/// Whether the value of a variable or header is sensitive, based on its name.
+pub fn is_sensitive_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+
+    SENSITIVE_NAME_PARTS.iter().any(|p| name.contains(p))
+}
+
+/// Whether the value of a header is sensitive, because it is marked as such or based on its name.
+pub fn is_sensitive_header(name: &HeaderName, value: &HeaderValue) -> bool {
+    value.is_sensitive()
+        || name == AUTHORIZATION
+        || name == PROXY_AUTHORIZATION
+        || name == COOKIE
+        || is_sensitive_name(name.as_str())
+}
+
+/// Hides the values of the secret variables of a request in the text where they appear.
+#[derive(Debug, Default, Clone)]
+pub struct Masker {
+    secrets: Vec<String>,
+}
+
+impl Masker {
+    pub(crate) fn new<I: IntoIterator<Item = String>>(secrets: I) -> Self {
+        let mut masker = Self::default();
+        masker.extend(secrets);
+
+        masker
+    }
+
+    pub(crate) fn extend<I: IntoIterator<Item = String>>(&mut self, secrets: I) {
+        for secret in secrets.into_iter().filter(|s| !s.is_empty()) {
+            // Secrets sent in urls and forms appear url-encoded, with spaces as `%20` or `+`.
+            let encoded = utf8_percent_encode(&secret, URL_ENCODED).to_string();
+            if encoded != secret {
+                self.secrets.push(encoded.replace("%20", "+"));
+                self.secrets.push(encoded);
+            }
+            self.secrets.push(secret);
+        }
+        // The longest secrets are replaced first, in case one contains another.
+        self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        self.secrets.dedup();
+    }
+
+    /// Whether `text` contains any of the secrets.
+    pub fn contains_secret(&self, text: &str) -> bool {
+        self.secrets.iter().any(|s| text.contains(s.as_str()))
+    }
+
+    /// `text` with every occurrence of the secrets replaced by [`MASK`].
+    pub fn mask<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.contains_secret(text) {
+            return Cow::Borrowed(text);
+        }
+
+        let mut masked = text.to_string();
+        for s in &self.secrets {
+            masked = masked.replace(s.as_str(), MASK);
+        }
+
+        Cow::Owned(masked)
+    }
+
+    /// The variables with their secrets masked, sorted by name.
+    pub(crate) fn mask_variables<'a>(
+        &self,
+        variables: &'a HashMap<String, String>,
+    ) -> BTreeMap<&'a str, Cow<'a, str>> {
+        variables
+            .iter()
+            .map(|(k, v)| (k.as_str(), self.mask(v)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{is_sensitive_name, Masker};
+
+    #[rstest]
+    #[case("Authorization", true)]
+    #[case("access_token", true)]
+    #[case("X-Api-Key", true)]
+    #[case("client_secret", true)]
+    #[case("host", false)]
+    #[case("username", false)]
+    fn test_is_sensitive_name(#[case] name: &str, #[case] expected: bool) {
+        assert_eq!(is_sensitive_name(name), expected);
+    }
+
+    #[test]
+    fn test_masker_masks_every_secret() {
+        let masker = Masker::new(["abc".to_string(), "abcdef".to_string(), String::new()]);
+
+        assert_eq!(
+            masker.mask("token=abcdef&other=abc"),
+            "token=********&other=********"
+        );
+        assert_eq!(masker.mask("nothing to hide"), "nothing to hide");
+    }
+
+    #[test]
+    fn test_masker_masks_url_encoded_secrets() {
+        let masker = Masker::new(["p@ss word".to_string()]);
+
+        assert_eq!(
+            masker.mask("https://example.com/?a=p%40ss%20word&b=p%40ss+word&c=p@ss word"),
+            "https://example.com/?a=********&b=********&c=********"
+        );
+    }
+}
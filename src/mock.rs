@@ -0,0 +1,372 @@
+use std::cmp::Reverse;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{debug, info};
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+use crate::error::Result;
+use crate::models::ExampleModel;
+use crate::RequestModel;
+
+/// Header selecting which example of a request is served, the first one is used by default.
+pub static EXAMPLE_HEADER: &str = "x-mock-example";
+
+/// A local server replying to the requests of a collection with their examples.
+pub struct MockServer {
+    routes: Vec<Route>,
+    logger: Option<RequestLogger>,
+}
+
+type RequestLogger = Box<dyn Fn(&str) + Send + Sync>;
+
+struct Route {
+    name: String,
+    method: String,
+    segments: Vec<Segment>,
+    examples: Vec<ExampleModel>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// A template or path parameter, matching any value.
+    Param,
+}
+
+impl MockServer {
+    /// Build a server from named request definitions. gRPC requests are ignored.
+    pub fn new(requests: Vec<(String, RequestModel)>) -> Self {
+        let routes = requests
+            .into_iter()
+            .filter(|(_, r)| r.grpc.is_none())
+            .map(|(name, r)| Route {
+                name,
+                method: reqwest::Method::from(&r.http.method).to_string(),
+                segments: path_template(&r.http.url),
+                examples: r.examples,
+            })
+            .collect();
+
+        Self {
+            routes,
+            logger: None,
+        }
+    }
+
+    /// Call `logger` with a line describing each request received and the example served.
+    pub fn with_request_logger<F: Fn(&str) + Send + Sync + 'static>(mut self, logger: F) -> Self {
+        self.logger = Some(Box::new(logger));
+        self
+    }
+
+    fn log(&self, line: String) {
+        debug!("{}", line);
+        if let Some(l) = &self.logger {
+            l(&line);
+        }
+    }
+
+    /// Accept connections on `addr` until the process is stopped.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("Mock server listening on {}", listener.local_addr()?);
+
+        self.serve_listener(listener).await
+    }
+
+    async fn serve_listener(self, listener: TcpListener) -> Result<()> {
+        let server = Arc::new(self);
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = Arc::clone(&server);
+
+            tokio::spawn(async move {
+                let service = service_fn(|req| {
+                    let server = Arc::clone(&server);
+                    async move { Ok::<_, Infallible>(server.respond(&req)) }
+                });
+
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    debug!("Mock server connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    fn respond(&self, req: &Request<Incoming>) -> Response<Full<Bytes>> {
+        let method = req.method().as_str();
+        let path = req.uri().path();
+
+        let Some(route) = self.find_route(method, path) else {
+            self.log(format!("{} {} -> no match", method, path));
+            return json_response(
+                StatusCode::NOT_FOUND,
+                json!({"error": format!("No request matches {} {}", method, path)}),
+            );
+        };
+
+        let selected = req
+            .headers()
+            .get(EXAMPLE_HEADER)
+            .and_then(|v| v.to_str().ok());
+        let example = match selected {
+            Some(name) => route.examples.iter().find(|e| e.name == name),
+            None => route.examples.first(),
+        };
+
+        let Some(example) = example else {
+            self.log(format!(
+                "{} {} -> {} (no example)",
+                method, path, route.name
+            ));
+            return json_response(
+                StatusCode::NOT_IMPLEMENTED,
+                json!({"error": format!("No example defined for {}", route.name)}),
+            );
+        };
+
+        self.log(format!(
+            "{} {} -> {}: {} ({})",
+            method, path, route.name, example.name, example.status
+        ));
+
+        example_response(example)
+    }
+
+    /// The matching route with the most literal segments, so `/users/me` is preferred over
+    /// `/users/:id`.
+    fn find_route(&self, method: &str, path: &str) -> Option<&Route> {
+        let segments: Vec<&str> = split_path(path).collect();
+
+        self.routes
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.method == method && r.matches(&segments))
+            .max_by_key(|(i, r)| {
+                let literals = r.segments.iter().filter(|s| **s != Segment::Param).count();
+                // Prefer the first definition on ties.
+                (literals, Reverse(*i))
+            })
+            .map(|(_, r)| r)
+    }
+}
+
+impl Route {
+    fn matches(&self, segments: &[&str]) -> bool {
+        self.segments.len() == segments.len()
+            && self.segments.iter().zip(segments).all(|(t, s)| match t {
+                Segment::Literal(l) => l == s,
+                Segment::Param => true,
+            })
+    }
+}
+
+fn example_response(example: &ExampleModel) -> Response<Full<Bytes>> {
+    let mut builder = Response::builder().status(example.status);
+
+    let has_content_type = example
+        .headers
+        .items()
+        .any(|h| h.key.eq_ignore_ascii_case("content-type"));
+    for h in example.headers.items() {
+        builder = builder.header(&h.key, &h.value);
+    }
+
+    let body = match &example.body {
+        None => Bytes::new(),
+        Some(Value::String(s)) => Bytes::from(s.clone()),
+        Some(v) => {
+            if !has_content_type {
+                builder = builder.header("content-type", "application/json");
+            }
+            Bytes::from(v.to_string())
+        }
+    };
+
+    builder.body(Full::new(body)).unwrap_or_else(|e| {
+        json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            json!({"error": format!("Invalid example {}: {}", example.name, e)}),
+        )
+    })
+}
+
+pub(crate) fn json_response(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    let mut res = Response::new(Full::new(Bytes::from(body.to_string())));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        "content-type",
+        "application/json".parse().expect("invalid header value"),
+    );
+
+    res
+}
+
+/// Segments of the path of a request url, where templates and path parameters match anything.
+/// The scheme and authority can be templates, e.g. `{{scheme}}://{{host}}/users`, and a template at
+/// the start of the url is assumed to be the base url, e.g. `{{host}}/users` or
+/// `{{host}}:{{port}}/users`.
+fn path_template(url: &str) -> Vec<Segment> {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+
+    let path = match url.find("://") {
+        Some(i) if !url[..i].contains('/') => {
+            let rest = &url[i + 3..];
+            rest.find('/').map(|i| &rest[i..]).unwrap_or_default()
+        }
+        _ if url.starts_with("{{") => {
+            let rest = url.find("}}").map(|i| &url[i + 2..]).unwrap_or_default();
+            match rest.strip_prefix(':') {
+                Some(port) => port.find('/').map(|i| &port[i..]).unwrap_or_default(),
+                None => rest,
+            }
+        }
+        _ => url,
+    };
+
+    split_path(path)
+        .map(|s| {
+            if s.contains("{{") || s.starts_with(':') || (s.starts_with('{') && s.ends_with('}')) {
+                Segment::Param
+            } else {
+                Segment::Literal(s.to_string())
+            }
+        })
+        .collect()
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::{path_template, MockServer, Segment, EXAMPLE_HEADER};
+    use crate::models::{ExampleModel, HttpMethod, HttpRequestModel, KeyValueList};
+    use crate::RequestModel;
+
+    #[rstest]
+    #[case("{{host}}/users/:id", vec![Segment::Literal("users".to_string()), Segment::Param])]
+    #[case("http://localhost:8080/users/{id}/", vec![Segment::Literal("users".to_string()), Segment::Param])]
+    #[case("https://example.com/{{version}}/me?q={{q}}", vec![Segment::Param, Segment::Literal("me".to_string())])]
+    #[case("{{scheme}}://{{host}}/users", vec![Segment::Literal("users".to_string())])]
+    #[case("{{host}}:{{port}}/users/:id", vec![Segment::Literal("users".to_string()), Segment::Param])]
+    #[case("https://example.com", vec![])]
+    fn test_path_template(#[case] url: &str, #[case] expected: Vec<Segment>) {
+        assert_eq!(path_template(url), expected);
+    }
+
+    fn request(method: HttpMethod, url: &str, examples: Vec<ExampleModel>) -> RequestModel {
+        RequestModel {
+            http: HttpRequestModel {
+                method,
+                url: url.to_string(),
+                ..Default::default()
+            },
+            examples,
+            ..Default::default()
+        }
+    }
+
+    fn example(name: &str, status: u16, body: Option<Value>) -> ExampleModel {
+        ExampleModel {
+            name: name.to_string(),
+            status,
+            headers: KeyValueList::from([("x-example", name)]),
+            body,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let server = MockServer::new(vec![
+            (
+                "get-user".to_string(),
+                request(
+                    HttpMethod::Get,
+                    "{{host}}/users/:id",
+                    vec![
+                        example("ok", 200, Some(json!({"id": 1}))),
+                        example("missing", 404, None),
+                    ],
+                ),
+            ),
+            (
+                "me".to_string(),
+                request(
+                    HttpMethod::Get,
+                    "{{host}}/users/me",
+                    vec![example("me", 200, Some(json!("plain text")))],
+                ),
+            ),
+            (
+                "create-user".to_string(),
+                request(HttpMethod::Post, "{{host}}/users", vec![]),
+            ),
+        ]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(server.serve_listener(listener));
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("{}/users/42", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(res.json::<Value>().await.unwrap(), json!({"id": 1}));
+
+        let res = client
+            .get(format!("{}/users/42", base_url))
+            .header(EXAMPLE_HEADER, "missing")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers()["x-example"], "missing");
+
+        let res = client
+            .get(format!("{}/users/me", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "plain text");
+
+        let res = client
+            .post(format!("{}/users", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_IMPLEMENTED);
+
+        let res = client
+            .delete(format!("{}/users", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,18 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Default, Debug, Serialize, Deserialize)]
-pub(crate) struct KeyValueList(Vec<KeyValuePair>);
+use crate::error::{ApiClientError, Result};
+use crate::RequestName;
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct KeyValueList(Vec<KeyValuePair>);
 
 impl KeyValueList {
-    #[allow(dead_code)]
-    pub(crate) fn new(values: Vec<KeyValuePair>) -> Self {
+    pub fn new(values: Vec<KeyValuePair>) -> Self {
         Self(values)
     }
 
-    pub(crate) fn items(&self) -> impl Iterator<Item = &KeyValuePair> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, pair: KeyValuePair) {
+        self.0.push(pair);
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &KeyValuePair> {
         self.0.iter().filter(|i| i.enabled.unwrap_or(true))
     }
 }
@@ -29,6 +44,9 @@ where
                     key: k.into(),
                     value: v.into(),
                     enabled: Some(true),
+                    enabled_if: None,
+                    encode: None,
+                    secret: None,
                 })
                 .collect(),
         )
@@ -36,12 +54,6 @@ where
 }
 
 impl<'a> KeyValueList {
-    pub(crate) fn as_map(&'a self) -> HashMap<&'a str, &'a str> {
-        self.items()
-            .map(|p| (p.key.as_str(), p.value.as_str()))
-            .collect()
-    }
-
     fn as_tuple_list(&'a self) -> Vec<(&'a str, &'a str)> {
         self.items()
             .map(|p| (p.key.as_str(), p.value.as_str()))
@@ -51,65 +63,373 @@ impl<'a> KeyValueList {
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct EnvironmentModel {
+    /// Variables whose value can be a map of variables, e.g. `auth: {token: ...}`, defining the
+    /// `auth.token` variable
+    #[serde(
+        default,
+        skip_serializing_if = "KeyValueList::is_empty",
+        deserialize_with = "deserialize_namespaced_variables"
+    )]
+    pub vars: KeyValueList,
+    /// Headers replacing the ones of the collection with the same name
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub headers: KeyValueList,
+    /// Authentication replacing the one of the collection, for requests inheriting it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<HttpAuth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    /// Host name sent instead of the one of the urls, for requests not defining their own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+}
+
+/// Variable of an environment, before its nested variables are flattened.
+#[derive(Deserialize)]
+struct NamespacedVariable {
+    key: String,
+    value: VariableValue,
+    #[serde(default)]
+    enabled: Option<bool>,
+    #[serde(default)]
+    encode: Option<bool>,
+    #[serde(default)]
+    secret: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VariableValue {
+    Text(String),
+    Bool(bool),
+    Number(serde_json::Number),
+    Map(BTreeMap<String, VariableValue>),
+}
+
+impl VariableValue {
+    /// The variables defined by the value, the nested ones being named after their path, e.g.
+    /// `auth.token`.
+    fn flatten(self, key: String, values: &mut Vec<(String, String)>) {
+        match self {
+            VariableValue::Text(v) => values.push((key, v)),
+            VariableValue::Bool(v) => values.push((key, v.to_string())),
+            VariableValue::Number(v) => values.push((key, v.to_string())),
+            VariableValue::Map(m) => {
+                for (k, v) in m {
+                    v.flatten(format!("{}.{}", key, k), values);
+                }
+            }
+        }
+    }
+}
+
+fn deserialize_namespaced_variables<'de, D>(
+    deserializer: D,
+) -> std::result::Result<KeyValueList, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let mut list = KeyValueList::default();
+
+    for var in Vec::<NamespacedVariable>::deserialize(deserializer)? {
+        let mut values = Vec::new();
+        var.value.flatten(var.key, &mut values);
+
+        for (key, value) in values {
+            list.push(KeyValuePair {
+                key,
+                value,
+                enabled: var.enabled,
+                enabled_if: None,
+                encode: var.encode,
+                secret: var.secret,
+            });
+        }
+    }
+
+    Ok(list)
+}
+
+/// Name of the file configuring the requests of a folder of a collection.
+pub static FOLDER_FILE_NAME: &str = "folder.yaml";
+
+/// Configuration shared by the requests of a folder of a collection.
+///
+/// Its headers, auth and variables take precedence over the ones of the collection and the
+/// environment, and are overridden by the ones of its subfolders and requests.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct FolderModel {
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub headers: KeyValueList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<HttpAuth>,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub vars: KeyValueList,
+}
+
+/// TLS configuration of a collection or an environment. Relative paths are resolved from the
+/// directory of the collection.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM bundle of additional root certificates to trust
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    /// Client certificate, either PEM (with `client_key`) or PKCS#12 (without `client_key`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    /// PEM encoded PKCS#8 private key of the client certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+    /// Password of the PKCS#12 client certificate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Skip verification of the server certificate
     #[serde(default)]
-    pub(crate) vars: KeyValueList,
+    pub insecure: bool,
 }
 
-#[derive(Default, Debug, Deserialize)]
-enum RequestType {
+/// External program signing the requests. It receives the rendered request as json on its stdin
+/// and prints the headers to add as json on its stdout, e.g. `{"headers": {"X-Signature": "..."}}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// Program and its arguments, which can contain templates, e.g. `["./sign.sh", "{{key_id}}"]`
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            username: None,
+            password: None,
+            no_proxy: Vec::new(),
+        }
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RequestType {
     #[default]
-    #[serde(alias = "http")]
+    #[serde(rename = "http", alias = "Http")]
     Http,
 }
 
-#[derive(Default, Debug, Deserialize)]
-struct RequestMetaModel {
-    _name: String,
-    #[serde(alias = "type")]
-    _type_: RequestType,
-    _seq: u32,
+/// Descriptive information about a request, which doesn't change how it is sent.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct RequestMetaModel {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<RequestType>,
+    /// Position of the request in listings and collection runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct KeyValuePair {
-    pub(crate) key: String,
-    pub(crate) value: String,
+impl RequestMetaModel {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn seq(&self) -> Option<u32> {
+        self.seq
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.type_.is_none()
+            && self.seq.is_none()
+            && self.description.is_none()
+            && self.tags.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyValuePair {
+    pub key: String,
+    pub value: String,
     // TODO: check serde_bool
-    pub(crate) enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Template enabling the header, parameter or form field only when it renders to a truthy
+    /// value, e.g. `{{is_staging}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_if: Option<String>,
+    /// Percent-encode the value of a query parameter, overriding the `encode_query` option of the
+    /// collection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encode: Option<bool>,
+    /// Hide the value in the logs and the outputs, on top of the values that look sensitive from
+    /// their name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<bool>,
+}
+
+impl KeyValuePair {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            enabled: None,
+            enabled_if: None,
+            encode: None,
+            secret: None,
+        }
+    }
+
+    pub fn is_secret(&self) -> bool {
+        self.secret.unwrap_or(false)
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
-pub(crate) struct HttpParamsModel {
-    #[serde(default)]
-    pub(crate) query: KeyValueList,
+pub struct HttpParamsModel {
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub query: KeyValueList,
+    /// Values of the `:name` or `{name}` placeholders of the url path
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub path: KeyValueList,
 }
 
 impl HttpParamsModel {
-    pub(crate) fn get_query_params(&self) -> Vec<(&str, &str)> {
-        self.query.as_tuple_list()
+    fn is_empty(&self) -> bool {
+        self.query.is_empty() && self.path.is_empty()
+    }
+
+    pub(crate) fn get_query_params(&self) -> impl Iterator<Item = &KeyValuePair> {
+        self.query.items()
+    }
+
+    pub(crate) fn get_path_params(&self) -> Vec<(&str, &str)> {
+        self.path.as_tuple_list()
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpBasicAuth {
-    pub(crate) username: String,
-    pub(crate) password: String,
+pub struct HttpBasicAuth {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpBearerToken {
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh: Option<TokenRefresh>,
+}
+
+/// Request of the collection fetching a new token, executed when the token expired or was rejected
+/// by the server. Its captures set the variables of the token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRefresh {
+    pub request: String,
+    /// Lifetime of the tokens, after which a new one is fetched before sending the request.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_in: Option<Duration>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpBearerToken {
-    pub(crate) token: String,
+pub struct HttpAwsSigV4Auth {
+    pub access_key: String,
+    pub secret_key: String,
+    pub region: String,
+    pub service: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+}
+
+/// Windows integrated authentication, with an NTLMv2 handshake. The domain can also be given in
+/// the username, as `DOMAIN\user`. Requires the `ntlm` feature.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpNtlmAuth {
+    pub username: String,
+    pub password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+/// Signature of the request with a shared secret, sent in a header.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpHmacAuth {
+    pub secret: String,
+    #[serde(default)]
+    pub algorithm: HmacAlgorithm,
+    /// Signed string, `{method}\n{path}\n{date}\n{body_hash}` by default. The placeholders are
+    /// replaced by the method, the path and query, the `Date` header (added if missing), the host
+    /// and the hex digest of the body of the final request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub string_to_sign: Option<String>,
+    /// Header receiving the signature, `Authorization` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Text written before the signature in the header, e.g. `HMAC {{key_id}}:`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub encoding: HmacEncoding,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacEncoding {
+    #[default]
+    Hex,
+    Base64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
-pub(crate) enum HttpAuth {
+pub enum HttpAuth {
+    /// Explicitly send no credentials, even if the collection defines some.
     None,
+    /// Use the credentials of the collection. Same as not defining any auth on the request.
+    Inherit,
     Basic(HttpBasicAuth),
     Bearer(HttpBearerToken),
+    #[serde(rename = "aws-sigv4")]
+    AwsSigV4(HttpAwsSigV4Auth),
+    Hmac(HttpHmacAuth),
+    Ntlm(HttpNtlmAuth),
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpMethod {
     #[default]
@@ -140,82 +460,686 @@ impl HttpMethod {
     }
 }
 
+impl FromStr for HttpMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "GET" => Ok(HttpMethod::Get),
+            "HEAD" => Ok(HttpMethod::Head),
+            "POST" => Ok(HttpMethod::Post),
+            "PUT" => Ok(HttpMethod::Put),
+            "DELETE" => Ok(HttpMethod::Delete),
+            "CONNECT" => Ok(HttpMethod::Connect),
+            "OPTIONS" => Ok(HttpMethod::Options),
+            "TRACE" => Ok(HttpMethod::Trace),
+            "PATCH" => Ok(HttpMethod::Patch),
+            _ => Err(format!("unsupported method: {}", s)),
+        }
+    }
+}
+
+impl From<&HttpMethod> for reqwest::Method {
+    fn from(method: &HttpMethod) -> Self {
+        match method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Head => reqwest::Method::HEAD,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+            HttpMethod::Connect => reqwest::Method::CONNECT,
+            HttpMethod::Options => reqwest::Method::OPTIONS,
+            HttpMethod::Trace => reqwest::Method::TRACE,
+            HttpMethod::Patch => reqwest::Method::PATCH,
+        }
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct CollectionModel {
-    #[serde(default)]
-    pub(crate) headers: KeyValueList,
-    pub(crate) auth: Option<HttpAuth>,
-    #[serde(default)]
-    pub(crate) vars: KeyValueList,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub headers: KeyValueList,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<HttpAuth>,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub vars: KeyValueList,
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<FollowRedirects>,
+    /// Local address the connections are opened from, unless the request sets its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_address: Option<IpAddr>,
+    /// Latency above which a request is considered failed, unless the request sets its own.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_latency: Option<Duration>,
+    /// Percent-encode the values of the query parameters, `true` by default. Disable it for
+    /// backends expecting values that are already encoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encode_query: Option<bool>,
+    /// Command signing the requests, unless the request sets its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+    /// Headers not sent by the requests, e.g. `Accept-Encoding`, whether they are added by default
+    /// or defined by the environment or a folder.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers_disabled: Vec<String>,
+    /// Value of the `User-Agent` header, `api-cli/<version>` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Header in which the ID of the run, also available as `{{requestId}}`, is sent, e.g.
+    /// `X-Request-Id`. It is not sent by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id_header: Option<String>,
+}
+
+/// Either `true`/`false`, or the maximum number of redirects to follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FollowRedirects {
+    Enabled(bool),
+    Max(usize),
+}
+
+/// Protocol family of the addresses the requests connect to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpVersion {
+    V4,
+    V6,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryBackoff {
+    Fixed,
+    #[default]
+    Exponential,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct GraphGLBody {
-    pub(crate) query: String,
+pub struct RetryPolicy {
+    #[serde(default = "RetryPolicy::default_max_attempts")]
+    pub max_attempts: u32,
     #[serde(default)]
-    pub(crate) variables: HashMap<String, Value>,
+    pub backoff: RetryBackoff,
+    #[serde(default = "RetryPolicy::default_delay", with = "humantime_serde")]
+    pub delay: Duration,
+    #[serde(default = "RetryPolicy::default_on_status")]
+    pub on_status: Vec<u16>,
+    #[serde(default = "RetryPolicy::default_on_connection_error")]
+    pub on_connection_error: bool,
+    #[serde(
+        default = "RetryPolicy::default_max_retry_after",
+        with = "humantime_serde"
+    )]
+    pub max_retry_after: Duration,
+}
+
+impl RetryPolicy {
+    fn default_max_attempts() -> u32 {
+        3
+    }
+
+    fn default_delay() -> Duration {
+        Duration::from_millis(500)
+    }
+
+    fn default_on_status() -> Vec<u16> {
+        vec![429, 502, 503, 504]
+    }
+
+    fn default_on_connection_error() -> bool {
+        true
+    }
+
+    fn default_max_retry_after() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    /// Get the delay to wait after the given (1-based) failed attempt.
+    pub(crate) fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            RetryBackoff::Fixed => self.delay,
+            RetryBackoff::Exponential => self.delay * 2u32.saturating_pow(attempt - 1),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: Self::default_max_attempts(),
+            backoff: RetryBackoff::default(),
+            delay: Self::default_delay(),
+            on_status: Self::default_on_status(),
+            on_connection_error: Self::default_on_connection_error(),
+            max_retry_after: Self::default_max_retry_after(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphGLBody {
+    pub query: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
-pub(crate) enum HttpBody {
+pub enum HttpBody {
     Text(HttpTextBody),
     Json(HttpJsonBody),
     GraphQL(HttpGraphQLBody),
     Binary(HttpBinaryBody),
     Form(HttpFormBody),
+    Ndjson(HttpNdjsonBody),
+}
+
+impl HttpBody {
+    /// Explicit content type of the body, if any.
+    pub(crate) fn content_type(&self) -> Option<&str> {
+        match self {
+            HttpBody::Text(t) => t.content_type.as_deref(),
+            HttpBody::Json(j) => j.content_type.as_deref(),
+            HttpBody::Binary(b) => b.content_type.as_deref(),
+            HttpBody::Ndjson(n) => n.content_type.as_deref(),
+            HttpBody::GraphQL(_) | HttpBody::Form(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpTextBody {
-    pub(crate) text: String,
+pub struct HttpTextBody {
+    pub text: String,
+    /// Value of the `Content-Type` header, replacing the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpJsonBody {
-    pub(crate) json: Value,
+pub struct HttpJsonBody {
+    pub json: Value,
+    /// Value of the `Content-Type` header, replacing the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
+/// Json documents sent one per line.
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpGraphQLBody {
-    pub(crate) graphql: GraphGLBody,
+pub struct HttpNdjsonBody {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ndjson: Vec<Value>,
+    /// Value of the `Content-Type` header, replacing the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpBinaryBody {
-    pub(crate) binary: String,
+pub struct HttpGraphQLBody {
+    pub graphql: GraphGLBody,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct HttpFormBody {
-    pub(crate) form: KeyValueList,
+pub struct HttpBinaryBody {
+    pub binary: String,
+    /// Value of the `Content-Type` header, replacing the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpFormBody {
+    #[serde(skip_serializing_if = "KeyValueList::is_empty")]
+    pub form: KeyValueList,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
-pub(crate) struct HttpRequestModel {
-    pub(crate) method: HttpMethod,
-    pub(crate) url: String, // validate len > 0
-    pub(crate) auth: Option<HttpAuth>,
-    #[serde(default)]
-    pub(crate) headers: KeyValueList,
-    #[serde(default)]
-    pub(crate) params: HttpParamsModel,
-    #[serde(default)]
-    pub(crate) body: Option<HttpBody>,
+pub struct HttpRequestModel {
+    pub method: HttpMethod,
+    pub url: String, // validate len > 0
+    /// Host name used in the `Host` header and for TLS (SNI and certificate verification) instead
+    /// of the one of the url, which remains the address connected to. Useful to test a virtual
+    /// host before its DNS points to the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<HttpAuth>,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub headers: KeyValueList,
+    #[serde(default, skip_serializing_if = "HttpParamsModel::is_empty")]
+    pub params: HttpParamsModel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<HttpBody>,
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    /// Latency above which the request is considered failed.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_latency: Option<Duration>,
+    /// Status the response must have for the request to succeed, e.g. `201`, `2xx` or `[404, 422]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_status: Option<ExpectedStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<FollowRedirects>,
+    /// Local address the connection is opened from, to pick the interface it goes through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_address: Option<IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamMode>,
+    /// Write the response body to this file instead of displaying it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save: Option<PathBuf>,
+    /// JSON Schema the response body is validated against, inline or in a file relative to the
+    /// request file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<ResponseSchemaModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+    /// Inherited or default headers not sent, e.g. `Accept`. The headers of the request are
+    /// always sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers_disabled: Vec<String>,
+    /// Value of the `User-Agent` header, replacing the one of the collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// How the following pages are requested when the response is a paginated list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+/// How the following pages of a list are requested, e.g. with `run --all-pages`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pagination {
+    #[serde(flatten)]
+    pub next: PaginationStrategy,
+    /// Json-path of the items of a page in the body, e.g. `$.data`. The body itself is the list
+    /// of items by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<String>,
+    /// Maximum number of pages requested, 100 by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_pages: Option<u32>,
+}
+
+/// Where the next page of a list is found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PaginationStrategy {
+    /// The `next` link of the `Link` header. The last page has none.
+    Link,
+    /// A cursor found in the body at the json-path `path`, sent in the `param` query parameter.
+    /// The last page has no cursor.
+    Cursor { path: String, param: String },
+    /// The number of the page, sent in the `param` query parameter and starting at `start`, 1 by
+    /// default. The last page is the first one without items.
+    Page {
+        param: String,
+        #[serde(default = "PaginationStrategy::default_start_page")]
+        start: u64,
+    },
+    /// The index of the first item of the page, sent in the `param` query parameter and starting
+    /// at `start`, 0 by default. The last page is the first one without items.
+    Offset {
+        param: String,
+        #[serde(default)]
+        start: u64,
+    },
+}
+
+impl PaginationStrategy {
+    fn default_start_page() -> u64 {
+        1
+    }
+}
+
+/// A status code, e.g. `404`, or a class of status codes, e.g. `2xx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "StatusValue", into = "StatusValue")]
+pub enum StatusMatcher {
+    Code(u16),
+    /// First digit of the codes of the class.
+    Class(u16),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StatusValue {
+    Code(u16),
+    Text(String),
+}
+
+impl StatusMatcher {
+    pub fn matches(&self, status: u16) -> bool {
+        match self {
+            StatusMatcher::Code(c) => *c == status,
+            StatusMatcher::Class(c) => status / 100 == *c,
+        }
+    }
+}
+
+impl FromStr for StatusMatcher {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid status `{}`, expected a code like 404 or a class like 2xx",
+                s
+            )
+        };
+
+        match s.to_lowercase().strip_suffix("xx") {
+            Some(c) => match c.parse() {
+                Ok(c @ 1..=5) => Ok(StatusMatcher::Class(c)),
+                _ => Err(invalid()),
+            },
+            None => match s.parse() {
+                Ok(c @ 100..=999) => Ok(StatusMatcher::Code(c)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+impl TryFrom<StatusValue> for StatusMatcher {
+    type Error = String;
+
+    fn try_from(value: StatusValue) -> std::result::Result<Self, Self::Error> {
+        match value {
+            StatusValue::Code(c) => c.to_string().parse(),
+            StatusValue::Text(s) => s.parse(),
+        }
+    }
+}
+
+impl From<StatusMatcher> for StatusValue {
+    fn from(matcher: StatusMatcher) -> Self {
+        match matcher {
+            StatusMatcher::Code(c) => StatusValue::Code(c),
+            StatusMatcher::Class(_) => StatusValue::Text(matcher.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for StatusMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusMatcher::Code(c) => write!(f, "{}", c),
+            StatusMatcher::Class(c) => write!(f, "{}xx", c),
+        }
+    }
+}
+
+/// Status a response is expected to have, either a single one or any of a list, e.g. `[404, 422]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged, try_from = "ExpectedStatusValue")]
+pub enum ExpectedStatus {
+    One(StatusMatcher),
+    Any(Vec<StatusMatcher>),
+}
+
+/// Unvalidated [`ExpectedStatus`], so its errors are reported instead of the ones of an untagged
+/// enum.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpectedStatusValue {
+    One(StatusValue),
+    Any(Vec<StatusValue>),
+}
+
+impl TryFrom<ExpectedStatusValue> for ExpectedStatus {
+    type Error = String;
+
+    fn try_from(value: ExpectedStatusValue) -> std::result::Result<Self, Self::Error> {
+        match value {
+            ExpectedStatusValue::One(v) => Ok(ExpectedStatus::One(v.try_into()?)),
+            ExpectedStatusValue::Any(v) => Ok(ExpectedStatus::Any(
+                v.into_iter()
+                    .map(StatusMatcher::try_from)
+                    .collect::<std::result::Result<_, _>>()?,
+            )),
+        }
+    }
+}
+
+impl ExpectedStatus {
+    pub fn matches(&self, status: u16) -> bool {
+        self.matchers().iter().any(|m| m.matches(status))
+    }
+
+    fn matchers(&self) -> &[StatusMatcher] {
+        match self {
+            ExpectedStatus::One(m) => std::slice::from_ref(m),
+            ExpectedStatus::Any(m) => m,
+        }
+    }
+}
+
+impl fmt::Display for ExpectedStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let matchers = self
+            .matchers()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", matchers.join(" or "))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResponseSchemaModel {
+    Path(PathBuf),
+    Inline(Value),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamMode {
+    /// Read the response as server-sent events.
+    Sse,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
-pub(crate) struct RequestVarsModel {
-    #[serde(alias = "pre-request", default)]
-    pub(crate) pre_request: KeyValueList,
-    #[serde(alias = "post-request", default)]
-    pub(crate) _post_request: KeyValueList,
+pub struct RequestVarsModel {
+    #[serde(
+        alias = "pre-request",
+        default,
+        skip_serializing_if = "KeyValueList::is_empty"
+    )]
+    pub pre_request: KeyValueList,
+    /// Variables captured from the response, with the json-path of their value in the body.
+    #[serde(
+        alias = "post-request",
+        default,
+        skip_serializing_if = "KeyValueList::is_empty"
+    )]
+    pub post_request: KeyValueList,
+}
+
+impl RequestVarsModel {
+    fn is_empty(&self) -> bool {
+        self.pre_request.is_empty() && self.post_request.is_empty()
+    }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcRequestModel {
+    /// Address of the server, e.g. `http://localhost:50051`.
+    pub url: String,
+    /// Full name of the method to call, e.g. `package.Service/Method`.
+    pub method: String,
+    /// Proto file defining the service, relative to the directory of the collection. Server
+    /// reflection is used if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proto: Option<PathBuf>,
+    /// Directories in which the imports of the proto file are searched, relative to the directory
+    /// of the collection.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub import_paths: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub metadata: KeyValueList,
+    /// Json representation of the request message.
+    #[serde(default)]
+    pub message: Value,
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<Duration>,
+    /// Latency above which the call is considered failed.
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_latency: Option<Duration>,
+}
+
+/// A request of a collection. Its `http` section is required, unless it is a gRPC request.
 #[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(try_from = "RequestValue")]
 pub struct RequestModel {
-    // _meta: RequestMetaModel,
-    pub(crate) http: HttpRequestModel,
+    #[serde(default, skip_serializing_if = "RequestMetaModel::is_empty")]
+    pub meta: RequestMetaModel,
+    pub http: HttpRequestModel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<GrpcRequestModel>,
+    #[serde(default, skip_serializing_if = "RequestVarsModel::is_empty")]
+    pub vars: RequestVarsModel,
+    /// Requests of the collection to execute before this one, e.g. `auth:login`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Example responses, served by the mock server and compared against with `run --diff`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<ExampleModel>,
+}
+
+/// Unvalidated [`RequestModel`], whose `http` section can be missing.
+#[derive(Deserialize)]
+struct RequestValue {
+    #[serde(default)]
+    meta: RequestMetaModel,
+    http: Option<HttpRequestModel>,
+    grpc: Option<GrpcRequestModel>,
     #[serde(default)]
-    pub(crate) vars: RequestVarsModel,
+    vars: RequestVarsModel,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    examples: Vec<ExampleModel>,
+}
+
+impl TryFrom<RequestValue> for RequestModel {
+    type Error = String;
+
+    fn try_from(value: RequestValue) -> std::result::Result<Self, Self::Error> {
+        let http = match (value.http, &value.grpc) {
+            (Some(http), _) => http,
+            (None, Some(_)) => HttpRequestModel::default(),
+            (None, None) => return Err("missing field `http`".to_string()),
+        };
+
+        Ok(Self {
+            meta: value.meta,
+            http,
+            grpc: value.grpc,
+            vars: value.vars,
+            depends_on: value.depends_on,
+            examples: value.examples,
+        })
+    }
+}
+
+impl RequestModel {
+    pub fn meta(&self) -> &RequestMetaModel {
+        &self.meta
+    }
+
+    /// Method of the request, `GRPC` for gRPC requests.
+    pub fn method(&self) -> &str {
+        match &self.grpc {
+            Some(_) => "GRPC",
+            None => self.http.method.as_str(),
+        }
+    }
+
+    /// Url of the request, before rendering its templates. For gRPC requests, the address of the
+    /// server followed by the method.
+    pub fn url(&self) -> String {
+        match &self.grpc {
+            Some(g) => format!("{}/{}", g.url.trim_end_matches('/'), g.method),
+            None => self.http.url.clone(),
+        }
+    }
+
+    /// Enabled headers of the request, before rendering their templates.
+    pub fn headers(&self) -> Vec<(&str, &str)> {
+        self.http.headers.as_tuple_list()
+    }
+
+    /// Enabled query parameters of the request, before rendering their templates.
+    pub fn query_params(&self) -> Vec<(&str, &str)> {
+        self.http.params.query.as_tuple_list()
+    }
+
+    /// Requests that must be executed before this one.
+    pub fn dependencies(&self) -> Result<Vec<RequestName>> {
+        self.depends_on
+            .iter()
+            .map(|d| d.parse().map_err(ApiClientError::new_invalid_request_name))
+            .collect()
+    }
+
+    /// Variables captured from the response, with the json-path of their value in the body.
+    pub fn captures(&self) -> Vec<(String, String)> {
+        self.vars
+            .post_request
+            .items()
+            .map(|i| (i.key.clone(), i.value.clone()))
+            .collect()
+    }
+}
+
+/// A saved response of a request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExampleModel {
+    pub name: String,
+    #[serde(default = "default_example_status")]
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    pub headers: KeyValueList,
+    /// Sent as is if it is a string, as json otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+fn default_example_status() -> u16 {
+    200
 }
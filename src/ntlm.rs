@@ -0,0 +1,585 @@
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use log::debug;
+use md4::{Digest, Md4};
+use md5::Md5;
+use reqwest::header::{HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Client, Request, Response, StatusCode};
+
+use crate::error::{ApiClientError, Result};
+use crate::models::RetryPolicy;
+use crate::send_with_retries;
+
+static SIGNATURE: &[u8] = b"NTLMSSP\0";
+
+const NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const REQUEST_TARGET: u32 = 0x0000_0004;
+const NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NEGOTIATE_ALWAYS_SIGN: u32 = 0x0000_8000;
+const NEGOTIATE_EXTENDED_SESSION_SECURITY: u32 = 0x0008_0000;
+const NEGOTIATE_TARGET_INFO: u32 = 0x0080_0000;
+const NEGOTIATE_128: u32 = 0x2000_0000;
+const NEGOTIATE_56: u32 = 0x8000_0000;
+
+const NEGOTIATE_FLAGS: u32 = NEGOTIATE_UNICODE
+    | REQUEST_TARGET
+    | NEGOTIATE_NTLM
+    | NEGOTIATE_ALWAYS_SIGN
+    | NEGOTIATE_EXTENDED_SESSION_SECURITY
+    | NEGOTIATE_TARGET_INFO
+    | NEGOTIATE_128
+    | NEGOTIATE_56;
+
+/// Id of the timestamp of the server in the target info of the challenge.
+const AV_TIMESTAMP: u16 = 7;
+
+/// Seconds between 1601-01-01, the epoch of the Windows timestamps, and 1970-01-01.
+const WINDOWS_EPOCH_OFFSET: u64 = 11_644_473_600;
+
+/// Credentials of the request being prepared, used to answer the challenge once it is sent.
+pub(crate) type SharedNtlmCredentials = Arc<Mutex<Option<NtlmCredentials>>>;
+
+/// Authentication scheme of the headers.
+const SCHEME: &str = "NTLM";
+
+/// Rendered credentials of an `ntlm` auth.
+#[derive(Debug)]
+pub(crate) struct NtlmCredentials {
+    pub(crate) username: String,
+    pub(crate) password: String,
+    pub(crate) domain: String,
+}
+
+impl NtlmCredentials {
+    /// The domain can be given separately or in the username, as `DOMAIN\user`.
+    pub(crate) fn new(username: String, password: String, domain: Option<String>) -> Self {
+        let (username, domain) = match (domain, username.split_once('\\')) {
+            (Some(d), _) => (username, d),
+            (None, Some((d, u))) => (u.to_string(), d.to_string()),
+            (None, None) => (username, String::new()),
+        };
+
+        Self {
+            username,
+            password,
+            domain,
+        }
+    }
+}
+
+/// The `Authorization` header starting the handshake.
+pub(crate) fn negotiate_header() -> Result<HeaderValue> {
+    let mut message = SIGNATURE.to_vec();
+    message.extend_from_slice(&1u32.to_le_bytes());
+    message.extend_from_slice(&NEGOTIATE_FLAGS.to_le_bytes());
+    // Empty domain and workstation
+    message.extend_from_slice(&[0; 16]);
+
+    header(&message)
+}
+
+/// Authenticate a request whose `Authorization` header starts the handshake: the request is sent
+/// as is, and if the server challenges it, sent again with the response to the challenge on the
+/// same connection, to the url the challenge came from once redirects are followed. Both requests
+/// are retried according to `policy`.
+pub(crate) async fn execute(
+    client: &Client,
+    request: Request,
+    credentials: &NtlmCredentials,
+    policy: Option<RetryPolicy>,
+) -> Result<Response> {
+    let mut authenticated = request.try_clone().expect("request is not cloneable");
+
+    let response = send(client, request, policy.as_ref()).await?;
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return Ok(response);
+    }
+
+    let Some(challenge) = find_challenge(&response) else {
+        return Ok(response);
+    };
+    *authenticated.url_mut() = response.url().clone();
+    // The body must be read for the connection to be reused.
+    let _ = response.bytes().await;
+
+    debug!("Answering the NTLM challenge of the server");
+    let message = authenticate_message(
+        credentials,
+        &challenge,
+        rand::random(),
+        windows_timestamp(SystemTime::now()),
+    )?;
+    authenticated
+        .headers_mut()
+        .insert(AUTHORIZATION, header(&message)?);
+
+    Ok(send(client, authenticated, policy.as_ref()).await?)
+}
+
+async fn send(
+    client: &Client,
+    request: Request,
+    policy: Option<&RetryPolicy>,
+) -> reqwest::Result<Response> {
+    match policy {
+        Some(p) => send_with_retries(client, request, p).await,
+        None => client.execute(request).await,
+    }
+}
+
+fn header(message: &[u8]) -> Result<HeaderValue> {
+    let mut value =
+        HeaderValue::from_str(&format!("{} {}", SCHEME, BASE64_STANDARD.encode(message)))?;
+    value.set_sensitive(true);
+
+    Ok(value)
+}
+
+/// The challenge message of the `WWW-Authenticate` headers of the response.
+fn find_challenge(response: &Response) -> Option<Vec<u8>> {
+    response
+        .headers()
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .filter_map(|v| v.split_once(' '))
+        .find(|(s, _)| s.eq_ignore_ascii_case(SCHEME))
+        .and_then(|(_, token)| BASE64_STANDARD.decode(token.trim()).ok())
+}
+
+/// Parsed challenge message sent by the server.
+#[derive(Debug)]
+struct Challenge<'a> {
+    flags: u32,
+    server_challenge: &'a [u8],
+    target_info: &'a [u8],
+}
+
+impl<'a> Challenge<'a> {
+    fn parse(message: &'a [u8]) -> Result<Self> {
+        let invalid = || ApiClientError::new_ntlm_error("invalid challenge".to_string());
+
+        if message.len() < 48 || !message.starts_with(SIGNATURE) || read_u32(message, 8) != Some(2)
+        {
+            return Err(invalid());
+        }
+
+        let len = read_u16(message, 40).ok_or_else(invalid)? as usize;
+        let offset = read_u32(message, 44).ok_or_else(invalid)? as usize;
+
+        Ok(Self {
+            flags: read_u32(message, 20).ok_or_else(invalid)?,
+            server_challenge: &message[24..32],
+            target_info: message.get(offset..offset + len).ok_or_else(invalid)?,
+        })
+    }
+
+    /// The timestamp of the server, if it sent one.
+    fn timestamp(&self) -> Option<[u8; 8]> {
+        let mut info = self.target_info;
+        while info.len() >= 4 {
+            let id = read_u16(info, 0)?;
+            let len = read_u16(info, 2)? as usize;
+            let value = info.get(4..4 + len)?;
+            if id == AV_TIMESTAMP {
+                return value.try_into().ok();
+            }
+            info = &info[4 + len..];
+        }
+
+        None
+    }
+}
+
+/// The NTLMv2 authenticate message answering a challenge.
+fn authenticate_message(
+    credentials: &NtlmCredentials,
+    challenge: &[u8],
+    client_challenge: [u8; 8],
+    timestamp: [u8; 8],
+) -> Result<Vec<u8>> {
+    let challenge = Challenge::parse(challenge)?;
+    let timestamp = challenge.timestamp().unwrap_or(timestamp);
+
+    let nt_hash = Md4::digest(utf16(&credentials.password));
+    let user_domain = format!(
+        "{}{}",
+        credentials.username.to_uppercase(),
+        credentials.domain
+    );
+    let ntlm_v2_hash = hmac_md5(&nt_hash, &utf16(&user_domain));
+
+    let mut blob = vec![1, 1, 0, 0, 0, 0, 0, 0];
+    blob.extend_from_slice(&timestamp);
+    blob.extend_from_slice(&client_challenge);
+    blob.extend_from_slice(&[0; 4]);
+    blob.extend_from_slice(challenge.target_info);
+    blob.extend_from_slice(&[0; 4]);
+
+    let nt_proof = hmac_md5(&ntlm_v2_hash, &[challenge.server_challenge, &blob].concat());
+    let nt_response = [&nt_proof[..], &blob].concat();
+    let lm_response = [
+        &hmac_md5(
+            &ntlm_v2_hash,
+            &[challenge.server_challenge, &client_challenge].concat(),
+        )[..],
+        &client_challenge,
+    ]
+    .concat();
+
+    let fields = [
+        lm_response,
+        nt_response,
+        utf16(&credentials.domain),
+        utf16(&credentials.username),
+        // Workstation and session key
+        Vec::new(),
+        Vec::new(),
+    ];
+
+    let mut message = SIGNATURE.to_vec();
+    message.extend_from_slice(&3u32.to_le_bytes());
+    let mut offset = 64;
+    for field in &fields {
+        message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+        message.extend_from_slice(&(field.len() as u16).to_le_bytes());
+        message.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += field.len();
+    }
+    message.extend_from_slice(&(challenge.flags & NEGOTIATE_FLAGS).to_le_bytes());
+    for field in fields {
+        message.extend_from_slice(&field);
+    }
+
+    Ok(message)
+}
+
+/// Tenths of microseconds since 1601-01-01.
+fn windows_timestamp(time: SystemTime) -> [u8; 8] {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let ticks = (since_epoch.as_secs() + WINDOWS_EPOCH_OFFSET) * 10_000_000
+        + u64::from(since_epoch.subsec_nanos() / 100);
+
+    ticks.to_le_bytes()
+}
+
+fn hmac_md5(key: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut mac = Hmac::<Md5>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(data);
+
+    mac.finalize().into_bytes().into()
+}
+
+fn utf16(value: &str) -> Vec<u8> {
+    value.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
+    use reqwest::header::AUTHORIZATION;
+    use reqwest::StatusCode;
+    use rstest::rstest;
+    use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+    use super::{
+        authenticate_message,
+        execute,
+        negotiate_header,
+        read_u16,
+        read_u32,
+        utf16,
+        windows_timestamp,
+        NtlmCredentials,
+    };
+    use crate::models::RetryPolicy;
+
+    fn credentials() -> NtlmCredentials {
+        NtlmCredentials::new(
+            "User".to_string(),
+            "Password".to_string(),
+            Some("Domain".to_string()),
+        )
+    }
+
+    /// Target info of the examples of MS-NLMP: the domain `Domain` and the server `Server`.
+    fn target_info() -> Vec<u8> {
+        let mut info = Vec::new();
+        for (id, value) in [(2u16, "Domain"), (1, "Server")] {
+            let value = utf16(value);
+            info.extend_from_slice(&id.to_le_bytes());
+            info.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            info.extend_from_slice(&value);
+        }
+        info.extend_from_slice(&[0; 4]);
+
+        info
+    }
+
+    fn challenge_message() -> Vec<u8> {
+        let info = target_info();
+
+        let mut message = b"NTLMSSP\0".to_vec();
+        message.extend_from_slice(&2u32.to_le_bytes());
+        // Empty target name
+        message.extend_from_slice(&[0, 0, 0, 0, 48, 0, 0, 0]);
+        message.extend_from_slice(&0xe28a8233u32.to_le_bytes());
+        message.extend_from_slice(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        message.extend_from_slice(&[0; 8]);
+        message.extend_from_slice(&(info.len() as u16).to_le_bytes());
+        message.extend_from_slice(&(info.len() as u16).to_le_bytes());
+        message.extend_from_slice(&48u32.to_le_bytes());
+        message.extend_from_slice(&info);
+
+        message
+    }
+
+    /// Field of an authenticate message, from its descriptor at `offset`.
+    fn field(message: &[u8], offset: usize) -> &[u8] {
+        let len = read_u16(message, offset).unwrap() as usize;
+        let start = read_u32(message, offset + 4).unwrap() as usize;
+
+        &message[start..start + len]
+    }
+
+    #[rstest]
+    #[case::separate_domain("user", Some("CORP"), "user", "CORP")]
+    #[case::domain_in_username("CORP\\user", None, "user", "CORP")]
+    #[case::no_domain("user", None, "user", "")]
+    fn test_credentials_domain(
+        #[case] username: &str,
+        #[case] domain: Option<&str>,
+        #[case] expected_username: &str,
+        #[case] expected_domain: &str,
+    ) {
+        let credentials = NtlmCredentials::new(
+            username.to_string(),
+            "pass".to_string(),
+            domain.map(str::to_string),
+        );
+
+        assert_eq!(credentials.username, expected_username);
+        assert_eq!(credentials.domain, expected_domain);
+    }
+
+    #[test]
+    fn test_negotiate_header() {
+        let header = negotiate_header().unwrap();
+
+        assert_eq!(header, "NTLM TlRMTVNTUAABAAAABYKIoAAAAAAAAAAAAAAAAAAAAAA=");
+        assert!(header.is_sensitive());
+    }
+
+    // Values of the NTLMv2 example of MS-NLMP, section 4.2.4.
+    #[test]
+    fn test_authenticate_message() {
+        let message =
+            authenticate_message(&credentials(), &challenge_message(), [0xaa; 8], [0; 8]).unwrap();
+
+        assert!(message.starts_with(b"NTLMSSP\0\x03\0\0\0"));
+        assert_eq!(
+            hex::encode(field(&message, 12)),
+            "86c35097ac9cec102554764a57cccc19aaaaaaaaaaaaaaaa"
+        );
+        let nt_response = field(&message, 20);
+        assert_eq!(
+            hex::encode(&nt_response[..16]),
+            "68cd0ab851e51c96aabc927bebef6a1c"
+        );
+        assert_eq!(&nt_response[16..24], &[1, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(field(&message, 28), utf16("Domain"));
+        assert_eq!(field(&message, 36), utf16("User"));
+    }
+
+    #[test]
+    fn test_authenticate_message_rejects_invalid_challenge() {
+        let err = authenticate_message(&credentials(), b"NTLMSSP\0", [0; 8], [0; 8]).unwrap_err();
+
+        assert!(err.to_string().contains("invalid challenge"), "{}", err);
+    }
+
+    #[test]
+    fn test_windows_timestamp() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+
+        assert_eq!(
+            u64::from_le_bytes(windows_timestamp(time)),
+            116_444_736_010_000_000
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_challenge() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAB",
+        ))
+        .respond_with(
+            ResponseTemplate::new(StatusCode::UNAUTHORIZED).insert_header(
+                "WWW-Authenticate",
+                format!("NTLM {}", BASE64_STANDARD.encode(challenge_message())),
+            ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAD",
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+        let credentials = credentials();
+        let client = reqwest::Client::new();
+        let mut request = client.get(server.uri()).build().unwrap();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, negotiate_header().unwrap());
+
+        let response = execute(&client, request, &credentials, None).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_execute_retries_handshake() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAB",
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::SERVICE_UNAVAILABLE))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAB",
+        ))
+        .respond_with(
+            ResponseTemplate::new(StatusCode::UNAUTHORIZED).insert_header(
+                "WWW-Authenticate",
+                format!("NTLM {}", BASE64_STANDARD.encode(challenge_message())),
+            ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+        Mock::given(matchers::header_regex(
+            "Authorization",
+            "^NTLM TlRMTVNTUAAD",
+        ))
+        .respond_with(ResponseTemplate::new(StatusCode::OK))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+        let credentials = credentials();
+        let client = reqwest::Client::new();
+        let mut request = client.get(server.uri()).build().unwrap();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, negotiate_header().unwrap());
+        let policy = RetryPolicy {
+            delay: Duration::from_millis(10),
+            ..Default::default()
+        };
+
+        let response = execute(&client, request, &credentials, Some(policy))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_challenge_after_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/old"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::FOUND).insert_header("Location", "/new"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/new"))
+            .and(matchers::header_regex(
+                "Authorization",
+                "^NTLM TlRMTVNTUAAB",
+            ))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::UNAUTHORIZED).insert_header(
+                    "WWW-Authenticate",
+                    format!("NTLM {}", BASE64_STANDARD.encode(challenge_message())),
+                ),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/new"))
+            .and(matchers::header_regex(
+                "Authorization",
+                "^NTLM TlRMTVNTUAAD",
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let credentials = credentials();
+        let client = reqwest::Client::new();
+        let mut request = client.get(format!("{}/old", server.uri())).build().unwrap();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, negotiate_header().unwrap());
+
+        let response = execute(&client, request, &credentials, None).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_execute_without_challenge() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::any())
+            .respond_with(ResponseTemplate::new(StatusCode::UNAUTHORIZED))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let request = client.get(server.uri()).build().unwrap();
+
+        let response = execute(&client, request, &credentials(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::io;
+use std::process::Command;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::error::{ApiClientError, Result};
+
+pub(crate) static OP_PREFIX: &str = "op://";
+
+/// Command line of 1Password, found in the `PATH`.
+static OP_PROGRAM: &str = "op";
+
+/// Values already read, by reference, so each one is read once per run.
+static CACHE: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(Default::default);
+
+/// Read a secret reference of the form `op://<vault>/<item>/<field>` with the 1Password command
+/// line, which handles the authentication.
+pub(crate) fn resolve_reference(reference: &str) -> Result<String> {
+    read_reference(OP_PROGRAM, reference)
+}
+
+fn read_reference(program: &str, reference: &str) -> Result<String> {
+    if let Some(value) = CACHE.lock().unwrap().get(reference) {
+        return Ok(value.clone());
+    }
+
+    let output = Command::new(program)
+        .args(["read", "--no-newline", reference])
+        .output()
+        .map_err(|e| {
+            let reason = match e.kind() {
+                io::ErrorKind::NotFound => format!("`{}` not found", program),
+                _ => e.to_string(),
+            };
+            ApiClientError::new_onepassword_error(reference, reason)
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = match stderr.trim() {
+            "" => format!("`{}` failed with {}", program, output.status),
+            e => e.to_string(),
+        };
+        return Err(ApiClientError::new_onepassword_error(reference, reason));
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).into_owned();
+    CACHE
+        .lock()
+        .unwrap()
+        .insert(reference.to_string(), value.clone());
+
+    Ok(value)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::read_reference;
+
+    #[test]
+    fn test_read_reference() {
+        // `echo` stands in for `op`, printing the arguments it receives.
+        let value = read_reference("echo", "op://dev/github/token").unwrap();
+
+        assert_eq!(value, "read --no-newline op://dev/github/token\n");
+    }
+
+    #[test]
+    fn test_read_reference_is_cached() {
+        read_reference("echo", "op://dev/cached/token").unwrap();
+
+        let value = read_reference("api-cli-missing-op", "op://dev/cached/token").unwrap();
+
+        assert_eq!(value, "read --no-newline op://dev/cached/token\n");
+    }
+
+    #[test]
+    fn test_read_reference_fails() {
+        let err = read_reference("false", "op://dev/failing/token").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "SecretError: Unable to read op://dev/failing/token from 1Password: `false` failed \
+             with exit status: 1"
+        );
+    }
+
+    #[test]
+    fn test_read_reference_without_op() {
+        let err = read_reference("api-cli-missing-op", "op://dev/other/token").unwrap_err();
+
+        assert!(err.to_string().contains("`api-cli-missing-op` not found"));
+    }
+}
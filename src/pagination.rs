@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use jsonpath_rust::{find_slice, JsonPathInst, JsonPathValue};
+use reqwest::header::{HeaderMap, LINK};
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::error::{ApiClientError, Result};
+use crate::models::{Pagination, PaginationStrategy};
+use crate::ApiResponse;
+
+const DEFAULT_MAX_PAGES: u32 = 100;
+
+/// Where the next page of a list is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NextPage {
+    /// The url of the page, replacing the one of the request.
+    Url(Url),
+    /// A query parameter selecting the page, replacing the one of the request if it has it.
+    Param { name: String, value: String },
+}
+
+/// Follows the pages of a list, from the responses to the previous ones.
+#[derive(Debug)]
+pub struct Paginator {
+    pagination: Pagination,
+    pages: u32,
+    items: u64,
+}
+
+impl Paginator {
+    pub fn new(pagination: Pagination) -> Self {
+        Self {
+            pagination,
+            pages: 0,
+            items: 0,
+        }
+    }
+
+    /// Number of pages read so far.
+    pub fn pages(&self) -> u32 {
+        self.pages
+    }
+
+    /// Read the items of a page, along with where the next page is. There is no next page after
+    /// the last one, or once the maximum number of pages is reached.
+    pub fn read_page(&mut self, res: &ApiResponse) -> Result<(Vec<Value>, Option<NextPage>)> {
+        self.pages += 1;
+        if !res.status().is_success() {
+            return Err(ApiClientError::new_pagination_error(format!(
+                "page {} returned {}",
+                self.pages,
+                res.status()
+            )));
+        }
+
+        let body = res.json().ok_or_else(|| {
+            ApiClientError::new_pagination_error(format!("page {} is not json", self.pages))
+        })?;
+        let items = match &self.pagination.items {
+            Some(path) => find_json_path(path, body)?.into_iter().next(),
+            None => Some(body.clone()),
+        };
+        let items = match items {
+            Some(Value::Array(items)) => items,
+            _ => {
+                return Err(ApiClientError::new_pagination_error(format!(
+                    "the items of page {} are not a list",
+                    self.pages
+                )))
+            }
+        };
+        self.items += items.len() as u64;
+
+        if self.pages >= self.pagination.max_pages.unwrap_or(DEFAULT_MAX_PAGES) {
+            return Ok((items, None));
+        }
+
+        let next = match &self.pagination.next {
+            PaginationStrategy::Link => next_link(res.headers(), res.url()).map(NextPage::Url),
+            PaginationStrategy::Cursor { path, param } => {
+                let cursor = match find_json_path(path, body)?.into_iter().next() {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(s)) => Some(s).filter(|s| !s.is_empty()),
+                    Some(v) => Some(v.to_string()),
+                };
+                cursor.map(|value| NextPage::Param {
+                    name: param.clone(),
+                    value,
+                })
+            }
+            PaginationStrategy::Page { param, start } => {
+                (!items.is_empty()).then(|| NextPage::Param {
+                    name: param.clone(),
+                    value: (start + self.pages as u64).to_string(),
+                })
+            }
+            PaginationStrategy::Offset { param, start } => {
+                (!items.is_empty()).then(|| NextPage::Param {
+                    name: param.clone(),
+                    value: (start + self.items).to_string(),
+                })
+            }
+        };
+
+        Ok((items, next))
+    }
+}
+
+/// The target of the `next` link of the `Link` headers, e.g. `<https://host/items?page=2>;
+/// rel="next"`, relative to the url of the response.
+fn next_link(headers: &HeaderMap, base: &Url) -> Option<Url> {
+    headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|link| {
+            let (target, params) = link.trim().strip_prefix('<')?.split_once('>')?;
+            let is_next = params.split(';').any(|p| {
+                p.trim()
+                    .strip_prefix("rel=")
+                    .map(|rel| {
+                        rel.trim_matches('"')
+                            .split_whitespace()
+                            .any(|r| r.eq_ignore_ascii_case("next"))
+                    })
+                    .unwrap_or(false)
+            });
+
+            is_next.then(|| base.join(target).ok()).flatten()
+        })
+}
+
+fn find_json_path(json_path: &str, value: &Value) -> Result<Vec<Value>> {
+    let path = JsonPathInst::from_str(json_path)
+        .map_err(|e| ApiClientError::new_invalid_json_path(json_path, e.to_string()))?;
+
+    // A path that matches nothing yields a single null, which is not a match.
+    Ok(find_slice(&path, value)
+        .into_iter()
+        .filter_map(|s| match s {
+            JsonPathValue::NoValue => None,
+            s => Some(s.to_data()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, LINK};
+    use reqwest::Url;
+    use rstest::rstest;
+
+    use super::next_link;
+
+    #[rstest]
+    #[case::absolute(
+        &[r#"<https://api.test/items?page=2>; rel="next", <https://api.test/items?page=9>; rel="last""#],
+        Some("https://api.test/items?page=2")
+    )]
+    #[case::relative(&[r#"</items?page=3>; rel="next""#], Some("https://api.test/items?page=3"))]
+    #[case::several_headers(
+        &[r#"<https://api.test/items?page=1>; rel="prev""#, "<https://api.test/items?page=3>; rel=next"],
+        Some("https://api.test/items?page=3")
+    )]
+    #[case::several_relations(
+        &[r#"<https://api.test/items?page=2>; rel="next last""#],
+        Some("https://api.test/items?page=2")
+    )]
+    #[case::last_page(&[r#"<https://api.test/items?page=1>; rel="first""#], None)]
+    #[case::no_header(&[], None)]
+    fn test_next_link(#[case] values: &[&str], #[case] expected: Option<&str>) {
+        let mut headers = HeaderMap::new();
+        for v in values {
+            headers.append(LINK, HeaderValue::from_str(v).unwrap());
+        }
+        let base = Url::parse("https://api.test/items?page=2").unwrap();
+
+        assert_eq!(
+            next_link(&headers, &base).map(String::from),
+            expected.map(str::to_string)
+        );
+    }
+}
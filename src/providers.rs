@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::env;
+
+use crate::secrets::get_keyring_secret;
+
+/// A source of variables looked up by name. Providers are asked for the variables referenced by
+/// the templates of a request that none of its files or variable maps define.
+pub trait VariableProvider: Send + Sync {
+    /// Value of a variable, or `None` if the provider doesn't define it.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Whether the values of the provider are hidden in the logs and the outputs.
+    fn is_secret(&self) -> bool {
+        false
+    }
+}
+
+impl VariableProvider for HashMap<String, String> {
+    fn get(&self, key: &str) -> Option<String> {
+        HashMap::get(self, key).cloned()
+    }
+}
+
+/// Variables of the process environment, named with a prefix, e.g. `API_CLI_VAR_token` for
+/// `token`.
+#[derive(Debug)]
+pub struct EnvProvider {
+    prefix: String,
+}
+
+impl EnvProvider {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl VariableProvider for EnvProvider {
+    fn get(&self, key: &str) -> Option<String> {
+        env::var(format!("{}{}", self.prefix, key)).ok()
+    }
+}
+
+/// Secrets of the OS keyring, by name.
+#[derive(Debug, Default)]
+pub struct KeyringProvider;
+
+impl VariableProvider for KeyringProvider {
+    fn get(&self, key: &str) -> Option<String> {
+        get_keyring_secret(key).ok()
+    }
+
+    fn is_secret(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvProvider, KeyringProvider, VariableProvider};
+
+    #[test]
+    fn test_env_provider_reads_prefixed_variables() {
+        std::env::set_var("API_CLI_TEST_PROVIDER_token", "some-token");
+
+        let provider = EnvProvider::new("API_CLI_TEST_PROVIDER_");
+
+        assert_eq!(provider.get("token"), Some("some-token".to_string()));
+        assert_eq!(provider.get("missing"), None);
+    }
+
+    #[test]
+    fn test_keyring_provider_reads_secrets() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        assert_eq!(KeyringProvider.get("api-cli-missing-secret"), None);
+        assert!(KeyringProvider.is_secret());
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// A token bucket limiting the pace of requests. A token is added to the bucket every interval
+/// and each request takes one, waiting for it when the bucket is empty. The bucket holds a single
+/// token, so the requests are never sent in bursts, even after a pause.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    /// When the next token is available, `None` while the bucket is full
+    next_token: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Allow a request every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_token: Mutex::new(None),
+        }
+    }
+
+    /// Allow `rate` requests per second.
+    pub fn per_second(rate: f64) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / rate))
+    }
+
+    /// Take a token, waiting until one is available.
+    pub async fn acquire(&self) {
+        let mut next_token = self.next_token.lock().await;
+
+        let now = Instant::now();
+        let available = match *next_token {
+            Some(t) if t > now => {
+                tokio::time::sleep_until(t).await;
+                t
+            }
+            _ => now,
+        };
+        *next_token = Some(available + self.interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::RateLimiter;
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::per_second(20.0);
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_does_not_accumulate_tokens() {
+        let limiter = RateLimiter::new(Duration::from_millis(50));
+        limiter.acquire().await;
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
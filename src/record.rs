@@ -0,0 +1,458 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{
+    HeaderMap,
+    HeaderName,
+    CONNECTION,
+    CONTENT_LENGTH,
+    CONTENT_TYPE,
+    HOST,
+    PROXY_AUTHORIZATION,
+    TE,
+    TRAILER,
+    TRANSFER_ENCODING,
+    UPGRADE,
+};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{debug, info};
+use reqwest::{redirect, Url};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::error::Result;
+use crate::masking::is_sensitive_header;
+use crate::mock::json_response;
+use crate::models::{
+    HttpBinaryBody,
+    HttpBody,
+    HttpJsonBody,
+    HttpParamsModel,
+    HttpRequestModel,
+    HttpTextBody,
+    KeyValueList,
+    KeyValuePair,
+};
+use crate::request_name::sanitize_segment;
+use crate::RequestModel;
+
+/// Headers describing a single connection, which are not forwarded nor recorded.
+static HOP_BY_HOP_HEADERS: [HeaderName; 7] = [
+    CONNECTION,
+    CONTENT_LENGTH,
+    PROXY_AUTHORIZATION,
+    TE,
+    TRAILER,
+    TRANSFER_ENCODING,
+    UPGRADE,
+];
+
+/// A local server forwarding the requests it receives to a target server, and converting each one
+/// to a request definition.
+pub struct RecordingProxy {
+    target: Url,
+    client: reqwest::Client,
+    recorder: Recorder,
+    logger: Option<RequestLogger>,
+}
+
+type Recorder = Box<dyn Fn(RecordedRequest) + Send + Sync>;
+type RequestLogger = Box<dyn Fn(&str) + Send + Sync>;
+
+/// A request received by a [`RecordingProxy`].
+#[derive(Debug)]
+pub struct RecordedRequest {
+    /// Name made of the method and the path of the request, e.g. `get users 42`. It is not unique.
+    pub name: String,
+    pub request: RequestModel,
+    /// Variables used in place of the values of the sensitive headers, e.g. `authorization`, which
+    /// are not recorded. They must be defined to send the request again.
+    pub variables: Vec<String>,
+}
+
+impl RecordingProxy {
+    /// Build a proxy forwarding the requests to `target`, calling `recorder` with each request
+    /// once the target replied.
+    pub fn new<F: Fn(RecordedRequest) + Send + Sync + 'static>(
+        target: Url,
+        recorder: F,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .redirect(redirect::Policy::none())
+            .build()?;
+
+        Ok(Self {
+            target,
+            client,
+            recorder: Box::new(recorder),
+            logger: None,
+        })
+    }
+
+    /// Call `logger` with a line describing each request received and the status of the target.
+    pub fn with_request_logger<F: Fn(&str) + Send + Sync + 'static>(mut self, logger: F) -> Self {
+        self.logger = Some(Box::new(logger));
+        self
+    }
+
+    fn log(&self, line: String) {
+        debug!("{}", line);
+        if let Some(l) = &self.logger {
+            l(&line);
+        }
+    }
+
+    /// Accept connections on `addr` until the process is stopped.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("Recording proxy listening on {}", listener.local_addr()?);
+
+        self.serve_listener(listener).await
+    }
+
+    async fn serve_listener(self, listener: TcpListener) -> Result<()> {
+        let proxy = Arc::new(self);
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let proxy = Arc::clone(&proxy);
+
+            tokio::spawn(async move {
+                let service = service_fn(|req| {
+                    let proxy = Arc::clone(&proxy);
+                    async move { Ok::<_, Infallible>(proxy.forward(req).await) }
+                });
+
+                if let Err(e) = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await
+                {
+                    debug!("Recording proxy connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn forward(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        let (parts, body) = req.into_parts();
+        let path = parts.uri.path().to_string();
+
+        let body = match body.collect().await {
+            Ok(b) => b.to_bytes(),
+            Err(e) => {
+                self.log(format!(
+                    "{} {} -> invalid request: {}",
+                    parts.method, path, e
+                ));
+                return json_response(StatusCode::BAD_REQUEST, json!({"error": e.to_string()}));
+            }
+        };
+
+        let path_and_query = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+        let url = match Url::parse(&format!(
+            "{}{}",
+            self.target.as_str().trim_end_matches('/'),
+            path_and_query
+        )) {
+            Ok(u) => u,
+            Err(e) => {
+                self.log(format!("{} {} -> invalid url: {}", parts.method, path, e));
+                return json_response(StatusCode::BAD_REQUEST, json!({"error": e.to_string()}));
+            }
+        };
+
+        let mut headers = parts.headers;
+        headers.remove(HOST);
+        for h in &HOP_BY_HOP_HEADERS {
+            headers.remove(h);
+        }
+
+        let response = self
+            .client
+            .request(parts.method.clone(), url.clone())
+            .headers(headers.clone())
+            .body(body.clone())
+            .send()
+            .await;
+
+        let response = match response {
+            Ok(r) => {
+                self.log(format!("{} {} -> {}", parts.method, path, r.status()));
+                proxied_response(r).await
+            }
+            Err(e) => {
+                self.log(format!("{} {} -> unreachable: {}", parts.method, path, e));
+                json_response(StatusCode::BAD_GATEWAY, json!({"error": e.to_string()}))
+            }
+        };
+
+        let (request, variables) = request_model(&parts.method, &url, &headers, &body);
+        (self.recorder)(RecordedRequest {
+            name: request_name(&parts.method, &path),
+            request,
+            variables,
+        });
+
+        response
+    }
+}
+
+async fn proxied_response(res: reqwest::Response) -> Response<Full<Bytes>> {
+    let status = res.status();
+    let mut headers = res.headers().clone();
+    for h in &HOP_BY_HOP_HEADERS {
+        headers.remove(h);
+    }
+
+    let body = match res.bytes().await {
+        Ok(b) => b,
+        Err(e) => {
+            return json_response(StatusCode::BAD_GATEWAY, json!({"error": e.to_string()}));
+        }
+    };
+
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+
+    response
+}
+
+fn request_name(method: &Method, path: &str) -> String {
+    let name = [method.as_str().to_lowercase()]
+        .into_iter()
+        .chain(
+            path.split('/')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    sanitize_segment(&name)
+}
+
+/// Request definition sending the same request as the one received, with the query parameters
+/// and body in their structured form when possible. The values of the sensitive headers are
+/// replaced by variables named after them, which are returned with the request.
+fn request_model(
+    method: &Method,
+    url: &Url,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> (RequestModel, Vec<String>) {
+    let mut base_url = url.clone();
+    base_url.set_query(None);
+
+    let query = KeyValueList::new(
+        url.query_pairs()
+            .map(|(k, v)| KeyValuePair::new(k, v))
+            .collect(),
+    );
+
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = (!body.is_empty()).then(|| request_body(body, content_type));
+
+    let mut recorded_headers = KeyValueList::default();
+    let mut variables = Vec::new();
+    for (name, value) in headers {
+        // The content type is part of the body.
+        if body.is_some() && name == CONTENT_TYPE {
+            continue;
+        }
+
+        let pair = match is_sensitive_header(name, value) {
+            true => {
+                let variable = name.as_str().replace('-', "_");
+                let mut pair = KeyValuePair::new(name.as_str(), format!("{{{{{}}}}}", variable));
+                pair.secret = Some(true);
+                if !variables.contains(&variable) {
+                    variables.push(variable);
+                }
+                pair
+            }
+            false => KeyValuePair::new(name.as_str(), String::from_utf8_lossy(value.as_bytes())),
+        };
+        recorded_headers.push(pair);
+    }
+
+    let request = RequestModel {
+        http: HttpRequestModel {
+            method: method.as_str().parse().unwrap_or_default(),
+            url: base_url.to_string(),
+            headers: recorded_headers,
+            params: HttpParamsModel {
+                query,
+                ..Default::default()
+            },
+            body,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    (request, variables)
+}
+
+fn request_body(body: &[u8], content_type: Option<String>) -> HttpBody {
+    if content_type.as_deref().is_some_and(|c| c.contains("json")) {
+        if let Ok(json) = serde_json::from_slice(body) {
+            return HttpBody::Json(HttpJsonBody {
+                json,
+                // Json bodies are sent as `application/json` by default.
+                content_type: content_type.filter(|c| c != "application/json"),
+            });
+        }
+    }
+
+    match std::str::from_utf8(body) {
+        Ok(text) => HttpBody::Text(HttpTextBody {
+            text: text.to_string(),
+            content_type,
+        }),
+        Err(_) => HttpBody::Binary(HttpBinaryBody {
+            binary: BASE64_STANDARD.encode(body),
+            content_type,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::StatusCode;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+    use super::{request_body, request_name, RecordedRequest, RecordingProxy};
+    use crate::models::{HttpBody, HttpMethod};
+
+    #[rstest]
+    #[case("GET", "/users/42", "get users 42")]
+    #[case("DELETE", "/", "delete")]
+    #[case("POST", "/v1/users:batch", "post v1 users-batch")]
+    fn test_request_name(#[case] method: &str, #[case] path: &str, #[case] expected: &str) {
+        assert_eq!(request_name(&method.parse().unwrap(), path), expected);
+    }
+
+    #[rstest]
+    #[case::json(b"{\"id\": 1}".to_vec(), Some("application/json"), "json")]
+    #[case::invalid_json(b"{".to_vec(), Some("application/json"), "text")]
+    #[case::text(b"a=1&b=2".to_vec(), Some("application/x-www-form-urlencoded"), "text")]
+    #[case::binary(vec![0xff, 0xfe], Some("image/png"), "binary")]
+    fn test_request_body(
+        #[case] body: Vec<u8>,
+        #[case] content_type: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let body = request_body(&body, content_type.map(str::to_string));
+
+        let kind = match body {
+            HttpBody::Json(j) => {
+                assert_eq!(j.content_type, None);
+                "json"
+            }
+            HttpBody::Text(t) => {
+                assert_eq!(t.content_type.as_deref(), content_type);
+                "text"
+            }
+            HttpBody::Binary(b) => {
+                assert_eq!(b.binary, "//4=");
+                "binary"
+            }
+            _ => "other",
+        };
+        assert_eq!(kind, expected);
+    }
+
+    #[tokio::test]
+    async fn test_recording_proxy() {
+        let target = MockServer::start().await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/api/users"))
+            .and(matchers::query_param("notify", "true"))
+            .and(matchers::header("Authorization", "Bearer abc"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::CREATED).set_body_json(json!({"id": 1})),
+            )
+            .expect(1)
+            .mount(&target)
+            .await;
+
+        let recorded = Arc::new(Mutex::new(Vec::<RecordedRequest>::new()));
+        let proxy = RecordingProxy::new(format!("{}/api", target.uri()).parse().unwrap(), {
+            let recorded = Arc::clone(&recorded);
+            move |r| recorded.lock().unwrap().push(r)
+        })
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(proxy.serve_listener(listener));
+
+        let res = reqwest::Client::new()
+            .post(format!("{}/users?notify=true", base_url))
+            .bearer_auth("abc")
+            .json(&json!({"name": "Jane"}))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.json::<Value>().await.unwrap(), json!({"id": 1}));
+
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        let RecordedRequest {
+            name,
+            request,
+            variables,
+        } = &recorded[0];
+        assert_eq!(name, "post users");
+        assert_eq!(request.http.method, HttpMethod::Post);
+        assert_eq!(request.http.url, format!("{}/api/users", target.uri()));
+
+        let query: Vec<_> = request.http.params.query.items().collect();
+        assert_eq!(query.len(), 1);
+        assert_eq!(
+            (query[0].key.as_str(), query[0].value.as_str()),
+            ("notify", "true")
+        );
+
+        let authorization = request
+            .http
+            .headers
+            .items()
+            .find(|h| h.key == "authorization")
+            .unwrap();
+        assert_eq!(authorization.value, "{{authorization}}");
+        assert_eq!(authorization.secret, Some(true));
+        assert_eq!(variables, &["authorization"]);
+        assert!(!serde_yaml::to_string(request).unwrap().contains("abc"));
+        assert!(!request
+            .http
+            .headers
+            .items()
+            .any(|h| h.key == "content-type" || h.key == "host"));
+
+        match &request.http.body {
+            Some(HttpBody::Json(j)) => assert_eq!(j.json, json!({"name": "Jane"})),
+            b => panic!("unexpected body: {:?}", b),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::sync::{Arc, Mutex};
+
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
+
+use crate::models::FollowRedirects;
+use crate::timing::RequestState;
+
+/// Maximum number of redirects followed when the request doesn't define it, same as reqwest.
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// A redirect followed while executing a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    /// Status of the response that redirected the request.
+    pub status: StatusCode,
+    /// Url to which the request was redirected.
+    pub location: Url,
+}
+
+pub(crate) type SharedRedirects = Arc<Mutex<Vec<Redirect>>>;
+
+/// Redirect policy following up to the configured number of redirects, and recording them in the
+/// state of the request.
+pub(crate) fn policy(follow: Option<FollowRedirects>) -> Policy {
+    let max = match follow {
+        None | Some(FollowRedirects::Enabled(true)) => DEFAULT_MAX_REDIRECTS,
+        Some(FollowRedirects::Enabled(false)) => 0,
+        Some(FollowRedirects::Max(m)) => m,
+    };
+
+    if max == 0 {
+        return Policy::none();
+    }
+
+    Policy::custom(move |attempt| {
+        if attempt.previous().len() > max {
+            return attempt.error(format!("too many redirects (max: {})", max));
+        }
+
+        if let Some(state) = RequestState::current() {
+            state.redirects.lock().unwrap().push(Redirect {
+                status: attempt.status(),
+                location: attempt.url().clone(),
+            });
+        }
+
+        attempt.follow()
+    })
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, CONTENT_ENCODING};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApiClientError, Result};
+use crate::models::{KeyValueList, KeyValuePair};
+use crate::response::ApiResponse;
+
+/// A response kept to be replayed later without sending its request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "KeyValueList::is_empty")]
+    headers: KeyValueList,
+    /// Base64 encoding of the body, which can be binary
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    body: String,
+    latency_ms: u64,
+}
+
+impl CachedResponse {
+    pub fn from_response(res: &ApiResponse) -> Self {
+        // The body is kept as it was decoded, so it must not be decoded again when replayed.
+        let decoded = res.compressed_size().is_some();
+        let headers = res
+            .headers()
+            .iter()
+            .filter(|(k, _)| !(decoded && *k == CONTENT_ENCODING))
+            .map(|(k, v)| KeyValuePair {
+                key: k.to_string(),
+                value: String::from_utf8_lossy(v.as_bytes()).to_string(),
+                enabled: None,
+                enabled_if: None,
+                encode: None,
+                secret: None,
+            })
+            .collect();
+
+        Self {
+            url: res.url().to_string(),
+            status: res.status().as_u16(),
+            headers: KeyValueList::new(headers),
+            body: BASE64_STANDARD.encode(res.body()),
+            latency_ms: res.latency().as_millis() as u64,
+        }
+    }
+
+    /// The response as it was received, with the latency of the original request.
+    pub fn replay(&self) -> Result<ApiResponse> {
+        let url = Url::parse(&self.url)
+            .map_err(|e| ApiClientError::new_invalid_url(self.url.clone(), e))?;
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|e| ApiClientError::new_invalid_cached_response(e.to_string()))?;
+        let body = BASE64_STANDARD
+            .decode(&self.body)
+            .map_err(|e| ApiClientError::new_invalid_cached_response(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        for h in self.headers.items() {
+            let (name, value) = crate::parse_header(h.key.clone(), h.value.clone())?;
+            headers.append(name, value);
+        }
+
+        Ok(ApiResponse::replayed(
+            url,
+            status,
+            headers,
+            Duration::from_millis(self.latency_ms),
+            Bytes::from(body),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, SET_COOKIE};
+    use reqwest::{StatusCode, Url};
+
+    use super::CachedResponse;
+    use crate::response::ApiResponse;
+
+    #[test]
+    fn test_cached_response_is_replayed_as_received() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        headers.append(SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("b=2"));
+        let res = ApiResponse::replayed(
+            Url::parse("http://localhost/items").unwrap(),
+            StatusCode::CREATED,
+            headers,
+            Duration::from_millis(42),
+            Bytes::from_static(&[0, 159, 146, 150]),
+        );
+
+        let cached: CachedResponse = serde_json::from_str(
+            &serde_json::to_string(&CachedResponse::from_response(&res)).unwrap(),
+        )
+        .unwrap();
+        let replayed = cached.replay().expect("replay failed");
+
+        assert_eq!(replayed.url().as_str(), "http://localhost/items");
+        assert_eq!(replayed.status(), StatusCode::CREATED);
+        assert_eq!(replayed.headers(), res.headers());
+        assert_eq!(replayed.latency(), Duration::from_millis(42));
+        assert_eq!(replayed.body(), &[0, 159, 146, 150]);
+    }
+
+    #[test]
+    fn test_cached_response_with_invalid_status() {
+        let cached: CachedResponse =
+            serde_json::from_str(r#"{"url": "http://localhost", "status": 1000, "latency_ms": 1}"#)
+                .unwrap();
+
+        assert!(cached.replay().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::error::{ApiClientError, Result};
+
+/// Result of one of the requests of a run.
+#[derive(Debug)]
+pub enum CaseOutcome {
+    Passed,
+    /// The request was executed, but some of its checks failed.
+    Failed(Vec<String>),
+    /// The request could not be executed.
+    Error(String),
+    /// The request was not executed.
+    Skipped(String),
+}
+
+#[derive(Debug)]
+pub struct CaseResult {
+    pub name: String,
+    /// Status of the response, if one was received.
+    pub status: Option<String>,
+    pub duration: Duration,
+    pub outcome: CaseOutcome,
+}
+
+/// Summary of the execution of several requests, which can be written for CI systems.
+#[derive(Debug)]
+pub struct RunReport {
+    pub name: String,
+    pub cases: Vec<CaseResult>,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "junit" => Ok(ReportFormat::Junit),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown report format: `{}`", s)),
+        }
+    }
+}
+
+impl RunReport {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            cases: Vec::new(),
+            duration: Duration::ZERO,
+        }
+    }
+
+    pub fn passed(&self) -> usize {
+        self.count(|o| matches!(o, CaseOutcome::Passed))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, CaseOutcome::Failed(_)))
+    }
+
+    pub fn errors(&self) -> usize {
+        self.count(|o| matches!(o, CaseOutcome::Error(_)))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, CaseOutcome::Skipped(_)))
+    }
+
+    /// Whether every request that was executed passed.
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0 && self.errors() == 0
+    }
+
+    fn count(&self, f: impl Fn(&CaseOutcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| f(&c.outcome)).count()
+    }
+
+    /// Write the report to `path` in the given format.
+    pub fn write(&self, format: ReportFormat, path: &Path) -> Result<()> {
+        let content = match format {
+            ReportFormat::Junit => self.to_junit(),
+            ReportFormat::Json => serde_json::to_string_pretty(&self.to_json())?,
+        };
+
+        fs::write(path, content).map_err(|e| ApiClientError::from_io_error_with_path(e, path))
+    }
+
+    /// The report as a JUnit XML document, with one test suite for the run.
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let counts = format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\"",
+            self.cases.len(),
+            self.failed(),
+            self.errors(),
+            self.skipped(),
+            self.duration.as_secs_f64(),
+        );
+        let name = escape_xml(&self.name);
+
+        let _ = writeln!(xml, "<testsuites name=\"api-cli\" {}>", counts);
+        let _ = writeln!(xml, "  <testsuite name=\"{}\" {}>", name, counts);
+
+        for case in &self.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                name,
+                case.duration.as_secs_f64()
+            );
+
+            match &case.outcome {
+                CaseOutcome::Passed => xml.push_str("/>\n"),
+                CaseOutcome::Failed(failures) => {
+                    xml.push_str(">\n");
+                    for f in failures {
+                        let f = escape_xml(f);
+                        let _ = writeln!(xml, "      <failure message=\"{}\">{}</failure>", f, f);
+                    }
+                    xml.push_str("    </testcase>\n");
+                }
+                CaseOutcome::Error(e) => {
+                    let e = escape_xml(e);
+                    let _ = writeln!(xml, ">\n      <error message=\"{}\">{}</error>", e, e);
+                    xml.push_str("    </testcase>\n");
+                }
+                CaseOutcome::Skipped(reason) => {
+                    let reason = escape_xml(reason);
+                    let _ = writeln!(xml, ">\n      <skipped message=\"{}\"/>", reason);
+                    xml.push_str("    </testcase>\n");
+                }
+            }
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+
+        xml
+    }
+
+    /// The report as a json document.
+    pub fn to_json(&self) -> Value {
+        let results: Vec<Value> = self
+            .cases
+            .iter()
+            .map(|c| {
+                let (result, failures, message) = match &c.outcome {
+                    CaseOutcome::Passed => ("passed", &[][..], None),
+                    CaseOutcome::Failed(f) => ("failed", &f[..], None),
+                    CaseOutcome::Error(e) => ("error", &[][..], Some(e)),
+                    CaseOutcome::Skipped(r) => ("skipped", &[][..], Some(r)),
+                };
+
+                json!({
+                    "name": c.name,
+                    "result": result,
+                    "status": c.status,
+                    "duration_ms": c.duration.as_secs_f64() * 1000.0,
+                    "failures": failures,
+                    "message": message,
+                })
+            })
+            .collect();
+
+        json!({
+            "name": self.name,
+            "duration_ms": self.duration.as_secs_f64() * 1000.0,
+            "tests": self.cases.len(),
+            "passed": self.passed(),
+            "failed": self.failed(),
+            "errors": self.errors(),
+            "skipped": self.skipped(),
+            "results": results,
+        })
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::{CaseOutcome, CaseResult, RunReport};
+
+    fn report() -> RunReport {
+        let case = |name: &str, outcome| CaseResult {
+            name: name.to_string(),
+            status: Some("200 OK".to_string()),
+            duration: Duration::from_millis(250),
+            outcome,
+        };
+
+        RunReport {
+            name: "users".to_string(),
+            cases: vec![
+                case("login", CaseOutcome::Passed),
+                case(
+                    "get <me>",
+                    CaseOutcome::Failed(vec!["Unexpected status \"404\"".to_string()]),
+                ),
+                case(
+                    "delete",
+                    CaseOutcome::Error("Connection refused".to_string()),
+                ),
+                case(
+                    "events",
+                    CaseOutcome::Skipped("Streamed request".to_string()),
+                ),
+            ],
+            duration: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        let report = report();
+
+        assert_eq!(report.passed(), 1);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.errors(), 1);
+        assert_eq!(report.skipped(), 1);
+        assert!(!report.is_success());
+    }
+
+    #[test]
+    fn test_to_junit() {
+        let counts = r#"tests="4" failures="1" errors="1" skipped="1" time="1.000""#;
+        let expected = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="api-cli" {counts}>
+  <testsuite name="users" {counts}>
+    <testcase name="login" classname="users" time="0.250"/>
+    <testcase name="get &lt;me&gt;" classname="users" time="0.250">
+      <failure message="Unexpected status &quot;404&quot;">Unexpected status &quot;404&quot;</failure>
+    </testcase>
+    <testcase name="delete" classname="users" time="0.250">
+      <error message="Connection refused">Connection refused</error>
+    </testcase>
+    <testcase name="events" classname="users" time="0.250">
+      <skipped message="Streamed request"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+
+        assert_eq!(report().to_junit(), expected);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = report().to_json();
+
+        assert_eq!(json["tests"], 4);
+        assert_eq!(json["passed"], 1);
+        assert_eq!(
+            json["results"][1],
+            json!({
+                "name": "get <me>",
+                "result": "failed",
+                "status": "200 OK",
+                "duration_ms": 250.0,
+                "failures": ["Unexpected status \"404\""],
+                "message": null,
+            })
+        );
+        assert_eq!(json["results"][2]["message"], "Connection refused");
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use crate::models::FOLDER_FILE_NAME;
+
+/// Separator of the folders in the name of a request, e.g. `users:get`.
+pub static REQUEST_NAME_SEPARATOR: char = ':';
+
+static REQUEST_FILE_EXTENSION: &str = "yaml";
+
+/// Characters that can't be used in file names on Windows, on top of the separators.
+static ILLEGAL_CHARACTERS: [char; 6] = ['<', '>', '"', '|', '?', '*'];
+
+/// Names that can't be used as a file name on Windows, even with an extension.
+static RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The name of a request in a collection, made of the folders containing the request followed by
+/// its own name, e.g. `users:get`.
+///
+/// `/` and `\` are accepted as separators when parsing and are normalized to `:`, so the name can
+/// be mapped to a path on any platform.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestName(Vec<String>);
+
+impl RequestName {
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Path of the request file, relative to the directory of its collection.
+    pub fn to_path(&self) -> PathBuf {
+        let (name, folders) = self.0.split_last().expect("empty request name");
+
+        let mut path: PathBuf = folders.iter().collect();
+        path.push(format!("{}.{}", name, REQUEST_FILE_EXTENSION));
+
+        path
+    }
+
+    /// Name of the request defined by a file, from its path relative to the directory of its
+    /// collection. Returns `None` if the path is not one of a request file.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        if path.extension()? != REQUEST_FILE_EXTENSION {
+            return None;
+        }
+
+        let mut segments = Vec::new();
+        for component in path.with_extension("").components() {
+            match component {
+                Component::Normal(s) => segments.push(s.to_str()?.to_string()),
+                _ => return None,
+            }
+        }
+
+        let name = segments.join(&REQUEST_NAME_SEPARATOR.to_string());
+        name.parse().ok()
+    }
+}
+
+impl FromStr for RequestName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments: Vec<String> = s
+            .split([REQUEST_NAME_SEPARATOR, '/', '\\'])
+            .map(str::to_string)
+            .collect();
+
+        for segment in &segments {
+            validate_segment(segment)
+                .map_err(|e| format!("invalid request name `{}`: {}", s, e))?;
+        }
+
+        let file_name = format!(
+            "{}.{}",
+            segments[segments.len() - 1],
+            REQUEST_FILE_EXTENSION
+        );
+        if file_name == FOLDER_FILE_NAME || (segments.len() == 1 && file_name == "collection.yaml")
+        {
+            return Err(format!(
+                "invalid request name `{}`: `{}` is reserved",
+                s, file_name
+            ));
+        }
+        if segments.len() > 1 && segments[0] == "environments" {
+            return Err(format!(
+                "invalid request name `{}`: `environments` is reserved",
+                s
+            ));
+        }
+
+        Ok(Self(segments))
+    }
+}
+
+impl fmt::Display for RequestName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(&REQUEST_NAME_SEPARATOR.to_string()))
+    }
+}
+
+/// Turn a display name, e.g. from another tool, into a valid folder or request name.
+pub(crate) fn sanitize_segment(name: &str) -> String {
+    let mut segment: String = name
+        .chars()
+        .map(|c| match c {
+            c if c == REQUEST_NAME_SEPARATOR || c == '/' || c == '\\' => '-',
+            c if c.is_control() || ILLEGAL_CHARACTERS.contains(&c) => '-',
+            c => c,
+        })
+        .collect();
+    segment = segment.trim().trim_end_matches(['.', ' ']).to_string();
+
+    let stem = segment.split('.').next().unwrap_or_default();
+    if segment.is_empty() || segment == "." {
+        segment = "unnamed".to_string();
+    } else if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        segment.insert(0, '_');
+    }
+
+    segment
+}
+
+fn validate_segment(segment: &str) -> Result<(), String> {
+    if segment.is_empty() {
+        return Err("empty folder or name".to_string());
+    }
+    if segment == "." || segment == ".." {
+        return Err(format!("`{}` is not allowed", segment));
+    }
+    if let Some(c) = segment
+        .chars()
+        .find(|c| c.is_control() || ILLEGAL_CHARACTERS.contains(c))
+    {
+        return Err(format!("{:?} is not allowed", c));
+    }
+    if segment.ends_with(['.', ' ']) || segment.starts_with(' ') {
+        return Err("names can't start with a space or end with a space or a dot".to_string());
+    }
+
+    let stem = segment.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        return Err(format!("`{}` is reserved on Windows", stem));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use rstest::rstest;
+
+    use super::RequestName;
+
+    #[rstest]
+    #[case("login", "login")]
+    #[case("users:get", "users:get")]
+    #[case("users/get", "users:get")]
+    #[case("users\\admin\\list", "users:admin:list")]
+    #[case("v1.2:get user", "v1.2:get user")]
+    fn test_parse_request_name(#[case] name: &str, #[case] expected: &str) {
+        let name: RequestName = name.parse().unwrap();
+
+        assert_eq!(name.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("users:")]
+    #[case(":users")]
+    #[case("users::get")]
+    #[case("users:..:get")]
+    #[case("what?")]
+    #[case("a|b")]
+    #[case("name.")]
+    #[case(" name")]
+    #[case("tab\tname")]
+    #[case("con")]
+    #[case("users:LPT1.log")]
+    #[case("users:folder")]
+    #[case("collection")]
+    #[case("environments:dev")]
+    fn test_parse_invalid_request_name(#[case] name: &str) {
+        assert!(name.parse::<RequestName>().is_err(), "{}", name);
+    }
+
+    #[rstest]
+    #[case("users:admin:list", Path::new("users").join("admin").join("list.yaml"))]
+    #[case("v1.2", PathBuf::from("v1.2.yaml"))]
+    fn test_request_name_to_path(#[case] name: &str, #[case] expected: PathBuf) {
+        let name: RequestName = name.parse().unwrap();
+
+        assert_eq!(name.to_path(), expected);
+    }
+
+    #[rstest]
+    #[case(Path::new("users").join("get.yaml"), Some("users:get"))]
+    #[case(PathBuf::from("login.yaml"), Some("login"))]
+    #[case(PathBuf::from("v1.2.yaml"), Some("v1.2"))]
+    #[case(PathBuf::from("login.json"), None)]
+    #[case(Path::new("users").join("folder.yaml"), None)]
+    #[case(Path::new("..").join("get.yaml"), None)]
+    fn test_request_name_from_path(#[case] path: PathBuf, #[case] expected: Option<&str>) {
+        assert_eq!(
+            RequestName::from_path(&path).map(|n| n.to_string()),
+            expected.map(str::to_string)
+        );
+    }
+
+    #[test]
+    fn test_request_name_round_trip() {
+        let name: RequestName = "users:admin:list".parse().unwrap();
+
+        assert_eq!(RequestName::from_path(&name.to_path()), Some(name));
+    }
+}
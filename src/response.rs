@@ -0,0 +1,302 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use once_cell::unsync::OnceCell;
+use reqwest::header::{HeaderMap, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::{Response, StatusCode, Url, Version};
+use serde_json::Value;
+
+use crate::decompression::decompress;
+use crate::error::Result;
+use crate::markup::Markup;
+use crate::redirect::Redirect;
+use crate::timing::Timings;
+
+static JSON_LINES_MEDIA_TYPES: [&str; 4] = [
+    "application/x-ndjson",
+    "application/ndjson",
+    "application/jsonl",
+    "application/x-jsonlines",
+];
+
+/// Media types of binary content that aren't covered by their top-level type, e.g. `image/*`.
+static BINARY_MEDIA_TYPES: [&str; 9] = [
+    "application/octet-stream",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "application/x-protobuf",
+    "application/protobuf",
+    "application/grpc",
+    "application/wasm",
+    "application/vnd.ms-excel",
+];
+
+static BINARY_TOP_LEVEL_TYPES: [&str; 4] = ["image", "audio", "video", "font"];
+
+static XML_MEDIA_TYPES: [&str; 2] = ["application/xml", "text/xml"];
+
+static HTML_MEDIA_TYPES: [&str; 2] = ["text/html", "application/xhtml+xml"];
+
+static YAML_MEDIA_TYPES: [&str; 4] = [
+    "application/yaml",
+    "application/x-yaml",
+    "text/yaml",
+    "text/x-yaml",
+];
+
+/// A response whose body has been fully read.
+#[derive(Debug)]
+pub struct ApiResponse {
+    url: Url,
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    latency: Duration,
+    timings: Timings,
+    redirects: Vec<Redirect>,
+    body: Bytes,
+    compressed_size: Option<u64>,
+    json: OnceCell<Option<Value>>,
+}
+
+impl ApiResponse {
+    pub(crate) async fn collect(
+        res: Response,
+        latency: Duration,
+        mut timings: Timings,
+        redirects: Vec<Redirect>,
+        decode: bool,
+    ) -> Result<Self> {
+        let url = res.url().clone();
+        let status = res.status();
+        let version = res.version();
+        let headers = res.headers().clone();
+
+        let start = Instant::now();
+        let mut body = res.bytes().await?;
+        timings.ttfb = latency;
+        timings.download = start.elapsed();
+
+        let mut compressed_size = None;
+        let encoding = headers.get(CONTENT_ENCODING).and_then(|v| v.to_str().ok());
+        if let (true, Some(encoding)) = (decode, encoding) {
+            if let Some(decoded) = decompress(encoding, &body)? {
+                compressed_size = Some(body.len() as u64);
+                body = Bytes::from(decoded);
+            }
+        }
+
+        Ok(Self {
+            url,
+            status,
+            version,
+            headers,
+            latency,
+            timings,
+            redirects,
+            body,
+            compressed_size,
+            json: OnceCell::new(),
+        })
+    }
+
+    /// A response that was received earlier, replayed from the cache.
+    pub(crate) fn replayed(
+        url: Url,
+        status: StatusCode,
+        headers: HeaderMap,
+        latency: Duration,
+        body: Bytes,
+    ) -> Self {
+        Self {
+            url,
+            status,
+            version: Version::HTTP_11,
+            headers,
+            latency,
+            timings: Timings {
+                ttfb: latency,
+                ..Default::default()
+            },
+            redirects: Vec::new(),
+            body,
+            compressed_size: None,
+            json: OnceCell::new(),
+        }
+    }
+
+    /// Url of the response, once the redirects are followed.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Time elapsed between sending the request and receiving the response headers.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Breakdown of the time spent on the request, including reading the body.
+    pub fn timings(&self) -> &Timings {
+        &self.timings
+    }
+
+    /// Redirects followed before receiving this response, in order.
+    pub fn redirects(&self) -> &[Redirect] {
+        &self.redirects
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The content codings applied to the body by the server, from the content encoding header.
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.headers
+            .get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    /// The size of the body as received, if it has been decompressed.
+    pub fn compressed_size(&self) -> Option<u64> {
+        self.compressed_size
+    }
+
+    /// The body as text, if it is valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.body).ok()
+    }
+
+    /// The body parsed as json, if it is valid json. The body is only parsed on the first call.
+    pub fn json(&self) -> Option<&Value> {
+        self.json
+            .get_or_init(|| serde_json::from_slice(&self.body).ok())
+            .as_ref()
+    }
+
+    /// Whether the body is binary content rather than text, from its content type or because it
+    /// is not valid UTF-8. SVG images are considered text.
+    pub fn is_binary(&self) -> bool {
+        if self.body.is_empty() {
+            return false;
+        }
+
+        let mime = self.media_type();
+        let top_level = mime.split('/').next().unwrap_or_default();
+        if (BINARY_TOP_LEVEL_TYPES.contains(&top_level) && !mime.ends_with("+xml"))
+            || BINARY_MEDIA_TYPES.contains(&mime.as_str())
+        {
+            return true;
+        }
+
+        self.text().is_none_or(|t| t.contains('\0'))
+    }
+
+    /// The documents of a newline-delimited json body, one per non-empty line. Only available
+    /// when the content type is one of the json lines media types and every line is valid json.
+    pub fn json_lines(&self) -> Option<Vec<Value>> {
+        if !JSON_LINES_MEDIA_TYPES.contains(&self.media_type().as_str()) {
+            return None;
+        }
+
+        self.text()?
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l).ok())
+            .collect()
+    }
+
+    /// The markup language of the body, from its content type.
+    pub fn markup(&self) -> Option<Markup> {
+        let mime = self.media_type();
+
+        if HTML_MEDIA_TYPES.contains(&mime.as_str()) {
+            Some(Markup::Html)
+        } else if XML_MEDIA_TYPES.contains(&mime.as_str()) || mime.ends_with("+xml") {
+            Some(Markup::Xml)
+        } else if YAML_MEDIA_TYPES.contains(&mime.as_str()) || mime.ends_with("+yaml") {
+            Some(Markup::Yaml)
+        } else {
+            None
+        }
+    }
+
+    /// The media type of the content type header, without its parameters, in lowercase.
+    fn media_type(&self) -> String {
+        self.headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| {
+                v.split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_lowercase()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A response whose body has been written to a file.
+#[derive(Debug)]
+pub struct SavedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    latency: Duration,
+    path: PathBuf,
+    size: u64,
+}
+
+impl SavedResponse {
+    pub(crate) fn new(res: &Response, latency: Duration, path: &Path, size: u64) -> Self {
+        Self {
+            status: res.status(),
+            version: res.version(),
+            headers: res.headers().clone(),
+            latency,
+            path: path.to_path_buf(),
+            size,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Time elapsed between sending the request and receiving the response headers.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// File in which the body was written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Size of the body, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+
+use jsonschema::paths::LocationSegment;
+use jsonschema::Validator;
+use serde_json::Value;
+
+use crate::error::{ApiClientError, Result};
+use crate::ApiResponse;
+
+/// A JSON Schema against which responses are validated.
+///
+/// Schemas are validated with the `jsonschema` crate, using the draft of their `$schema` or
+/// 2020-12 by default, with the `format` keyword enforced. References to other files or urls are
+/// not resolved and fail to compile.
+#[derive(Debug, Clone)]
+pub struct JsonSchema(Validator);
+
+/// A location of a json document that doesn't match a schema.
+#[derive(Debug, PartialEq)]
+pub struct SchemaViolation {
+    /// Json path of the invalid value, e.g. `$.items[0].id`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl JsonSchema {
+    pub fn new(schema: Value) -> Result<Self> {
+        if !matches!(schema, Value::Object(_) | Value::Bool(_)) {
+            return Err(ApiClientError::new_invalid_schema(
+                "a schema must be an object or a boolean".to_string(),
+            ));
+        }
+
+        jsonschema::options()
+            .should_validate_formats(true)
+            .build(&schema)
+            .map(Self)
+            .map_err(|e| ApiClientError::new_invalid_schema(e.to_string()))
+    }
+
+    /// Validate a json document, returning the locations that don't match the schema.
+    pub fn validate(&self, instance: &Value) -> Vec<SchemaViolation> {
+        self.0
+            .iter_errors(instance)
+            .map(|e| SchemaViolation {
+                path: json_path(e.instance_path().iter()),
+                message: e.to_string(),
+            })
+            .collect()
+    }
+
+    /// Validate the body of a response, which must be json.
+    pub fn validate_response(&self, res: &ApiResponse) -> Vec<SchemaViolation> {
+        match res.json() {
+            Some(v) => self.validate(v),
+            None => vec![SchemaViolation {
+                path: "$".to_string(),
+                message: "response body is not valid json".to_string(),
+            }],
+        }
+    }
+}
+
+/// Json path of a location given as a json pointer, e.g. `$.items[0].id` for `/items/0/id`.
+fn json_path<'a>(segments: impl Iterator<Item = LocationSegment<'a>>) -> String {
+    segments.fold("$".to_string(), |path, s| match s {
+        LocationSegment::Property(p) => format!("{}.{}", path, p),
+        LocationSegment::Index(i) => format!("{}[{}]", path, i),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    use super::JsonSchema;
+
+    fn violations(schema: Value, instance: Value) -> Vec<String> {
+        JsonSchema::new(schema)
+            .expect("invalid schema")
+            .validate(&instance)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[rstest]
+    #[case::type_(json!({"type": "string"}), json!("a"))]
+    #[case::integer(json!({"type": "integer"}), json!(1.0))]
+    #[case::types(json!({"type": ["string", "null"]}), json!(null))]
+    #[case::enum_(json!({"enum": ["a", 1]}), json!(1.0))]
+    #[case::const_(json!({"const": {"a": [1]}}), json!({"a": [1]}))]
+    #[case::properties(
+        json!({"properties": {"a": {"type": "number"}}, "required": ["a"]}),
+        json!({"a": 1, "b": 2}),
+    )]
+    #[case::pattern_properties(
+        json!({"patternProperties": {"^x-": {"type": "string"}}, "additionalProperties": false}),
+        json!({"x-a": "b"}),
+    )]
+    #[case::items(json!({"items": {"type": "integer"}, "minItems": 1}), json!([1, 2]))]
+    #[case::prefix_items(
+        json!({"prefixItems": [{"type": "string"}], "items": {"type": "number"}}),
+        json!(["a", 1, 2]),
+    )]
+    #[case::contains(json!({"contains": {"const": 2}}), json!([1, 2]))]
+    #[case::string(json!({"minLength": 2, "maxLength": 3, "pattern": "^a"}), json!("ab"))]
+    #[case::format(json!({"format": "date-time"}), json!("2024-01-02T03:04:05Z"))]
+    #[case::number(json!({"minimum": 1, "exclusiveMaximum": 3, "multipleOf": 0.5}), json!(2.5))]
+    #[case::any_of(json!({"anyOf": [{"type": "string"}, {"type": "number"}]}), json!(1))]
+    #[case::one_of(json!({"oneOf": [{"minimum": 2}, {"maximum": 1}]}), json!(3))]
+    #[case::not(json!({"not": {"type": "null"}}), json!(1))]
+    #[case::if_then(json!({"if": {"minimum": 0}, "then": {"maximum": 10}}), json!(-20))]
+    #[case::ref_(
+        json!({"$defs": {"id": {"type": "integer"}}, "properties": {"id": {"$ref": "#/$defs/id"}}}),
+        json!({"id": 1}),
+    )]
+    #[case::recursive_ref(
+        json!({"properties": {"child": {"$ref": "#"}}, "required": ["name"]}),
+        json!({"name": "a", "child": {"name": "b"}}),
+    )]
+    #[case::true_(json!(true), json!({"a": 1}))]
+    fn test_validate_accepts_valid_documents(#[case] schema: Value, #[case] instance: Value) {
+        assert_eq!(violations(schema, instance), Vec::<String>::new());
+    }
+
+    #[rstest]
+    #[case::type_(json!({"type": "string"}), json!(1), "$: 1 is not of type \"string\"")]
+    #[case::integer(json!({"type": "integer"}), json!(1.5), "$: 1.5 is not of type \"integer\"")]
+    #[case::enum_(json!({"enum": ["a", "b"]}), json!("c"), "$: \"c\" is not one of \"a\" or \"b\"")]
+    #[case::const_(json!({"const": 1}), json!(2), "$: 1 was expected")]
+    #[case::required(json!({"required": ["id"]}), json!({}), "$: \"id\" is a required property")]
+    #[case::additional_properties(
+        json!({"properties": {"a": {}}, "additionalProperties": false}),
+        json!({"a": 1, "b": 2}),
+        "$: Additional properties are not allowed ('b' was unexpected)",
+    )]
+    #[case::nested(
+        json!({"properties": {"items": {"items": {"properties": {"id": {"type": "integer"}}}}}}),
+        json!({"items": [{"id": 1}, {"id": "2"}]}),
+        "$.items[1].id: \"2\" is not of type \"integer\"",
+    )]
+    #[case::min_items(json!({"minItems": 1}), json!([]), "$: [] has less than 1 item")]
+    #[case::unique_items(json!({"uniqueItems": true}), json!([1, 1.0]), "$: [1,1.0] has non-unique elements")]
+    #[case::tuple(
+        json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "items": [{"type": "string"}],
+            "additionalItems": false,
+        }),
+        json!(["a", "b"]),
+        "$: Additional items are not allowed (\"b\" was unexpected)",
+    )]
+    #[case::max_length(json!({"maxLength": 2}), json!("abc"), "$: \"abc\" is longer than 2 characters")]
+    #[case::pattern(json!({"pattern": "^a+$"}), json!("b"), "$: \"b\" does not match \"^a+$\"")]
+    #[case::format(json!({"format": "uuid"}), json!("123"), "$: \"123\" is not a \"uuid\"")]
+    #[case::minimum(json!({"minimum": 1}), json!(0), "$: 0 is less than the minimum of 1")]
+    #[case::exclusive_maximum(
+        json!({"exclusiveMaximum": 1}),
+        json!(1),
+        "$: 1 is greater than or equal to the maximum of 1",
+    )]
+    #[case::multiple_of(json!({"multipleOf": 3}), json!(4), "$: 4 is not a multiple of 3")]
+    #[case::any_of(
+        json!({"anyOf": [{"type": "string"}, {"type": "null"}]}),
+        json!(1),
+        "$: 1 is not valid under any of the schemas listed in the 'anyOf' keyword",
+    )]
+    #[case::one_of(
+        json!({"oneOf": [{"type": "number"}, {"type": "integer"}]}),
+        json!(1),
+        "$: 1 is valid under more than one of the schemas listed in the 'oneOf' keyword",
+    )]
+    #[case::not(json!({"not": {"type": "null"}}), json!(null), "$: {\"type\":\"null\"} is not allowed for null")]
+    #[case::if_else(
+        json!({"if": {"type": "string"}, "else": {"type": "number"}}),
+        json!(true),
+        "$: true is not of type \"number\"",
+    )]
+    #[case::ref_(
+        json!({"definitions": {"id": {"type": "integer"}}, "items": {"$ref": "#/definitions/id"}}),
+        json!(["a"]),
+        "$[0]: \"a\" is not of type \"integer\"",
+    )]
+    #[case::false_(json!({"properties": {"a": false}}), json!({"a": 1}), "$.a: False schema does not allow 1")]
+    fn test_validate_reports_violations(
+        #[case] schema: Value,
+        #[case] instance: Value,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(violations(schema, instance), vec![expected.to_string()]);
+    }
+
+    #[rstest]
+    #[case::not_a_schema(
+        json!("string"),
+        "InvalidSchema: Invalid response schema: a schema must be an object or a boolean",
+    )]
+    #[case::invalid_keyword(
+        json!({"type": "text"}),
+        "InvalidSchema: Invalid response schema: \"text\" is not valid under any of the schemas listed in the 'anyOf' keyword",
+    )]
+    #[case::unresolvable_ref(
+        json!({"$ref": "#/missing"}),
+        "InvalidSchema: Invalid response schema: Pointer '/missing' does not exist",
+    )]
+    #[case::remote_ref(
+        json!({"$ref": "https://example.com/schema.json"}),
+        "InvalidSchema: Invalid response schema: Resource 'https://example.com/schema.json' is not \
+         present in a registry and retrieving it failed: `resolve-http` feature or a custom \
+         resolver is required to resolve external schemas via HTTP",
+    )]
+    fn test_new_rejects_invalid_schemas(#[case] schema: Value, #[case] expected: &str) {
+        let err = JsonSchema::new(schema).unwrap_err();
+
+        assert_eq!(err.to_string(), expected);
+    }
+}
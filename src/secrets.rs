@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+
+use keyring::Entry;
+
+use crate::error::Result;
+use crate::onepassword::{self, OP_PREFIX};
+use crate::vault::{self, VAULT_PREFIX};
+
+static KEYRING_SERVICE: &str = "api-cli";
+static SECRET_PREFIX: &str = "secret:";
+
+fn entry(name: &str) -> keyring::Result<Entry> {
+    Entry::new(KEYRING_SERVICE, name)
+}
+
+pub(crate) fn get_keyring_secret(name: &str) -> keyring::Result<String> {
+    entry(name)?.get_password()
+}
+
+/// Get a secret from the OS keyring
+pub fn get_secret(name: &str) -> Result<String> {
+    Ok(get_keyring_secret(name)?)
+}
+
+/// Store a secret in the OS keyring, replacing any existing value
+pub fn set_secret(name: &str, value: &str) -> Result<()> {
+    Ok(entry(name)?.set_password(value)?)
+}
+
+/// Delete a secret from the OS keyring
+pub fn delete_secret(name: &str) -> Result<()> {
+    Ok(entry(name)?.delete_credential()?)
+}
+
+/// Resolve a value of the form `secret:<name>` from the OS keyring, `vault:<path>#<field>` from
+/// HashiCorp Vault or `op://<vault>/<item>/<field>` from 1Password. Any other value is returned as
+/// is.
+pub(crate) fn resolve_value(value: &str) -> Result<Cow<'_, str>> {
+    if let Some(name) = value.strip_prefix(SECRET_PREFIX) {
+        return Ok(Cow::Owned(get_secret(name)?));
+    }
+    if let Some(reference) = value.strip_prefix(VAULT_PREFIX) {
+        return Ok(Cow::Owned(vault::resolve_reference(reference)?));
+    }
+    if value.starts_with(OP_PREFIX) {
+        return Ok(Cow::Owned(onepassword::resolve_reference(value)?));
+    }
+
+    Ok(Cow::Borrowed(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::resolve_value;
+
+    #[test]
+    fn test_resolve_value_returns_plain_values_as_is() {
+        let value = resolve_value("some-value").unwrap();
+
+        assert!(matches!(value, Cow::Borrowed("some-value")));
+    }
+
+    #[test]
+    fn test_resolve_value_fails_on_missing_secret() {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+
+        assert!(resolve_value("secret:api-cli-missing-secret").is_err());
+    }
+}
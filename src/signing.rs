@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApiClientError, Result};
+
+/// Rendered request written to the stdin of the signing command.
+#[derive(Debug, Serialize)]
+struct SigningInput<'a> {
+    method: &'a str,
+    url: &'a str,
+    headers: BTreeMap<&'a str, &'a str>,
+    /// Body of the request if it is valid utf-8
+    body: Option<&'a str>,
+    body_base64: Option<String>,
+}
+
+/// Json printed by the signing command on its stdout.
+#[derive(Debug, Deserialize)]
+struct SigningOutput {
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+/// Sign a fully built request with an external command, which receives the request as json on its
+/// stdin and prints the headers to add as json on its stdout. The returned headers replace the
+/// ones with the same name and are marked as sensitive.
+pub(crate) fn sign_request(request: &mut Request, command: &[String]) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(ApiClientError::new_signing_error(
+            "the signing command is empty".to_string(),
+        ));
+    };
+
+    let body = request.body().and_then(|b| b.as_bytes());
+    let input = SigningInput {
+        method: request.method().as_str(),
+        url: request.url().as_str(),
+        headers: request
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str(), v.to_str().ok()?)))
+            .collect(),
+        body: body.and_then(|b| std::str::from_utf8(b).ok()),
+        body_base64: body.map(|b| BASE64_STANDARD.encode(b)),
+    };
+    let input = serde_json::to_vec(&input)?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ApiClientError::new_signing_error(format!("{}: {}", program, e)))?;
+
+    // Written from another thread, so a command printing before reading its input can't block.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    // The command may not read its input, e.g. to sign only the url.
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApiClientError::new_signing_error(format!(
+            "{} failed with {}: {}",
+            program,
+            output.status,
+            stderr.trim()
+        )));
+    }
+
+    let output: SigningOutput = serde_json::from_slice(&output.stdout).map_err(|e| {
+        ApiClientError::new_signing_error(format!("invalid output of {}: {}", program, e))
+    })?;
+
+    for (name, value) in output.headers {
+        let header_name = HeaderName::try_from(&name)
+            .map_err(|_| ApiClientError::new_invalid_header_name(name.clone()))?;
+        let mut header_value = HeaderValue::try_from(&value)
+            .map_err(|_| ApiClientError::new_invalid_header_value(name, value))?;
+        header_value.set_sensitive(true);
+
+        request.headers_mut().insert(header_name, header_value);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::sign_request;
+
+    fn command(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn test_sign_request_adds_returned_headers() {
+        let input = std::env::temp_dir().join(format!("api-cli-test-{}", uuid::Uuid::new_v4()));
+        let mut request = reqwest::Client::new()
+            .post("http://localhost/users?page=1")
+            .header("X-Signature", "unsigned")
+            .body("{\"name\": \"Alice\"}")
+            .build()
+            .unwrap();
+
+        let script = format!(
+            "cat > {}; echo '{{\"headers\": {{\"X-Signature\": \"signed\", \"X-Key\": \"k1\"}}}}'",
+            input.display()
+        );
+        sign_request(&mut request, &command(&script)).expect("signing failed");
+
+        let content = std::fs::read(&input).unwrap();
+        std::fs::remove_file(&input).unwrap();
+        let input: Value = serde_json::from_slice(&content).unwrap();
+        assert_eq!(
+            input,
+            json!({
+                "method": "POST",
+                "url": "http://localhost/users?page=1",
+                "headers": {"x-signature": "unsigned"},
+                "body": "{\"name\": \"Alice\"}",
+                "body_base64": "eyJuYW1lIjogIkFsaWNlIn0=",
+            })
+        );
+
+        let headers = request.headers();
+        assert_eq!(headers.get_all("X-Signature").iter().count(), 1);
+        assert_eq!(headers["X-Signature"], "signed");
+        assert_eq!(headers["X-Key"], "k1");
+        assert!(headers["X-Key"].is_sensitive());
+    }
+
+    #[rstest::rstest]
+    #[case::failure("echo 'bad key' >&2; exit 3", "sh failed with exit status: 3: bad key")]
+    #[case::invalid_output("echo nope", "invalid output of sh")]
+    #[case::invalid_header("echo '{\"headers\": {\"X A\": \"a\"}}'", "Invalid header name")]
+    fn test_sign_request_fails(#[case] script: &str, #[case] expected: &str) {
+        let mut request = reqwest::Client::new()
+            .get("http://localhost")
+            .build()
+            .unwrap();
+
+        let err = sign_request(&mut request, &command(script)).unwrap_err();
+
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
@@ -0,0 +1,259 @@
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderValue, AUTHORIZATION, HOST};
+use reqwest::Request;
+use sha2::{Digest, Sha256};
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use crate::error::Result;
+
+static ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Characters that are percent-encoded in the canonical request: everything except the unreserved
+/// characters of RFC 3986.
+pub(crate) const URI_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Rendered credentials of an `aws-sigv4` auth.
+#[derive(Debug)]
+pub(crate) struct AwsCredentials {
+    pub(crate) access_key: String,
+    pub(crate) secret_key: String,
+    pub(crate) region: String,
+    pub(crate) service: String,
+    pub(crate) session_token: Option<String>,
+}
+
+/// Sign a fully built request with AWS Signature Version 4, adding the `x-amz-*` and
+/// `Authorization` headers.
+pub(crate) fn sign_request(
+    request: &mut Request,
+    credentials: &AwsCredentials,
+    now: OffsetDateTime,
+) -> Result<()> {
+    let amz_date = now
+        .format(format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .expect("invalid date format");
+    let date = &amz_date[..8];
+
+    let payload_hash = hex_sha256(
+        request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .unwrap_or_default(),
+    );
+
+    let host = match (request.url().host_str(), request.url().port()) {
+        (Some(h), Some(p)) => format!("{}:{}", h, p),
+        (Some(h), None) => h.to_string(),
+        (None, _) => String::new(),
+    };
+
+    let headers = request.headers_mut();
+    headers.insert(HOST, HeaderValue::from_str(&host)?);
+    headers.insert("x-amz-date", HeaderValue::from_str(&amz_date)?);
+    if credentials.service == "s3" {
+        headers.insert(
+            "x-amz-content-sha256",
+            HeaderValue::from_str(&payload_hash)?,
+        );
+    }
+    if let Some(token) = &credentials.session_token {
+        headers.insert("x-amz-security-token", HeaderValue::from_str(token)?);
+    }
+
+    let (canonical_headers, signed_headers) = canonical_headers(request);
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method().as_str(),
+        canonical_uri(request, &credentials.service),
+        canonical_query_string(request),
+        canonical_headers,
+        signed_headers,
+        payload_hash,
+    );
+
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, credentials.region, credentials.service
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let signing_key = [
+        date,
+        credentials.region.as_str(),
+        credentials.service.as_str(),
+        "aws4_request",
+    ]
+    .iter()
+    .fold(
+        format!("AWS4{}", credentials.secret_key).into_bytes(),
+        |key, data| hmac_sha256(&key, data.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, credentials.access_key, scope, signed_headers, signature
+    );
+    request
+        .headers_mut()
+        .insert(AUTHORIZATION, HeaderValue::from_str(&authorization)?);
+
+    Ok(())
+}
+
+fn canonical_uri(request: &Request, service: &str) -> String {
+    let path = request.url().path();
+
+    // The path of the url is already encoded once, all services except s3 expect it twice.
+    if service == "s3" {
+        return path.to_string();
+    }
+
+    path.split('/')
+        .map(|s| utf8_percent_encode(s, URI_ENCODE_SET).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query_string(request: &Request) -> String {
+    let mut params: Vec<(String, String)> = request
+        .url()
+        .query_pairs()
+        .map(|(k, v)| {
+            (
+                utf8_percent_encode(&k, URI_ENCODE_SET).to_string(),
+                utf8_percent_encode(&v, URI_ENCODE_SET).to_string(),
+            )
+        })
+        .collect();
+    params.sort();
+
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Signs the host, the content type and all `x-amz-*` headers.
+fn canonical_headers(request: &Request) -> (String, String) {
+    let mut headers: Vec<(&str, String)> = request
+        .headers()
+        .iter()
+        .filter(|(k, _)| {
+            let k = k.as_str();
+            k == "host" || k == "content-type" || k.starts_with("x-amz-")
+        })
+        .map(|(k, v)| {
+            let value = String::from_utf8_lossy(v.as_bytes());
+            (
+                k.as_str(),
+                value.split_whitespace().collect::<Vec<_>>().join(" "),
+            )
+        })
+        .collect();
+    headers.sort();
+
+    let canonical = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect::<String>();
+    let signed = headers
+        .iter()
+        .map(|(k, _)| *k)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    (canonical, signed)
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::AUTHORIZATION;
+    use time::macros::datetime;
+
+    use super::{sign_request, AwsCredentials};
+
+    fn credentials(session_token: Option<&str>) -> AwsCredentials {
+        AwsCredentials {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            region: "us-east-1".to_string(),
+            service: "iam".to_string(),
+            session_token: session_token.map(String::from),
+        }
+    }
+
+    // Example from the AWS documentation on signing requests.
+    #[test]
+    fn test_sign_request() {
+        let mut request = reqwest::Client::new()
+            .get("https://iam.amazonaws.com/?Version=2010-05-08&Action=ListUsers")
+            .header(
+                "Content-Type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )
+            .build()
+            .unwrap();
+
+        sign_request(
+            &mut request,
+            &credentials(None),
+            datetime!(2015-08-30 12:36:00 UTC),
+        )
+        .unwrap();
+
+        assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+        assert_eq!(
+            request.headers()[AUTHORIZATION],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    #[test]
+    fn test_sign_request_with_session_token() {
+        let mut request = reqwest::Client::new()
+            .get("https://iam.amazonaws.com/")
+            .build()
+            .unwrap();
+
+        sign_request(
+            &mut request,
+            &credentials(Some("some-token")),
+            datetime!(2015-08-30 12:36:00 UTC),
+        )
+        .unwrap();
+
+        assert_eq!(request.headers()["x-amz-security-token"], "some-token");
+        assert!(request.headers()[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"));
+    }
+}
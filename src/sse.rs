@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+
+use crate::error::Result;
+
+/// An event received from a `text/event-stream` response.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Type of the event, if it is not the default `message`.
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub data: String,
+}
+
+/// A response whose body is read as a stream of server-sent events.
+#[derive(Debug)]
+pub struct SseStream {
+    response: Response,
+    parser: SseParser,
+}
+
+impl SseStream {
+    pub(crate) fn new(response: Response) -> Self {
+        Self {
+            response,
+            parser: SseParser::default(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    /// Wait for the next event, or `None` once the server closes the stream.
+    pub async fn next_event(&mut self) -> Result<Option<SseEvent>> {
+        loop {
+            if let Some(event) = self.parser.events.pop_front() {
+                return Ok(Some(event));
+            }
+
+            match self.response.chunk().await? {
+                Some(chunk) => self.parser.feed(&chunk),
+                // As per the spec, an event that is not terminated by a blank line is discarded.
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Incremental parser for the `text/event-stream` format. Chunks can be split anywhere, incomplete
+/// lines are kept until the rest of the line is received.
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_data: bool,
+    events: VecDeque<SseEvent>,
+}
+
+impl SseParser {
+    fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            self.process_line(line);
+        }
+    }
+
+    fn process_line(&mut self, line: &str) {
+        if line.is_empty() {
+            let event = std::mem::take(&mut self.current);
+            if std::mem::take(&mut self.has_data) {
+                self.events.push_back(event);
+            }
+            return;
+        }
+
+        // Lines starting with a colon are comments, usually used as keep-alives.
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+            None => (line, ""),
+        };
+
+        match field {
+            "data" => {
+                if self.has_data {
+                    self.current.data.push('\n');
+                }
+                self.current.data.push_str(value);
+                self.has_data = true;
+            }
+            "event" => self.current.event = Some(value.to_string()),
+            "id" => self.current.id = Some(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SseEvent, SseParser};
+
+    fn event(event: Option<&str>, id: Option<&str>, data: &str) -> SseEvent {
+        SseEvent {
+            event: event.map(String::from),
+            id: id.map(String::from),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+
+        parser.feed(b"event: update\r\nid: 1\r\nda");
+        assert!(parser.events.is_empty());
+
+        parser.feed(b"ta: first\r\ndata: second\r\n\r\n: keep-alive\n\ndata:last\n\n");
+
+        assert_eq!(
+            parser.events.into_iter().collect::<Vec<_>>(),
+            vec![
+                event(Some("update"), Some("1"), "first\nsecond"),
+                event(None, None, "last")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser_ignores_unterminated_event() {
+        let mut parser = SseParser::default();
+
+        parser.feed(b"data: first\n\ndata: unterminated");
+
+        assert_eq!(
+            parser.events.into_iter().collect::<Vec<_>>(),
+            vec![event(None, None, "first")]
+        );
+    }
+}
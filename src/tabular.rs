@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use serde_json::Value;
+use tabled::builder::Builder;
+use tabled::settings::Style;
+
+use crate::error::{ApiClientError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabularFormat {
+    Table,
+    Csv,
+}
+
+impl FromStr for TabularFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(TabularFormat::Table),
+            "csv" => Ok(TabularFormat::Csv),
+            _ => Err(format!("unknown format: `{}`", s)),
+        }
+    }
+}
+
+/// How the json arrays are displayed in columns: one row per item, one column per field.
+#[derive(Debug)]
+pub struct Tabular {
+    format: TabularFormat,
+    fields: Vec<String>,
+}
+
+impl Tabular {
+    /// Display the selected fields, or every field of the items if there are none.
+    pub fn new(format: TabularFormat, fields: Vec<String>) -> Self {
+        Self { format, fields }
+    }
+
+    /// Render an array of objects as a table or csv. The columns are the selected fields, or
+    /// every field of the items, in alphabetical order for each item.
+    pub fn render(&self, value: &Value) -> Result<String> {
+        let Value::Array(items) = value else {
+            return Err(ApiClientError::new_not_tabular(
+                "it is not a json array".to_string(),
+            ));
+        };
+
+        let columns = match self.fields.as_slice() {
+            [] => item_fields(items)?,
+            fields => fields.to_vec(),
+        };
+        let rows: Vec<Vec<String>> = items
+            .iter()
+            .map(|item| columns.iter().map(|c| cell(item, c)).collect())
+            .collect();
+
+        Ok(match self.format {
+            TabularFormat::Table => to_table(columns, rows),
+            TabularFormat::Csv => to_csv(&columns, &rows),
+        })
+    }
+}
+
+/// The fields of the items, in alphabetical order for each item since the json objects don't keep
+/// the order of their fields. The fields missing from the previous items are added after theirs.
+fn item_fields(items: &[Value]) -> Result<Vec<String>> {
+    let mut fields: Vec<String> = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let Value::Object(o) = item else {
+            return Err(ApiClientError::new_not_tabular(format!(
+                "item {} is not an object",
+                i + 1
+            )));
+        };
+
+        for k in o.keys() {
+            if !fields.contains(k) {
+                fields.push(k.clone());
+            }
+        }
+    }
+
+    Ok(fields)
+}
+
+/// The value of a field of an item, where `a.b` is the field `b` of the field `a` and `a.0` is the
+/// first element of the array `a`. Missing fields and nulls are empty.
+fn cell(item: &Value, field: &str) -> String {
+    let value = field.split('.').try_fold(item, |v, key| match v {
+        Value::Object(o) => o.get(key),
+        Value::Array(a) => key.parse::<usize>().ok().and_then(|i| a.get(i)),
+        _ => None,
+    });
+
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+    }
+}
+
+fn to_table(columns: Vec<String>, rows: Vec<Vec<String>>) -> String {
+    let mut builder = Builder::default();
+    builder.push_record(columns);
+    for row in rows {
+        builder.push_record(row);
+    }
+
+    builder.build().with(Style::modern()).to_string()
+}
+
+/// Write the rows as csv, following RFC 4180, with the fields as header.
+fn to_csv(columns: &[String], rows: &[Vec<String>]) -> String {
+    std::iter::once(columns)
+        .chain(rows.iter().map(Vec::as_slice))
+        .map(|record| {
+            record
+                .iter()
+                .map(|v| csv_field(v))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    use super::{Tabular, TabularFormat};
+    use crate::error::Result;
+
+    fn render(value: Value, fields: &[&str]) -> Result<String> {
+        let fields = fields.iter().map(|f| f.to_string()).collect();
+
+        Tabular::new(TabularFormat::Csv, fields).render(&value)
+    }
+
+    #[test]
+    fn test_render_csv_with_every_field() {
+        let value = json!([
+            {"id": 1, "name": "Doe, John"},
+            {"id": 2, "name": "say \"hi\"", "active": true},
+        ]);
+
+        assert_eq!(
+            render(value, &[]).unwrap(),
+            "id,name,active\n1,\"Doe, John\",\n2,\"say \"\"hi\"\"\",true"
+        );
+    }
+
+    #[test]
+    fn test_render_csv_with_selected_fields() {
+        let value = json!([
+            {"id": 1, "owner": {"name": "alice"}, "tags": ["a", "b"]},
+            {"id": 2, "owner": null, "tags": []},
+        ]);
+
+        assert_eq!(
+            render(value, &["owner.name", "tags.1", "id", "missing"]).unwrap(),
+            "owner.name,tags.1,id,missing\nalice,b,1,\n,,2,"
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let tabular = Tabular::new(TabularFormat::Table, vec!["id".to_string()]);
+
+        let table = tabular.render(&json!([{"id": 1}, {"id": 2}])).unwrap();
+
+        assert!(table.contains("│ id │"), "{}", table);
+        assert!(table.contains("│ 2  │"), "{}", table);
+    }
+
+    #[rstest]
+    #[case(json!({"id": 1}))]
+    #[case(json!([{"id": 1}, 2]))]
+    fn test_render_invalid_body(#[case] value: Value) {
+        assert!(render(value, &[]).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A boolean expression selecting requests by their tags, e.g. `smoke && !slow`.
+///
+/// Tags are combined with `&&`, `||` and `!`, and grouped with parentheses. `&&` binds tighter
+/// than `||`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagExpression {
+    Tag(String),
+    Not(Box<TagExpression>),
+    And(Box<TagExpression>, Box<TagExpression>),
+    Or(Box<TagExpression>, Box<TagExpression>),
+}
+
+impl TagExpression {
+    /// Whether a request with the given tags is selected by the expression.
+    pub fn matches<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        match self {
+            TagExpression::Tag(t) => tags.iter().any(|s| s.as_ref() == t),
+            TagExpression::Not(e) => !e.matches(tags),
+            TagExpression::And(l, r) => l.matches(tags) && r.matches(tags),
+            TagExpression::Or(l, r) => l.matches(tags) || r.matches(tags),
+        }
+    }
+}
+
+impl fmt::Display for TagExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagExpression::Tag(t) => write!(f, "{}", t),
+            TagExpression::Not(e) => write!(f, "!{}", e),
+            TagExpression::And(l, r) => write!(f, "({} && {})", l, r),
+            TagExpression::Or(l, r) => write!(f, "({} || {})", l, r),
+        }
+    }
+}
+
+impl FromStr for TagExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser { tokens, pos: 0 };
+
+        let expression = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expression),
+            Some(t) => Err(format!("unexpected `{}` in tag expression `{}`", t, s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Tag(t) => write!(f, "{}", t),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '/')
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '!' => tokens.push(Token::Not),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '&' | '|' => {
+                if chars.next_if(|(_, n)| *n == c).is_none() {
+                    return Err(format!("expected `{}{}` in tag expression `{}`", c, c, s));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            c if is_tag_char(c) => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, n)) = chars.next_if(|(_, n)| is_tag_char(*n)) {
+                    end = j + n.len_utf8();
+                }
+                tokens.push(Token::Tag(s[i..end].to_string()));
+            }
+            c => return Err(format!("unexpected `{}` in tag expression `{}`", c, s)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next_if(&mut self, token: Token) -> bool {
+        if self.tokens.get(self.pos) == Some(&token) {
+            self.pos += 1;
+            return true;
+        }
+
+        false
+    }
+
+    fn parse_or(&mut self) -> Result<TagExpression, String> {
+        let mut expression = self.parse_and()?;
+        while self.next_if(Token::Or) {
+            expression = TagExpression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<TagExpression, String> {
+        let mut expression = self.parse_not()?;
+        while self.next_if(Token::And) {
+            expression = TagExpression::And(Box::new(expression), Box::new(self.parse_not()?));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_not(&mut self) -> Result<TagExpression, String> {
+        if self.next_if(Token::Not) {
+            return Ok(TagExpression::Not(Box::new(self.parse_not()?)));
+        }
+
+        if self.next_if(Token::Open) {
+            let expression = self.parse_or()?;
+            if !self.next_if(Token::Close) {
+                return Err("missing `)` in tag expression".to_string());
+            }
+
+            return Ok(expression);
+        }
+
+        match self.tokens.get(self.pos) {
+            Some(Token::Tag(t)) => {
+                let tag = t.clone();
+                self.pos += 1;
+                Ok(TagExpression::Tag(tag))
+            }
+            Some(t) => Err(format!("expected a tag, found `{}`", t)),
+            None => Err("expected a tag at the end of the tag expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::TagExpression;
+
+    #[rstest]
+    #[case("smoke", &["smoke", "slow"], true)]
+    #[case("smoke", &["slow"], false)]
+    #[case("smoke && !slow", &["smoke"], true)]
+    #[case("smoke && !slow", &["smoke", "slow"], false)]
+    #[case("smoke || users", &["users"], true)]
+    #[case("!(smoke || users)", &["users"], false)]
+    #[case("a || b && c", &["a"], true)]
+    #[case("(a || b) && c", &["a"], false)]
+    #[case("team:api && v2.1", &["team:api", "v2.1"], true)]
+    fn test_tag_expression_matches(
+        #[case] expression: &str,
+        #[case] tags: &[&str],
+        #[case] expected: bool,
+    ) {
+        let expression: TagExpression = expression.parse().unwrap();
+
+        assert_eq!(expression.matches(tags), expected);
+    }
+
+    #[test]
+    fn test_tag_expression_precedence() {
+        let expression: TagExpression = "a || !b && c".parse().unwrap();
+
+        assert_eq!(expression.to_string(), "(a || (!b && c))");
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("smoke &")]
+    #[case("smoke &&")]
+    #[case("(smoke")]
+    #[case("smoke)")]
+    #[case("smoke slow")]
+    #[case("smoke, slow")]
+    fn test_tag_expression_invalid(#[case] expression: &str) {
+        assert!(expression.parse::<TagExpression>().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use tower::{Layer, Service};
+
+use crate::models::IpVersion;
+use crate::redirect::SharedRedirects;
+
+/// Breakdown of the time spent on a request.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Timings {
+    /// Time spent resolving the host name, if a new connection was opened.
+    pub dns: Option<Duration>,
+    /// Time spent establishing the connection, including the TLS handshake, if a new connection
+    /// was opened.
+    pub connect: Option<Duration>,
+    /// Time between sending the request and receiving the response headers.
+    pub ttfb: Duration,
+    /// Time spent reading the body.
+    pub download: Duration,
+}
+
+pub(crate) type SharedTimings = Arc<Mutex<Timings>>;
+
+/// Host name of a url replaced by the one a request sends, with the address of the original host,
+/// to which the connections are still opened.
+#[derive(Debug, Clone)]
+pub(crate) struct HostOverride {
+    pub(crate) host: String,
+    pub(crate) address: String,
+}
+
+pub(crate) type SharedHostOverride = Arc<Mutex<Option<HostOverride>>>;
+
+/// State of a single execution of a request, used and recorded by the resolver, connector and
+/// redirect policy of its client. Since a client can be shared by concurrent requests, the state
+/// is bound to the task executing the request with [`RequestState::scope`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestState {
+    pub(crate) timings: SharedTimings,
+    pub(crate) redirects: SharedRedirects,
+    pub(crate) host_override: SharedHostOverride,
+}
+
+tokio::task_local! {
+    static REQUEST_STATE: RequestState;
+}
+
+impl RequestState {
+    /// The state of the request executed by the current task, if any.
+    pub(crate) fn current() -> Option<RequestState> {
+        REQUEST_STATE.try_with(RequestState::clone).ok()
+    }
+
+    /// Run `f` with this state as the one of the current request.
+    pub(crate) async fn scope<F: Future>(self, f: F) -> F::Output {
+        REQUEST_STATE.scope(self, f).await
+    }
+}
+
+/// Resolver recording the time spent on dns resolution, resolving the overridden host of the
+/// request to the address of its url and keeping only the addresses of the given protocol family.
+///
+/// It replaces the default resolver of reqwest, and like it resolves names with the resolver of
+/// the system (`getaddrinfo`) from a blocking thread, so names resolve the same way.
+pub(crate) struct TimedResolver {
+    pub(crate) ip_version: Option<IpVersion>,
+}
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        // The connection can be completed in the background, the state is taken when it starts.
+        let state = RequestState::current();
+        let ip_version = self.ip_version;
+        let host_override = state
+            .as_ref()
+            .and_then(|s| s.host_override.lock().unwrap().clone());
+        let host = match host_override {
+            Some(o) if o.host.eq_ignore_ascii_case(name.as_str()) => o.address,
+            _ => name.as_str().to_string(),
+        };
+
+        Box::pin(async move {
+            let start = Instant::now();
+            let mut addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(s) = state {
+                s.timings.lock().unwrap().dns = Some(start.elapsed());
+            }
+
+            if let Some(version) = ip_version {
+                let family = match version {
+                    IpVersion::V4 => "IPv4",
+                    IpVersion::V6 => "IPv6",
+                };
+                addrs.retain(|a| a.is_ipv4() == (version == IpVersion::V4));
+                if addrs.is_empty() {
+                    return Err(format!("no {} address found for {}", family, host).into());
+                }
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Connector layer recording the time spent opening connections.
+#[derive(Clone)]
+pub(crate) struct TimedConnectLayer;
+
+impl<S> Layer<S> for TimedConnectLayer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect { inner }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TimedConnect<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for TimedConnect<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        let state = RequestState::current();
+        let start = Instant::now();
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let res = fut.await;
+            let Some(state) = state else {
+                return res;
+            };
+
+            // The connector also resolves the host name, which is already accounted for.
+            let mut timings = state.timings.lock().unwrap();
+            timings.connect = Some(
+                start
+                    .elapsed()
+                    .saturating_sub(timings.dns.unwrap_or_default()),
+            );
+
+            res
+        })
+    }
+}
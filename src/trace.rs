@@ -0,0 +1,146 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+
+/// A W3C trace context, sent in the `traceparent` header so the request can be located in
+/// distributed tracing, e.g. `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceParent {
+    trace_id: u128,
+    parent_id: u64,
+    flags: u8,
+}
+
+/// The only version of the format, the other fields of future versions are ignored.
+const VERSION: &str = "00";
+const SAMPLED: u8 = 0x01;
+
+impl TraceParent {
+    /// A new sampled trace, with random IDs.
+    pub fn new() -> Self {
+        let mut rng = rand::thread_rng();
+
+        Self {
+            // IDs made of zeros are invalid.
+            trace_id: rng.gen_range(1..=u128::MAX),
+            parent_id: rng.gen_range(1..=u64::MAX),
+            flags: SAMPLED,
+        }
+    }
+
+    /// The ID of the trace, as 32 hexadecimal characters.
+    pub fn trace_id(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+}
+
+impl Default for TraceParent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{:032x}-{:016x}-{:02x}",
+            VERSION, self.trace_id, self.parent_id, self.flags
+        )
+    }
+}
+
+impl FromStr for TraceParent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid traceparent `{}`, expected `00-<trace id>-<parent id>-<flags>`",
+                s
+            )
+        };
+        // Only lowercase hexadecimal digits are allowed.
+        let hex = |field: &str, len: usize| {
+            let valid = field.len() == len
+                && field
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+            match valid {
+                true => u128::from_str_radix(field, 16).map_err(|_| invalid()),
+                false => Err(invalid()),
+            }
+        };
+
+        let mut fields = s.split('-');
+        let (Some(version), Some(trace_id), Some(parent_id), Some(flags)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        if hex(version, 2)? == 0xff || (version == VERSION && fields.next().is_some()) {
+            return Err(invalid());
+        }
+
+        let trace_id = hex(trace_id, 32)?;
+        let parent_id = hex(parent_id, 16)? as u64;
+        let flags = hex(flags, 2)? as u8;
+        if trace_id == 0 || parent_id == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            trace_id,
+            parent_id,
+            flags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::TraceParent;
+
+    #[test]
+    fn test_new_trace_parent_is_valid() {
+        let trace = TraceParent::new();
+        let value = trace.to_string();
+
+        assert_eq!(value.len(), 55);
+        assert!(value.starts_with(&format!("00-{}-", trace.trace_id())));
+        assert!(value.ends_with("-01"));
+        assert_eq!(value.parse::<TraceParent>(), Ok(trace));
+    }
+
+    #[rstest]
+    #[case("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")]
+    #[case("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00")]
+    fn test_parse_trace_parent(#[case] value: &str) {
+        let trace: TraceParent = value.parse().expect("invalid traceparent");
+
+        assert_eq!(trace.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace.to_string(), value);
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::missing_flags("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7")]
+    #[case::extra_field("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-00")]
+    #[case::invalid_version("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")]
+    #[case::uppercase("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01")]
+    #[case::short_trace_id("00-4bf92f3577b34da6-00f067aa0ba902b7-01")]
+    #[case::zero_trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01")]
+    #[case::zero_parent_id("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01")]
+    fn test_parse_invalid_trace_parent(#[case] value: &str) {
+        assert_eq!(
+            value.parse::<TraceParent>(),
+            Err(format!(
+                "invalid traceparent `{}`, expected `00-<trace id>-<parent id>-<flags>`",
+                value
+            ))
+        );
+    }
+}
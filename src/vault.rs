@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::{env, fs, thread};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::error::{ApiClientError, Result};
+
+pub(crate) static VAULT_PREFIX: &str = "vault:";
+
+/// Secrets already read, by address and path, so each one is read once per run.
+static CACHE: Lazy<Mutex<HashMap<String, Map<String, Value>>>> = Lazy::new(Default::default);
+
+/// Connection to a Vault server, configured like the `vault` command line.
+#[derive(Debug)]
+pub(crate) struct VaultConfig {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+}
+
+impl VaultConfig {
+    /// Read the configuration from `VAULT_ADDR`, `VAULT_TOKEN` (or `~/.vault-token`) and
+    /// `VAULT_NAMESPACE`.
+    fn from_env() -> Result<Self> {
+        let address = env::var("VAULT_ADDR")
+            .map_err(|_| ApiClientError::new_vault_error("VAULT_ADDR is not set".to_string()))?;
+        let token = match env::var("VAULT_TOKEN") {
+            Ok(t) => t,
+            Err(_) => dirs::home_dir()
+                .map(|h| h.join(".vault-token"))
+                .and_then(|p| fs::read_to_string(p).ok())
+                .map(|t| t.trim().to_string())
+                .ok_or_else(|| {
+                    ApiClientError::new_vault_error(
+                        "VAULT_TOKEN is not set and ~/.vault-token doesn't exist".to_string(),
+                    )
+                })?,
+        };
+
+        Ok(Self {
+            address,
+            token,
+            namespace: env::var("VAULT_NAMESPACE").ok(),
+        })
+    }
+}
+
+/// Read a field of a secret from a reference of the form `<path>#<field>`, e.g.
+/// `secret/data/github#token`. Both versions of the KV secrets engine are supported.
+pub(crate) fn resolve_reference(reference: &str) -> Result<String> {
+    read_field(&VaultConfig::from_env()?, reference)
+}
+
+fn read_field(config: &VaultConfig, reference: &str) -> Result<String> {
+    let Some((path, field)) = reference.rsplit_once('#') else {
+        return Err(ApiClientError::new_vault_error(format!(
+            "missing field in `{}`, expected `<path>#<field>`",
+            reference
+        )));
+    };
+
+    let secret = read_secret(config, path.trim_matches('/'))?;
+    match secret.get(field) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(v) => Ok(v.to_string()),
+        None => Err(ApiClientError::new_vault_error(format!(
+            "field `{}` not found in {}",
+            field, path
+        ))),
+    }
+}
+
+fn read_secret(config: &VaultConfig, path: &str) -> Result<Map<String, Value>> {
+    let key = format!("{}/{}", config.address, path);
+    if let Some(secret) = CACHE.lock().unwrap().get(&key) {
+        return Ok(secret.clone());
+    }
+
+    // Requests are rendered synchronously, possibly from within a runtime, so the secret is read
+    // from a thread with its own runtime.
+    let secret = thread::scope(|s| {
+        s.spawn(|| fetch_secret(config, path))
+            .join()
+            .expect("vault thread panicked")
+    })?;
+    CACHE.lock().unwrap().insert(key, secret.clone());
+
+    Ok(secret)
+}
+
+fn fetch_secret(config: &VaultConfig, path: &str) -> Result<Map<String, Value>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        let url = format!("{}/v1/{}", config.address.trim_end_matches('/'), path);
+        let mut req = reqwest::Client::new()
+            .get(url)
+            .header("X-Vault-Token", &config.token);
+        if let Some(ns) = &config.namespace {
+            req = req.header("X-Vault-Namespace", ns);
+        }
+
+        let res = req.send().await?;
+        if !res.status().is_success() {
+            return Err(ApiClientError::new_vault_error(format!(
+                "{} returned {}",
+                path,
+                res.status()
+            )));
+        }
+
+        let mut body: Value = res.json().await?;
+        // KV v2 nests the secret in `data.data`, next to its metadata.
+        let data = match body["data"].get("metadata") {
+            Some(_) => body["data"]["data"].take(),
+            None => body["data"].take(),
+        };
+
+        match data {
+            Value::Object(o) => Ok(o),
+            _ => Err(ApiClientError::new_vault_error(format!(
+                "{} is not a secret",
+                path
+            ))),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::json;
+    use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+    use super::{read_field, VaultConfig};
+
+    async fn vault_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(matchers::path("/v1/secret/data/github"))
+            .and(matchers::header("X-Vault-Token", "root"))
+            .and(matchers::header("X-Vault-Namespace", "team"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {"data": {"token": "gh-token", "port": 8080}, "metadata": {"version": 1}}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::path("/v1/kv/app"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"data": {"key": "app-key"}})),
+            )
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    fn config(server: &MockServer) -> VaultConfig {
+        VaultConfig {
+            address: server.uri(),
+            token: "root".to_string(),
+            namespace: Some("team".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_field_caches_secrets() {
+        let server = vault_server().await;
+        let config = config(&server);
+
+        assert_eq!(
+            read_field(&config, "secret/data/github#token").unwrap(),
+            "gh-token"
+        );
+        assert_eq!(
+            read_field(&config, "secret/data/github#port").unwrap(),
+            "8080"
+        );
+        assert_eq!(read_field(&config, "kv/app#key").unwrap(), "app-key");
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+    }
+
+    #[rstest]
+    #[case::missing_field("secret/data/github", "missing field")]
+    #[case::unknown_field("secret/data/github#password", "field `password` not found")]
+    #[case::unknown_secret("secret/data/unknown#token", "404 Not Found")]
+    #[tokio::test]
+    async fn test_read_field_fails(#[case] reference: &str, #[case] expected: &str) {
+        let server = vault_server().await;
+
+        let err = read_field(&config(&server), reference).unwrap_err();
+
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+}
@@ -0,0 +1,144 @@
+use log::{debug, info, log_enabled, Level};
+use reqwest::tls::TlsInfo;
+use reqwest::{Request, Response};
+use sha2::{Digest, Sha256};
+
+use crate::masking::{is_sensitive_header, Masker, MASK};
+use crate::timing::Timings;
+
+/// Target of the log records describing the requests and responses as they go over the wire.
+///
+/// The outgoing request is logged at the `info` level, the connection and the response headers
+/// at the `debug` level.
+pub static WIRE_LOG_TARGET: &str = "api_cli::wire";
+
+/// Log the request, with its secrets masked.
+pub(crate) fn log_request(request: &Request, masker: &Masker) {
+    if !log_enabled!(target: WIRE_LOG_TARGET, Level::Info) {
+        return;
+    }
+
+    info!(
+        target: WIRE_LOG_TARGET,
+        "> {} {}",
+        request.method(),
+        masker.mask(request.url().as_str())
+    );
+    for (name, value) in request.headers() {
+        let value = match is_sensitive_header(name, value) {
+            true => MASK.into(),
+            false => masker
+                .mask(&String::from_utf8_lossy(value.as_bytes()))
+                .into_owned(),
+        };
+        info!(target: WIRE_LOG_TARGET, "> {}: {}", name, value);
+    }
+
+    match request.body().and_then(|b| b.as_bytes()) {
+        Some(b) if !b.is_empty() => {
+            info!(target: WIRE_LOG_TARGET, ">");
+            for line in body_lines(b, masker) {
+                info!(target: WIRE_LOG_TARGET, "> {}", line);
+            }
+        }
+        Some(_) => {}
+        None if request.body().is_some() => info!(target: WIRE_LOG_TARGET, "> <streamed body>"),
+        None => {}
+    }
+}
+
+pub(crate) fn log_response(response: &Response, timings: &Timings) {
+    if !log_enabled!(target: WIRE_LOG_TARGET, Level::Debug) {
+        return;
+    }
+
+    match response.remote_addr() {
+        Some(addr) => debug!(target: WIRE_LOG_TARGET, "* Connected to {}", addr),
+        None => debug!(target: WIRE_LOG_TARGET, "* Connected to {}", response.url()),
+    }
+    match (timings.dns, timings.connect) {
+        (None, None) => debug!(target: WIRE_LOG_TARGET, "* Reused an existing connection"),
+        (dns, connect) => debug!(
+            target: WIRE_LOG_TARGET,
+            "* DNS lookup: {:?}, connect: {:?}",
+            dns.unwrap_or_default(),
+            connect.unwrap_or_default()
+        ),
+    }
+
+    if let Some(cert) = response
+        .extensions()
+        .get::<TlsInfo>()
+        .and_then(|t| t.peer_certificate())
+    {
+        debug!(
+            target: WIRE_LOG_TARGET,
+            "* TLS peer certificate: {} bytes, SHA-256 fingerprint {}",
+            cert.len(),
+            fingerprint(cert)
+        );
+    }
+
+    debug!(
+        target: WIRE_LOG_TARGET,
+        "< {:?} {}",
+        response.version(),
+        response.status()
+    );
+    for (name, value) in response.headers() {
+        let value = match is_sensitive_header(name, value) {
+            true => MASK.into(),
+            false => String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        };
+        debug!(target: WIRE_LOG_TARGET, "< {}: {}", name, value);
+    }
+}
+
+/// The lines of a request body, with its secrets masked.
+fn body_lines(body: &[u8], masker: &Masker) -> Vec<String> {
+    match std::str::from_utf8(body) {
+        Ok(s) => s.lines().map(|l| masker.mask(l).into_owned()).collect(),
+        Err(_) => vec![format!("<{} bytes of binary data>", body.len())],
+    }
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{body_lines, fingerprint};
+    use crate::masking::Masker;
+
+    #[test]
+    fn test_body_lines_are_masked() {
+        let masker = Masker::new(["s3cr3t p@ss".to_string()]);
+
+        assert_eq!(
+            body_lines(b"{\n  \"password\": \"s3cr3t p@ss\"\n}", &masker),
+            vec!["{", "  \"password\": \"********\"", "}"]
+        );
+        assert_eq!(
+            body_lines(b"user=admin&password=s3cr3t+p%40ss", &masker),
+            vec!["user=admin&password=********"]
+        );
+        assert_eq!(
+            body_lines(&[0xff, 0xfe], &masker),
+            vec!["<2 bytes of binary data>"]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint(b""),
+            "E3:B0:C4:42:98:FC:1C:14:9A:FB:F4:C8:99:6F:B9:24:\
+             27:AE:41:E4:64:9B:93:4C:A4:95:99:1B:78:52:B8:55"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use uuid::Uuid;
+
+#[test]
+fn test_config_file_is_used() {
+    let collections = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/collections");
+    let config_path = env::temp_dir().join(format!("api-cli-{}.yaml", Uuid::new_v4()));
+    fs::write(
+        &config_path,
+        format!("base_directory: {}\n", collections.display()),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_api-cli"))
+        .args(["collection", "list"])
+        .env("API_CLI_CONFIG", &config_path)
+        .env_remove("API_CLI_BASE_DIRECTORY")
+        .output()
+        .unwrap();
+    fs::remove_file(&config_path).unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "GitHub\n");
+}
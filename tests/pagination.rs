@@ -0,0 +1,70 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use reqwest::StatusCode;
+use serde_json::json;
+use uuid::Uuid;
+use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn test_all_pages_fails_on_unexpected_status() {
+    let server = MockServer::start().await;
+    Mock::given(matchers::query_param_is_missing("page"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!([1, 2])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(matchers::query_param("page", "2"))
+        .respond_with(
+            ResponseTemplate::new(StatusCode::NON_AUTHORITATIVE_INFORMATION)
+                .set_body_json(json!([3])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(matchers::query_param("page", "3"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!([])))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let base_dir = env::temp_dir().join(format!("api-cli-{}", Uuid::new_v4()));
+    fs::create_dir_all(base_dir.join("Pages")).unwrap();
+    fs::write(base_dir.join("Pages/collection.yaml"), "timeout: 5s\n").unwrap();
+    fs::write(
+        base_dir.join("Pages/List.yaml"),
+        format!(
+            "http:\n  method: GET\n  url: {}/items\n  pagination:\n    type: page\n    param: page\n",
+            server.uri()
+        ),
+    )
+    .unwrap();
+
+    let output = tokio::task::spawn_blocking(move || {
+        let output = Command::new(env!("CARGO_BIN_EXE_api-cli"))
+            .args([
+                "run",
+                "Pages",
+                "List",
+                "--all-pages",
+                "--expect-status",
+                "200",
+            ])
+            .env("API_CLI_BASE_DIRECTORY", &base_dir)
+            .env("API_CLI_CONFIG", base_dir.join("config.yaml"))
+            .output()
+            .unwrap();
+        fs::remove_dir_all(&base_dir).unwrap();
+        output
+    })
+    .await
+    .unwrap();
+
+    assert!(!output.status.success(), "{:?}", output);
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("203"),
+        "{:?}",
+        output
+    );
+}